@@ -28,6 +28,7 @@
         nativeBuildInputs = with pkgs; [
           rustToolchain
           pkg-config
+          gettext
           wrapGAppsHook4
         ];
 
@@ -57,7 +58,13 @@
 
           inherit nativeBuildInputs buildInputs;
 
+          LOCALEDIR = "${placeholder "out"}/share/locale";
+
           postInstall = ''
+            for lang in $(cat po/LINGUAS); do
+              install -d $out/share/locale/$lang/LC_MESSAGES
+              msgfmt po/$lang.po -o $out/share/locale/$lang/LC_MESSAGES/camper.mo
+            done
             install -Dm644 res/net.knoopx.camper.svg $out/share/icons/hicolor/scalable/apps/net.knoopx.camper.svg
             install -Dm644 res/net.knoopx.camper.desktop $out/share/applications/net.knoopx.camper.desktop
           '';
@@ -0,0 +1,34 @@
+use gtk4::prelude::*;
+use libadwaita as adw;
+
+/// Whether the desktop allows non-essential motion (`gtk-enable-animations`).
+pub fn animations_enabled() -> bool {
+    gtk4::Settings::default()
+        .map(|s| s.is_gtk_enable_animations())
+        .unwrap_or(true)
+}
+
+pub fn high_contrast() -> bool {
+    adw::StyleManager::default().is_high_contrast()
+}
+
+/// Crossfade when motion is allowed, switch instantly otherwise. Follows
+/// later changes to the setting for as long as the stack is alive.
+pub fn bind_stack_transition(stack: &gtk4::Stack) {
+    let apply = |stack: &gtk4::Stack, enabled: bool| {
+        stack.set_transition_type(if enabled {
+            gtk4::StackTransitionType::Crossfade
+        } else {
+            gtk4::StackTransitionType::None
+        });
+    };
+    apply(stack, animations_enabled());
+
+    let Some(settings) = gtk4::Settings::default() else { return };
+    let stack = stack.downgrade();
+    settings.connect_gtk_enable_animations_notify(move |s| {
+        if let Some(stack) = stack.upgrade() {
+            apply(&stack, s.is_gtk_enable_animations());
+        }
+    });
+}
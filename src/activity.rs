@@ -0,0 +1,185 @@
+use gettextrs::gettext;
+use gtk4::glib;
+use gtk4::prelude::*;
+use libadwaita as adw;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How often the indicator looks for changes.
+const POLL: Duration = Duration::from_millis(250);
+
+static TASKS: Mutex<Vec<Task>> = Mutex::new(Vec::new());
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+/// Bumped on every change, so the indicator only redraws when needed.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Work going on in the background, such as a download.
+#[derive(Debug, Clone)]
+pub struct Task {
+    id: u64,
+    pub label: String,
+    /// How much is done, from 0 to 1, when that is known.
+    pub progress: Option<f64>,
+    /// Why it failed; failed tasks stay listed until dismissed.
+    pub error: Option<String>,
+}
+
+/// A running task, listed until dropped.
+#[derive(Debug)]
+pub struct Handle {
+    id: u64,
+}
+
+/// Lists a task as running until the returned handle is dropped.
+pub fn start(label: impl Into<String>) -> Handle {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    update(|tasks| {
+        tasks.push(Task {
+            id,
+            label: label.into(),
+            progress: None,
+            error: None,
+        })
+    });
+    Handle { id }
+}
+
+impl Handle {
+    pub fn progress(&self, fraction: f64) {
+        let fraction = fraction.clamp(0.0, 1.0);
+        update(|tasks| {
+            if let Some(task) = tasks.iter_mut().find(|t| t.id == self.id) {
+                task.progress = Some(fraction);
+            }
+        });
+    }
+
+    /// Ends the task, keeping it listed with `error`.
+    pub fn fail(self, error: impl Into<String>) {
+        let error = error.into();
+        update(|tasks| {
+            if let Some(task) = tasks.iter_mut().find(|t| t.id == self.id) {
+                task.error = Some(error);
+            }
+        });
+    }
+}
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        update(|tasks| tasks.retain(|t| t.id != self.id || t.error.is_some()));
+    }
+}
+
+fn update(f: impl FnOnce(&mut Vec<Task>)) {
+    if let Ok(mut tasks) = TASKS.lock() {
+        f(&mut tasks);
+        GENERATION.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+pub fn tasks() -> Vec<Task> {
+    TASKS.lock().map(|tasks| tasks.clone()).unwrap_or_default()
+}
+
+/// Forgets the tasks that failed.
+pub fn dismiss_failed() {
+    update(|tasks| tasks.retain(|t| t.error.is_none()));
+}
+
+fn build_row(task: &Task) -> gtk4::Box {
+    let row = gtk4::Box::new(gtk4::Orientation::Vertical, 4);
+    let label = gtk4::Label::new(Some(&task.label));
+    label.set_xalign(0.0);
+    label.set_ellipsize(gtk4::pango::EllipsizeMode::End);
+    row.append(&label);
+    match &task.error {
+        Some(error) => {
+            let error = gtk4::Label::new(Some(error));
+            error.set_xalign(0.0);
+            error.set_wrap(true);
+            error.add_css_class("caption");
+            error.add_css_class("error");
+            row.append(&error);
+        }
+        None => {
+            if let Some(fraction) = task.progress {
+                let bar = gtk4::ProgressBar::new();
+                bar.set_fraction(fraction);
+                row.append(&bar);
+            }
+        }
+    }
+    row
+}
+
+/// Header bar button showing a spinner while tasks run, or a warning when
+/// one failed, with a popover listing them.
+pub fn indicator() -> gtk4::MenuButton {
+    let list = gtk4::Box::new(gtk4::Orientation::Vertical, 12);
+    let clear = gtk4::Button::with_label(&gettext("Clear Failed"));
+    clear.add_css_class("flat");
+    clear.set_halign(gtk4::Align::End);
+    clear.connect_clicked(|_| dismiss_failed());
+
+    let content = gtk4::Box::new(gtk4::Orientation::Vertical, 12);
+    content.set_width_request(280);
+    content.set_margin_start(6);
+    content.set_margin_end(6);
+    content.set_margin_top(6);
+    content.set_margin_bottom(6);
+    content.append(&list);
+    content.append(&clear);
+    let popover = gtk4::Popover::new();
+    popover.set_child(Some(&content));
+
+    let spinner = adw::Spinner::new();
+    let warning = gtk4::Image::from_icon_name("dialog-warning-symbolic");
+    let icon = gtk4::Stack::new();
+    icon.add_named(&spinner, Some("busy"));
+    icon.add_named(&warning, Some("failed"));
+
+    let button = gtk4::MenuButton::new();
+    button.set_child(Some(&icon));
+    button.add_css_class("flat");
+    button.set_popover(Some(&popover));
+    button.set_visible(false);
+
+    let mut shown = u64::MAX;
+    let weak = button.downgrade();
+    glib::timeout_add_local(POLL, move || {
+        let Some(button) = weak.upgrade() else {
+            return glib::ControlFlow::Break;
+        };
+        let generation = GENERATION.load(Ordering::Relaxed);
+        if generation == shown {
+            return glib::ControlFlow::Continue;
+        }
+        shown = generation;
+        let tasks = tasks();
+
+        while let Some(child) = list.first_child() {
+            list.remove(&child);
+        }
+        for task in &tasks {
+            list.append(&build_row(task));
+        }
+        let failed = tasks.iter().filter(|t| t.error.is_some()).count();
+        let running = tasks.len() - failed;
+        clear.set_visible(failed > 0);
+        icon.set_visible_child_name(if running > 0 { "busy" } else { "failed" });
+        button.set_visible(!tasks.is_empty());
+        if tasks.is_empty() {
+            button.popdown();
+        }
+        let tooltip = if failed > 0 {
+            gettext("{} tasks failed").replace("{}", &failed.to_string())
+        } else {
+            gettext("{} tasks running").replace("{}", &running.to_string())
+        };
+        button.set_tooltip_text(Some(&tooltip));
+        glib::ControlFlow::Continue
+    });
+    button
+}
@@ -0,0 +1,566 @@
+use crate::album_grid::{card_size, AlbumData, AlbumGridOutput, Click};
+use crate::preferences::CardAction;
+use crate::N_;
+use gettextrs::gettext;
+use gtk4::prelude::*;
+use libadwaita as adw;
+use relm4::Sender;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// How long a card must stay bound before its artwork is requested, so
+/// cards flung past while scrolling never start a download.
+const ART_DELAY: std::time::Duration = std::time::Duration::from_millis(80);
+/// How long the pointer must rest on a card before its album is resolved
+/// ahead of a click.
+const PREFETCH_DELAY: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Entries of the context menu, as actions of the card's `card` group.
+const MENU_ACTIONS: &[(&str, CardAction, &str)] = &[
+    ("play", CardAction::Play, N_("Play")),
+    ("enqueue", CardAction::Enqueue, N_("Add to Queue")),
+    ("open-in-browser", CardAction::OpenInBrowser, N_("Open in Browser")),
+    ("copy-link", CardAction::CopyLink, N_("Copy Link")),
+    ("keep-offline", CardAction::KeepOffline, N_("Keep Offline")),
+    ("download", CardAction::Download, N_("Download")),
+    ("wishlist", CardAction::Wishlist, N_("Add to Wishlist")),
+];
+
+/// The widgets of one album card. Cards are recycled by list views, so
+/// everything that depends on the album is set in [`Card::bind`].
+#[derive(Clone)]
+pub struct Card {
+    pub root: adw::Clamp,
+    art_frame: gtk4::Frame,
+    image: gtk4::Image,
+    /// Shown over the empty frame while artwork loads or after it failed.
+    placeholder: gtk4::Image,
+    title: gtk4::Label,
+    artist: gtk4::Label,
+    genre_label: gtk4::Label,
+    rating_label: gtk4::Label,
+    /// The context menu's rating, holding the bound album's stars.
+    rate_action: gtk4::gio::SimpleAction,
+    /// Whether the bound album is on the listen later list.
+    later_action: gtk4::gio::SimpleAction,
+    play_circle: gtk4::Box,
+    /// Offered for releases not in the library yet.
+    wishlist_button: gtk4::Button,
+    data: Rc<RefCell<Option<AlbumData>>>,
+    art_load: Rc<RefCell<Option<gtk4::glib::JoinHandle<()>>>>,
+}
+
+impl Card {
+    /// Grid views activate their items themselves; cards laid out by hand
+    /// pass `activatable` to handle clicks and Enter on their own.
+    pub fn new(sender: &Sender<AlbumGridOutput>, activatable: bool) -> Self {
+        let card = gtk4::Box::new(gtk4::Orientation::Vertical, 0);
+
+        let size = card_size();
+        let image = gtk4::Image::new();
+        image.set_pixel_size(size);
+        image.set_size_request(size, size);
+
+        let placeholder = gtk4::Image::from_icon_name("folder-music-symbolic");
+        placeholder.set_pixel_size(48);
+        placeholder.add_css_class("art-placeholder");
+        placeholder.set_halign(gtk4::Align::Center);
+        placeholder.set_valign(gtk4::Align::Center);
+        placeholder.set_can_target(false);
+
+        let art_frame = gtk4::Frame::new(None);
+        art_frame.add_css_class("album-art");
+        art_frame.set_child(Some(&image));
+
+        let overlay = gtk4::Overlay::new();
+
+        let play_icon = gtk4::Image::from_icon_name("media-playback-start-symbolic");
+        play_icon.set_pixel_size(24);
+        play_icon.add_css_class("play-overlay-icon");
+        play_icon.set_valign(gtk4::Align::Center);
+        play_icon.set_vexpand(true);
+
+        let play_circle = gtk4::Box::new(gtk4::Orientation::Vertical, 0);
+        play_circle.set_halign(gtk4::Align::Center);
+        play_circle.set_valign(gtk4::Align::Center);
+        play_circle.add_css_class("play-overlay");
+        play_circle.set_width_request(48);
+        play_circle.set_height_request(48);
+        play_circle.append(&play_icon);
+        play_circle.set_opacity(0.0);
+
+        let wishlist_button = gtk4::Button::from_icon_name("emblem-favorite-symbolic");
+        wishlist_button.add_css_class("circular");
+        wishlist_button.add_css_class("osd");
+        wishlist_button.add_css_class("wishlist-button");
+        wishlist_button.set_halign(gtk4::Align::End);
+        wishlist_button.set_valign(gtk4::Align::Start);
+        wishlist_button.set_margin_top(6);
+        wishlist_button.set_margin_end(6);
+        wishlist_button.set_tooltip_text(Some(&gettext("Add to Wishlist")));
+
+        overlay.set_child(Some(&art_frame));
+        overlay.add_overlay(&placeholder);
+        overlay.add_overlay(&play_circle);
+        overlay.add_overlay(&wishlist_button);
+        card.append(&overlay);
+
+        let title = gtk4::Label::new(None);
+        title.set_ellipsize(gtk4::pango::EllipsizeMode::End);
+        title.set_lines(1);
+        title.set_halign(gtk4::Align::Start);
+        title.set_margin_top(4);
+        title.add_css_class("album-title");
+        card.append(&title);
+
+        let artist = gtk4::Label::new(None);
+        artist.set_ellipsize(gtk4::pango::EllipsizeMode::End);
+        artist.set_lines(1);
+        artist.set_halign(gtk4::Align::Start);
+        artist.add_css_class("dim-label");
+        artist.add_css_class("caption");
+        card.append(&artist);
+
+        let genre_label = gtk4::Label::new(None);
+        genre_label.set_ellipsize(gtk4::pango::EllipsizeMode::End);
+        genre_label.set_lines(1);
+        genre_label.set_halign(gtk4::Align::Start);
+        genre_label.add_css_class("dim-label");
+        genre_label.add_css_class("caption");
+        genre_label.add_css_class("album-genre");
+        card.append(&genre_label);
+
+        let rating_label = gtk4::Label::new(None);
+        rating_label.set_halign(gtk4::Align::Start);
+        rating_label.add_css_class("caption");
+        rating_label.add_css_class("album-rating");
+        rating_label.set_visible(false);
+        card.append(&rating_label);
+
+        let rate_action = gtk4::gio::SimpleAction::new_stateful(
+            "rate",
+            Some(gtk4::glib::VariantTy::BYTE),
+            &0u8.to_variant(),
+        );
+        let later_action = gtk4::gio::SimpleAction::new_stateful("listen-later", None, &false.to_variant());
+
+        let root = if activatable {
+            adw::Clamp::builder()
+                .accessible_role(gtk4::AccessibleRole::Button)
+                .focusable(true)
+                .build()
+        } else {
+            adw::Clamp::new()
+        };
+        root.set_maximum_size(size);
+        root.add_css_class("album-card");
+        root.set_child(Some(&card));
+        root.set_cursor_from_name(Some("pointer"));
+
+        let this = Self {
+            root,
+            art_frame,
+            image,
+            placeholder,
+            title,
+            artist,
+            genre_label,
+            rating_label,
+            rate_action,
+            later_action,
+            play_circle,
+            wishlist_button,
+            data: Rc::default(),
+            art_load: Rc::default(),
+        };
+        this.connect_artist_link(sender);
+        this.connect_modified_click(sender);
+        this.connect_context_menu(sender);
+        this.connect_wishlist(sender);
+        this.connect_drag();
+        this.connect_hover(sender);
+        if activatable {
+            this.connect_activate(sender);
+        }
+        this
+    }
+
+    /// Artists with a known band ID link to their discography; claiming the
+    /// press keeps the card from being activated and starting playback.
+    fn connect_artist_link(&self, sender: &Sender<AlbumGridOutput>) {
+        let data = self.data.clone();
+        let sender = sender.clone();
+        let artist_click = gtk4::GestureClick::new();
+        artist_click.connect_pressed(|gesture, _, _, _| {
+            let linked = gesture.widget().is_some_and(|w| w.has_css_class("artist-link"));
+            if linked {
+                gesture.set_state(gtk4::EventSequenceState::Claimed);
+            }
+        });
+        artist_click.connect_released(move |gesture, _, _, _| {
+            let linked = gesture.widget().is_some_and(|w| w.has_css_class("artist-link"));
+            if let (true, Some(data)) = (linked, data.borrow().clone()) {
+                sender.emit(AlbumGridOutput::ArtistClicked(data));
+            }
+        });
+        self.artist.add_controller(artist_click);
+    }
+
+    /// Middle and modified clicks. Claiming the press keeps grid views from
+    /// also activating the card as a plain click.
+    fn connect_modified_click(&self, sender: &Sender<AlbumGridOutput>) {
+        let data = self.data.clone();
+        let sender = sender.clone();
+        let gesture = gtk4::GestureClick::new();
+        gesture.set_button(0);
+        gesture.connect_pressed(|gesture, _, _, _| {
+            if modified_click(gesture).is_some() {
+                gesture.set_state(gtk4::EventSequenceState::Claimed);
+            }
+        });
+        gesture.connect_released(move |gesture, _, _, _| {
+            if let (Some(click), Some(data)) = (modified_click(gesture), data.borrow().clone()) {
+                sender.emit(AlbumGridOutput::Clicked(data, click));
+            }
+        });
+        self.root.add_controller(gesture);
+    }
+
+    /// Right click and long press offer every card action, regardless of
+    /// the click preferences; cards focusable on their own also open the
+    /// menu with the Menu key or Shift+F10.
+    fn connect_context_menu(&self, sender: &Sender<AlbumGridOutput>) {
+        let actions = gtk4::gio::SimpleActionGroup::new();
+        for &(name, action, _) in MENU_ACTIONS {
+            let data = self.data.clone();
+            let sender = sender.clone();
+            let entry = gtk4::gio::SimpleAction::new(name, None);
+            entry.connect_activate(move |_, _| {
+                if let Some(data) = data.borrow().clone() {
+                    sender.emit(AlbumGridOutput::Clicked(data, Click::Menu(action)));
+                }
+            });
+            actions.add_action(&entry);
+        }
+        let (data, sender, label) = (self.data.clone(), sender.clone(), self.rating_label.clone());
+        self.rate_action.connect_activate(move |action, stars| {
+            let Some(stars) = stars.and_then(|s| s.get::<u8>()) else { return };
+            action.set_state(&stars.to_variant());
+            show_rating(&label, stars);
+            if let Some(data) = data.borrow().clone() {
+                sender.emit(AlbumGridOutput::Clicked(data, Click::Rate(Some(stars).filter(|&s| s > 0))));
+            }
+        });
+        actions.add_action(&self.rate_action);
+        let (data, sender) = (self.data.clone(), sender.clone());
+        let note = gtk4::gio::SimpleAction::new("note", None);
+        note.connect_activate(move |_, _| {
+            if let Some(data) = data.borrow().clone() {
+                sender.emit(AlbumGridOutput::Clicked(data, Click::EditNote));
+            }
+        });
+        actions.add_action(&note);
+        let (data, sender) = (self.data.clone(), sender.clone());
+        self.later_action.connect_activate(move |action, _| {
+            let listed = !action.state().and_then(|s| s.get::<bool>()).unwrap_or(false);
+            action.set_state(&listed.to_variant());
+            if let Some(data) = data.borrow().clone() {
+                sender.emit(AlbumGridOutput::Clicked(data, Click::ListenLater(listed)));
+            }
+        });
+        actions.add_action(&self.later_action);
+        self.root.insert_action_group("card", Some(&actions));
+
+        let right_click = gtk4::GestureClick::new();
+        right_click.set_button(gtk4::gdk::BUTTON_SECONDARY);
+        let root = self.root.clone();
+        right_click.connect_pressed(move |gesture, _, x, y| {
+            gesture.set_state(gtk4::EventSequenceState::Claimed);
+            popup_menu(&root, x, y);
+        });
+        self.root.add_controller(right_click);
+
+        let long_press = gtk4::GestureLongPress::new();
+        long_press.set_touch_only(true);
+        let root = self.root.clone();
+        long_press.connect_pressed(move |gesture, x, y| {
+            gesture.set_state(gtk4::EventSequenceState::Claimed);
+            popup_menu(&root, x, y);
+        });
+        self.root.add_controller(long_press);
+
+        let keys = gtk4::EventControllerKey::new();
+        let root = self.root.clone();
+        keys.connect_key_pressed(move |_, key, _, state| {
+            let shift = state.contains(gtk4::gdk::ModifierType::SHIFT_MASK);
+            match key {
+                gtk4::gdk::Key::Menu => {}
+                gtk4::gdk::Key::F10 if shift => {}
+                _ if is_listen_later_key(key, state) => return toggle_listen_later(&root),
+                _ => return gtk4::glib::Propagation::Proceed,
+            }
+            popup_menu(&root, root.width() as f64 / 2.0, root.height() as f64 / 2.0);
+            gtk4::glib::Propagation::Stop
+        });
+        self.root.add_controller(keys);
+    }
+
+    /// The button only sends the request; it stays off until the card is
+    /// bound again, by which time the library knows the outcome.
+    fn connect_wishlist(&self, sender: &Sender<AlbumGridOutput>) {
+        let data = self.data.clone();
+        let sender = sender.clone();
+        self.wishlist_button.connect_clicked(move |button| {
+            if let Some(data) = data.borrow().clone() {
+                button.set_sensitive(false);
+                sender.emit(AlbumGridOutput::Clicked(data, Click::Menu(CardAction::Wishlist)));
+            }
+        });
+    }
+
+    /// Cards are dragged as their album, for the player to queue. Nothing
+    /// outside the app accepts them, so dropping one elsewhere does nothing.
+    fn connect_drag(&self) {
+        let data = self.data.clone();
+        let image = self.image.clone();
+        let source = gtk4::DragSource::new();
+        source.set_actions(gtk4::gdk::DragAction::COPY);
+        source.connect_prepare(move |_, _, _| {
+            let data = data.borrow().clone()?;
+            let object = gtk4::glib::BoxedAnyObject::new(data);
+            Some(gtk4::gdk::ContentProvider::for_value(&object.to_value()))
+        });
+        source.connect_drag_begin(move |source, _| {
+            if let Some(art) = image.paintable() {
+                source.set_icon(Some(&art), 0, 0);
+            }
+        });
+        self.root.add_controller(source);
+    }
+
+    /// Fades the play button in, and asks for the album to be prefetched
+    /// once the pointer rests on the card.
+    fn connect_hover(&self, sender: &Sender<AlbumGridOutput>) {
+        let enter_circle = self.play_circle.clone();
+        let leave_circle = self.play_circle.clone();
+        let prefetch: Rc<RefCell<Option<gtk4::glib::JoinHandle<()>>>> = Rc::default();
+        let motion = gtk4::EventControllerMotion::new();
+        let (data, sender, pending) = (self.data.clone(), sender.clone(), prefetch.clone());
+        motion.connect_enter(move |_, _, _| {
+            let (data, sender) = (data.clone(), sender.clone());
+            let handle = gtk4::glib::spawn_future_local(async move {
+                gtk4::glib::timeout_future(PREFETCH_DELAY).await;
+                if let Some(data) = data.borrow().clone() {
+                    sender.emit(AlbumGridOutput::Hovered(data));
+                }
+            });
+            if let Some(previous) = pending.replace(Some(handle)) {
+                previous.abort();
+            }
+            if !crate::a11y::animations_enabled() {
+                enter_circle.set_opacity(1.0);
+                return;
+            }
+            let target = adw::PropertyAnimationTarget::new(&enter_circle, "opacity");
+            let anim = adw::TimedAnimation::new(&enter_circle, enter_circle.opacity(), 1.0, 150, target);
+            anim.play();
+        });
+        motion.connect_leave(move |_| {
+            if let Some(handle) = prefetch.take() {
+                handle.abort();
+            }
+            if !crate::a11y::animations_enabled() {
+                leave_circle.set_opacity(0.0);
+                return;
+            }
+            let target = adw::PropertyAnimationTarget::new(&leave_circle, "opacity");
+            let anim = adw::TimedAnimation::new(&leave_circle, leave_circle.opacity(), 0.0, 150, target);
+            anim.play();
+        });
+        self.root.add_controller(motion);
+    }
+
+    fn connect_activate(&self, sender: &Sender<AlbumGridOutput>) {
+        let click_data = self.data.clone();
+        let click_sender = sender.clone();
+        let gesture = gtk4::GestureClick::new();
+        gesture.connect_released(move |gesture, _, _, _| {
+            if modified_click(gesture).is_some() {
+                return;
+            }
+            if let Some(data) = click_data.borrow().clone() {
+                click_sender.emit(AlbumGridOutput::Clicked(data, Click::Primary));
+            }
+        });
+        self.root.add_controller(gesture);
+
+        let key_data = self.data.clone();
+        let key_sender = sender.clone();
+        let key_ctrl = gtk4::EventControllerKey::new();
+        key_ctrl.connect_key_pressed(move |_, key, _, _| {
+            let activate = matches!(key, gtk4::gdk::Key::Return | gtk4::gdk::Key::KP_Enter | gtk4::gdk::Key::space);
+            match key_data.borrow().clone() {
+                Some(data) if activate => {
+                    key_sender.emit(AlbumGridOutput::Clicked(data, Click::Primary));
+                    gtk4::glib::Propagation::Stop
+                }
+                _ => gtk4::glib::Propagation::Proceed,
+            }
+        });
+        self.root.add_controller(key_ctrl);
+    }
+
+    /// Fills the card with `data`, or clears it. Artwork loads only while
+    /// the card stays bound; rebinding drops the pending load.
+    pub fn bind(&self, data: Option<&AlbumData>) {
+        if let Some(handle) = self.art_load.take() {
+            handle.abort();
+        }
+        self.play_circle.set_opacity(0.0);
+        self.image.set_paintable(None::<&gtk4::gdk::Paintable>);
+        self.show_placeholder(ArtState::Loading);
+        self.data.replace(data.cloned());
+        self.root.set_tooltip_text(None);
+        let Some(data) = data else { return };
+
+        self.title.set_label(&data.title);
+        self.artist.set_label(&data.artist);
+        if data.band_id.is_some() {
+            self.artist.add_css_class("artist-link");
+            self.artist.set_cursor_from_name(Some("pointer"));
+        } else {
+            self.artist.remove_css_class("artist-link");
+            self.artist.set_cursor(None);
+        }
+        self.wishlist_button.set_visible(!data.url.is_empty() && crate::library::owned(&data.url).is_none());
+        self.wishlist_button.set_sensitive(true);
+        self.wishlist_button.update_property(&[gtk4::accessible::Property::Label(
+            &gettext("Add “{}” to wishlist").replace("{}", &data.title),
+        )]);
+        self.genre_label.set_label(data.genre.as_deref().unwrap_or(""));
+        self.genre_label.set_visible(data.genre.is_some());
+        let stars = crate::ratings::get(&data.url).unwrap_or(0);
+        self.rate_action.set_enabled(!data.url.is_empty());
+        self.rate_action.set_state(&stars.to_variant());
+        show_rating(&self.rating_label, stars);
+        self.later_action.set_enabled(!data.url.is_empty());
+        self.later_action.set_state(&crate::listen_later::contains(&data.url).to_variant());
+        // Notes show on hover, where they don't crowd the grid.
+        self.root.set_tooltip_text(crate::notes::get(&data.url).as_deref());
+        self.root.update_property(&[
+            gtk4::accessible::Property::Label(&accessible_label(data)),
+            gtk4::accessible::Property::Description(data.genre.as_deref().unwrap_or("")),
+        ]);
+
+        let Some(url) = data.art_url.as_deref().map(crate::data_saver::art_url) else { return };
+        let size = card_size() * self.image.scale_factor();
+        let card = self.clone();
+        let handle = gtk4::glib::spawn_future_local(async move {
+            gtk4::glib::timeout_future(ART_DELAY).await;
+            // Failures aren't cached, so binding the card again (scrolling
+            // back to it, or the grid's retry once online) tries again.
+            match crate::image_loader::load(&url, size).await {
+                Some(texture) => {
+                    card.image.set_paintable(Some(&texture));
+                    card.show_placeholder(ArtState::Loaded);
+                }
+                None => card.show_placeholder(ArtState::Failed),
+            }
+        });
+        self.art_load.replace(Some(handle));
+    }
+
+    fn show_placeholder(&self, state: ArtState) {
+        self.placeholder.set_visible(state != ArtState::Loaded);
+        self.placeholder.set_icon_name(Some(match state {
+            ArtState::Failed => "image-missing-symbolic",
+            _ => "folder-music-symbolic",
+        }));
+        if state == ArtState::Failed {
+            self.art_frame.add_css_class("failed");
+        } else {
+            self.art_frame.remove_css_class("failed");
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArtState {
+    Loading,
+    Loaded,
+    Failed,
+}
+
+fn modified_click(gesture: &gtk4::GestureClick) -> Option<Click> {
+    let state = gesture.current_event_state();
+    match gesture.current_button() {
+        gtk4::gdk::BUTTON_MIDDLE => Some(Click::Middle),
+        gtk4::gdk::BUTTON_PRIMARY if state.contains(gtk4::gdk::ModifierType::CONTROL_MASK) => Some(Click::Middle),
+        gtk4::gdk::BUTTON_PRIMARY if state.contains(gtk4::gdk::ModifierType::SHIFT_MASK) => Some(Click::Shift),
+        _ => None,
+    }
+}
+
+fn show_rating(label: &gtk4::Label, stars: u8) {
+    label.set_label(&crate::ratings::stars(stars));
+    label.set_visible(stars > 0);
+    label.update_property(&[gtk4::accessible::Property::Label(&rating_label(stars))]);
+}
+
+/// L, without Ctrl or Alt, puts the focused card's album on the listen
+/// later list or takes it off.
+pub fn is_listen_later_key(key: gtk4::gdk::Key, state: gtk4::gdk::ModifierType) -> bool {
+    let modifiers = gtk4::gdk::ModifierType::CONTROL_MASK | gtk4::gdk::ModifierType::ALT_MASK;
+    matches!(key, gtk4::gdk::Key::l | gtk4::gdk::Key::L) && !state.intersects(modifiers)
+}
+
+/// Toggles the listen later entry of the card rooted at `root`.
+pub fn toggle_listen_later(root: &impl IsA<gtk4::Widget>) -> gtk4::glib::Propagation {
+    match root.activate_action("card.listen-later", None) {
+        Ok(()) => gtk4::glib::Propagation::Stop,
+        Err(_) => gtk4::glib::Propagation::Proceed,
+    }
+}
+
+fn popup_menu(root: &adw::Clamp, x: f64, y: f64) {
+    let model = gtk4::gio::Menu::new();
+    for &(name, _, label) in MENU_ACTIONS {
+        model.append(Some(&gettext(label)), Some(&format!("card.{name}")));
+    }
+    let rating = gtk4::gio::Menu::new();
+    for stars in 0..=crate::ratings::MAX {
+        let item = gtk4::gio::MenuItem::new(Some(&rating_label(stars)), None);
+        item.set_action_and_target_value(Some("card.rate"), Some(&stars.to_variant()));
+        rating.append_item(&item);
+    }
+    model.append_submenu(Some(&gettext("Rating")), &rating);
+    model.append(Some(&gettext("Edit Note…")), Some("card.note"));
+    model.append(Some(&gettext("Listen Later")), Some("card.listen-later"));
+    let menu = gtk4::PopoverMenu::from_model(Some(&model));
+    menu.set_parent(root);
+    menu.set_has_arrow(false);
+    menu.set_halign(gtk4::Align::Start);
+    menu.set_pointing_to(Some(&gtk4::gdk::Rectangle::new(x as i32, y as i32, 1, 1)));
+    // Unparenting right away would drop the menu before the chosen action
+    // is activated.
+    menu.connect_closed(|menu| {
+        let menu = menu.clone();
+        gtk4::glib::idle_add_local_once(move || menu.unparent());
+    });
+    menu.popup();
+}
+
+/// A rating in words, e.g. "3 Stars".
+pub fn rating_label(stars: u8) -> String {
+    match stars {
+        0 => gettext("No Rating"),
+        1 => gettext("1 Star"),
+        n => gettext("{} Stars").replace("{}", &n.to_string()),
+    }
+}
+
+pub fn accessible_label(data: &AlbumData) -> String {
+    gettext("{title} by {artist}")
+        .replace("{title}", &data.title)
+        .replace("{artist}", &data.artist)
+}
@@ -1,10 +1,71 @@
-use gtk4::gdk_pixbuf::Pixbuf;
+use crate::album_card::Card;
+use crate::bandcamp::ClientError;
+use crate::preferences::CardAction;
+use gettextrs::gettext;
+use gtk4::glib::BoxedAnyObject;
 use gtk4::prelude::*;
 use libadwaita as adw;
 use libadwaita::prelude::*;
 use relm4::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+/// Edge length of card artwork in pixels, adjusted by the zoom actions.
+pub const CARD_SIZE_DEFAULT: i32 = 180;
+pub const CARD_SIZE_MIN: i32 = 120;
+pub const CARD_SIZE_MAX: i32 = 360;
+pub const CARD_SIZE_STEP: i32 = 30;
+
+/// Albums are added to the grid this many at a time, one batch per idle
+/// callback, so replacing a large library never stalls the main loop.
+const APPEND_CHUNK: usize = 200;
+
+/// Artwork of this many albums per batch is downloaded ahead of the cards
+/// showing it, behind whatever cards are on screen.
+const PREFETCH_LIMIT: usize = 96;
+
+/// Entries of the index rail; `#` collects titles not starting with A–Z.
+const INDEX_LETTERS: &str = "#ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+thread_local! {
+    static CARD_SIZE: Cell<i32> = const { Cell::new(CARD_SIZE_DEFAULT) };
+    /// Every live grid, so size changes and network recovery reach cards
+    /// already built.
+    static GRIDS: RefCell<Vec<(gtk4::glib::WeakRef<gtk4::GridView>, relm4::Sender<AlbumGridMsg>)>> =
+        const { RefCell::new(Vec::new()) };
+}
 
-#[derive(Debug, Clone)]
+fn broadcast(msg: impl Fn() -> AlbumGridMsg) {
+    GRIDS.with(|grids| {
+        grids.borrow_mut().retain(|(grid, sender)| {
+            let alive = grid.upgrade().is_some();
+            if alive {
+                sender.emit(msg());
+            }
+            alive
+        });
+    });
+}
+
+pub fn card_size() -> i32 {
+    CARD_SIZE.with(Cell::get)
+}
+
+/// Resizes the cards of all grids, including ones created later.
+pub fn set_card_size(size: i32) {
+    let size = size.clamp(CARD_SIZE_MIN, CARD_SIZE_MAX);
+    CARD_SIZE.with(|c| c.set(size));
+    broadcast(|| AlbumGridMsg::CardSizeChanged);
+}
+
+/// Reloads artwork that failed to load, e.g. once the network is back.
+pub fn retry_artwork() {
+    broadcast(|| AlbumGridMsg::RetryArt);
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AlbumData {
     pub title: String,
     pub artist: String,
@@ -14,6 +75,8 @@ pub struct AlbumData {
     pub band_id: Option<u64>,
     pub item_id: Option<u64>,
     pub item_type: Option<String>,
+    /// Download page, for purchases.
+    pub download_url: Option<String>,
 }
 
 impl From<crate::bandcamp::Album> for AlbumData {
@@ -27,6 +90,7 @@ impl From<crate::bandcamp::Album> for AlbumData {
             band_id: a.band_id,
             item_id: a.item_id,
             item_type: a.item_type,
+            download_url: None,
         }
     }
 }
@@ -36,32 +100,142 @@ impl From<crate::bandcamp::CollectionItem> for AlbumData {
         Self {
             title: item.title,
             artist: item.artist,
-            genre: None,
+            genre: crate::genres::get(&item.url),
             art_url: item.art_url,
             url: item.url,
+            band_id: item.band_id,
+            item_id: item.item_id,
+            item_type: item.item_type,
+            download_url: item.download_url,
+        }
+    }
+}
+
+impl From<&crate::bandcamp::Purchase> for AlbumData {
+    fn from(purchase: &crate::bandcamp::Purchase) -> Self {
+        Self {
+            title: purchase.title.clone(),
+            artist: purchase.artist.clone(),
+            genre: None,
+            art_url: purchase.art_url.clone(),
+            url: purchase.url.clone(),
+            band_id: None,
+            item_id: None,
+            item_type: None,
+            download_url: purchase.download_url.clone(),
+        }
+    }
+}
+
+/// The release a playing track is from, as far as the track knows it.
+impl From<crate::player::Track> for AlbumData {
+    fn from(track: crate::player::Track) -> Self {
+        let url = crate::bandcamp::parse_release_link(&track.url).map_or(track.url, |link| link.url);
+        Self {
+            title: if track.album.is_empty() { track.title } else { track.album },
+            artist: track.album_artist,
+            genre: None,
+            art_url: track.art_url,
+            url,
             band_id: None,
             item_id: None,
             item_type: None,
+            download_url: None,
         }
     }
 }
 
+/// A titled group of albums, e.g. one artist's releases.
+#[derive(Debug, Clone)]
+pub struct Section {
+    pub title: String,
+    pub albums: Vec<AlbumData>,
+}
+
+/// One line of cards in a sectioned grid. List views can't wrap items, so
+/// sections are laid out as rows of `columns` cards.
+struct Row {
+    section: String,
+    albums: Vec<AlbumData>,
+    columns: usize,
+}
+
 pub struct AlbumGrid {
-    wrap_box: adw::WrapBox,
+    /// `BoxedAnyObject`s holding `AlbumData`, shown through a recycling
+    /// grid view so only visible cards exist as widgets.
+    store: gtk4::gio::ListStore,
+    /// Albums not yet moved into `store` by the idle callback.
+    pending: Rc<RefCell<VecDeque<AlbumData>>>,
+    appending: Rc<RefCell<Option<gtk4::glib::SourceId>>>,
+    /// Artwork prefetches for the albums shown, stopped when they're replaced.
+    prefetches: RefCell<Vec<tokio::task::AbortHandle>>,
+    progress: gtk4::ProgressBar,
+    grid_view: gtk4::GridView,
+    /// Letter buttons for jumping through name-sorted grids.
+    index_rail: gtk4::ScrolledWindow,
+    index_buttons: Vec<(char, gtk4::Button)>,
+    /// Grouped albums, shown instead of `store` while not empty.
+    sections: Vec<Section>,
+    /// One `ListStore` of `Row`s per section, flattened into the list view.
+    section_store: gtk4::gio::ListStore,
+    sections_view: gtk4::ListView,
+    sections_scroll: gtk4::ScrolledWindow,
+    columns: usize,
     stack: gtk4::Stack,
-    current: Vec<AlbumData>,
+    error_page: adw::StatusPage,
 }
 
 #[derive(Debug)]
 pub enum AlbumGridMsg {
     Append(Vec<AlbumData>),
     Replace(Vec<AlbumData>),
+    /// Shows the albums grouped under headers.
+    ReplaceSections(Vec<Section>),
+    /// A fetch started. Albums already shown stay visible.
+    Loading,
+    /// A fetch failed. Albums already shown stay visible. Rate-limited
+    /// fetches are retried once the limit lifts.
+    Error(ClientError),
+    /// Moves keyboard focus to the first card.
+    Focus,
+    /// Shows the letter index; only meaningful while sorted by title.
+    ShowIndex(bool),
+    JumpTo(char),
+    /// The width changed; sections may need another column count.
+    Relayout,
+    CardSizeChanged,
+    /// Rebinds the visible cards, reloading artwork that failed before.
+    RetryArt,
+}
+
+/// How a card was clicked. What the non-primary clicks do is set in the
+/// preferences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Click {
+    Primary,
+    /// Middle click, or Ctrl and the primary button.
+    Middle,
+    /// Shift and the primary button.
+    Shift,
+    /// An entry of the card's context menu.
+    Menu(CardAction),
+    /// Stars picked in the card's context menu; none takes the rating away.
+    Rate(Option<u8>),
+    /// "Edit Note…" in the card's context menu.
+    EditNote,
+    /// "Listen Later" toggled in the card's context menu, or with L.
+    ListenLater(bool),
 }
 
 #[derive(Debug, Clone)]
 pub enum AlbumGridOutput {
-    Clicked(AlbumData),
+    Clicked(AlbumData, Click),
+    ArtistClicked(AlbumData),
+    /// The pointer rested on a card; its album may be clicked next.
+    Hovered(AlbumData),
     ScrolledToBottom,
+    /// The error page's retry button was pressed.
+    Retry,
 }
 
 #[relm4::component(pub)]
@@ -79,41 +253,147 @@ impl SimpleComponent for AlbumGrid {
     }
 
     fn init(_: Self::Init, root: Self::Root, sender: ComponentSender<Self>) -> ComponentParts<Self> {
-        let wrap_box = adw::WrapBox::new();
-        wrap_box.set_child_spacing(6);
-        wrap_box.set_line_spacing(8);
-        wrap_box.set_margin_start(8);
-        wrap_box.set_margin_end(8);
-        wrap_box.set_margin_top(8);
-        wrap_box.set_margin_bottom(8);
-        wrap_box.set_valign(gtk4::Align::Start);
-        wrap_box.set_halign(gtk4::Align::Fill);
-        wrap_box.set_justify(adw::JustifyMode::Fill);
+        let store = gtk4::gio::ListStore::new::<BoxedAnyObject>();
+
+        let factory = gtk4::SignalListItemFactory::new();
+        let s = sender.output_sender().clone();
+        factory.connect_setup(move |_, item| {
+            if let Some(item) = item.downcast_ref::<gtk4::ListItem>() {
+                setup_grid_item(item, &s);
+            }
+        });
+
+        let grid_view = gtk4::GridView::new(
+            Some(gtk4::NoSelection::new(Some(store.clone()))),
+            Some(factory),
+        );
+        grid_view.add_css_class("album-grid");
+        grid_view.set_max_columns(32);
+        grid_view.set_single_click_activate(true);
+        // Tab leaves the grid instead of stepping through every card;
+        // arrows move between cards.
+        grid_view.set_tab_behavior(gtk4::ListTabBehavior::Item);
+        grid_view.set_margin_start(8);
+        grid_view.set_margin_end(8);
+        grid_view.set_margin_top(8);
+        grid_view.set_margin_bottom(8);
+        let s = sender.clone();
+        grid_view.connect_activate(move |grid, position| {
+            if let Some(data) = grid.model().and_then(|m| m.item(position)).as_ref().and_then(album_data) {
+                s.output(AlbumGridOutput::Clicked(data, Click::Primary)).ok();
+            }
+        });
+
+        // Focus rests on the cells around the cards, out of reach of the
+        // cards' own key handling.
+        let keys = gtk4::EventControllerKey::new();
+        let grid = grid_view.downgrade();
+        keys.connect_key_pressed(move |_, key, _, state| {
+            let card = grid.upgrade().and_then(|g| g.focus_child()).and_then(|cell| cell.first_child());
+            match card {
+                Some(card) if crate::album_card::is_listen_later_key(key, state) => {
+                    crate::album_card::toggle_listen_later(&card)
+                }
+                _ => gtk4::glib::Propagation::Proceed,
+            }
+        });
+        grid_view.add_controller(keys);
 
         let scroll = gtk4::ScrolledWindow::new();
         scroll.set_hscrollbar_policy(gtk4::PolicyType::Never);
         scroll.set_vexpand(true);
         scroll.set_hexpand(true);
-        scroll.set_child(Some(&wrap_box));
+        scroll.set_child(Some(&grid_view));
+
+        let progress = gtk4::ProgressBar::new();
+        progress.add_css_class("osd");
+        progress.set_valign(gtk4::Align::Start);
+        progress.set_visible(false);
+
+        let overlay = gtk4::Overlay::new();
+        overlay.set_child(Some(&scroll));
+        overlay.add_overlay(&progress);
+
+        let index_box = gtk4::Box::new(gtk4::Orientation::Vertical, 0);
+        index_box.add_css_class("index-rail");
+        index_box.set_valign(gtk4::Align::Center);
+        let index_buttons: Vec<(char, gtk4::Button)> = INDEX_LETTERS
+            .chars()
+            .map(|letter| {
+                let button = gtk4::Button::with_label(&letter.to_string());
+                button.add_css_class("flat");
+                button.set_tooltip_text(Some(&gettext("Jump to {}").replace("{}", &letter.to_string())));
+                let s = sender.clone();
+                button.connect_clicked(move |_| s.input(AlbumGridMsg::JumpTo(letter)));
+                index_box.append(&button);
+                (letter, button)
+            })
+            .collect();
+        let index_rail = gtk4::ScrolledWindow::new();
+        index_rail.set_policy(gtk4::PolicyType::Never, gtk4::PolicyType::External);
+        index_rail.set_child(Some(&index_box));
+        index_rail.set_visible(false);
+
+        let content = gtk4::Box::new(gtk4::Orientation::Horizontal, 0);
+        content.append(&overlay);
+        content.append(&index_rail);
+
+        let (sections_page, section_store, sections_view, sections_scroll) = build_sections_view(&sender);
 
         let empty_page = adw::StatusPage::new();
         empty_page.set_icon_name(Some("folder-music-symbolic"));
-        empty_page.set_title("No Albums");
+        empty_page.set_title(&gettext("No Albums"));
         empty_page.set_vexpand(true);
 
+        let loading_page = adw::StatusPage::new();
+        loading_page.set_paintable(Some(&adw::SpinnerPaintable::new(Some(&loading_page))));
+        loading_page.set_title(&gettext("Loading…"));
+        loading_page.set_vexpand(true);
+
+        let error_page = adw::StatusPage::new();
+        error_page.set_icon_name(Some("network-error-symbolic"));
+        error_page.set_title(&gettext("Couldn't Load Albums"));
+        error_page.set_vexpand(true);
+        let retry_btn = gtk4::Button::with_label(&gettext("Try Again"));
+        retry_btn.add_css_class("pill");
+        retry_btn.add_css_class("suggested-action");
+        retry_btn.set_halign(gtk4::Align::Center);
+        let s = sender.clone();
+        retry_btn.connect_clicked(move |_| {
+            s.output(AlbumGridOutput::Retry).ok();
+        });
+        error_page.set_child(Some(&retry_btn));
+
         let stack = gtk4::Stack::new();
         stack.set_vexpand(true);
         stack.set_hexpand(true);
-        stack.set_transition_type(gtk4::StackTransitionType::Crossfade);
         stack.set_transition_duration(150);
+        crate::a11y::bind_stack_transition(&stack);
         stack.add_named(&empty_page, Some("empty"));
-        stack.add_named(&scroll, Some("content"));
+        stack.add_named(&loading_page, Some("loading"));
+        stack.add_named(&error_page, Some("error"));
+        stack.add_named(&content, Some("content"));
+        stack.add_named(&sections_page, Some("sections"));
         stack.set_visible_child_name("empty");
 
+        GRIDS.with(|grids| grids.borrow_mut().push((grid_view.downgrade(), sender.input_sender().clone())));
+
         let model = Self {
-            wrap_box,
+            store,
+            pending: Rc::default(),
+            appending: Rc::default(),
+            prefetches: RefCell::default(),
+            progress,
+            grid_view,
+            index_rail,
+            index_buttons,
+            sections: Vec::new(),
+            section_store,
+            sections_view,
+            sections_scroll,
+            columns: 1,
             stack: stack.clone(),
-            current: Vec::new(),
+            error_page,
         };
         let widgets = view_output!();
         root.append(&stack);
@@ -132,158 +412,457 @@ impl SimpleComponent for AlbumGrid {
     fn update(&mut self, msg: Self::Input, sender: ComponentSender<Self>) {
         match msg {
             AlbumGridMsg::Append(items) => {
-                if !items.is_empty() {
-                    self.stack.set_visible_child_name("content");
-                }
-                self.append_cards(&items, &sender);
-                self.current.extend(items);
+                self.queue(items);
+                self.show_current();
+                self.update_index();
             }
             AlbumGridMsg::Replace(items) => {
-                if self.same_albums(&items) {
-                    return;
+                self.clear_sections();
+                if !self.same_albums(&items) {
+                    self.cancel_pending();
+                    self.store.remove_all();
+                    self.queue(items);
+                }
+                self.show_current();
+                self.update_index();
+            }
+            AlbumGridMsg::ReplaceSections(sections) => {
+                self.cancel_pending();
+                self.store.remove_all();
+                self.sections = sections.into_iter().filter(|s| !s.albums.is_empty()).collect();
+                self.columns = self.fitting_columns();
+                self.rebuild_rows();
+                self.show_current();
+            }
+            AlbumGridMsg::Loading => {
+                if self.is_empty() {
+                    self.stack.set_visible_child_name("loading");
+                }
+            }
+            AlbumGridMsg::Error(e) => {
+                if self.is_empty() {
+                    self.error_page.set_description(Some(&e.message()));
+                    self.stack.set_visible_child_name("error");
+                }
+                if let ClientError::RateLimited(secs) = e {
+                    let s = sender.clone();
+                    gtk4::glib::timeout_add_seconds_local_once(secs as u32, move || {
+                        s.output(AlbumGridOutput::Retry).ok();
+                    });
+                }
+            }
+            AlbumGridMsg::Focus => {
+                if !self.sections.is_empty() {
+                    self.sections_view.child_focus(gtk4::DirectionType::TabForward);
+                } else if self.store.n_items() > 0 {
+                    self.grid_view.scroll_to(0, gtk4::ListScrollFlags::FOCUS, None);
                 }
-                while let Some(child) = self.wrap_box.first_child() {
-                    self.wrap_box.remove(&child);
+            }
+            AlbumGridMsg::ShowIndex(show) => {
+                self.index_rail.set_visible(show);
+                self.update_index();
+            }
+            AlbumGridMsg::JumpTo(letter) => {
+                // Later letters may still be waiting for the idle callback.
+                self.flush_pending();
+                let target = letter_rank(letter);
+                let position = (0..self.store.n_items()).find(|&i| {
+                    self.store
+                        .item(i)
+                        .as_ref()
+                        .and_then(album_data)
+                        .is_some_and(|data| letter_rank(index_letter(&data.title)) >= target)
+                });
+                if let Some(position) = position {
+                    self.grid_view.scroll_to(position, gtk4::ListScrollFlags::FOCUS, None);
                 }
-                if items.is_empty() {
-                    self.stack.set_visible_child_name("empty");
-                } else {
-                    self.stack.set_visible_child_name("content");
-                    self.append_cards(&items, &sender);
+            }
+            AlbumGridMsg::Relayout => {
+                let columns = self.fitting_columns();
+                if columns != self.columns && !self.sections.is_empty() {
+                    self.columns = columns;
+                    self.rebuild_rows();
+                }
+            }
+            AlbumGridMsg::CardSizeChanged => {
+                // Swapping the factories out and back rebuilds the visible
+                // cards, which read the size when they are set up.
+                let factory = self.grid_view.factory();
+                self.grid_view.set_factory(None::<&gtk4::ListItemFactory>);
+                self.grid_view.set_factory(factory.as_ref());
+                let factory = self.sections_view.factory();
+                self.sections_view.set_factory(None::<&gtk4::ListItemFactory>);
+                self.sections_view.set_factory(factory.as_ref());
+                sender.input(AlbumGridMsg::Relayout);
+            }
+            AlbumGridMsg::RetryArt => {
+                let n = self.store.n_items();
+                self.store.items_changed(0, n, n);
+                if !self.sections.is_empty() {
+                    self.rebuild_rows();
                 }
-                self.current = items;
             }
         }
     }
 }
 
 impl AlbumGrid {
-    fn append_cards(&self, items: &[AlbumData], sender: &ComponentSender<Self>) {
-        for data in items {
-            let card = build_card(data, sender);
-            self.wrap_box.append(&card);
+    fn is_empty(&self) -> bool {
+        self.store.n_items() == 0 && self.sections.is_empty()
+    }
+
+    fn show_current(&self) {
+        let page = if !self.sections.is_empty() {
+            "sections"
+        } else if self.store.n_items() > 0 {
+            "content"
+        } else {
+            "empty"
+        };
+        self.stack.set_visible_child_name(page);
+    }
+
+    /// Adds the first batch right away and the rest from an idle callback.
+    fn queue(&self, items: Vec<AlbumData>) {
+        self.prefetch(items.iter().filter_map(|a| a.art_url.clone()).take(PREFETCH_LIMIT).collect());
+        self.pending.borrow_mut().extend(items);
+        if !append_chunk(&self.store, &self.pending, &self.progress) || self.appending.borrow().is_some() {
+            return;
         }
+        let store = self.store.clone();
+        let pending = self.pending.clone();
+        let progress = self.progress.clone();
+        let appending = self.appending.clone();
+        let id = gtk4::glib::idle_add_local(move || {
+            if append_chunk(&store, &pending, &progress) {
+                gtk4::glib::ControlFlow::Continue
+            } else {
+                appending.take();
+                gtk4::glib::ControlFlow::Break
+            }
+        });
+        *self.appending.borrow_mut() = Some(id);
     }
 
-    fn same_albums(&self, items: &[AlbumData]) -> bool {
-        self.current.len() == items.len()
-            && self.current.iter().zip(items).all(|(a, b)| a.url == b.url)
+    fn flush_pending(&self) {
+        while append_chunk(&self.store, &self.pending, &self.progress) {}
+        if let Some(id) = self.appending.take() {
+            id.remove();
+        }
     }
-}
 
-fn build_card(data: &AlbumData, sender: &ComponentSender<AlbumGrid>) -> adw::Clamp {
-    let card = gtk4::Box::new(gtk4::Orientation::Vertical, 0);
-
-    let image = gtk4::Image::new();
-    image.set_pixel_size(180);
-
-    let art_frame = gtk4::Frame::new(None);
-    art_frame.add_css_class("album-art");
-    art_frame.set_child(Some(&image));
-
-    let overlay = gtk4::Overlay::new();
-
-    let play_icon = gtk4::Image::from_icon_name("media-playback-start-symbolic");
-    play_icon.set_pixel_size(24);
-    play_icon.add_css_class("play-overlay-icon");
-    play_icon.set_valign(gtk4::Align::Center);
-    play_icon.set_vexpand(true);
-
-    let play_circle = gtk4::Box::new(gtk4::Orientation::Vertical, 0);
-    play_circle.set_halign(gtk4::Align::Center);
-    play_circle.set_valign(gtk4::Align::Center);
-    play_circle.add_css_class("play-overlay");
-    play_circle.set_width_request(48);
-    play_circle.set_height_request(48);
-    play_circle.append(&play_icon);
-    play_circle.set_opacity(0.0);
-
-    overlay.set_child(Some(&art_frame));
-    overlay.add_overlay(&play_circle);
-    card.append(&overlay);
-
-    let title = gtk4::Label::new(Some(&data.title));
-    title.set_ellipsize(gtk4::pango::EllipsizeMode::End);
-    title.set_lines(1);
-    title.set_halign(gtk4::Align::Start);
-    title.set_margin_top(4);
-    title.add_css_class("album-title");
-    card.append(&title);
-
-    let artist = gtk4::Label::new(Some(&data.artist));
-    artist.set_ellipsize(gtk4::pango::EllipsizeMode::End);
-    artist.set_lines(1);
-    artist.set_halign(gtk4::Align::Start);
-    artist.add_css_class("dim-label");
-    artist.add_css_class("caption");
-    card.append(&artist);
-
-    if let Some(genre) = &data.genre {
-        let genre_label = gtk4::Label::new(Some(genre));
-        genre_label.set_ellipsize(gtk4::pango::EllipsizeMode::End);
-        genre_label.set_lines(1);
-        genre_label.set_halign(gtk4::Align::Start);
-        genre_label.add_css_class("dim-label");
-        genre_label.add_css_class("caption");
-        genre_label.add_css_class("album-genre");
-        card.append(&genre_label);
+    /// Greys out letters no album starts with.
+    fn update_index(&self) {
+        if !self.index_rail.is_visible() {
+            return;
+        }
+        let mut present = std::collections::HashSet::new();
+        for i in 0..self.store.n_items() {
+            if let Some(data) = self.store.item(i).as_ref().and_then(album_data) {
+                present.insert(index_letter(&data.title));
+            }
+        }
+        present.extend(self.pending.borrow().iter().map(|data| index_letter(&data.title)));
+        for (letter, button) in &self.index_buttons {
+            button.set_sensitive(present.contains(letter));
+        }
     }
 
-    if let Some(url) = data.art_url.clone() {
-        gtk4::glib::spawn_future_local(async move {
-            if let Ok(resp) = reqwest::get(&url).await {
-                if let Ok(bytes) = resp.bytes().await {
-                    let stream = gtk4::gio::MemoryInputStream::from_bytes(&gtk4::glib::Bytes::from(&bytes));
-                    if let Ok(pb) = Pixbuf::from_stream(&stream, None::<&gtk4::gio::Cancellable>) {
-                        let texture = gtk4::gdk::Texture::for_pixbuf(&pb);
-                        image.set_paintable(Some(&texture));
-                    }
-                }
+    fn prefetch(&self, urls: Vec<String>) {
+        if urls.is_empty() || crate::data_saver::active() {
+            return;
+        }
+        let task = relm4::spawn(async move {
+            let mut downloads = tokio::task::JoinSet::new();
+            for url in urls {
+                downloads.spawn(crate::art_cache::prefetch(url));
             }
+            while downloads.join_next().await.is_some() {}
         });
+        let mut prefetches = self.prefetches.borrow_mut();
+        prefetches.retain(|handle| !handle.is_finished());
+        prefetches.push(task.abort_handle());
+    }
+
+    fn cancel_pending(&self) {
+        for handle in self.prefetches.take() {
+            handle.abort();
+        }
+        self.pending.borrow_mut().clear();
+        if let Some(id) = self.appending.take() {
+            id.remove();
+        }
+        self.progress.set_visible(false);
+    }
+
+    fn same_albums(&self, items: &[AlbumData]) -> bool {
+        self.pending.borrow().is_empty()
+            && self.store.n_items() as usize == items.len()
+            && items.iter().enumerate().all(|(i, b)| {
+                self.store
+                    .item(i as u32)
+                    .as_ref()
+                    .and_then(album_data)
+                    .is_some_and(|a| a.url == b.url)
+            })
+    }
+
+    fn clear_sections(&mut self) {
+        if !self.sections.is_empty() {
+            self.sections.clear();
+            self.section_store.remove_all();
+        }
+    }
+
+    /// How many cards fit side by side in the sections view.
+    fn fitting_columns(&self) -> usize {
+        let width = self.sections_scroll.width().max(self.stack.width()) - 16;
+        let cell = card_size() + ROW_SPACING;
+        ((width + ROW_SPACING) / cell).max(1) as usize
+    }
+
+    fn rebuild_rows(&self) {
+        let columns = self.columns;
+        let sections: Vec<gtk4::gio::ListStore> = self
+            .sections
+            .iter()
+            .map(|section| {
+                let rows = gtk4::gio::ListStore::new::<BoxedAnyObject>();
+                let objects: Vec<BoxedAnyObject> = section
+                    .albums
+                    .chunks(columns)
+                    .map(|chunk| {
+                        BoxedAnyObject::new(Row {
+                            section: section.title.clone(),
+                            albums: chunk.to_vec(),
+                            columns,
+                        })
+                    })
+                    .collect();
+                rows.extend_from_slice(&objects);
+                rows
+            })
+            .collect();
+        self.section_store.splice(0, self.section_store.n_items(), &sections);
     }
+}
+
+/// Space between cards in a sectioned row.
+const ROW_SPACING: i32 = 6;
+
+/// Builds the list view used for sectioned grids, with a header per
+/// section and a copy of the current section's header pinned to the top
+/// once the real one has scrolled away.
+fn build_sections_view(
+    sender: &ComponentSender<AlbumGrid>,
+) -> (gtk4::Overlay, gtk4::gio::ListStore, gtk4::ListView, gtk4::ScrolledWindow) {
+    let section_store = gtk4::gio::ListStore::new::<gtk4::gio::ListStore>();
+    let flattened = gtk4::FlattenListModel::new(Some(section_store.clone()));
+
+    // Row widgets and the section each currently shows, for the pinned header.
+    let rows: Rc<RefCell<Vec<(gtk4::glib::WeakRef<gtk4::Box>, Rc<RefCell<String>>)>>> = Rc::default();
+
+    let row_factory = gtk4::SignalListItemFactory::new();
+    let s = sender.output_sender().clone();
+    let registry = rows.clone();
+    row_factory.connect_setup(move |_, item| {
+        if let Some(item) = item.downcast_ref::<gtk4::ListItem>() {
+            setup_row(item, &s, &registry);
+        }
+    });
+
+    let header_factory = gtk4::SignalListItemFactory::new();
+    header_factory.connect_setup(|_, item| {
+        let Some(header) = item.downcast_ref::<gtk4::ListHeader>() else { return };
+        let label = gtk4::Label::new(None);
+        label.add_css_class("heading");
+        label.add_css_class("section-header");
+        label.set_halign(gtk4::Align::Start);
+        label.set_ellipsize(gtk4::pango::EllipsizeMode::End);
+        header.set_child(Some(&label));
+        header.connect_item_notify(move |header| {
+            let title = header.item().and_downcast::<BoxedAnyObject>().map(|row| row.borrow::<Row>().section.clone());
+            label.set_label(title.as_deref().unwrap_or(""));
+        });
+    });
 
-    let clamp = adw::Clamp::new();
-    clamp.set_maximum_size(180);
-    clamp.set_child(Some(&card));
-    clamp.set_focusable(true);
-    clamp.set_cursor_from_name(Some("pointer"));
-
-    let enter_circle = play_circle.clone();
-    let leave_circle = play_circle.clone();
-    let motion = gtk4::EventControllerMotion::new();
-    motion.connect_enter(move |_, _, _| {
-        let target = adw::PropertyAnimationTarget::new(&enter_circle, "opacity");
-        let anim = adw::TimedAnimation::new(&enter_circle, enter_circle.opacity(), 1.0, 150, target);
-        anim.play();
+    let sections_view = gtk4::ListView::new(Some(gtk4::NoSelection::new(Some(flattened))), Some(row_factory));
+    sections_view.set_header_factory(Some(&header_factory));
+    sections_view.add_css_class("album-sections");
+    sections_view.set_margin_start(8);
+    sections_view.set_margin_end(8);
+
+    let sections_scroll = gtk4::ScrolledWindow::new();
+    sections_scroll.set_hscrollbar_policy(gtk4::PolicyType::Never);
+    sections_scroll.set_vexpand(true);
+    sections_scroll.set_hexpand(true);
+    sections_scroll.set_child(Some(&sections_view));
+
+    let pinned = gtk4::Label::new(None);
+    pinned.add_css_class("heading");
+    pinned.add_css_class("section-header");
+    pinned.add_css_class("pinned");
+    pinned.set_halign(gtk4::Align::Fill);
+    pinned.set_valign(gtk4::Align::Start);
+    pinned.set_xalign(0.0);
+    pinned.set_can_target(false);
+    pinned.set_visible(false);
+
+    let view = sections_view.clone();
+    let header = pinned.clone();
+    sections_scroll.vadjustment().connect_value_changed(move |adj| {
+        let current = (adj.value() > 0.0).then(|| top_section(&view, &rows)).flatten();
+        header.set_visible(current.is_some());
+        header.set_label(current.as_deref().unwrap_or(""));
     });
-    motion.connect_leave(move |_| {
-        let target = adw::PropertyAnimationTarget::new(&leave_circle, "opacity");
-        let anim = adw::TimedAnimation::new(&leave_circle, leave_circle.opacity(), 0.0, 150, target);
-        anim.play();
+
+    // Column counts follow the width.
+    let s = sender.input_sender().clone();
+    sections_scroll.hadjustment().connect_page_size_notify(move |_| {
+        s.emit(AlbumGridMsg::Relayout);
     });
-    clamp.add_controller(motion);
 
-    let click_data = data.clone();
-    let click_sender = sender.clone();
-    let gesture = gtk4::GestureClick::new();
-    gesture.connect_released(move |_, _, _, _| {
-        click_sender.output(AlbumGridOutput::Clicked(click_data.clone())).ok();
+    let page = gtk4::Overlay::new();
+    page.set_child(Some(&sections_scroll));
+    page.add_overlay(&pinned);
+
+    (page, section_store, sections_view, sections_scroll)
+}
+
+/// The section of the row at the top of the view, or `None` while a real
+/// header is there.
+fn top_section(
+    view: &gtk4::ListView,
+    rows: &RefCell<Vec<(gtk4::glib::WeakRef<gtk4::Box>, Rc<RefCell<String>>)>>,
+) -> Option<String> {
+    let mut widget = view.pick(24.0, 1.0, gtk4::PickFlags::DEFAULT);
+    while let Some(w) = widget {
+        if w.has_css_class("section-header") {
+            return None;
+        }
+        if w.has_css_class("album-row") {
+            let rows = rows.borrow();
+            let (_, section) = rows.iter().find(|(row, _)| row.upgrade().is_some_and(|row| row.upcast_ref::<gtk4::Widget>() == &w))?;
+            let section = section.borrow().clone();
+            return Some(section);
+        }
+        widget = w.parent();
+    }
+    None
+}
+
+fn setup_row(
+    list_item: &gtk4::ListItem,
+    sender: &relm4::Sender<AlbumGridOutput>,
+    registry: &RefCell<Vec<(gtk4::glib::WeakRef<gtk4::Box>, Rc<RefCell<String>>)>>,
+) {
+    // Focus goes to the cards, not the row around them.
+    list_item.set_activatable(false);
+    list_item.set_focusable(false);
+
+    let row = gtk4::Box::new(gtk4::Orientation::Horizontal, ROW_SPACING);
+    row.set_homogeneous(true);
+    row.add_css_class("album-row");
+    list_item.set_child(Some(&row));
+
+    let section = Rc::new(RefCell::new(String::new()));
+    let mut registry = registry.borrow_mut();
+    registry.retain(|(row, _)| row.upgrade().is_some());
+    registry.push((row.downgrade(), section.clone()));
+
+    let cards: RefCell<Vec<Card>> = RefCell::default();
+    let sender = sender.clone();
+    list_item.connect_item_notify(move |list_item| {
+        let object = list_item.item().and_downcast::<BoxedAnyObject>();
+        let data = object.as_ref().map(|o| o.borrow::<Row>());
+        let columns = data.as_ref().map_or(0, |row| row.columns);
+        section.replace(data.as_ref().map(|row| row.section.clone()).unwrap_or_default());
+
+        let mut cards = cards.borrow_mut();
+        while cards.len() < columns {
+            let card = Card::new(&sender, true);
+            row.append(&card.root);
+            cards.push(card);
+        }
+        for (i, card) in cards.iter().enumerate() {
+            let album = data.as_ref().and_then(|row| row.albums.get(i));
+            card.bind(album);
+            // Slots past the end of a section keep the row's alignment.
+            card.root.set_visible(i < columns);
+            card.root.set_opacity(if album.is_some() { 1.0 } else { 0.0 });
+            card.root.set_can_target(album.is_some());
+            card.root.set_focusable(album.is_some());
+        }
     });
-    clamp.add_controller(gesture);
+}
+
+/// The index rail entry a title is listed under. Only the first character
+/// counts, matching how the library sorts by name.
+fn index_letter(title: &str) -> char {
+    match title.chars().next().map(|c| c.to_ascii_uppercase()) {
+        Some(c @ 'A'..='Z') => c,
+        _ => '#',
+    }
+}
+
+/// Position of a letter on the rail, `#` first.
+fn letter_rank(letter: char) -> usize {
+    INDEX_LETTERS.find(letter).unwrap_or(0)
+}
 
-    let key_data = data.clone();
-    let key_sender = sender.clone();
+/// Lets Down in a toolbar entry continue into the results, from where the
+/// grid view's own arrow, Page Up/Down and Home/End bindings take over.
+pub fn connect_focus_results(entry: &impl IsA<gtk4::Widget>, focus: impl Fn() + 'static) {
     let key_ctrl = gtk4::EventControllerKey::new();
     key_ctrl.connect_key_pressed(move |_, key, _, _| {
-        if key == gtk4::gdk::Key::Return || key == gtk4::gdk::Key::KP_Enter || key == gtk4::gdk::Key::space {
-            key_sender.output(AlbumGridOutput::Clicked(key_data.clone())).ok();
+        if key == gtk4::gdk::Key::Down {
+            focus();
             gtk4::glib::Propagation::Stop
         } else {
             gtk4::glib::Propagation::Proceed
         }
     });
-    clamp.add_controller(key_ctrl);
+    entry.add_controller(key_ctrl);
+}
+
+/// Moves the next batch of pending albums into the store and returns
+/// whether any are left.
+fn append_chunk(
+    store: &gtk4::gio::ListStore,
+    pending: &RefCell<VecDeque<AlbumData>>,
+    progress: &gtk4::ProgressBar,
+) -> bool {
+    let (chunk, remaining) = {
+        let mut pending = pending.borrow_mut();
+        let n = pending.len().min(APPEND_CHUNK);
+        let chunk: Vec<BoxedAnyObject> = pending.drain(..n).map(BoxedAnyObject::new).collect();
+        (chunk, pending.len())
+    };
+    store.extend_from_slice(&chunk);
+
+    let shown = store.n_items() as usize;
+    progress.set_fraction(shown as f64 / (shown + remaining).max(1) as f64);
+    progress.set_visible(remaining > 0);
+    remaining > 0
+}
 
-    clamp
+pub fn album_data(object: &gtk4::glib::Object) -> Option<AlbumData> {
+    let boxed = object.downcast_ref::<BoxedAnyObject>()?;
+    let data = boxed.borrow::<AlbumData>().clone();
+    Some(data)
+}
+
+/// Grid items hold a recycled card that is rebound whenever the item
+/// changes; activation is handled by the grid view.
+fn setup_grid_item(list_item: &gtk4::ListItem, sender: &relm4::Sender<AlbumGridOutput>) {
+    let card = Card::new(sender, false);
+    list_item.set_child(Some(&card.root));
+    list_item.connect_item_notify(move |list_item| {
+        let data = list_item.item().as_ref().and_then(album_data);
+        if let Some(data) = &data {
+            list_item.set_accessible_label(&crate::album_card::accessible_label(data));
+            list_item.set_accessible_description(data.genre.as_deref().unwrap_or(""));
+        }
+        card.bind(data.as_ref());
+    });
 }
@@ -1,16 +1,81 @@
-use crate::album_grid::AlbumData;
-use crate::bandcamp::{AlbumDetails, BandcampClient};
+use crate::activity;
+use crate::album_grid::{self, AlbumData, Click};
+use crate::artist::{ArtistInit, ArtistOutput, ArtistPage};
+use crate::audio_cache;
+use crate::data_saver;
+use crate::bandcamp::{self, Account, AlbumDetails, BandcampClient, ClientError, FanProfile, ReleaseLink};
+use crate::calendar::{self, CalendarInit, CalendarOutput, CalendarPage};
+use crate::diagnostics;
 use crate::discover::{DiscoverMsg, DiscoverOutput, DiscoverPage};
+use crate::downloads;
+use crate::duplicates::{DuplicatesInit, DuplicatesOutput, DuplicatesPage};
+use crate::exchange;
+use crate::genres;
+use crate::overflow;
 use crate::library::{LibraryMsg, LibraryOutput, LibraryPage};
-use crate::login::{LoginOutput, LoginPage};
+use crate::listen_later;
+use crate::login::{LoginMsg, LoginOutput, LoginPage};
+use crate::notes;
 use crate::player::{Player, PlayerMsg, PlayerOutput, Track};
+use crate::plays;
+use crate::portal::GlobalShortcut;
+use crate::recap;
+use crate::remote;
+use crate::purchases::{PurchasesOutput, PurchasesPage};
+use crate::ratings;
+use crate::preferences::{
+    CardAction, ColorScheme, CookieBackend, DataSaver, DownloadFormat, HistoryRetention, StartPage,
+};
 use crate::search::{SearchMsg, SearchOutput, SearchPage};
-use crate::storage::{self, UiState};
-use gtk4::gdk;
+use crate::skips;
+use crate::storage::{self, Settings, UiState};
+use gettextrs::gettext;
+use gtk4::{gdk, gio};
 use gtk4::prelude::*;
 use libadwaita as adw;
 use libadwaita::prelude::*;
+use relm4::actions::{AccelsPlus, RelmAction, RelmActionGroup};
 use relm4::prelude::*;
+use std::collections::HashMap;
+
+relm4::new_action_group!(WindowActionGroup, "win");
+relm4::new_stateless_action!(PreferencesAction, WindowActionGroup, "preferences");
+relm4::new_stateless_action!(ShortcutsAction, WindowActionGroup, "shortcuts");
+relm4::new_stateless_action!(DiagnosticsAction, WindowActionGroup, "diagnostics");
+relm4::new_stateless_action!(RecapAction, WindowActionGroup, "recap");
+relm4::new_stateless_action!(CalendarAction, WindowActionGroup, "calendar");
+relm4::new_stateless_action!(PurchasesAction, WindowActionGroup, "purchases");
+relm4::new_stateless_action!(DuplicatesAction, WindowActionGroup, "duplicates");
+relm4::new_stateless_action!(AboutAction, WindowActionGroup, "about");
+relm4::new_stateless_action!(ProfileAction, WindowActionGroup, "profile");
+relm4::new_stateless_action!(LogoutAction, WindowActionGroup, "logout");
+relm4::new_stateless_action!(QuitAction, WindowActionGroup, "quit");
+relm4::new_stateless_action!(ZoomInAction, WindowActionGroup, "zoom-in");
+relm4::new_stateless_action!(ZoomOutAction, WindowActionGroup, "zoom-out");
+relm4::new_stateless_action!(ZoomResetAction, WindowActionGroup, "zoom-reset");
+
+/// Tabs in the order they appear in the view switcher.
+const TABS: [&str; 3] = ["search", "discover", "library"];
+
+/// How many destructive actions can be undone.
+const UNDO_LIMIT: usize = 20;
+
+/// How often the session is refreshed while signed in.
+const KEEP_ALIVE_SECS: u32 = 6 * 60 * 60;
+
+/// How often release reminders due are looked for.
+const REMINDER_CHECK_SECS: u32 = 60 * 60;
+
+/// Albums loaded at once when several are queued together.
+const QUEUE_CONCURRENCY: usize = 4;
+
+/// A destructive action that can be reverted with Ctrl+Z or a toast.
+#[derive(Debug)]
+enum UndoEntry {
+    QueueTrack(usize, Track),
+    /// A release added to the wishlist (`true`) or taken off it.
+    Wishlist(AlbumData, bool),
+}
 
 fn find_child_by_name(widget: &impl IsA<gtk4::Widget>, name: &str) -> Option<gtk4::Widget> {
     let mut child = widget.first_child();
@@ -26,6 +91,33 @@ fn find_child_by_name(widget: &impl IsA<gtk4::Widget>, name: &str) -> Option<gtk
     None
 }
 
+/// Whether the controller's current event happened over a child of its
+/// widget that scrolls sideways itself, such as a carousel, a grid wider
+/// than the window or a slider.
+fn over_sideways_scroller(controller: &impl IsA<gtk4::EventController>) -> bool {
+    let Some(widget) = controller.widget() else { return false };
+    let Some(native) = widget.native() else { return false };
+    let Some((x, y)) = controller.current_event().and_then(|event| event.position()) else { return false };
+    let (dx, dy) = native.surface_transform();
+    let mut target = native.pick(x - dx, y - dy, gtk4::PickFlags::DEFAULT);
+    while let Some(w) = target {
+        if w == widget {
+            break;
+        }
+        let scrolls = w.is::<adw::Carousel>()
+            || w.is::<gtk4::Scale>()
+            || w.is::<crate::waveform::Waveform>()
+            || w
+                .downcast_ref::<gtk4::ScrolledWindow>()
+                .is_some_and(|sw| sw.hscrollbar_policy() != gtk4::PolicyType::Never);
+        if scrolls {
+            return true;
+        }
+        target = w.parent();
+    }
+    false
+}
+
 pub struct App {
     mode: AppMode,
     login: Controller<LoginPage>,
@@ -33,16 +125,75 @@ pub struct App {
     search: Option<Controller<SearchPage>>,
     library: Option<Controller<LibraryPage>>,
     player: Option<Controller<Player>>,
+    artist_pages: Vec<Controller<ArtistPage>>,
+    calendar: Option<Controller<CalendarPage>>,
+    purchases: Option<Controller<PurchasesPage>>,
+    duplicates: Option<Controller<DuplicatesPage>>,
     client: Option<BandcampClient>,
+    profile: Option<FanProfile>,
     current_album: Option<AlbumDetails>,
+    /// Genres of the cards releases were played from, by release URL.
+    genres: HashMap<String, String>,
+    /// Albums being loaded to play together; starting anything else to
+    /// play drops the rest.
+    queue_batch: Option<QueueBatch>,
+    /// Tells results of a batch apart from those of the one before.
+    next_batch: u64,
     toast_overlay: adw::ToastOverlay,
     toolbars: Option<Toolbars>,
     narrow_breakpoint: adw::Breakpoint,
+    mobile_breakpoint: adw::Breakpoint,
+    /// The player's volume and extras, folded into a menu on narrow
+    /// windows.
+    player_fold: Option<overflow::Fold>,
+    settings: Settings,
+    settings_save: storage::Debouncer,
     ui_state: UiState,
+    ui_state_save: storage::Debouncer,
+    pending_link: Option<ReleaseLink>,
+    last_clipboard_link: Option<String>,
+    online: bool,
+    retry: Option<AppMsg>,
+    /// Oldest first, each with the ID its toast undoes it by.
+    undo_stack: Vec<(u64, UndoEntry)>,
+    next_undo_id: u64,
+    keep_alive: Option<gtk4::glib::SourceId>,
+    reminder_check: Option<gtk4::glib::SourceId>,
+    /// An automatic refresh skipped to save data, run once that stops.
+    deferred_refresh: bool,
+    /// Whether the user was already asked to sign in again.
+    session_expired: bool,
+    remote: Option<remote::Server>,
+    /// Listens for the portal's global shortcuts while they are enabled.
+    global_shortcuts: Option<gtk4::glib::JoinHandle<()>>,
+}
+
+/// What can be done about a failed request.
+enum Retry {
+    /// Nothing but trying again by hand.
+    Manual,
+    /// The page that failed retries by itself.
+    Automatic,
+    /// Offered on the toast, and sent by itself once a rate limit lifts.
+    With(AppMsg),
+}
+
+/// Albums being loaded to play one after another, see
+/// [`AppMsg::PlayAlbums`].
+struct QueueBatch {
+    id: u64,
+    task: activity::Handle,
+    total: usize,
+    /// Albums loaded so far, whether they could be queued or not.
+    done: usize,
+    queued: usize,
 }
 
 struct Toolbars {
     stack: gtk4::Stack,
+    discover: crate::discover::Toolbar,
+    /// Filters and sorting, folded into menus on narrow windows.
+    _folds: Vec<overflow::Fold>,
 }
 
 #[derive(Debug, Default, PartialEq)]
@@ -57,18 +208,134 @@ pub enum AppMsg {
     LoginSuccess(String),
     ClientReady(BandcampClient),
     ClientError(String),
+    ProfileLoaded(FanProfile),
+    OpenProfile,
     DiscoverAction(DiscoverOutput),
     SearchAction(SearchOutput),
     LibraryAction(LibraryOutput),
+    ArtistAction(ArtistOutput),
+    CalendarAction(CalendarOutput),
+    PurchasesAction(PurchasesOutput),
+    DuplicatesAction(DuplicatesOutput),
+    /// Opens the artist of an album: their discography, or the releases
+    /// given, when opened from the library.
+    ShowArtist(AlbumData, Option<Vec<AlbumData>>),
+    /// Opens an artist or label account by its ID.
+    ShowAccount(Account),
+    FollowArtist { band_id: u64, url: String, name: String },
+    Followed(Result<String, ClientError>),
+    NavigationPopped(adw::NavigationPage),
     PlayerAction(PlayerOutput),
+    /// An album card was clicked; what happens depends on the click.
+    ActivateAlbum(AlbumData, Click),
     PlayAlbum(AlbumData),
-    AlbumLoaded(Result<AlbumDetails, String>),
+    /// Play several albums in turn, starting with the first as soon as it
+    /// loads and queueing the others as they follow.
+    PlayAlbums(Vec<AlbumData>),
+    /// The next album of a batch loaded.
+    BatchAlbum(u64, Result<AlbumDetails, ClientError>),
+    BatchDone(u64),
+    EnqueueAlbum(AlbumData),
+    /// Download every track of the album to play it offline.
+    PinAlbum(AlbumData),
+    AlbumPinned(Result<String, String>),
+    /// Save a purchase to the download folder.
+    DownloadAlbum(AlbumData),
+    AlbumDownloaded(Result<String, String>),
+    /// Add the release to the wishlist on Bandcamp, or take it off.
+    WishlistAlbum(AlbumData, bool),
+    /// Give the release at this URL stars, or take its rating away.
+    RateAlbum(String, Option<u8>),
+    /// Open the note on the release at this URL, titled so, for editing.
+    EditNote(String, String),
+    /// Replace the note on the release at this URL; blank removes it.
+    SetNote(String, String),
+    /// Put the release on the listen later list, or take it off.
+    ListenLater(AlbumData, bool),
+    Wishlisted(Result<(AlbumData, bool), ClientError>),
+    EnqueueUrl(String),
+    /// Play the first search result for the query.
+    SearchAndPlay(String),
+    SearchAndPlayDone(Result<Option<AlbumData>, ClientError>, String),
+    /// An album to append to the queue.
+    AlbumQueued(Result<AlbumDetails, ClientError>),
+    PlayLink(ReleaseLink),
+    OpenUrl(String),
+    /// An album to play, starting at a track and optionally some seconds
+    /// into it.
+    AlbumLoaded(Result<AlbumDetails, ClientError>, usize, Option<f64>),
     OpenInBrowser,
     TabChanged,
     SaveUiState,
+    SaveSettings,
+    ExportSettings,
+    ImportSettings,
+    SettingsImported(storage::Bundle),
     Logout,
+    ShowPreferences,
+    ShowShortcuts,
+    ShowDiagnostics,
+    /// The listening recap for a year.
+    ShowRecap(i32),
+    /// Upcoming releases of wishlisted and followed artists.
+    ShowCalendar,
+    /// Announce the releases reminded of that came out.
+    CheckReminders,
+    /// The fan's order history.
+    ShowPurchases,
+    /// Likely duplicates in the collection, to hide from the library.
+    ShowDuplicates,
+    ShowAbout,
+    SetColorScheme(ColorScheme),
+    SetWatchClipboard(bool),
+    SetRunInBackground(bool),
+    SetGlobalShortcuts(bool),
+    SetCrossfeed(bool),
+    SetStartPage(StartPage),
+    SetResumePlayback(bool),
+    SetAutoRefreshLibrary(bool),
+    SetDataSaver(DataSaver),
+    /// Show prices converted to this currency, or as sold with `None`.
+    SetPriceCurrency(Option<String>),
+    /// The connection became metered or stopped being so.
+    MeteredChanged,
+    SetCardSize(i32),
+    SetCookieBackend(CookieBackend),
+    SetHistoryRetention(HistoryRetention),
+    SetEphemeralLogin(bool),
+    SetMiddleClick(CardAction),
+    SetShiftClick(CardAction),
+    SetAudioCache(bool),
+    SetDownloadDir(String),
+    SetDownloadTemplate(String),
+    SetDownloadFormat(DownloadFormat),
+    SetRemoteControl(bool),
+    SetRemotePort(u16),
+    SetRemoteToken(String),
+    KeepAlive,
+    ClearHistory,
+    Raise,
+    Quit,
+    ClipboardChanged,
+    ClipboardText(String),
     ShowToast(String),
+    /// A toast whose button feeds `action` back into the app.
+    ShowActionToast {
+        title: String,
+        button: String,
+        action: Box<AppMsg>,
+    },
+    SelectTab(String),
+    /// Browse a tag in Discover, by its slug.
+    ShowTag(String),
+    NetworkChanged(bool),
+    /// Revert the entry with this ID, or the most recent one.
+    Undo(Option<u64>),
+    /// Move to the next (`1`) or previous (`-1`) tab.
+    SwipeTab(i32),
     PlayerToggle,
+    PlayerSetPlaying(bool),
+    PlayerSetShuffle(bool),
     PlayerNext,
     PlayerPrev,
     PlayerVolumeUp,
@@ -84,47 +351,83 @@ impl Component for App {
 
     view! {
         adw::ApplicationWindow {
-            set_title: Some("Camper"),
+            set_title: Some(&gettext("Camper")),
             set_default_width: 625,
             set_default_height: 625,
-            set_size_request: (625, 400),
+            set_size_request: (360, 400),
 
             #[local_ref]
             toast_overlay -> adw::ToastOverlay {
                 #[name = "main_stack"]
                 gtk4::Stack {
-                    set_transition_type: gtk4::StackTransitionType::Crossfade,
 
                     add_named[Some("login")] = model.login.widget() {},
 
-                    add_named[Some("main")] = &gtk4::Box {
-                        set_orientation: gtk4::Orientation::Vertical,
+                    #[name = "bottom_sheet"]
+                    add_named[Some("main")] = &adw::BottomSheet {
+                        set_can_open: false,
+
+                        #[wrap(Some)]
+                        set_content = &gtk4::Box {
+                            set_orientation: gtk4::Orientation::Vertical,
+
+                            #[name = "header_bar"]
+                            adw::HeaderBar {
+                                #[wrap(Some)]
+                                #[name = "view_switcher"]
+                                set_title_widget = &adw::ViewSwitcher {
+                                    set_policy: adw::ViewSwitcherPolicy::Wide,
+                                },
 
-                        #[name = "header_bar"]
-                        adw::HeaderBar {
-                            #[wrap(Some)]
-                            #[name = "view_switcher"]
-                            set_title_widget = &adw::ViewSwitcher {
-                                set_policy: adw::ViewSwitcherPolicy::Wide,
+                                pack_end = &gtk4::MenuButton {
+                                    set_icon_name: "open-menu-symbolic",
+                                    set_tooltip_text: Some(&gettext("Main Menu")),
+                                    set_primary: true,
+                                    set_menu_model: Some(&primary_menu),
+                                },
+
+                                #[name = "account_button"]
+                                pack_end = &gtk4::MenuButton {
+                                    add_css_class: "flat",
+                                    add_css_class: "circular",
+                                    #[watch]
+                                    set_tooltip_text: model.profile.as_ref().map(|p| p.name.as_str()),
+
+                                    #[wrap(Some)]
+                                    #[name = "avatar"]
+                                    set_child = &adw::Avatar {
+                                        set_size: 24,
+                                        set_show_initials: true,
+                                        #[watch]
+                                        set_text: model.profile.as_ref().map(|p| p.name.as_str()),
+                                    },
+                                },
                             },
 
-                            #[name = "logout_button"]
-                            pack_end = &gtk4::Button {
-                                set_icon_name: "system-log-out-symbolic",
-                                set_tooltip_text: Some("Logout"),
-                                connect_clicked => AppMsg::Logout,
+                            adw::Banner {
+                                set_title: &gettext("You're offline — showing your cached library"),
+                                #[watch]
+                                set_revealed: !model.online,
                             },
-                        },
 
-                        #[name = "content_stack"]
-                        adw::ViewStack {
-                            set_vexpand: true,
-                        },
+                            #[name = "content_stack"]
+                            adw::ViewStack {
+                                set_vexpand: true,
+                            },
 
-                        gtk4::Separator {},
+                            #[name = "switcher_bar"]
+                            adw::ViewSwitcherBar {},
+                        },
 
+                        #[wrap(Some)]
                         #[name = "player_box"]
-                        gtk4::Box {},
+                        set_bottom_bar = &gtk4::Box {},
+
+                        #[wrap(Some)]
+                        #[name = "now_playing_box"]
+                        set_sheet = &gtk4::Box {
+                            set_orientation: gtk4::Orientation::Vertical,
+                        },
                     },
                 },
             },
@@ -140,20 +443,54 @@ impl Component for App {
             gtk4::STYLE_PROVIDER_PRIORITY_APPLICATION,
         );
 
+        storage::migrate_legacy_layout();
+        let mut settings = storage::load_settings();
+        let remote = start_remote(&mut settings);
+
         let login = LoginPage::builder()
-            .launch(())
+            .launch(settings.ephemeral_login.unwrap_or(false))
             .forward(sender.input_sender(), |msg| match msg {
                 LoginOutput::Success(cookies) => AppMsg::LoginSuccess(cookies),
             });
 
+        // Loaded per account once signed in.
+        let ui_state = UiState::default();
+        settings.color_scheme.unwrap_or_default().apply();
+        album_grid::set_card_size(settings.card_size.unwrap_or(album_grid::CARD_SIZE_DEFAULT));
+
         let toast_overlay = adw::ToastOverlay::new();
 
+        let network_monitor = gio::NetworkMonitor::default();
+        let s = sender.clone();
+        network_monitor.connect_network_changed(move |_, available| {
+            s.input(AppMsg::NetworkChanged(available));
+        });
+        let s = sender.clone();
+        network_monitor.connect_network_metered_notify(move |_| {
+            s.input(AppMsg::MeteredChanged);
+        });
+        data_saver::update(settings.data_saver.unwrap_or_default());
+        set_price_currency(settings.price_currency.clone());
+
         let narrow_breakpoint = adw::Breakpoint::new(adw::BreakpointCondition::new_length(
             adw::BreakpointConditionLengthType::MaxWidth,
             900.0,
             adw::LengthUnit::Px,
         ));
 
+        // Only the last matching breakpoint applies, so the mobile one
+        // repeats the narrow setters it still needs.
+        let mobile_breakpoint = adw::Breakpoint::new(adw::BreakpointCondition::new_length(
+            adw::BreakpointConditionLengthType::MaxWidth,
+            600.0,
+            adw::LengthUnit::Sp,
+        ));
+
+        let global_shortcuts = settings
+            .global_shortcuts
+            .unwrap_or(false)
+            .then(|| bind_global_shortcuts(&root, &sender));
+
         let model = Self {
             mode: AppMode::Login,
             login,
@@ -161,29 +498,132 @@ impl Component for App {
             search: None,
             library: None,
             player: None,
+            artist_pages: Vec::new(),
+            calendar: None,
+            purchases: None,
+            duplicates: None,
             client: None,
+            profile: None,
             current_album: None,
+            genres: HashMap::new(),
+            queue_batch: None,
+            next_batch: 0,
             toast_overlay: toast_overlay.clone(),
             toolbars: None,
             narrow_breakpoint: narrow_breakpoint.clone(),
-            ui_state: storage::load_ui_state(),
+            mobile_breakpoint: mobile_breakpoint.clone(),
+            player_fold: None,
+            settings,
+            settings_save: storage::Debouncer::default(),
+            ui_state,
+            ui_state_save: storage::Debouncer::default(),
+            pending_link: None,
+            last_clipboard_link: None,
+            online: network_monitor.is_network_available(),
+            retry: None,
+            undo_stack: Vec::new(),
+            next_undo_id: 0,
+            keep_alive: None,
+            reminder_check: None,
+            deferred_refresh: false,
+            session_expired: false,
+            remote,
+            global_shortcuts,
         };
 
         let toast_overlay = &model.toast_overlay;
+        let primary_menu = primary_menu();
         let widgets = view_output!();
+        crate::a11y::bind_stack_transition(&widgets.main_stack);
+        widgets.header_bar.pack_end(&activity::indicator());
 
         narrow_breakpoint.add_setter(
             &widgets.view_switcher,
             "policy",
             Some(&adw::ViewSwitcherPolicy::Narrow.to_value()),
         );
-        narrow_breakpoint.add_setter(
-            &widgets.logout_button,
-            "visible",
-            Some(&false.to_value()),
-        );
         root.add_breakpoint(narrow_breakpoint);
 
+        widgets.switcher_bar.set_stack(Some(&widgets.content_stack));
+        mobile_breakpoint.add_setter(&widgets.view_switcher, "visible", Some(&false.to_value()));
+        mobile_breakpoint.add_setter(&widgets.switcher_bar, "reveal", Some(&true.to_value()));
+        mobile_breakpoint.add_setter(&widgets.bottom_sheet, "can-open", Some(&true.to_value()));
+        root.add_breakpoint(mobile_breakpoint);
+
+        let mut actions = RelmActionGroup::<WindowActionGroup>::new();
+        let s = sender.clone();
+        actions.add_action(RelmAction::<PreferencesAction>::new_stateless(move |_| {
+            s.input(AppMsg::ShowPreferences);
+        }));
+        let s = sender.clone();
+        actions.add_action(RelmAction::<ShortcutsAction>::new_stateless(move |_| {
+            s.input(AppMsg::ShowShortcuts);
+        }));
+        let s = sender.clone();
+        actions.add_action(RelmAction::<DiagnosticsAction>::new_stateless(move |_| {
+            s.input(AppMsg::ShowDiagnostics);
+        }));
+        let s = sender.clone();
+        actions.add_action(RelmAction::<RecapAction>::new_stateless(move |_| {
+            s.input(AppMsg::ShowRecap(recap::default_year()));
+        }));
+        let s = sender.clone();
+        actions.add_action(RelmAction::<CalendarAction>::new_stateless(move |_| {
+            s.input(AppMsg::ShowCalendar);
+        }));
+        let s = sender.clone();
+        actions.add_action(RelmAction::<PurchasesAction>::new_stateless(move |_| {
+            s.input(AppMsg::ShowPurchases);
+        }));
+        let s = sender.clone();
+        actions.add_action(RelmAction::<DuplicatesAction>::new_stateless(move |_| {
+            s.input(AppMsg::ShowDuplicates);
+        }));
+        let s = sender.clone();
+        actions.add_action(RelmAction::<AboutAction>::new_stateless(move |_| {
+            s.input(AppMsg::ShowAbout);
+        }));
+        let s = sender.clone();
+        actions.add_action(RelmAction::<ProfileAction>::new_stateless(move |_| {
+            s.input(AppMsg::OpenProfile);
+        }));
+        let s = sender.clone();
+        actions.add_action(RelmAction::<LogoutAction>::new_stateless(move |_| {
+            s.input(AppMsg::Logout);
+        }));
+        let s = sender.clone();
+        actions.add_action(RelmAction::<QuitAction>::new_stateless(move |_| {
+            s.input(AppMsg::Quit);
+        }));
+        let s = sender.clone();
+        actions.add_action(RelmAction::<ZoomInAction>::new_stateless(move |_| {
+            s.input(AppMsg::SetCardSize(album_grid::card_size() + album_grid::CARD_SIZE_STEP));
+        }));
+        let s = sender.clone();
+        actions.add_action(RelmAction::<ZoomOutAction>::new_stateless(move |_| {
+            s.input(AppMsg::SetCardSize(album_grid::card_size() - album_grid::CARD_SIZE_STEP));
+        }));
+        let s = sender.clone();
+        actions.add_action(RelmAction::<ZoomResetAction>::new_stateless(move |_| {
+            s.input(AppMsg::SetCardSize(album_grid::CARD_SIZE_DEFAULT));
+        }));
+        actions.register_for_widget(&root);
+
+        let app = relm4::main_application();
+        app.set_accelerators_for_action::<PreferencesAction>(&["<Control>comma"]);
+        app.set_accelerators_for_action::<ShortcutsAction>(&["<Control>question"]);
+        app.set_accelerators_for_action::<QuitAction>(&["<Control>q"]);
+        app.set_accelerators_for_action::<ZoomInAction>(&["<Control>plus", "<Control>equal", "<Control>KP_Add"]);
+        app.set_accelerators_for_action::<ZoomOutAction>(&["<Control>minus", "<Control>KP_Subtract"]);
+        app.set_accelerators_for_action::<ZoomResetAction>(&["<Control>0", "<Control>KP_0"]);
+
+        // With background playback enabled, closing only hides the window;
+        // it comes back through MPRIS Raise or by launching the app again.
+        root.set_hide_on_close(model.settings.run_in_background.unwrap_or(false));
+        if model.settings.run_in_background.unwrap_or(false) {
+            request_background(&root, &sender);
+        }
+
         let s = sender.clone();
         let content_stack = widgets.content_stack.clone();
         let key_ctrl = gtk4::EventControllerKey::new();
@@ -217,6 +657,22 @@ impl Component for App {
 
             if !focused_on_text {
                 match key {
+                    gdk::Key::v if ctrl => {
+                        let s = s.clone();
+                        content_stack.clipboard().read_text_async(
+                            None::<&gtk4::gio::Cancellable>,
+                            move |result| {
+                                if let Ok(Some(text)) = result {
+                                    s.input(AppMsg::OpenUrl(text.to_string()));
+                                }
+                            },
+                        );
+                        return gtk4::glib::Propagation::Stop;
+                    }
+                    gdk::Key::z if ctrl => {
+                        s.input(AppMsg::Undo(None));
+                        return gtk4::glib::Propagation::Stop;
+                    }
                     gdk::Key::space => {
                         s.input(AppMsg::PlayerToggle);
                         return gtk4::glib::Propagation::Stop;
@@ -245,9 +701,72 @@ impl Component for App {
         });
         root.add_controller(key_ctrl);
 
-        if let Some(cookies) = storage::load_cookies() {
-            sender.input(AppMsg::LoginSuccess(cookies));
-        }
+        // Touchscreen swipes
+        let swipe = gtk4::GestureSwipe::new();
+        swipe.set_touch_only(true);
+        let s = sender.clone();
+        swipe.connect_swipe(move |_, vx, vy| {
+            if vx.abs() > 800.0 && vx.abs() > vy.abs() * 2.0 {
+                s.input(AppMsg::SwipeTab(if vx < 0.0 { 1 } else { -1 }));
+            }
+        });
+        widgets.content_stack.add_controller(swipe);
+
+        // Touchpad swipes arrive as smooth horizontal scrolling. Only
+        // scrolling no child took counts, and none that starts over a child
+        // scrolling sideways itself.
+        let scroll = gtk4::EventControllerScroll::new(
+            gtk4::EventControllerScrollFlags::HORIZONTAL,
+        );
+        scroll.set_propagation_phase(gtk4::PropagationPhase::Bubble);
+        let scroll_dx = std::rc::Rc::new(std::cell::Cell::new(0.0));
+        let ignored = std::rc::Rc::new(std::cell::Cell::new(false));
+        let (dx, ignore) = (scroll_dx.clone(), ignored.clone());
+        scroll.connect_scroll_begin(move |controller| {
+            dx.set(0.0);
+            ignore.set(over_sideways_scroller(controller));
+        });
+        let dx = scroll_dx.clone();
+        scroll.connect_scroll(move |_, x, _| {
+            if !ignored.get() {
+                dx.set(dx.get() + x);
+            }
+            gtk4::glib::Propagation::Proceed
+        });
+        let s = sender.clone();
+        scroll.connect_scroll_end(move |_| {
+            let total = scroll_dx.replace(0.0);
+            if total.abs() > 150.0 {
+                s.input(AppMsg::SwipeTab(if total > 0.0 { 1 } else { -1 }));
+            }
+        });
+        widgets.content_stack.add_controller(scroll);
+
+        let drop_target = gtk4::DropTarget::new(gtk4::glib::Type::INVALID, gdk::DragAction::COPY);
+        drop_target.set_types(&[gdk::FileList::static_type(), String::static_type()]);
+        let s = sender.clone();
+        drop_target.connect_drop(move |_, value, _, _| {
+            if let Ok(files) = value.get::<gdk::FileList>() {
+                for file in files.files() {
+                    s.input(AppMsg::OpenUrl(file.uri().to_string()));
+                }
+                true
+            } else if let Ok(text) = value.get::<String>() {
+                s.input(AppMsg::OpenUrl(text));
+                true
+            } else {
+                false
+            }
+        });
+        root.add_controller(drop_target);
+
+        let s = sender.clone();
+        root.clipboard().connect_changed(move |_| {
+            s.input(AppMsg::ClipboardChanged);
+        });
+
+        let backend = model.settings.cookie_backend.unwrap_or_default();
+        sender.oneshot_command(async move { AppCmd::CookiesLoaded(storage::load_cookies(backend).await) });
 
         ComponentParts { model, widgets }
     }
@@ -257,30 +776,99 @@ impl Component for App {
         widgets: &mut Self::Widgets,
         msg: Self::Input,
         sender: ComponentSender<Self>,
-        _root: &Self::Root,
+        root: &Self::Root,
     ) {
         match msg {
             AppMsg::LoginSuccess(cookies) => {
                 if self.client.is_some() || self.mode == AppMode::Main {
                     return;
                 }
+                self.login.emit(LoginMsg::SigningIn(cookies.clone()));
                 let cookies_clone = cookies.clone();
+                let backend = self.settings.cookie_backend.unwrap_or_default();
                 sender.oneshot_command(async move {
                     match BandcampClient::new(cookies).await {
                         Ok(client) => {
-                            let _ = storage::save_cookies(&cookies_clone);
+                            let _ = storage::save_cookies(&cookies_clone, backend).await;
+                            let _ = storage::save_fan_info(client.fan());
                             AppCmd::ClientReady(client)
                         }
+                        // Unreachable rather than rejected: keep the session
+                        // and fall back to the cached library.
+                        Err(e) if is_network_error(&e) => match storage::load_fan_info()
+                            .map(|fan| BandcampClient::offline(cookies_clone, fan))
+                        {
+                            Some(Ok(client)) => AppCmd::ClientReady(client),
+                            _ => AppCmd::ClientError(e.to_string()),
+                        },
                         Err(e) => {
-                            storage::clear_cookies();
+                            storage::clear_cookies().await;
                             AppCmd::ClientError(e.to_string())
                         }
                     }
                 });
             }
             AppMsg::ClientReady(client) => {
+                // Discover's artwork follows its first fetch; have a
+                // connection to the art host waiting by then.
+                if self.online {
+                    relm4::spawn(bandcamp::warm_up());
+                }
+                storage::set_account(Some(client.fan().fan_id));
+                self.ui_state = storage::load_ui_state();
+                self.session_expired = false;
+
+                let s = sender.clone();
+                self.keep_alive = Some(gtk4::glib::timeout_add_seconds_local(KEEP_ALIVE_SECS, move || {
+                    s.input(AppMsg::KeepAlive);
+                    gtk4::glib::ControlFlow::Continue
+                }));
+                storage::prune_history(self.settings.history_retention.unwrap_or_default());
+                plays::reload(&storage::load_history());
+                ratings::reload(true);
+                notes::reload(true);
+                listen_later::reload(true);
+                skips::reload(true);
+
+                let s = sender.clone();
+                self.reminder_check = Some(gtk4::glib::timeout_add_seconds_local(REMINDER_CHECK_SECS, move || {
+                    s.input(AppMsg::CheckReminders);
+                    gtk4::glib::ControlFlow::Continue
+                }));
+                sender.input(AppMsg::CheckReminders);
+
                 let username = client.fan().username.clone();
-                sender.input(AppMsg::ShowToast(format!("Welcome, {}!", username)));
+                sender.input(AppMsg::ShowActionToast {
+                    title: gettext("Welcome, {}!").replace("{}", &username),
+                    button: gettext("Open Library"),
+                    action: Box::new(AppMsg::SelectTab("library".to_string())),
+                });
+
+                // Once per year, and only with something to show.
+                if let Some(year) = recap::season_year() {
+                    if self.ui_state.recap_offered != Some(year)
+                        && recap::compute(&storage::load_history(), year).listens > 0
+                    {
+                        self.ui_state.recap_offered = Some(year);
+                        sender.input(AppMsg::SaveUiState);
+                        sender.input(AppMsg::ShowActionToast {
+                            title: gettext("Your {} in Camper is ready").replace("{}", &year.to_string()),
+                            button: gettext("Show"),
+                            action: Box::new(AppMsg::ShowRecap(year)),
+                        });
+                    }
+                }
+
+                let profile = FanProfile::from_fan(client.fan());
+                widgets
+                    .account_button
+                    .set_menu_model(Some(&account_menu(&profile.name)));
+                self.profile = Some(profile);
+
+                let profile_client = client.clone();
+                sender.oneshot_command(async move {
+                    AppCmd::ProfileLoaded(profile_client.get_fan_profile().await.ok())
+                });
 
                 let discover = DiscoverPage::builder()
                     .launch(())
@@ -291,19 +879,23 @@ impl Component for App {
                     .launch(())
                     .forward(sender.input_sender(), AppMsg::SearchAction);
                 search.emit(SearchMsg::SetClient(client.clone()));
+                // Recent results are kept on disk as long as searches are.
+                let retention = self.settings.history_retention.unwrap_or_default();
+                let keep = matches!(retention, HistoryRetention::Forever | HistoryRetention::Days90);
+                search.emit(SearchMsg::SetKeepCache(keep));
 
                 let library = LibraryPage::builder()
                     .launch(())
                     .forward(sender.input_sender(), AppMsg::LibraryAction);
-                library.emit(LibraryMsg::SetClient(client.clone()));
-
-                let player = Player::builder()
-                    .launch(())
-                    .forward(sender.input_sender(), AppMsg::PlayerAction);
+                let refresh = self.settings.auto_refresh_library.unwrap_or(true) && self.may_refresh();
+                library.emit(LibraryMsg::SetClient(client.clone(), refresh));
 
-                if let Some(vol) = self.ui_state.volume {
-                    player.emit(PlayerMsg::SetVolume(vol));
-                }
+                // A player kept through logging out plays on as it was.
+                let kept_player = self.player.is_some();
+                let player = match self.player.take() {
+                    Some(player) => player,
+                    None => self.launch_player(root, &widgets.player_box, &widgets.now_playing_box, &sender),
+                };
 
                 if let Some(ref q) = self.ui_state.search_query {
                     if !q.is_empty() {
@@ -326,11 +918,17 @@ impl Component for App {
                 if let Some(sort) = self.ui_state.library_sort {
                     library.emit(LibraryMsg::SetSort(sort));
                 }
+                if let Some(hidden) = self.ui_state.hidden_albums.clone().filter(|hidden| !hidden.is_empty()) {
+                    library.emit(LibraryMsg::SetHidden(hidden.into_iter().collect()));
+                }
                 if let Some(ref q) = self.ui_state.library_query {
                     if !q.is_empty() {
                         library.emit(LibraryMsg::SetQuery(q.clone()));
                     }
                 }
+                if let Some(stars) = self.ui_state.library_rating.filter(|&stars| stars > 0) {
+                    library.emit(LibraryMsg::SetMinRating(stars));
+                }
 
                 let search_toolbar =
                     crate::search::build_toolbar(search.sender(), &self.ui_state);
@@ -342,41 +940,48 @@ impl Component for App {
                 let toolbar_stack = gtk4::Stack::new();
                 toolbar_stack.set_hhomogeneous(true);
                 toolbar_stack.add_named(&search_toolbar, Some("search"));
-                toolbar_stack.add_named(&discover_toolbar, Some("discover"));
+                toolbar_stack.add_named(&discover_toolbar.root, Some("discover"));
                 toolbar_stack.add_named(&library_toolbar, Some("library"));
                 widgets.header_bar.pack_start(&toolbar_stack);
 
+                let breakpoints = [&self.narrow_breakpoint, &self.mobile_breakpoint];
+                let discover_filters: Vec<gtk4::Widget> = ["discover-genre", "discover-sort"]
+                    .into_iter()
+                    .filter_map(|name| find_child_by_name(&discover_toolbar.root, name))
+                    .collect();
+                let library_sort: Vec<gtk4::Widget> = ["library-sort", "library-rating"]
+                    .into_iter()
+                    .filter_map(|name| find_child_by_name(&library_toolbar, name))
+                    .collect();
+                let folds = [
+                    overflow::fold(root, &breakpoints, &discover_filters, &gettext("Filters")),
+                    overflow::fold(root, &breakpoints, &library_sort, &gettext("Sort and Filter")),
+                ];
+
                 self.toolbars = Some(Toolbars {
                     stack: toolbar_stack,
+                    discover: discover_toolbar,
+                    _folds: folds.into_iter().flatten().collect(),
                 });
 
                 widgets.content_stack.add_titled_with_icon(
-                    search.widget(),
+                    &navigation_view(search.widget(), &gettext("Search"), &sender),
                     Some("search"),
-                    "Search",
+                    &gettext("Search"),
                     "system-search-symbolic",
                 );
                 widgets.content_stack.add_titled_with_icon(
-                    discover.widget(),
+                    &navigation_view(discover.widget(), &gettext("Discover"), &sender),
                     Some("discover"),
-                    "Discover",
+                    &gettext("Discover"),
                     "web-browser-symbolic",
                 );
                 widgets.content_stack.add_titled_with_icon(
-                    library.widget(),
+                    &navigation_view(library.widget(), &gettext("Library"), &sender),
                     Some("library"),
-                    "Library",
+                    &gettext("Library"),
                     "folder-music-symbolic",
                 );
-                widgets.player_box.append(player.widget());
-
-                if let Some(extra) =
-                    find_child_by_name(player.widget(), "player-extra-controls")
-                {
-                    self.narrow_breakpoint
-                        .add_setter(&extra, "visible", Some(&false.to_value()));
-                }
-
                 widgets
                     .view_switcher
                     .set_stack(Some(&widgets.content_stack));
@@ -395,14 +1000,21 @@ impl Component for App {
                 self.client = Some(client);
                 self.mode = AppMode::Main;
 
-                let tab = match self.ui_state.active_tab.as_deref() {
-                    Some("search" | "discover" | "library") => {
-                        self.ui_state.active_tab.as_deref().unwrap_or("library")
-                    }
+                let start_page = self.settings.start_page.unwrap_or_default();
+                let tab = match start_page.tab().or(self.ui_state.active_tab.as_deref()) {
+                    Some(tab @ ("search" | "discover" | "library")) => tab,
                     _ => "library",
                 };
                 widgets.content_stack.set_visible_child_name(tab);
                 sender.input(AppMsg::TabChanged);
+
+                if let Some(link) = self.pending_link.take() {
+                    sender.input(AppMsg::PlayLink(link));
+                } else if self.settings.resume_playback.unwrap_or(false) && !kept_player {
+                    if let Some(link) = self.ui_state.last_played.clone() {
+                        sender.input(AppMsg::PlayLink(link));
+                    }
+                }
             }
             AppMsg::TabChanged => {
                 if let Some(toolbars) = &self.toolbars {
@@ -410,7 +1022,8 @@ impl Component for App {
                     let name = active.as_ref().map(|s| s.as_str()).unwrap_or("");
                     toolbars.stack.set_visible_child_name(name);
 
-                    if name == "library" {
+                    let auto_refresh = self.settings.auto_refresh_library.unwrap_or(true);
+                    if name == "library" && auto_refresh && self.may_refresh() {
                         if let Some(library) = &self.library {
                             library.emit(LibraryMsg::Refresh);
                         }
@@ -420,15 +1033,147 @@ impl Component for App {
                     sender.input(AppMsg::SaveUiState);
                 }
             }
+            AppMsg::ProfileLoaded(profile) => {
+                widgets
+                    .account_button
+                    .set_menu_model(Some(&account_menu(&profile.name)));
+                if let Some(url) = profile.avatar_url.clone() {
+                    let avatar = widgets.avatar.clone();
+                    let size = avatar.size() * avatar.scale_factor();
+                    gtk4::glib::spawn_future_local(async move {
+                        if let Some(texture) = crate::image_loader::load(&url, size).await {
+                            avatar.set_custom_image(Some(&texture));
+                        }
+                    });
+                }
+                self.profile = Some(profile);
+            }
+            AppMsg::OpenProfile => {
+                let Some(profile) = &self.profile else { return };
+                open_in_browser(root, profile.url.clone(), &sender);
+            }
             AppMsg::SaveUiState => {
-                let _ = storage::save_ui_state(&self.ui_state);
+                let state = self.ui_state.clone();
+                self.ui_state_save.schedule(move || {
+                    let _ = storage::save_ui_state(&state);
+                });
+            }
+            AppMsg::SaveSettings => {
+                let settings = self.settings.clone();
+                self.settings_save.schedule(move || {
+                    let _ = storage::save_settings(&settings);
+                });
+            }
+            AppMsg::ExportSettings => {
+                let mut settings = self.settings.clone();
+                settings.remote_token = None;
+                let mut bundle = storage::Bundle {
+                    settings,
+                    ui_state: self.ui_state.clone(),
+                    ..Default::default()
+                };
+                // What is kept per account only goes along when signed in.
+                if self.client.is_some() {
+                    bundle.history = storage::load_history();
+                    bundle.ratings = storage::load_ratings();
+                    bundle.notes = storage::load_notes();
+                    bundle.listen_later = storage::load_listen_later();
+                    bundle.skips = storage::load_skips();
+                }
+                let dialog = gtk4::FileDialog::builder()
+                    .title(gettext("Export Settings"))
+                    .initial_name("camper-settings.json")
+                    .build();
+                let window = root.clone();
+                let s = sender.clone();
+                gtk4::glib::spawn_future_local(async move {
+                    let Ok(file) = dialog.save_future(Some(&window)).await else { return };
+                    let Some(path) = file.path() else { return };
+                    match storage::export_bundle(path, &bundle) {
+                        Ok(()) => s.input(AppMsg::ShowToast(gettext("Settings exported"))),
+                        Err(e) => s.input(AppMsg::ShowToast(
+                            gettext("Export failed: {}").replace("{}", &e.to_string()),
+                        )),
+                    }
+                });
+            }
+            AppMsg::ImportSettings => {
+                let dialog = gtk4::FileDialog::builder()
+                    .title(gettext("Import Settings"))
+                    .build();
+                let window = root.clone();
+                let s = sender.clone();
+                gtk4::glib::spawn_future_local(async move {
+                    let Ok(file) = dialog.open_future(Some(&window)).await else { return };
+                    let Some(path) = file.path() else { return };
+                    match storage::import_bundle(path) {
+                        Ok(bundle) => s.input(AppMsg::SettingsImported(bundle)),
+                        Err(e) => s.input(AppMsg::ShowToast(
+                            gettext("Import failed: {}").replace("{}", &e.to_string()),
+                        )),
+                    }
+                });
+            }
+            AppMsg::SettingsImported(bundle) => {
+                let token = self.settings.remote_token.take();
+                // The session stays in the store it was saved to on this
+                // machine; switching stores moves it, which an import
+                // doesn't do.
+                let cookie_backend = self.settings.cookie_backend;
+                self.settings = bundle.settings;
+                self.settings.remote_token = token.or(self.settings.remote_token.take());
+                self.settings.cookie_backend = cookie_backend;
+                self.remote = None;
+                self.remote = start_remote(&mut self.settings);
+                self.ui_state = bundle.ui_state;
+                self.settings.color_scheme.unwrap_or_default().apply();
+                album_grid::set_card_size(self.settings.card_size.unwrap_or(album_grid::CARD_SIZE_DEFAULT));
+                root.set_hide_on_close(self.settings.run_in_background.unwrap_or(false));
+                set_price_currency(self.settings.price_currency.clone());
+                if self.client.is_some() {
+                    let retention = self.settings.history_retention.unwrap_or_default();
+                    let saved = storage::replace_history(bundle.history, retention)
+                        .and_then(|_| storage::save_ratings(&bundle.ratings))
+                        .and_then(|_| storage::save_notes(&bundle.notes))
+                        .and_then(|_| storage::save_listen_later(&bundle.listen_later))
+                        .and_then(|_| storage::save_skips(&bundle.skips));
+                    if let Err(e) = saved {
+                        diagnostics::error("storage", format!("Failed to import history: {e}"));
+                    }
+                    plays::reload(&storage::load_history());
+                    ratings::reload(true);
+                    notes::reload(true);
+                    listen_later::reload(true);
+                    skips::reload(true);
+                    if let Some(library) = &self.library {
+                        library.emit(LibraryMsg::RatingsChanged);
+                        library.emit(LibraryMsg::NotesChanged);
+                    }
+                    if let Some(player) = &self.player {
+                        player.emit(PlayerMsg::RatingsChanged);
+                        player.emit(PlayerMsg::NotesChanged);
+                        player.emit(PlayerMsg::SkipsChanged);
+                    }
+                    if let Some(discover) = &self.discover {
+                        discover.emit(DiscoverMsg::ListenLaterChanged);
+                    }
+                }
+                sender.input(AppMsg::SaveSettings);
+                sender.input(AppMsg::SaveUiState);
+                sender.input(AppMsg::ShowToast(gettext("Settings imported")));
             }
             AppMsg::ClientError(e) => {
-                sender.input(AppMsg::ShowToast(format!("Login failed: {}", e)));
+                diagnostics::error("login", e.clone());
+                self.login.emit(LoginMsg::Failed(e));
             }
             AppMsg::DiscoverAction(action) => match action {
-                DiscoverOutput::Play(data) => sender.input(AppMsg::PlayAlbum(data)),
-                DiscoverOutput::Error(e) => sender.input(AppMsg::ShowToast(e)),
+                DiscoverOutput::Activate(data, click) => sender.input(AppMsg::ActivateAlbum(data, click)),
+                DiscoverOutput::ShowArtist(data) => sender.input(AppMsg::ShowArtist(data, None)),
+                DiscoverOutput::Prefetch(data) => self.prefetch_album(data),
+                DiscoverOutput::PlayAll(albums) => sender.input(AppMsg::PlayAlbums(albums)),
+                DiscoverOutput::Error(e) => {
+                    self.report_error(&sender, "discover", gettext("Discover failed: {}"), e, Retry::Automatic);
+                }
                 DiscoverOutput::GenreChanged(i) => {
                     self.ui_state.discover_genre = Some(i);
                     self.ui_state.discover_tag = Some(String::new());
@@ -444,16 +1189,35 @@ impl Component for App {
                 }
             },
             AppMsg::SearchAction(action) => match action {
-                SearchOutput::Play(data) => sender.input(AppMsg::PlayAlbum(data)),
-                SearchOutput::Error(e) => sender.input(AppMsg::ShowToast(e)),
+                SearchOutput::Activate(data, click) => sender.input(AppMsg::ActivateAlbum(data, click)),
+                SearchOutput::ShowArtist(data) => sender.input(AppMsg::ShowArtist(data, None)),
+                SearchOutput::Prefetch(data) => self.prefetch_album(data),
+                SearchOutput::Error(e) => {
+                    self.report_error(&sender, "search", gettext("Search failed: {}"), e, Retry::Automatic);
+                }
                 SearchOutput::QueryChanged(q) => {
-                    self.ui_state.search_query = Some(q);
-                    sender.input(AppMsg::SaveUiState);
+                    // The last query is history too; only restore it when
+                    // history outlives the session.
+                    let retention = self.settings.history_retention.unwrap_or_default();
+                    if matches!(retention, HistoryRetention::Forever | HistoryRetention::Days90) {
+                        self.ui_state.search_query = Some(q);
+                        sender.input(AppMsg::SaveUiState);
+                    }
+                }
+                SearchOutput::Searched(query) => {
+                    let retention = self.settings.history_retention.unwrap_or_default();
+                    if let Err(e) = storage::record_search(query, retention) {
+                        diagnostics::error("history", format!("Failed to record search: {e}"));
+                    }
                 }
             },
             AppMsg::LibraryAction(action) => match action {
-                LibraryOutput::Play(data) => sender.input(AppMsg::PlayAlbum(data)),
-                LibraryOutput::Error(e) => sender.input(AppMsg::ShowToast(e)),
+                LibraryOutput::Activate(data, click) => sender.input(AppMsg::ActivateAlbum(data, click)),
+                LibraryOutput::Prefetch(data) => self.prefetch_album(data),
+                LibraryOutput::ShowArtist(data, owned) => sender.input(AppMsg::ShowArtist(data, Some(owned))),
+                LibraryOutput::Error(e) => {
+                    self.report_error(&sender, "library", gettext("Library failed: {}"), e, Retry::Automatic);
+                }
                 LibraryOutput::SortChanged(sort) => {
                     self.ui_state.library_sort = Some(sort);
                     sender.input(AppMsg::SaveUiState);
@@ -462,115 +1226,974 @@ impl Component for App {
                     self.ui_state.library_query = Some(q);
                     sender.input(AppMsg::SaveUiState);
                 }
+                LibraryOutput::MinRatingChanged(stars) => {
+                    self.ui_state.library_rating = Some(stars);
+                    sender.input(AppMsg::SaveUiState);
+                }
+                LibraryOutput::Changed => {
+                    if let Some(player) = &self.player {
+                        player.emit(PlayerMsg::LibraryChanged);
+                    }
+                }
             },
-            AppMsg::PlayerAction(output) => match output {
-                PlayerOutput::NowPlaying => {}
-                PlayerOutput::Wishlist => {
-                    sender.input(AppMsg::OpenInBrowser);
+            AppMsg::ArtistAction(action) => match action {
+                ArtistOutput::Activate(data, click) => sender.input(AppMsg::ActivateAlbum(data, click)),
+                ArtistOutput::Prefetch(data) => self.prefetch_album(data),
+                ArtistOutput::OpenInBrowser(url) => open_in_browser(root, url, &sender),
+                ArtistOutput::Follow { band_id, url, name } => {
+                    sender.input(AppMsg::FollowArtist { band_id, url, name });
                 }
-                PlayerOutput::VolumeChanged(v) => {
-                    self.ui_state.volume = Some(v);
+                ArtistOutput::ShowArtist(artist) => sender.input(AppMsg::ShowAccount(artist)),
+                ArtistOutput::PlayAll(albums) => sender.input(AppMsg::PlayAlbums(albums)),
+                ArtistOutput::Error(e) => {
+                    self.report_error(&sender, "artist", gettext("Artist failed: {}"), e, Retry::Automatic);
+                }
+            },
+            AppMsg::CalendarAction(action) => match action {
+                CalendarOutput::Activate(data, click) => sender.input(AppMsg::ActivateAlbum(data, click)),
+                CalendarOutput::Prefetch(data) => self.prefetch_album(data),
+                CalendarOutput::Remind(remind, reminders) => {
+                    self.ui_state.reminders = remind.then_some(reminders);
                     sender.input(AppMsg::SaveUiState);
                 }
+                CalendarOutput::Error(e) => {
+                    let context = gettext("Release calendar failed: {}");
+                    self.report_error(&sender, "calendar", context, e, Retry::Automatic);
+                }
             },
-            AppMsg::PlayAlbum(data) => {
-                if data.url.is_empty() {
-                    sender.input(AppMsg::ShowToast("No album URL".to_string()));
-                    return;
+            AppMsg::PurchasesAction(action) => match action {
+                PurchasesOutput::Activate(data, click) => sender.input(AppMsg::ActivateAlbum(data, click)),
+                PurchasesOutput::Download(data) => sender.input(AppMsg::DownloadAlbum(data)),
+                PurchasesOutput::Error(e) => {
+                    self.report_error(&sender, "purchases", gettext("Purchases failed: {}"), e, Retry::Manual);
+                }
+            },
+            AppMsg::ShowPurchases => {
+                let Some(client) = self.client.clone() else { return };
+                let Some(nav) = library_navigation(&widgets.content_stack) else { return };
+                let page = PurchasesPage::builder()
+                    .launch(client)
+                    .forward(sender.input_sender(), AppMsg::PurchasesAction);
+                while nav.pop() {}
+                nav.push(&adw::NavigationPage::new(page.widget(), &gettext("Purchases")));
+                self.purchases = Some(page);
+                sender.input(AppMsg::SelectTab("library".to_string()));
+            }
+            AppMsg::DuplicatesAction(DuplicatesOutput::SetHidden(url, hide)) => {
+                let hidden = self.ui_state.hidden_albums.get_or_insert_with(Vec::new);
+                hidden.retain(|h| *h != url);
+                if hide {
+                    hidden.push(url);
+                }
+                if let Some(library) = &self.library {
+                    library.emit(LibraryMsg::SetHidden(hidden.iter().cloned().collect()));
+                }
+                sender.input(AppMsg::SaveUiState);
+            }
+            AppMsg::ShowDuplicates => {
+                let Some(library) = &self.library else { return };
+                let Some(nav) = library_navigation(&widgets.content_stack) else { return };
+                let init = DuplicatesInit {
+                    collection: library.model().collection().to_vec(),
+                    hidden: self.ui_state.hidden_albums.iter().flatten().cloned().collect(),
+                };
+                let page = DuplicatesPage::builder()
+                    .launch(init)
+                    .forward(sender.input_sender(), AppMsg::DuplicatesAction);
+                while nav.pop() {}
+                nav.push(&adw::NavigationPage::new(page.widget(), &gettext("Duplicates")));
+                self.duplicates = Some(page);
+                sender.input(AppMsg::SelectTab("library".to_string()));
+            }
+            AppMsg::ShowCalendar => {
+                let Some(client) = self.client.clone() else { return };
+                let Some(nav) = library_navigation(&widgets.content_stack) else { return };
+                let wishlisted = storage::load_library_cache()
+                    .map(|cache| cache.wishlist.iter().filter_map(|item| item.band_id).collect())
+                    .unwrap_or_default();
+                let page = CalendarPage::builder()
+                    .launch(CalendarInit {
+                        client,
+                        wishlisted,
+                        remind: self.ui_state.reminders.is_some(),
+                    })
+                    .forward(sender.input_sender(), AppMsg::CalendarAction);
+                while nav.pop() {}
+                nav.push(&adw::NavigationPage::new(page.widget(), &gettext("Release Calendar")));
+                self.calendar = Some(page);
+                sender.input(AppMsg::SelectTab("library".to_string()));
+            }
+            AppMsg::CheckReminders => {
+                let Some(reminders) = self.ui_state.reminders.as_mut() else { return };
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0);
+                let due = calendar::take_due(reminders, now);
+                if !due.is_empty() {
+                    due.iter().for_each(calendar::notify);
+                    sender.input(AppMsg::SaveUiState);
+                }
+            }
+            AppMsg::ShowArtist(data, owned) => {
+                if let Some(band_id) = data.band_id {
+                    let account = Account { band_id, name: data.artist };
+                    self.push_artist_page(&widgets.content_stack, &sender, account, owned);
                 }
+            }
+            AppMsg::ShowAccount(account) => {
+                self.push_artist_page(&widgets.content_stack, &sender, account, None);
+            }
+            AppMsg::FollowArtist { band_id, url, name } => {
                 if let Some(client) = self.client.clone() {
-                    // Use direct tralbum loading when IDs are available
-                    if let (Some(band_id), Some(item_id), Some(ref item_type)) =
-                        (data.band_id, data.item_id, &data.item_type)
-                    {
-                        let url = data.url.clone();
-                        let itype = item_type.clone();
-                        sender.oneshot_command(async move {
-                            match client
-                                .get_album_details_by_id(band_id, &itype, item_id, &url)
-                                .await
-                            {
-                                Ok(details) => AppCmd::AlbumLoaded(Ok(details)),
-                                Err(e) => AppCmd::AlbumLoaded(Err(e.to_string())),
-                            }
-                        });
+                    sender.oneshot_command(async move {
+                        let result = client.follow_band(&url, band_id).await;
+                        AppCmd::Followed(result.map(|_| name).map_err(ClientError::from))
+                    });
+                }
+            }
+            AppMsg::Followed(result) => match result {
+                Ok(name) => sender.input(AppMsg::ShowToast(gettext("Following “{}”").replace("{}", &name))),
+                Err(e) => {
+                    let context = gettext("Failed to follow artist: {}");
+                    self.report_error(&sender, "follow", context, e, Retry::Manual);
+                }
+            },
+            AppMsg::NavigationPopped(page) => {
+                // Drop the controller of whichever pushed page was popped
+                let popped = page.child();
+                self.artist_pages
+                    .retain(|c| popped.as_ref() != Some(c.widget().upcast_ref::<gtk4::Widget>()));
+                let calendar = self.calendar.as_ref().map(|c| c.widget().upcast_ref::<gtk4::Widget>());
+                if calendar.is_some() && calendar == popped.as_ref() {
+                    self.calendar = None;
+                }
+                let purchases = self.purchases.as_ref().map(|c| c.widget().upcast_ref::<gtk4::Widget>());
+                if purchases.is_some() && purchases == popped.as_ref() {
+                    self.purchases = None;
+                }
+                let duplicates = self.duplicates.as_ref().map(|c| c.widget().upcast_ref::<gtk4::Widget>());
+                if duplicates.is_some() && duplicates == popped.as_ref() {
+                    self.duplicates = None;
+                }
+            }
+            // Nothing is recorded for whoever listens while signed out.
+            AppMsg::PlayerAction(PlayerOutput::NowPlaying(_) | PlayerOutput::Listened(_) | PlayerOutput::Skipped(_))
+                if self.client.is_none() => {}
+            AppMsg::PlayerAction(output) => match output {
+                PlayerOutput::NowPlaying(track) => {
+                    // Remember the track by its release and position there,
+                    // whatever the queue around it, so it can be resumed on
+                    // the next launch.
+                    if let Some(link) = bandcamp::parse_release_link(&track.url) {
+                        self.ui_state.last_played = Some(link);
+                        sender.input(AppMsg::SaveUiState);
+                    }
+                }
+                PlayerOutput::Wishlist => {
+                    sender.input(AppMsg::OpenInBrowser);
+                }
+                PlayerOutput::OpenInBrowser(url) => open_in_browser(root, url, &sender),
+                PlayerOutput::ShowTag(tag) => sender.input(AppMsg::ShowTag(tag)),
+                PlayerOutput::ShowLabel(label) => sender.input(AppMsg::ShowAccount(label)),
+                PlayerOutput::Rate(url, stars) => sender.input(AppMsg::RateAlbum(url, stars)),
+                PlayerOutput::EditNote(url, title) => sender.input(AppMsg::EditNote(url, title)),
+                PlayerOutput::SetAutoSkip(url, on) => {
+                    if let Err(e) = skips::set_auto_skip(&url, on) {
+                        diagnostics::error("storage", format!("Failed to save auto-skip: {e}"));
+                        sender.input(AppMsg::ShowToast(gettext("Couldn't save auto-skip")));
+                    }
+                    if let Some(player) = &self.player {
+                        player.emit(PlayerMsg::SkipsChanged);
+                    }
+                }
+                PlayerOutput::Skipped(track) => {
+                    // Skips are listening history too, and kept only with it.
+                    if self.settings.history_retention.unwrap_or_default() != HistoryRetention::Off {
+                        if let Err(e) = skips::record(&track) {
+                            diagnostics::error("history", format!("Failed to record skip: {e}"));
+                        }
+                    }
+                }
+                PlayerOutput::EnqueueAlbum(data) => sender.input(AppMsg::EnqueueAlbum(data)),
+                PlayerOutput::SetWishlisted(track, wishlisted) => {
+                    sender.input(AppMsg::WishlistAlbum(AlbumData::from(track), wishlisted));
+                }
+                PlayerOutput::DownloadFree(track) => {
+                    let email_required = track.free_download.is_some_and(|free| free.email_required);
+                    let data = AlbumData::from(track);
+                    if email_required {
+                        // Bandcamp only mails the link; its page asks for the address.
+                        open_in_browser(root, data.url, &sender);
                     } else {
-                        // Fallback: resolve via HTML scrape
-                        let url = data.url.clone();
-                        sender.oneshot_command(async move {
-                            match client.get_album_details(&url).await {
-                                Ok(details) => AppCmd::AlbumLoaded(Ok(details)),
-                                Err(e) => AppCmd::AlbumLoaded(Err(e.to_string())),
-                            }
-                        });
+                        sender.input(AppMsg::DownloadAlbum(data));
                     }
                 }
-            }
-            AppMsg::AlbumLoaded(result) => match result {
-                Ok(details) => {
-                    let tracks: Vec<Track> = details
-                        .tracks
-                        .iter()
-                        .filter(|t| t.stream_url.is_some())
-                        .cloned()
-                        .map(Track::from)
-                        .collect();
-
-                    if tracks.is_empty() {
-                        sender
-                            .input(AppMsg::ShowToast("No playable tracks".to_string()));
+                PlayerOutput::Raise => sender.input(AppMsg::Raise),
+                PlayerOutput::Quit => sender.input(AppMsg::Quit),
+                PlayerOutput::CollapseNowPlaying => widgets.bottom_sheet.set_open(false),
+                PlayerOutput::MiniMode(active) => {
+                    if active {
+                        root.set_visible(false);
                     } else {
-                        self.current_album = Some(details);
-                        if let Some(player) = &self.player {
-                            player.emit(PlayerMsg::PlayQueue(tracks, 0));
+                        root.present();
+                    }
+                }
+                PlayerOutput::Toast(title) => sender.input(AppMsg::ShowToast(title)),
+                PlayerOutput::TrackRemoved(idx, track) => {
+                    let title = gettext("Removed “{}” from queue").replace("{}", &track.title);
+                    // Tracks removed earlier from further down the queue
+                    // now go back one place sooner.
+                    for (_, entry) in &mut self.undo_stack {
+                        if let UndoEntry::QueueTrack(i, _) = entry {
+                            if *i > idx {
+                                *i -= 1;
+                            }
                         }
                     }
+                    self.push_undo(&sender, title, UndoEntry::QueueTrack(idx, track));
+                }
+                PlayerOutput::VolumeChanged(v) => {
+                    self.ui_state.volume = Some(v);
+                    sender.input(AppMsg::SaveUiState);
+                }
+                PlayerOutput::Listened(track) => {
+                    let url = match track.url.split_once('#') {
+                        Some((release, _)) => Some(release.to_string()),
+                        None => self.current_album.as_ref().map(|a| a.url.clone()),
+                    };
+                    let listen = storage::Listen {
+                        title: track.title,
+                        artist: track.artist,
+                        album: track.album,
+                        // Releases looked up for the library have one even
+                        // when played from elsewhere.
+                        genre: url.as_ref().and_then(|url| self.genres.get(url).cloned().or_else(|| genres::get(url))),
+                        url,
+                        played_at: storage::now(),
+                        duration: track.duration,
+                    };
+                    let retention = self.settings.history_retention.unwrap_or_default();
+                    if retention != HistoryRetention::Off {
+                        plays::add(&listen);
+                    }
+                    if let Err(e) = storage::record_listen(listen, retention) {
+                        diagnostics::error("history", format!("Failed to record listen: {e}"));
+                    }
                 }
-                Err(e) => sender.input(AppMsg::ShowToast(format!("Failed: {}", e))),
             },
-            AppMsg::OpenInBrowser => {
-                if let Some(album) = self.current_album.as_ref() {
-                    if let Err(e) = open::that(&album.url) {
-                        sender.input(AppMsg::ShowToast(format!(
-                            "Failed to open browser: {}",
-                            e
-                        )));
+            AppMsg::ActivateAlbum(data, click) => {
+                let action = match click {
+                    Click::Primary => CardAction::Play,
+                    Click::Middle => self.settings.middle_click.unwrap_or(CardAction::Enqueue),
+                    Click::Shift => self.settings.shift_click.unwrap_or(CardAction::OpenInBrowser),
+                    Click::Menu(action) => action,
+                    Click::Rate(stars) => return sender.input(AppMsg::RateAlbum(data.url, stars)),
+                    Click::EditNote => return sender.input(AppMsg::EditNote(data.url, data.title)),
+                    Click::ListenLater(listed) => return sender.input(AppMsg::ListenLater(data, listed)),
+                };
+                match action {
+                    CardAction::Play => sender.input(AppMsg::PlayAlbum(data)),
+                    CardAction::Enqueue => sender.input(AppMsg::EnqueueAlbum(data)),
+                    CardAction::OpenInBrowser if !data.url.is_empty() => open_in_browser(root, data.url, &sender),
+                    CardAction::KeepOffline => sender.input(AppMsg::PinAlbum(data)),
+                    CardAction::Download => sender.input(AppMsg::DownloadAlbum(data)),
+                    CardAction::Wishlist => sender.input(AppMsg::WishlistAlbum(data, true)),
+                    CardAction::CopyLink if !data.url.is_empty() => {
+                        root.clipboard().set_text(&data.url);
+                        sender.input(AppMsg::ShowToast(gettext("Link copied")));
+                    }
+                    CardAction::OpenInBrowser | CardAction::CopyLink => {
+                        sender.input(AppMsg::ShowToast(gettext("No album URL")))
                     }
                 }
             }
-            AppMsg::Logout => {
-                storage::clear_cookies();
-                self.mode = AppMode::Login;
-                self.client = None;
-
-                if let Some(d) = self.discover.take() {
-                    widgets.content_stack.remove(d.widget());
+            AppMsg::EnqueueAlbum(data) => {
+                if data.url.is_empty() {
+                    sender.input(AppMsg::ShowToast(gettext("No album URL")));
+                    return;
                 }
-                if let Some(s) = self.search.take() {
-                    widgets.content_stack.remove(s.widget());
+                if let Some(genre) = data.genre.clone() {
+                    self.genres.insert(data.url.clone(), genre);
                 }
-                if let Some(l) = self.library.take() {
-                    widgets.content_stack.remove(l.widget());
+                if let Some(client) = self.client.clone() {
+                    sender.oneshot_command(async move {
+                        let result = load_album(&client, &data).await;
+                        AppCmd::AlbumQueued(result.map_err(ClientError::from))
+                    });
                 }
-                if let Some(p) = self.player.take() {
-                    widgets.player_box.remove(p.widget());
+            }
+            AppMsg::PinAlbum(data) => {
+                if data.url.is_empty() {
+                    sender.input(AppMsg::ShowToast(gettext("No album URL")));
+                    return;
                 }
-
-                if let Some(toolbars) = self.toolbars.take() {
-                    widgets.header_bar.remove(&toolbars.stack);
+                if let Some(client) = self.client.clone() {
+                    sender.input(AppMsg::ShowToast(
+                        gettext("Saving “{}” for offline listening…").replace("{}", &data.title),
+                    ));
+                    let task = activity::start(gettext("Saving “{}” offline").replace("{}", &data.title));
+                    sender.oneshot_command(async move {
+                        let result = load_album(&client, &data).await;
+                        let details = match result {
+                            Ok(details) => details,
+                            Err(e) => {
+                                task.fail(e.to_string());
+                                return AppCmd::AlbumPinned(Err(e.to_string()));
+                            }
+                        };
+                        audio_cache::remember_album(&details);
+                        let total = details.tracks.len();
+                        for (i, track) in details.tracks.iter().enumerate() {
+                            if let Some(stream_url) = &track.stream_url {
+                                if let Err(e) = audio_cache::store(track.url.clone(), stream_url.clone(), true).await {
+                                    task.fail(e.clone());
+                                    return AppCmd::AlbumPinned(Err(e));
+                                }
+                            }
+                            task.progress((i + 1) as f64 / total as f64);
+                        }
+                        AppCmd::AlbumPinned(Ok(data.title))
+                    });
                 }
             }
-            AppMsg::PlayerToggle => {
-                if let Some(player) = &self.player {
-                    player.emit(PlayerMsg::Toggle);
+            AppMsg::AlbumPinned(result) => match result {
+                Ok(title) => {
+                    sender.input(AppMsg::ShowToast(gettext("“{}” is available offline").replace("{}", &title)));
                 }
-            }
-            AppMsg::PlayerNext => {
-                if let Some(player) = &self.player {
-                    player.emit(PlayerMsg::Next);
+                Err(e) => {
+                    diagnostics::error("audio-cache", e.clone());
+                    sender.input(AppMsg::ShowToast(gettext("Failed to save album: {}").replace("{}", &e)));
+                }
+            },
+            AppMsg::DownloadAlbum(data) => {
+                if data.download_url.is_none() && data.url.is_empty() {
+                    sender.input(AppMsg::ShowToast(gettext("No album URL")));
+                    return;
+                }
+                if let Some(client) = self.client.clone() {
+                    sender.input(AppMsg::ShowToast(gettext("Downloading “{}”…").replace("{}", &data.title)));
+                    let options = downloads::Options {
+                        dir: self
+                            .settings
+                            .download_dir
+                            .as_ref()
+                            .map(std::path::PathBuf::from)
+                            .unwrap_or_else(downloads::default_dir),
+                        template: self
+                            .settings
+                            .download_template
+                            .clone()
+                            .unwrap_or_else(|| downloads::DEFAULT_TEMPLATE.to_string()),
+                        format: self.settings.download_format.unwrap_or_default(),
+                    };
+                    let task = activity::start(gettext("Downloading “{}”").replace("{}", &data.title));
+                    sender.oneshot_command(async move {
+                        let title = data.title.clone();
+                        let result = downloads::download(client, data, options, |done| task.progress(done))
+                            .await
+                            .map_err(|e| e.message());
+                        if let Err(e) = &result {
+                            task.fail(e.clone());
+                        }
+                        AppCmd::AlbumDownloaded(result.map(|_| title))
+                    });
+                }
+            }
+            AppMsg::AlbumDownloaded(result) => match result {
+                Ok(title) => sender.input(AppMsg::ShowToast(gettext("Downloaded “{}”").replace("{}", &title))),
+                Err(e) => {
+                    diagnostics::error("download", e.clone());
+                    sender.input(AppMsg::ShowToast(gettext("Failed to download album: {}").replace("{}", &e)));
+                }
+            },
+            AppMsg::RateAlbum(url, stars) => {
+                if let Err(e) = ratings::set(&url, stars) {
+                    diagnostics::error("storage", format!("Failed to save rating: {e}"));
+                    sender.input(AppMsg::ShowToast(gettext("Couldn't save the rating")));
+                }
+                if let Some(library) = &self.library {
+                    library.emit(LibraryMsg::RatingsChanged);
+                }
+                if let Some(player) = &self.player {
+                    player.emit(PlayerMsg::RatingsChanged);
+                }
+            }
+            AppMsg::EditNote(url, title) => {
+                if url.is_empty() {
+                    sender.input(AppMsg::ShowToast(gettext("No album URL")));
+                    return;
+                }
+                let s = sender.clone();
+                let note = notes::get(&url);
+                notes::build_dialog(&title, note, move |text| s.input(AppMsg::SetNote(url.clone(), text)))
+                    .present(Some(root));
+            }
+            AppMsg::SetNote(url, text) => {
+                if let Err(e) = notes::set(&url, &text) {
+                    diagnostics::error("storage", format!("Failed to save note: {e}"));
+                    sender.input(AppMsg::ShowToast(gettext("Couldn't save the note")));
+                }
+                if let Some(library) = &self.library {
+                    library.emit(LibraryMsg::NotesChanged);
                 }
-            }
-            AppMsg::PlayerPrev => {
+                if let Some(player) = &self.player {
+                    player.emit(PlayerMsg::NotesChanged);
+                }
+            }
+            AppMsg::ListenLater(data, listed) => {
+                if data.url.is_empty() {
+                    sender.input(AppMsg::ShowToast(gettext("No album URL")));
+                    return;
+                }
+                if let Err(e) = listen_later::set(&data, listed) {
+                    diagnostics::error("storage", format!("Failed to save listen later list: {e}"));
+                    sender.input(AppMsg::ShowToast(gettext("Couldn't save the listen later list")));
+                } else {
+                    let message = if listed {
+                        gettext("Added “{}” to Listen Later")
+                    } else {
+                        gettext("Removed “{}” from Listen Later")
+                    };
+                    sender.input(AppMsg::ShowToast(message.replace("{}", &data.title)));
+                }
+                if let Some(discover) = &self.discover {
+                    discover.emit(DiscoverMsg::ListenLaterChanged);
+                }
+            }
+            AppMsg::WishlistAlbum(data, wishlisted) => {
+                if data.url.is_empty() {
+                    sender.input(AppMsg::ShowToast(gettext("No album URL")));
+                    return;
+                }
+                if let Some(client) = self.client.clone() {
+                    sender.oneshot_command(async move {
+                        let ids = match (data.band_id, data.item_type.clone(), data.item_id) {
+                            (Some(band_id), Some(item_type), Some(item_id)) => Some((band_id, item_type, item_id)),
+                            _ => None,
+                        };
+                        let result = client.set_wishlisted(&data.url, ids, wishlisted).await;
+                        AppCmd::Wishlisted(result.map(|_| (data, wishlisted)).map_err(ClientError::from))
+                    });
+                }
+            }
+            AppMsg::Wishlisted(result) => match result {
+                Ok((data, wishlisted)) => {
+                    let toast = if wishlisted {
+                        gettext("Added “{}” to your wishlist")
+                    } else {
+                        gettext("Removed “{}” from your wishlist")
+                    };
+                    let title = toast.replace("{}", &data.title);
+                    self.push_undo(&sender, title, UndoEntry::Wishlist(data.clone(), wishlisted));
+                    // Discography cards only know the artist's page; the
+                    // next library refresh brings in the release itself.
+                    let release = bandcamp::parse_release_link(&data.url);
+                    if let (Some(library), Some(release)) = (&self.library, release) {
+                        let item = bandcamp::CollectionItem {
+                            title: data.title,
+                            artist: data.artist,
+                            art_url: data.art_url,
+                            url: release.url,
+                            download_url: None,
+                            band_id: data.band_id,
+                            item_id: data.item_id,
+                            item_type: data.item_type,
+                        };
+                        library.emit(LibraryMsg::SetWishlisted(item, wishlisted));
+                    }
+                }
+                Err(e) => {
+                    let context = gettext("Failed to update wishlist: {}");
+                    self.report_error(&sender, "wishlist", context, e, Retry::Manual);
+                }
+            },
+            AppMsg::EnqueueUrl(url) => {
+                let Some(link) = bandcamp::parse_release_link(&url) else {
+                    sender.input(AppMsg::ShowToast(gettext("Not a Bandcamp link: {}").replace("{}", url.trim())));
+                    return;
+                };
+                if let Some(client) = self.client.clone() {
+                    sender.oneshot_command(async move {
+                        let result = client.get_album_details(&link.url).await;
+                        AppCmd::AlbumQueued(result.map_err(ClientError::from))
+                    });
+                }
+            }
+            AppMsg::SearchAndPlay(query) => {
+                if let Some(client) = self.client.clone() {
+                    sender.oneshot_command(async move {
+                        let result = client
+                            .search(&query)
+                            .await
+                            .map(|albums| albums.into_iter().next().map(AlbumData::from));
+                        AppCmd::SearchAndPlayDone(result.map_err(ClientError::from), query)
+                    });
+                }
+            }
+            AppMsg::SearchAndPlayDone(result, query) => match result {
+                Ok(Some(data)) => sender.input(AppMsg::PlayAlbum(data)),
+                Ok(None) => sender.input(AppMsg::ShowToast(gettext("No results for “{}”").replace("{}", &query))),
+                Err(e) => {
+                    let retry = Retry::With(AppMsg::SearchAndPlay(query));
+                    self.report_error(&sender, "search", gettext("Search failed: {}"), e, retry);
+                }
+            },
+            AppMsg::AlbumQueued(result) => match result {
+                Ok(details) => {
+                    let title = details.tracks.first().map(|t| t.album.clone()).unwrap_or_default();
+                    let tracks: Vec<Track> = details.tracks.into_iter().map(Track::from).collect();
+                    if !tracks.iter().any(Track::streamable) {
+                        sender.input(AppMsg::ShowToast(gettext("No playable tracks")));
+                    } else if let Some(player) = &self.player {
+                        player.emit(PlayerMsg::Enqueue(tracks));
+                        sender.input(AppMsg::ShowToast(gettext("Added “{}” to the queue").replace("{}", &title)));
+                    }
+                }
+                Err(e) => self.report_error(&sender, "album", gettext("Failed to load album: {}"), e, Retry::Manual),
+            },
+            AppMsg::PlayAlbums(albums) => {
+                let Some(client) = self.client.clone() else { return };
+                let albums: Vec<AlbumData> = albums.into_iter().filter(|a| !a.url.is_empty()).collect();
+                if albums.is_empty() {
+                    return;
+                }
+                for album in &albums {
+                    if let Some(genre) = album.genre.clone() {
+                        self.genres.insert(album.url.clone(), genre);
+                    }
+                }
+                self.next_batch += 1;
+                let id = self.next_batch;
+                let title = gettext("Queueing {} albums").replace("{}", &albums.len().to_string());
+                let task = activity::start(title);
+                self.queue_batch = Some(QueueBatch { id, task, total: albums.len(), done: 0, queued: 0 });
+                sender.command(move |out, shutdown| {
+                    shutdown
+                        .register(async move {
+                            let limit = std::sync::Arc::new(tokio::sync::Semaphore::new(QUEUE_CONCURRENCY));
+                            let mut tasks = tokio::task::JoinSet::new();
+                            for (i, album) in albums.into_iter().enumerate() {
+                                let (client, limit) = (client.clone(), limit.clone());
+                                tasks.spawn(async move {
+                                    let _permit = limit.acquire_owned().await;
+                                    (i, load_album(&client, &album).await.map_err(ClientError::from))
+                                });
+                            }
+                            // Passed on in order, each as soon as the ones
+                            // before it are in.
+                            let mut loaded = std::collections::BTreeMap::new();
+                            let mut next = 0;
+                            while let Some(joined) = tasks.join_next().await {
+                                let Ok((i, result)) = joined else { continue };
+                                loaded.insert(i, result);
+                                while let Some(result) = loaded.remove(&next) {
+                                    out.send(AppCmd::BatchAlbum(id, result)).ok();
+                                    next += 1;
+                                }
+                            }
+                            for result in loaded.into_values() {
+                                out.send(AppCmd::BatchAlbum(id, result)).ok();
+                            }
+                            out.send(AppCmd::BatchDone(id)).ok();
+                        })
+                        .drop_on_shutdown()
+                });
+            }
+            AppMsg::BatchAlbum(id, result) => {
+                let Some(batch) = self.queue_batch.as_mut().filter(|batch| batch.id == id) else { return };
+                batch.done += 1;
+                batch.task.progress(batch.done as f64 / batch.total as f64);
+                let details = match result {
+                    Ok(details) => details,
+                    Err(e) => {
+                        diagnostics::error("album", format!("Failed to load album: {e}"));
+                        return;
+                    }
+                };
+                let tracks: Vec<Track> = details.tracks.iter().cloned().map(Track::from).collect();
+                if !tracks.iter().any(Track::streamable) {
+                    return;
+                }
+                batch.queued += 1;
+                if batch.queued == 1 {
+                    sender.input(AppMsg::AlbumLoaded(Ok(details), 0, None));
+                } else if let Some(player) = &self.player {
+                    player.emit(PlayerMsg::Enqueue(tracks));
+                }
+            }
+            AppMsg::BatchDone(id) => {
+                if self.queue_batch.as_ref().map(|batch| batch.id) != Some(id) {
+                    return;
+                }
+                let Some(batch) = self.queue_batch.take() else { return };
+                let toast = if batch.queued == 0 {
+                    gettext("None of these albums could be played")
+                } else if batch.queued < batch.total {
+                    gettext("Queued {queued} of {total} albums")
+                        .replace("{queued}", &batch.queued.to_string())
+                        .replace("{total}", &batch.total.to_string())
+                } else {
+                    gettext("Queued {} albums").replace("{}", &batch.queued.to_string())
+                };
+                sender.input(AppMsg::ShowToast(toast));
+            }
+            AppMsg::PlayAlbum(data) => {
+                if data.url.is_empty() {
+                    sender.input(AppMsg::ShowToast(gettext("No album URL")));
+                    return;
+                }
+                self.queue_batch = None;
+                self.retry = Some(AppMsg::PlayAlbum(data.clone()));
+                if let Some(genre) = data.genre.clone() {
+                    self.genres.insert(data.url.clone(), genre);
+                }
+                if let Some(client) = self.client.clone() {
+                    sender.oneshot_command(async move {
+                        let result = load_album(&client, &data).await;
+                        album_loaded(result, &data.url, 0, None)
+                    });
+                }
+            }
+            AppMsg::PlayLink(link) => {
+                self.retry = Some(AppMsg::PlayLink(link.clone()));
+                if let Some(client) = self.client.clone() {
+                    let (start, position) = (link.track.unwrap_or(0), link.position);
+                    sender.oneshot_command(async move {
+                        let result = client.get_album_details(&link.url).await;
+                        album_loaded(result, &link.url, start, position)
+                    });
+                }
+            }
+            AppMsg::OpenUrl(url) => {
+                let link = bandcamp::parse_release_link(&url).or_else(|| {
+                    bandcamp::parse_artist_link(&url).map(|url| ReleaseLink {
+                        url,
+                        track: None,
+                        position: None,
+                    })
+                });
+                match link {
+                    Some(link) if self.client.is_some() => sender.input(AppMsg::PlayLink(link)),
+                    Some(link) => self.pending_link = Some(link),
+                    None => sender.input(AppMsg::ShowToast(
+                        gettext("Not a Bandcamp link: {}").replace("{}", url.trim()),
+                    )),
+                }
+            }
+            AppMsg::AlbumLoaded(result, start, position) => match result {
+                Ok(details) => {
+                    // Tracks without a stream are queued too, greyed out
+                    // and skipped, so the album shows as it is sold.
+                    let tracks: Vec<Track> = details.tracks.iter().cloned().map(Track::from).collect();
+
+                    if !tracks.iter().any(Track::streamable) {
+                        sender
+                            .input(AppMsg::ShowToast(gettext("No playable tracks")));
+                    } else {
+                        if self.settings.audio_cache.unwrap_or(false) {
+                            audio_cache::remember_album(&details);
+                        }
+                        self.current_album = Some(details);
+                        // Removed tracks belonged to the queue being replaced.
+                        self.undo_stack.retain(|(_, entry)| !matches!(entry, UndoEntry::QueueTrack(..)));
+                        if let Some(player) = &self.player {
+                            let start = start.min(tracks.len() - 1);
+                            player.emit(PlayerMsg::PlayQueue(tracks, start));
+                            if let Some(position) = position {
+                                player.emit(PlayerMsg::SeekOnStart(position));
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    let retry = self.retry.take().map_or(Retry::Manual, Retry::With);
+                    self.report_error(&sender, "album", gettext("Failed to load album: {}"), e, retry);
+                }
+            },
+            AppMsg::OpenInBrowser => {
+                if let Some(album) = self.current_album.as_ref() {
+                    open_in_browser(root, album.url.clone(), &sender);
+                }
+            }
+            AppMsg::Logout => {
+                if self.ui_state_save.cancel() {
+                    let _ = storage::save_ui_state(&self.ui_state);
+                }
+                if let Some(id) = self.keep_alive.take() {
+                    id.remove();
+                }
+                if let Some(id) = self.reminder_check.take() {
+                    id.remove();
+                }
+                relm4::spawn(storage::clear_cookies());
+                // Otherwise the webview signs straight back in as the same
+                // account.
+                self.login.emit(LoginMsg::ClearData);
+                storage::set_account(None);
+                plays::reload(&storage::History::default());
+                ratings::reload(false);
+                notes::reload(false);
+                listen_later::reload(false);
+                skips::reload(false);
+                self.ui_state = UiState::default();
+                self.mode = AppMode::Login;
+                self.client = None;
+                self.queue_batch = None;
+                self.profile = None;
+                self.undo_stack.clear();
+                widgets.avatar.set_custom_image(None::<&gdk::Paintable>);
+
+                for name in ["search", "discover", "library"] {
+                    if let Some(page) = widgets.content_stack.child_by_name(name) {
+                        widgets.content_stack.remove(&page);
+                    }
+                }
+                self.discover = None;
+                self.search = None;
+                self.artist_pages.clear();
+                self.calendar = None;
+                self.purchases = None;
+                self.duplicates = None;
+                // Streams need no session, so the queue plays on into the
+                // next one unless it holds releases this account owns.
+                if self.player.as_ref().is_some_and(|p| p.model().needs_account()) {
+                    self.player_fold = None;
+                    if let Some(p) = self.player.take() {
+                        widgets.player_box.remove(p.widget());
+                    }
+                    while let Some(child) = widgets.now_playing_box.first_child() {
+                        widgets.now_playing_box.remove(&child);
+                    }
+                }
+                widgets.bottom_sheet.set_open(false);
+                self.library = None;
+                if let Some(player) = &self.player {
+                    player.emit(PlayerMsg::LibraryChanged);
+                }
+
+                if let Some(toolbars) = self.toolbars.take() {
+                    widgets.header_bar.remove(&toolbars.stack);
+                }
+            }
+            AppMsg::ShowPreferences => {
+                let Some(client) = &self.client else { return };
+                let dialog = crate::preferences::build_dialog(
+                    sender.input_sender(),
+                    &self.settings,
+                    &client.fan().username,
+                );
+                dialog.present(Some(root));
+            }
+            AppMsg::ShowShortcuts => show_shortcuts(root),
+            AppMsg::ShowDiagnostics => diagnostics::build_dialog().present(Some(root)),
+            AppMsg::ShowRecap(year) => recap::build_dialog(year).present(Some(root)),
+            AppMsg::ShowAbout => show_about(root),
+            AppMsg::SetColorScheme(scheme) => {
+                scheme.apply();
+                self.settings.color_scheme = Some(scheme);
+                sender.input(AppMsg::SaveSettings);
+            }
+            AppMsg::SetWatchClipboard(enabled) => {
+                self.settings.watch_clipboard = Some(enabled);
+                sender.input(AppMsg::SaveSettings);
+            }
+            AppMsg::SetRunInBackground(enabled) => {
+                if enabled {
+                    request_background(root, &sender);
+                }
+                root.set_hide_on_close(enabled);
+                self.settings.run_in_background = Some(enabled);
+                sender.input(AppMsg::SaveSettings);
+            }
+            AppMsg::SetGlobalShortcuts(enabled) => {
+                if let Some(handle) = self.global_shortcuts.take() {
+                    handle.abort();
+                }
+                if enabled {
+                    self.global_shortcuts = Some(bind_global_shortcuts(root, &sender));
+                }
+                self.settings.global_shortcuts = Some(enabled);
+                sender.input(AppMsg::SaveSettings);
+            }
+            AppMsg::SetCrossfeed(enabled) => {
+                self.settings.crossfeed = Some(enabled);
+                if let Some(player) = &self.player {
+                    player.emit(PlayerMsg::SetCrossfeed(enabled));
+                }
+                sender.input(AppMsg::SaveSettings);
+            }
+            AppMsg::SetStartPage(page) => {
+                self.settings.start_page = Some(page);
+                sender.input(AppMsg::SaveSettings);
+            }
+            AppMsg::SetResumePlayback(enabled) => {
+                self.settings.resume_playback = Some(enabled);
+                sender.input(AppMsg::SaveSettings);
+            }
+            AppMsg::SetAutoRefreshLibrary(enabled) => {
+                self.settings.auto_refresh_library = Some(enabled);
+                sender.input(AppMsg::SaveSettings);
+            }
+            AppMsg::SetCookieBackend(backend) => {
+                if let Some(client) = self.client.clone() {
+                    relm4::spawn(async move {
+                        if let Err(e) = storage::save_cookies(&client.cookies(), backend).await {
+                            diagnostics::error("storage", format!("Failed to move session: {e}"));
+                        }
+                    });
+                }
+                self.settings.cookie_backend = Some(backend);
+                sender.input(AppMsg::SaveSettings);
+            }
+            AppMsg::SetHistoryRetention(retention) => {
+                storage::prune_history(retention);
+                if matches!(retention, HistoryRetention::Session | HistoryRetention::Off) {
+                    self.ui_state.search_query = None;
+                    sender.input(AppMsg::SaveUiState);
+                }
+                if let Some(search) = &self.search {
+                    let keep = matches!(retention, HistoryRetention::Forever | HistoryRetention::Days90);
+                    search.emit(SearchMsg::SetKeepCache(keep));
+                }
+                self.settings.history_retention = Some(retention);
+                sender.input(AppMsg::SaveSettings);
+            }
+            AppMsg::KeepAlive => {
+                let Some(client) = self.client.clone() else { return };
+                if !self.online {
+                    return;
+                }
+                let backend = self.settings.cookie_backend.unwrap_or_default();
+                sender.oneshot_command(async move {
+                    let result = match client.keep_alive().await {
+                        Ok(Some(cookies)) => storage::save_cookies(&cookies, backend).await.map(|_| true),
+                        Ok(None) => Ok(false),
+                        Err(e) => Err(e),
+                    };
+                    AppCmd::KeepAliveDone(result.map_err(ClientError::from))
+                });
+            }
+            AppMsg::SetEphemeralLogin(enabled) => {
+                self.settings.ephemeral_login = Some(enabled);
+                sender.input(AppMsg::SaveSettings);
+            }
+            AppMsg::SetMiddleClick(action) => {
+                self.settings.middle_click = Some(action);
+                sender.input(AppMsg::SaveSettings);
+            }
+            AppMsg::SetShiftClick(action) => {
+                self.settings.shift_click = Some(action);
+                sender.input(AppMsg::SaveSettings);
+            }
+            AppMsg::SetDownloadDir(dir) => {
+                self.settings.download_dir = Some(dir);
+                sender.input(AppMsg::SaveSettings);
+            }
+            AppMsg::SetDownloadTemplate(template) => {
+                self.settings.download_template = Some(template).filter(|t| !t.is_empty());
+                sender.input(AppMsg::SaveSettings);
+            }
+            AppMsg::SetDownloadFormat(format) => {
+                self.settings.download_format = Some(format);
+                sender.input(AppMsg::SaveSettings);
+            }
+            AppMsg::SetAudioCache(enabled) => {
+                self.settings.audio_cache = Some(enabled);
+                if let Some(player) = &self.player {
+                    player.emit(PlayerMsg::SetAudioCache(enabled));
+                }
+                sender.input(AppMsg::SaveSettings);
+            }
+            AppMsg::SetRemoteControl(enabled) => {
+                self.settings.remote_control = Some(enabled);
+                self.remote = None;
+                self.remote = start_remote(&mut self.settings);
+                sender.input(AppMsg::SaveSettings);
+            }
+            AppMsg::SetRemotePort(port) => {
+                self.settings.remote_port = Some(port);
+                self.remote = None;
+                self.remote = start_remote(&mut self.settings);
+                sender.input(AppMsg::SaveSettings);
+            }
+            AppMsg::SetRemoteToken(token) => {
+                remote::set_token(token.clone());
+                self.settings.remote_token = Some(token);
+                sender.input(AppMsg::SaveSettings);
+            }
+            AppMsg::ClearHistory => {
+                storage::clear_history();
+                plays::reload(&storage::History::default());
+                if let Some(search) = &self.search {
+                    search.emit(SearchMsg::ClearCache);
+                }
+                self.ui_state.search_query = None;
+                sender.input(AppMsg::SaveUiState);
+                sender.input(AppMsg::ShowToast(gettext("History cleared")));
+            }
+            AppMsg::SetCardSize(size) => {
+                album_grid::set_card_size(size);
+                let size = album_grid::card_size();
+                if self.settings.card_size != Some(size) {
+                    self.settings.card_size = Some(size);
+                    sender.input(AppMsg::SaveSettings);
+                }
+            }
+            AppMsg::Raise => root.present(),
+            AppMsg::Quit => relm4::main_application().quit(),
+            AppMsg::ClipboardChanged => {
+                if self.mode != AppMode::Main || !self.settings.watch_clipboard.unwrap_or(false) {
+                    return;
+                }
+                // Links copied from the app itself need no offer to play.
+                if root.clipboard().is_local() {
+                    return;
+                }
+                let s = sender.clone();
+                root.clipboard()
+                    .read_text_async(None::<&gtk4::gio::Cancellable>, move |result| {
+                        if let Ok(Some(text)) = result {
+                            s.input(AppMsg::ClipboardText(text.to_string()));
+                        }
+                    });
+            }
+            AppMsg::ClipboardText(text) => {
+                let Some(link) = bandcamp::parse_release_link(&text) else {
+                    return;
+                };
+                if self.last_clipboard_link.as_deref() == Some(link.url.as_str()) {
+                    return;
+                }
+                self.last_clipboard_link = Some(link.url.clone());
+
+                sender.input(AppMsg::ShowActionToast {
+                    title: gettext("Bandcamp link copied"),
+                    button: gettext("Play"),
+                    action: Box::new(AppMsg::PlayLink(link)),
+                });
+            }
+            AppMsg::PlayerToggle => {
+                if let Some(player) = &self.player {
+                    player.emit(PlayerMsg::Toggle);
+                }
+            }
+            AppMsg::PlayerSetPlaying(playing) => {
+                if let Some(player) = &self.player {
+                    player.emit(PlayerMsg::SetPlaying(playing));
+                }
+            }
+            AppMsg::PlayerSetShuffle(shuffle) => {
+                if let Some(player) = &self.player {
+                    player.emit(PlayerMsg::SetShuffle(shuffle));
+                }
+            }
+            AppMsg::PlayerNext => {
+                if let Some(player) = &self.player {
+                    player.emit(PlayerMsg::Next);
+                }
+            }
+            AppMsg::PlayerPrev => {
                 if let Some(player) = &self.player {
                     player.emit(PlayerMsg::Prev);
                 }
@@ -590,6 +2213,116 @@ impl Component for App {
             AppMsg::ShowToast(msg) => {
                 self.toast_overlay.add_toast(adw::Toast::new(&msg));
             }
+            AppMsg::ShowActionToast { title, button, action } => {
+                let toast = adw::Toast::new(&title);
+                toast.set_button_label(Some(&button));
+                // The toast is dismissed once its button is clicked, so the
+                // message is only ever sent once.
+                let action = std::cell::Cell::new(Some(*action));
+                let s = sender.clone();
+                toast.connect_button_clicked(move |_| {
+                    if let Some(msg) = action.take() {
+                        s.input(msg);
+                    }
+                });
+                self.toast_overlay.add_toast(toast);
+            }
+            AppMsg::Undo(id) => {
+                // Toasts of entries already undone, or dropped with the
+                // queue they belonged to, do nothing.
+                let position = match id {
+                    Some(id) => self.undo_stack.iter().position(|(entry, _)| *entry == id),
+                    None => self.undo_stack.len().checked_sub(1),
+                };
+                let Some(position) = position else { return };
+                match self.undo_stack.remove(position).1 {
+                    UndoEntry::QueueTrack(idx, track) => {
+                        // Tracks removed from further down, or later from the
+                        // same place, go back one place later now that this
+                        // one is back.
+                        for (i, (_, entry)) in self.undo_stack.iter_mut().enumerate() {
+                            if let UndoEntry::QueueTrack(other, _) = entry {
+                                if *other > idx || (*other == idx && i >= position) {
+                                    *other += 1;
+                                }
+                            }
+                        }
+                        if let Some(player) = &self.player {
+                            player.emit(PlayerMsg::RestoreTrack(idx, track));
+                        }
+                    }
+                    UndoEntry::Wishlist(data, wishlisted) => sender.input(AppMsg::WishlistAlbum(data, !wishlisted)),
+                }
+            }
+            AppMsg::SwipeTab(delta) => {
+                if self.mode != AppMode::Main {
+                    return;
+                }
+                let Some(nav) = widgets
+                    .content_stack
+                    .visible_child()
+                    .and_downcast::<adw::NavigationView>()
+                else {
+                    return;
+                };
+                // With a page pushed, horizontal swipes belong to the
+                // navigation view's own back gesture.
+                if nav.navigation_stack().n_items() > 1 {
+                    return;
+                }
+                let current = widgets.content_stack.visible_child_name();
+                let index = TABS
+                    .iter()
+                    .position(|t| Some(*t) == current.as_deref())
+                    .unwrap_or(0) as i32;
+                if let Some(tab) = TABS.get((index + delta).max(0) as usize) {
+                    widgets.content_stack.set_visible_child_name(tab);
+                }
+            }
+            AppMsg::ShowTag(tag) => {
+                let (Some(discover), Some(toolbars)) = (&self.discover, &self.toolbars) else {
+                    return;
+                };
+                toolbars.discover.show_tag(&tag);
+                discover.emit(DiscoverMsg::ShowTag(tag));
+                // Back to the grid, should an artist be open on top of it.
+                let nav = widgets.content_stack.child_by_name("discover");
+                if let Some(nav) = nav.and_downcast::<adw::NavigationView>() {
+                    while nav.pop() {}
+                }
+                sender.input(AppMsg::SelectTab("discover".to_string()));
+            }
+            AppMsg::SelectTab(name) => {
+                if self.mode == AppMode::Main {
+                    widgets.content_stack.set_visible_child_name(&name);
+                }
+            }
+            AppMsg::NetworkChanged(available) => {
+                let reconnected = available && !self.online;
+                self.online = available;
+                if reconnected {
+                    album_grid::retry_artwork();
+                    if self.may_refresh() {
+                        self.refresh();
+                    }
+                }
+            }
+            AppMsg::SetDataSaver(mode) => {
+                self.settings.data_saver = Some(mode);
+                sender.input(AppMsg::SaveSettings);
+                sender.input(AppMsg::MeteredChanged);
+            }
+            AppMsg::SetPriceCurrency(currency) => {
+                self.settings.price_currency = currency.clone();
+                set_price_currency(currency);
+                sender.input(AppMsg::SaveSettings);
+            }
+            AppMsg::MeteredChanged => {
+                let saving = data_saver::update(self.settings.data_saver.unwrap_or_default());
+                if !saving && std::mem::take(&mut self.deferred_refresh) {
+                    self.refresh();
+                }
+            }
         }
 
         widgets
@@ -602,6 +2335,19 @@ impl Component for App {
         self.update_view(widgets, sender);
     }
 
+    /// Writes out whatever is still waiting on a debounced save.
+    fn shutdown(&mut self, _widgets: &mut Self::Widgets, _output: relm4::Sender<Self::Output>) {
+        if self.ui_state_save.cancel() {
+            let _ = storage::save_ui_state(&self.ui_state);
+        }
+        if self.settings_save.cancel() {
+            let _ = storage::save_settings(&self.settings);
+        }
+        if self.settings.history_retention == Some(HistoryRetention::Session) {
+            storage::clear_history();
+        }
+    }
+
     fn update_cmd(
         &mut self,
         msg: Self::CommandOutput,
@@ -611,14 +2357,376 @@ impl Component for App {
         match msg {
             AppCmd::ClientReady(client) => sender.input(AppMsg::ClientReady(client)),
             AppCmd::ClientError(e) => sender.input(AppMsg::ClientError(e)),
-            AppCmd::AlbumLoaded(r) => sender.input(AppMsg::AlbumLoaded(r)),
+            AppCmd::CookiesLoaded(Some(cookies)) => sender.input(AppMsg::LoginSuccess(cookies)),
+            AppCmd::CookiesLoaded(None) => {}
+            AppCmd::KeepAliveDone(Ok(true)) => {
+                diagnostics::record(diagnostics::Level::Info, "session", "Session cookies refreshed");
+            }
+            AppCmd::KeepAliveDone(Ok(false)) => {}
+            AppCmd::KeepAliveDone(Err(ClientError::SessionExpired)) => {
+                let context = gettext("Keep-alive failed: {}");
+                self.report_error(&sender, "session", context, ClientError::SessionExpired, Retry::Manual);
+            }
+            AppCmd::KeepAliveDone(Err(e)) => diagnostics::error("session", format!("Keep-alive failed: {e}")),
+            AppCmd::ProfileLoaded(Some(profile)) => sender.input(AppMsg::ProfileLoaded(profile)),
+            AppCmd::ProfileLoaded(None) => {}
+            AppCmd::AlbumLoaded(r, start, position) => sender.input(AppMsg::AlbumLoaded(r, start, position)),
+            AppCmd::AlbumQueued(r) => sender.input(AppMsg::AlbumQueued(r)),
+            AppCmd::BatchAlbum(id, r) => sender.input(AppMsg::BatchAlbum(id, r)),
+            AppCmd::BatchDone(id) => sender.input(AppMsg::BatchDone(id)),
+            AppCmd::AlbumPinned(r) => sender.input(AppMsg::AlbumPinned(r)),
+            AppCmd::AlbumDownloaded(r) => sender.input(AppMsg::AlbumDownloaded(r)),
+            AppCmd::Wishlisted(r) => sender.input(AppMsg::Wishlisted(r)),
+            AppCmd::Followed(r) => sender.input(AppMsg::Followed(r)),
+            AppCmd::SearchAndPlayDone(r, query) => sender.input(AppMsg::SearchAndPlayDone(r, query)),
+        }
+    }
+}
+
+impl App {
+    /// Keeps `entry` to be undone with Ctrl+Z, and shows `title` in a toast
+    /// with a button undoing this entry in particular.
+    fn push_undo(&mut self, sender: &ComponentSender<Self>, title: String, entry: UndoEntry) {
+        if self.undo_stack.len() == UNDO_LIMIT {
+            self.undo_stack.remove(0);
+        }
+        let id = self.next_undo_id;
+        self.next_undo_id += 1;
+        self.undo_stack.push((id, entry));
+        sender.input(AppMsg::ShowActionToast {
+            title,
+            button: gettext("Undo"),
+            action: Box::new(AppMsg::Undo(Some(id))),
+        });
+    }
+
+    /// Whether a refresh nobody asked for may run now; while saving data
+    /// it is put off until the saver turns off.
+    fn may_refresh(&mut self) -> bool {
+        if data_saver::active() {
+            self.deferred_refresh = true;
+            return false;
+        }
+        true
+    }
+
+    /// Pushes an artist's page onto the visible tab, or the library's when
+    /// the visible one has no navigation, e.g. when opened from the player.
+    fn push_artist_page(
+        &mut self,
+        content_stack: &adw::ViewStack,
+        sender: &ComponentSender<Self>,
+        account: Account,
+        owned: Option<Vec<AlbumData>>,
+    ) {
+        let Some(client) = self.client.clone() else {
+            return;
+        };
+        let nav = match content_stack.visible_child().and_downcast::<adw::NavigationView>() {
+            Some(nav) => nav,
+            None => {
+                let Some(nav) = library_navigation(content_stack) else {
+                    return;
+                };
+                content_stack.set_visible_child_name("library");
+                nav
+            }
+        };
+        let page = ArtistPage::builder()
+            .launch(ArtistInit {
+                client,
+                band_id: account.band_id,
+                name: account.name.clone(),
+                owned,
+            })
+            .forward(sender.input_sender(), AppMsg::ArtistAction);
+        nav.push(&adw::NavigationPage::new(page.widget(), &account.name));
+        self.artist_pages.push(page);
+    }
+
+    /// Starts the player for a session, set up as last left.
+    fn launch_player(
+        &mut self,
+        window: &adw::ApplicationWindow,
+        player_box: &gtk4::Box,
+        now_playing_box: &gtk4::Box,
+        sender: &ComponentSender<Self>,
+    ) -> Controller<Player> {
+        let player = Player::builder()
+            .launch(now_playing_box.clone())
+            .forward(sender.input_sender(), AppMsg::PlayerAction);
+
+        if let Some(vol) = self.ui_state.volume {
+            player.emit(PlayerMsg::SetVolume(vol));
+        }
+        player.emit(PlayerMsg::SetAudioCache(self.settings.audio_cache.unwrap_or(false)));
+        if self.settings.crossfeed.unwrap_or(false) {
+            player.emit(PlayerMsg::SetCrossfeed(true));
         }
+
+        player_box.append(player.widget());
+        if let Some(extra) = find_child_by_name(player.widget(), "player-extra-controls") {
+            let breakpoints = [&self.narrow_breakpoint, &self.mobile_breakpoint];
+            self.player_fold = overflow::fold(window, &breakpoints, &[extra], &gettext("More Controls"));
+        }
+        player
     }
+
+    /// Resolves an album the pointer rests on, so clicking it plays right
+    /// away from the client's details cache. Failures are left for the
+    /// click to report; nothing is fetched while saving data.
+    fn prefetch_album(&self, data: AlbumData) {
+        let Some(client) = self.client.clone() else { return };
+        if data.url.is_empty() || !self.online || self.session_expired || data_saver::active() {
+            return;
+        }
+        relm4::spawn(async move {
+            let details = load_album(&client, &data).await;
+            // The player shows the first track's large artwork.
+            if let Some(url) = details.ok().and_then(|d| d.tracks.into_iter().find_map(|t| t.art_url)) {
+                crate::art_cache::prefetch(url).await;
+            }
+        });
+    }
+
+    /// Logs a failed request and tells the user what to do about it.
+    /// `context` frames errors without a message of their own, e.g.
+    /// "Search failed: {}".
+    fn report_error(
+        &mut self,
+        sender: &ComponentSender<Self>,
+        source: &'static str,
+        context: String,
+        e: ClientError,
+        retry: Retry,
+    ) {
+        diagnostics::error(source, e.to_string());
+        let toast = match (e, retry) {
+            (ClientError::SessionExpired, _) => {
+                // Every request fails the same way until then; ask once.
+                if std::mem::replace(&mut self.session_expired, true) {
+                    return;
+                }
+                AppMsg::ShowActionToast {
+                    title: ClientError::SessionExpired.message(),
+                    button: gettext("Sign In"),
+                    action: Box::new(AppMsg::Logout),
+                }
+            }
+            (e @ ClientError::RateLimited(_), Retry::Automatic) => AppMsg::ShowToast(e.message()),
+            (e @ ClientError::RateLimited(secs), Retry::With(retry)) => {
+                let s = sender.input_sender().clone();
+                gtk4::glib::timeout_add_seconds_local_once(secs as u32, move || s.emit(retry));
+                AppMsg::ShowToast(e.message())
+            }
+            (ClientError::RateLimited(secs), Retry::Manual) => {
+                AppMsg::ShowToast(gettext("Rate limited, try again in {}s").replace("{}", &secs.to_string()))
+            }
+            // The offline banner says so already.
+            (ClientError::Offline, _) if !self.online => return,
+            (e @ ClientError::Offline, _) => AppMsg::ShowToast(e.message()),
+            (ClientError::Other(message), Retry::With(retry)) => AppMsg::ShowActionToast {
+                title: context.replace("{}", &message),
+                button: gettext("Retry"),
+                action: Box::new(retry),
+            },
+            (ClientError::Other(message), _) => AppMsg::ShowToast(context.replace("{}", &message)),
+        };
+        sender.input(toast);
+    }
+
+    fn refresh(&self) {
+        if let Some(library) = &self.library {
+            library.emit(LibraryMsg::Refresh);
+        }
+        if let Some(discover) = &self.discover {
+            discover.emit(DiscoverMsg::Refresh);
+        }
+    }
+}
+
+/// Wraps a tab's page so detail pages can be pushed on top of it without
+/// tearing down the grid underneath.
+fn navigation_view(
+    child: &impl IsA<gtk4::Widget>,
+    title: &str,
+    sender: &ComponentSender<App>,
+) -> adw::NavigationView {
+    let view = adw::NavigationView::new();
+    view.add(&adw::NavigationPage::new(child, title));
+    let s = sender.clone();
+    view.connect_popped(move |_, page| {
+        s.input(AppMsg::NavigationPopped(page.clone()));
+    });
+    view
+}
+
+/// The remote-control server the settings ask for, if any. Enabling it
+/// generates the token clients authenticate with, unless one exists.
+fn start_remote(settings: &mut Settings) -> Option<remote::Server> {
+    if !settings.remote_control.unwrap_or(false) {
+        return None;
+    }
+    let token = settings.remote_token.get_or_insert_with(remote::generate_token).clone();
+    remote::set_token(token);
+    Some(remote::start(settings.remote_port.unwrap_or(remote::DEFAULT_PORT)))
+}
+
+fn open_in_browser(window: &adw::ApplicationWindow, url: String, sender: &ComponentSender<App>) {
+    let window = window.clone();
+    let s = sender.clone();
+    gtk4::glib::spawn_future_local(async move {
+        if let Err(e) = crate::portal::open_uri(&window, &url).await {
+            s.input(AppMsg::ShowToast(gettext("Failed to open browser: {}").replace("{}", &e.to_string())));
+        }
+    });
+}
+
+/// Background playback needs the portal's permission inside Flatpak;
+/// the setting is turned back off if it is refused.
+fn request_background(window: &adw::ApplicationWindow, sender: &ComponentSender<App>) {
+    let window = window.clone();
+    let s = sender.clone();
+    gtk4::glib::spawn_future_local(async move {
+        match crate::portal::request_background(&window).await {
+            Ok(true) => {}
+            Ok(false) => {
+                s.input(AppMsg::SetRunInBackground(false));
+                s.input(AppMsg::ShowToast(gettext("Background playback was not allowed")));
+            }
+            Err(e) => diagnostics::error("portal", e.to_string()),
+        }
+    });
+}
+
+/// Listens for play/pause/next/previous from anywhere on the desktop, for
+/// Wayland sessions whose media keys don't reach MPRIS.
+fn bind_global_shortcuts(
+    window: &adw::ApplicationWindow,
+    sender: &ComponentSender<App>,
+) -> gtk4::glib::JoinHandle<()> {
+    let window = window.clone();
+    let s = sender.clone();
+    gtk4::glib::spawn_future_local(async move {
+        let activated = |shortcut| {
+            s.input(match shortcut {
+                GlobalShortcut::PlayPause => AppMsg::PlayerToggle,
+                GlobalShortcut::Next => AppMsg::PlayerNext,
+                GlobalShortcut::Previous => AppMsg::PlayerPrev,
+            })
+        };
+        if let Err(e) = crate::portal::global_shortcuts(&window, activated).await {
+            diagnostics::error("portal", e.to_string());
+            s.input(AppMsg::ShowToast(gettext("Global shortcuts are not available")));
+        }
+    })
+}
+
+/// The library tab's navigation stack, which pages about the library are
+/// pushed onto.
+fn library_navigation(content_stack: &adw::ViewStack) -> Option<adw::NavigationView> {
+    content_stack.child_by_name("library").and_downcast::<adw::NavigationView>()
+}
+
+/// Converts prices shown from now on, loading exchange rates if needed.
+fn set_price_currency(currency: Option<String>) {
+    if currency.is_some() {
+        relm4::spawn(exchange::refresh());
+    }
+    exchange::set_currency(currency);
+}
+
+fn primary_menu() -> gio::Menu {
+    let menu = gio::Menu::new();
+
+    let section = gio::Menu::new();
+    section.append(Some(&gettext("Preferences")), Some("win.preferences"));
+    section.append(Some(&gettext("Keyboard Shortcuts")), Some("win.shortcuts"));
+    section.append(Some(&gettext("Purchases")), Some("win.purchases"));
+    section.append(Some(&gettext("Duplicates")), Some("win.duplicates"));
+    section.append(Some(&gettext("Release Calendar")), Some("win.calendar"));
+    section.append(Some(&gettext("Year in Review")), Some("win.recap"));
+    section.append(Some(&gettext("Diagnostics")), Some("win.diagnostics"));
+    section.append(Some(&gettext("About Camper")), Some("win.about"));
+    menu.append_section(None, &section);
+
+    let section = gio::Menu::new();
+    section.append(Some(&gettext("Quit")), Some("win.quit"));
+    menu.append_section(None, &section);
+
+    menu
+}
+
+fn account_menu(name: &str) -> gio::Menu {
+    let section = gio::Menu::new();
+    section.append(Some(&gettext("My Profile on Bandcamp")), Some("win.profile"));
+    section.append(Some(&gettext("Logout")), Some("win.logout"));
+    let menu = gio::Menu::new();
+    menu.append_section(Some(name), &section);
+    menu
+}
+
+/// Falls back to the album's tracks on disk when Bandcamp can't be reached.
+fn album_loaded(result: anyhow::Result<AlbumDetails>, url: &str, start: usize, position: Option<f64>) -> AppCmd {
+    let result = match result {
+        Err(e) if is_network_error(&e) => audio_cache::album(url).ok_or(e),
+        result => result,
+    };
+    AppCmd::AlbumLoaded(result.map_err(ClientError::from), start, position)
+}
+
+/// Loads an album's details, straight by its IDs when the card has them.
+async fn load_album(client: &BandcampClient, data: &AlbumData) -> anyhow::Result<AlbumDetails> {
+    let ids = match (data.band_id, data.item_type.as_deref(), data.item_id) {
+        (Some(band_id), Some(item_type), Some(item_id)) => Some((band_id, item_type, item_id)),
+        _ => None,
+    };
+    client.get_release_details(&data.url, ids).await
+}
+
+fn is_network_error(e: &anyhow::Error) -> bool {
+    e.downcast_ref::<reqwest::Error>()
+        .is_some_and(|e| e.is_connect() || e.is_timeout() || e.is_request())
+}
+
+fn show_shortcuts(parent: &adw::ApplicationWindow) {
+    let builder = gtk4::Builder::from_string(include_str!("shortcuts.ui"));
+    if let Some(window) = builder.object::<gtk4::ShortcutsWindow>("help_overlay") {
+        window.set_transient_for(Some(parent));
+        window.present();
+    }
+}
+
+fn show_about(parent: &adw::ApplicationWindow) {
+    let dialog = adw::AboutDialog::builder()
+        .application_name(gettext("Camper"))
+        .application_icon("net.knoopx.camper")
+        .version(env!("CARGO_PKG_VERSION"))
+        .comments(env!("CARGO_PKG_DESCRIPTION"))
+        .developer_name("knoopx")
+        .license_type(gtk4::License::MitX11)
+        .website("https://github.com/knoopx/camper")
+        .issue_url("https://github.com/knoopx/camper/issues")
+        .build();
+    dialog.add_link(&gettext("Bandcamp"), "https://bandcamp.com");
+    dialog.present(Some(parent));
 }
 
 #[derive(Debug)]
 pub enum AppCmd {
     ClientReady(BandcampClient),
     ClientError(String),
-    AlbumLoaded(Result<AlbumDetails, String>),
+    CookiesLoaded(Option<String>),
+    /// Whether the keep-alive ping refreshed the stored cookies.
+    KeepAliveDone(Result<bool, ClientError>),
+    ProfileLoaded(Option<FanProfile>),
+    AlbumLoaded(Result<AlbumDetails, ClientError>, usize, Option<f64>),
+    AlbumQueued(Result<AlbumDetails, ClientError>),
+    BatchAlbum(u64, Result<AlbumDetails, ClientError>),
+    BatchDone(u64),
+    AlbumPinned(Result<String, String>),
+    AlbumDownloaded(Result<String, String>),
+    Wishlisted(Result<(AlbumData, bool), ClientError>),
+    Followed(Result<String, ClientError>),
+    SearchAndPlayDone(Result<Option<AlbumData>, ClientError>, String),
 }
@@ -0,0 +1,189 @@
+use crate::diagnostics;
+use std::fs;
+use std::path::PathBuf;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tokio::sync::oneshot;
+
+/// Least recently used files are evicted once the cache grows past this.
+const MAX_BYTES: u64 = 256 * 1024 * 1024;
+/// Scanning the directory on every write is wasteful; evict every so often.
+const EVICT_EVERY: usize = 32;
+
+static WRITES: AtomicUsize = AtomicUsize::new(0);
+
+/// Downloads running at once; opening a large library would otherwise fire
+/// hundreds of requests and starve the audio stream.
+const MAX_DOWNLOADS: usize = 6;
+
+/// Which downloads get a free slot first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+    /// Art someone is looking at.
+    Visible,
+    /// Art that may be scrolled to soon.
+    Prefetch,
+}
+
+struct Pool {
+    running: usize,
+    visible: VecDeque<oneshot::Sender<Slot>>,
+    prefetch: VecDeque<oneshot::Sender<Slot>>,
+}
+
+static POOL: Mutex<Pool> = Mutex::new(Pool {
+    running: 0,
+    visible: VecDeque::new(),
+    prefetch: VecDeque::new(),
+});
+
+/// A running download's place in the pool, handed on to the next waiter
+/// when dropped.
+struct Slot(());
+
+impl Slot {
+    async fn acquire(priority: Priority) -> Slot {
+        let rx = {
+            let Ok(mut pool) = POOL.lock() else { return Slot(()) };
+            if pool.running < MAX_DOWNLOADS {
+                pool.running += 1;
+                return Slot(());
+            }
+            let (tx, rx) = oneshot::channel();
+            match priority {
+                Priority::Visible => pool.visible.push_back(tx),
+                Priority::Prefetch => pool.prefetch.push_back(tx),
+            }
+            rx
+        };
+        // The pool never drops a sender without passing a slot on.
+        rx.await.unwrap_or_else(|_| Slot(()))
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        loop {
+            let next = {
+                let Ok(mut pool) = POOL.lock() else { return };
+                match pool.visible.pop_front().or_else(|| pool.prefetch.pop_front()) {
+                    Some(tx) => tx,
+                    None => {
+                        pool.running = pool.running.saturating_sub(1);
+                        return;
+                    }
+                }
+            };
+            // A waiter that gave up hands the slot straight back; forget it
+            // rather than releasing it twice.
+            match next.send(Slot(())) {
+                Ok(()) => return,
+                Err(slot) => std::mem::forget(slot),
+            }
+        }
+    }
+}
+
+fn cache_dir() -> PathBuf {
+    crate::storage::cache_dir().join("art")
+}
+
+/// FNV-1a, so file names stay stable across Rust releases.
+pub fn key(url: &str) -> String {
+    let hash = url.bytes().fold(0xcbf29ce484222325_u64, |h, b| {
+        (h ^ b as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{hash:016x}")
+}
+
+fn path_for(url: &str) -> PathBuf {
+    cache_dir().join(key(url))
+}
+
+/// The cached file for `url`, if it has been downloaded before.
+pub fn cached_path(url: &str) -> Option<PathBuf> {
+    let path = path_for(url);
+    path.exists().then_some(path)
+}
+
+/// Artwork bytes for `url`, from disk when cached and downloaded (and
+/// stored) otherwise.
+pub async fn fetch(url: &str) -> Option<Vec<u8>> {
+    fetch_with(url, Priority::Visible).await
+}
+
+/// Downloads `url` into the cache, behind any visible artwork, unless it
+/// is there already.
+pub async fn prefetch(url: String) {
+    if cached_path(&url).is_none() {
+        fetch_with(&url, Priority::Prefetch).await;
+    }
+}
+
+async fn fetch_with(url: &str, priority: Priority) -> Option<Vec<u8>> {
+    let path = path_for(url);
+    if let Ok(bytes) = fs::read(&path) {
+        // The modification time doubles as the last access time for LRU.
+        if let Ok(file) = fs::File::options().write(true).open(&path) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+        return Some(bytes);
+    }
+
+    let _slot = Slot::acquire(priority).await;
+    // Another load may have finished it while this one waited.
+    if let Ok(bytes) = fs::read(&path) {
+        return Some(bytes);
+    }
+    let bytes = crate::bandcamp::http().get(url).send().await.ok()?.bytes().await.ok()?.to_vec();
+    if let Err(e) = fs::create_dir_all(cache_dir()).and_then(|_| fs::write(&path, &bytes)) {
+        diagnostics::error("art-cache", format!("Failed to cache {url}: {e}"));
+    } else if WRITES.fetch_add(1, Ordering::Relaxed) % EVICT_EVERY == 0 {
+        evict();
+    }
+    Some(bytes)
+}
+
+fn entries() -> Vec<(PathBuf, u64, SystemTime)> {
+    let Ok(dir) = fs::read_dir(cache_dir()) else { return Vec::new() };
+    dir.flatten()
+        .filter_map(|entry| {
+            let meta = entry.metadata().ok()?;
+            meta.is_file()
+                .then(|| (entry.path(), meta.len(), meta.modified().unwrap_or(SystemTime::UNIX_EPOCH)))
+        })
+        .collect()
+}
+
+/// Removes the least recently used files until the cache fits `MAX_BYTES`.
+pub fn evict() {
+    let mut entries = entries();
+    let mut total: u64 = entries.iter().map(|(_, size, _)| size).sum();
+    if total <= MAX_BYTES {
+        return;
+    }
+    entries.sort_by_key(|(_, _, modified)| *modified);
+    for (path, size, _) in entries {
+        if total <= MAX_BYTES {
+            break;
+        }
+        if fs::remove_file(&path).is_ok() {
+            total -= size;
+        }
+    }
+}
+
+/// Bytes currently used by cached artwork.
+pub fn usage() -> u64 {
+    entries().iter().map(|(_, size, _)| size).sum()
+}
+
+pub fn clear() {
+    if let Err(e) = fs::remove_dir_all(cache_dir()) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            diagnostics::error("art-cache", format!("Failed to clear cache: {e}"));
+        }
+    }
+}
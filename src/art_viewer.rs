@@ -0,0 +1,257 @@
+use gettextrs::gettext;
+use gtk4::gio;
+use gtk4::prelude::*;
+use libadwaita as adw;
+use libadwaita::prelude::*;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+const MIN_ZOOM: f64 = 0.1;
+const MAX_ZOOM: f64 = 8.0;
+const ZOOM_STEP: f64 = 1.25;
+
+/// Artwork shown at its original resolution, fitted to the dialog until
+/// zoomed. `None` zoom means fit.
+struct Viewer {
+    picture: gtk4::Picture,
+    scrolled: gtk4::ScrolledWindow,
+    texture: RefCell<Option<gtk4::gdk::Texture>>,
+    zoom: Cell<Option<f64>>,
+}
+
+impl Viewer {
+    /// The scale the picture is currently drawn at, fitted or not.
+    fn scale(&self) -> f64 {
+        if let Some(zoom) = self.zoom.get() {
+            return zoom;
+        }
+        let Some(texture) = self.texture.borrow().clone() else { return 1.0 };
+        let width = self.scrolled.width() as f64 / texture.width() as f64;
+        let height = self.scrolled.height() as f64 / texture.height() as f64;
+        width.min(height).min(1.0)
+    }
+
+    fn set_zoom(&self, zoom: Option<f64>) {
+        let zoom = zoom.map(|z| z.clamp(MIN_ZOOM, MAX_ZOOM));
+        self.zoom.set(zoom);
+        let Some(texture) = self.texture.borrow().clone() else { return };
+        match zoom {
+            Some(zoom) => {
+                // Keep the point at the centre of the view where it was.
+                let hadj = self.scrolled.hadjustment();
+                let vadj = self.scrolled.vadjustment();
+                let old_width = hadj.upper().max(1.0);
+                let old_height = vadj.upper().max(1.0);
+                let cx = (hadj.value() + hadj.page_size() / 2.0) / old_width;
+                let cy = (vadj.value() + vadj.page_size() / 2.0) / old_height;
+
+                let width = (texture.width() as f64 * zoom).round() as i32;
+                let height = (texture.height() as f64 * zoom).round() as i32;
+                self.picture.set_can_shrink(false);
+                self.picture.set_size_request(width, height);
+                self.scrolled.set_policy(gtk4::PolicyType::Automatic, gtk4::PolicyType::Automatic);
+
+                let (hadj, vadj) = (hadj.clone(), vadj.clone());
+                gtk4::glib::idle_add_local_once(move || {
+                    hadj.set_value(cx * hadj.upper() - hadj.page_size() / 2.0);
+                    vadj.set_value(cy * vadj.upper() - vadj.page_size() / 2.0);
+                });
+            }
+            None => {
+                self.picture.set_can_shrink(true);
+                self.picture.set_size_request(-1, -1);
+                self.scrolled.set_policy(gtk4::PolicyType::Never, gtk4::PolicyType::Never);
+            }
+        }
+    }
+}
+
+/// bcbits serves the uploaded original as format 0; any other format of
+/// the same image only differs in the suffix.
+fn original_url(url: &str) -> String {
+    match url.rsplit_once('_') {
+        Some((base, suffix)) if suffix.ends_with(".jpg") => format!("{base}_0.jpg"),
+        _ => url.to_string(),
+    }
+}
+
+/// Full-size view of the artwork at `url`, with zoom, pan and saving.
+pub fn build_dialog(url: &str, title: &str) -> adw::Dialog {
+    let url = original_url(url);
+
+    let dialog = adw::Dialog::new();
+    dialog.set_title(title);
+    dialog.set_content_width(720);
+    dialog.set_content_height(720);
+
+    let picture = gtk4::Picture::new();
+    picture.set_content_fit(gtk4::ContentFit::Contain);
+    picture.set_can_shrink(true);
+    picture.set_alternative_text(Some(&gettext("Artwork for {}").replace("{}", title)));
+
+    let scrolled = gtk4::ScrolledWindow::new();
+    scrolled.set_policy(gtk4::PolicyType::Never, gtk4::PolicyType::Never);
+    scrolled.set_vexpand(true);
+    scrolled.set_hexpand(true);
+    scrolled.set_child(Some(&picture));
+
+    let viewer = Rc::new(Viewer {
+        picture,
+        scrolled: scrolled.clone(),
+        texture: RefCell::default(),
+        zoom: Cell::new(None),
+    });
+
+    let loading = adw::StatusPage::new();
+    loading.set_paintable(Some(&adw::SpinnerPaintable::new(Some(&loading))));
+
+    let failed = adw::StatusPage::new();
+    failed.set_icon_name(Some("image-missing-symbolic"));
+    failed.set_title(&gettext("Artwork Unavailable"));
+
+    let stack = gtk4::Stack::new();
+    crate::a11y::bind_stack_transition(&stack);
+    stack.add_named(&loading, Some("loading"));
+    stack.add_named(&failed, Some("error"));
+    stack.add_named(&scrolled, Some("image"));
+
+    let toast_overlay = adw::ToastOverlay::new();
+    toast_overlay.set_child(Some(&stack));
+
+    let zoom_out = gtk4::Button::from_icon_name("zoom-out-symbolic");
+    zoom_out.set_tooltip_text(Some(&gettext("Zoom Out")));
+    let zoom_fit = gtk4::Button::from_icon_name("zoom-fit-best-symbolic");
+    zoom_fit.set_tooltip_text(Some(&gettext("Fit to Window")));
+    let zoom_in = gtk4::Button::from_icon_name("zoom-in-symbolic");
+    zoom_in.set_tooltip_text(Some(&gettext("Zoom In")));
+    let zoom_box = gtk4::Box::new(gtk4::Orientation::Horizontal, 0);
+    zoom_box.add_css_class("linked");
+    zoom_box.append(&zoom_out);
+    zoom_box.append(&zoom_fit);
+    zoom_box.append(&zoom_in);
+    zoom_box.set_sensitive(false);
+
+    let save_btn = gtk4::Button::from_icon_name("document-save-symbolic");
+    save_btn.set_tooltip_text(Some(&gettext("Save Image")));
+    save_btn.set_sensitive(false);
+
+    let header = adw::HeaderBar::new();
+    header.pack_start(&zoom_box);
+    header.pack_end(&save_btn);
+
+    let toolbar_view = adw::ToolbarView::new();
+    toolbar_view.add_top_bar(&header);
+    toolbar_view.set_content(Some(&toast_overlay));
+    dialog.set_child(Some(&toolbar_view));
+
+    let v = viewer.clone();
+    zoom_out.connect_clicked(move |_| v.set_zoom(Some(v.scale() / ZOOM_STEP)));
+    let v = viewer.clone();
+    zoom_fit.connect_clicked(move |_| v.set_zoom(None));
+    let v = viewer.clone();
+    zoom_in.connect_clicked(move |_| v.set_zoom(Some(v.scale() * ZOOM_STEP)));
+
+    connect_gestures(&viewer);
+
+    let bytes: Rc<RefCell<Option<Vec<u8>>>> = Rc::default();
+    let saved = bytes.clone();
+    let save_name = url.rsplit('/').next().unwrap_or("artwork.jpg").to_string();
+    let overlay = toast_overlay.clone();
+    save_btn.connect_clicked(move |btn| {
+        let Some(bytes) = saved.borrow().clone() else { return };
+        let window = btn.root().and_downcast::<gtk4::Window>();
+        let file_dialog = gtk4::FileDialog::new();
+        file_dialog.set_title(&gettext("Save Image"));
+        file_dialog.set_initial_name(Some(&save_name));
+        let overlay = overlay.clone();
+        gtk4::glib::spawn_future_local(async move {
+            // Dismissing the file chooser is not an error worth reporting.
+            let Ok(file) = file_dialog.save_future(window.as_ref()).await else { return };
+            let result = file
+                .replace_contents_future(bytes, None, false, gio::FileCreateFlags::REPLACE_DESTINATION)
+                .await;
+            match result {
+                Ok(_) => overlay.add_toast(adw::Toast::new(&gettext("Image saved"))),
+                Err((_, e)) => {
+                    crate::diagnostics::error("art-viewer", format!("Failed to save image: {e}"));
+                    overlay.add_toast(adw::Toast::new(&gettext("Failed to save image: {}").replace("{}", &e.to_string())));
+                }
+            }
+        });
+    });
+
+    gtk4::glib::spawn_future_local(async move {
+        let fetch_url = url.clone();
+        let result = relm4::spawn(async move {
+            let data = crate::art_cache::fetch(&fetch_url).await?;
+            let decoded = data.clone();
+            let texture = relm4::spawn_blocking(move || {
+                gtk4::gdk::Texture::from_bytes(&gtk4::glib::Bytes::from(&decoded)).ok()
+            })
+            .await
+            .ok()
+            .flatten()?;
+            Some((data, texture))
+        })
+        .await
+        .ok()
+        .flatten();
+
+        let Some((data, texture)) = result else {
+            crate::diagnostics::error("art-viewer", format!("Failed to load {url}"));
+            stack.set_visible_child_name("error");
+            return;
+        };
+        bytes.replace(Some(data));
+        viewer.picture.set_paintable(Some(&texture));
+        viewer.texture.replace(Some(texture));
+        zoom_box.set_sensitive(true);
+        save_btn.set_sensitive(true);
+        stack.set_visible_child_name("image");
+    });
+
+    dialog
+}
+
+/// Pinch and Ctrl+scroll zoom; dragging with the mouse pans once the
+/// image overflows.
+fn connect_gestures(viewer: &Rc<Viewer>) {
+    let pinch = gtk4::GestureZoom::new();
+    let start = Rc::new(Cell::new(1.0));
+    let (v, s) = (viewer.clone(), start.clone());
+    pinch.connect_begin(move |_, _| s.set(v.scale()));
+    let v = viewer.clone();
+    pinch.connect_scale_changed(move |_, scale| v.set_zoom(Some(start.get() * scale)));
+    viewer.scrolled.add_controller(pinch);
+
+    let scroll = gtk4::EventControllerScroll::new(gtk4::EventControllerScrollFlags::VERTICAL);
+    let v = viewer.clone();
+    scroll.connect_scroll(move |controller, _, dy| {
+        if !controller.current_event_state().contains(gtk4::gdk::ModifierType::CONTROL_MASK) {
+            return gtk4::glib::Propagation::Proceed;
+        }
+        let factor = if dy < 0.0 { ZOOM_STEP } else { 1.0 / ZOOM_STEP };
+        v.set_zoom(Some(v.scale() * factor));
+        gtk4::glib::Propagation::Stop
+    });
+    viewer.scrolled.add_controller(scroll);
+
+    let drag = gtk4::GestureDrag::new();
+    let origin = Rc::new(Cell::new((0.0, 0.0)));
+    let (v, o) = (viewer.clone(), origin.clone());
+    drag.connect_drag_begin(move |gesture, _, _| {
+        // Touch already pans through the scrolled window itself.
+        if gesture.device().is_some_and(|d| d.source() == gtk4::gdk::InputSource::Touchscreen) {
+            gesture.set_state(gtk4::EventSequenceState::Denied);
+            return;
+        }
+        o.set((v.scrolled.hadjustment().value(), v.scrolled.vadjustment().value()));
+    });
+    let v = viewer.clone();
+    drag.connect_drag_update(move |_, dx, dy| {
+        let (x, y) = origin.get();
+        v.scrolled.hadjustment().set_value(x - dx);
+        v.scrolled.vadjustment().set_value(y - dy);
+    });
+    viewer.scrolled.add_controller(drag);
+}
@@ -0,0 +1,280 @@
+use crate::album_grid::{AlbumData, AlbumGrid, AlbumGridMsg, AlbumGridOutput, Click};
+use crate::bandcamp::{Account, BandcampClient, ClientError, Merch};
+use gettextrs::gettext;
+use gtk4::prelude::*;
+use relm4::prelude::*;
+
+/// An artist's discography, or just their releases in the library, pushed
+/// onto a tab's navigation stack. Labels also list the artists they put out.
+pub struct ArtistPage {
+    grid: Controller<AlbumGrid>,
+    client: BandcampClient,
+    band_id: u64,
+    name: String,
+    /// Library releases shown instead of fetching the discography.
+    owned: Option<Vec<AlbumData>>,
+    /// The artist's home page, once a release shows where it is.
+    home: Option<String>,
+    /// Whether the account turned out to be a label with a roster.
+    label: bool,
+    /// The releases shown, for playing them all.
+    albums: Vec<AlbumData>,
+}
+
+pub struct ArtistInit {
+    pub client: BandcampClient,
+    pub band_id: u64,
+    pub name: String,
+    /// Shows these releases from the library rather than the discography.
+    pub owned: Option<Vec<AlbumData>>,
+}
+
+/// What the page shows: releases, merch and, for labels, the roster.
+#[derive(Debug, Default)]
+pub struct Contents {
+    albums: Vec<AlbumData>,
+    roster: Vec<Account>,
+    merch: Vec<Merch>,
+}
+
+type Loaded = Result<Contents, ClientError>;
+
+#[derive(Debug)]
+pub enum ArtistMsg {
+    Loaded(Loaded),
+    GridAction(AlbumGridOutput),
+    ShowArtist(Account),
+    OpenInBrowser,
+    Follow,
+    PlayAll,
+}
+
+#[derive(Debug)]
+pub enum ArtistOutput {
+    Activate(AlbumData, Click),
+    /// Resolve the album ahead of a likely click.
+    Prefetch(AlbumData),
+    OpenInBrowser(String),
+    Follow { band_id: u64, url: String, name: String },
+    /// Open an artist from a label's roster.
+    ShowArtist(Account),
+    /// Play every release shown, in order.
+    PlayAll(Vec<AlbumData>),
+    Error(ClientError),
+}
+
+#[relm4::component(pub)]
+impl Component for ArtistPage {
+    type Init = ArtistInit;
+    type Input = ArtistMsg;
+    type Output = ArtistOutput;
+    type CommandOutput = Loaded;
+
+    view! {
+        gtk4::Box {
+            set_orientation: gtk4::Orientation::Vertical,
+            set_hexpand: true,
+            set_vexpand: true,
+
+            gtk4::CenterBox {
+                add_css_class: "compact-toolbar",
+                set_margin_start: 6,
+                set_margin_end: 6,
+                set_margin_top: 6,
+
+                #[wrap(Some)]
+                set_start_widget = &gtk4::Button {
+                    set_icon_name: "go-previous-symbolic",
+                    set_tooltip_text: Some(&gettext("Back")),
+                    add_css_class: "flat",
+                    set_action_name: Some("navigation.pop"),
+                },
+
+                #[wrap(Some)]
+                set_center_widget = &gtk4::Label {
+                    set_label: &model.name,
+                    add_css_class: "heading",
+                    set_ellipsize: gtk4::pango::EllipsizeMode::End,
+                },
+
+                #[wrap(Some)]
+                set_end_widget = &gtk4::Box {
+                    set_spacing: 4,
+
+                    gtk4::Button {
+                        set_icon_name: "media-playback-start-symbolic",
+                        set_tooltip_text: Some(&gettext("Play All")),
+                        add_css_class: "flat",
+                        #[watch]
+                        set_sensitive: !model.albums.is_empty(),
+                        connect_clicked => ArtistMsg::PlayAll,
+                    },
+
+                    gtk4::Button {
+                        set_icon_name: "web-browser-symbolic",
+                        set_tooltip_text: Some(&gettext("View on Bandcamp")),
+                        add_css_class: "flat",
+                        #[watch]
+                        set_sensitive: model.home.is_some(),
+                        connect_clicked => ArtistMsg::OpenInBrowser,
+                    },
+
+                    gtk4::Button {
+                        set_label: &gettext("Follow"),
+                        set_tooltip_text: Some(&gettext("Follow “{}” on Bandcamp").replace("{}", &model.name)),
+                        add_css_class: "flat",
+                        #[watch]
+                        set_sensitive: model.home.is_some(),
+                        connect_clicked => ArtistMsg::Follow,
+                    },
+                },
+            },
+
+            // Big labels sign hundreds of artists; keep the releases in view.
+            gtk4::ScrolledWindow {
+                set_hscrollbar_policy: gtk4::PolicyType::Never,
+                set_propagate_natural_height: true,
+                set_max_content_height: 96,
+                set_margin_start: 12,
+                set_margin_end: 12,
+                #[watch]
+                set_visible: model.label,
+
+                #[name = "roster"]
+                gtk4::FlowBox {
+                    add_css_class: "tag-chips",
+                    set_selection_mode: gtk4::SelectionMode::None,
+                    set_column_spacing: 4,
+                    set_row_spacing: 4,
+                    set_max_children_per_line: 12,
+                    update_property: &[gtk4::accessible::Property::Label(&gettext("Artists"))],
+                },
+            },
+
+            #[name = "merch_items"]
+            gtk4::FlowBox {
+                add_css_class: "merch-items",
+                set_selection_mode: gtk4::SelectionMode::None,
+                set_column_spacing: 4,
+                set_row_spacing: 4,
+                set_max_children_per_line: 6,
+                set_margin_start: 12,
+                set_margin_end: 12,
+                set_visible: false,
+                update_property: &[gtk4::accessible::Property::Label(&gettext("Merch"))],
+            },
+        }
+    }
+
+    fn init(init: Self::Init, root: Self::Root, sender: ComponentSender<Self>) -> ComponentParts<Self> {
+        let grid = AlbumGrid::builder()
+            .launch(())
+            .forward(sender.input_sender(), ArtistMsg::GridAction);
+
+        let ArtistInit { client, band_id, name, owned } = init;
+        let model = Self { grid, client, band_id, name, owned, home: None, label: false, albums: Vec::new() };
+
+        let widgets = view_output!();
+        root.append(model.grid.widget());
+        model.fetch(&sender);
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update_with_view(
+        &mut self,
+        widgets: &mut Self::Widgets,
+        msg: Self::Input,
+        sender: ComponentSender<Self>,
+        _root: &Self::Root,
+    ) {
+        match msg {
+            ArtistMsg::Loaded(result) => match result {
+                Ok(Contents { albums, roster, merch }) => {
+                    self.home = albums.iter().find_map(|a| crate::bandcamp::artist_home(&a.url));
+                    self.grid.emit(AlbumGridMsg::Replace(albums.clone()));
+                    self.albums = albums;
+                    self.label = !roster.is_empty();
+                    show_roster(&widgets.roster, &roster, &sender);
+                    let s = sender.clone();
+                    crate::merch::show(&widgets.merch_items, &merch, move |url| {
+                        s.output(ArtistOutput::OpenInBrowser(url)).ok();
+                    });
+                }
+                Err(e) => {
+                    self.grid.emit(AlbumGridMsg::Error(e.clone()));
+                    sender.output(ArtistOutput::Error(e)).ok();
+                }
+            },
+            ArtistMsg::GridAction(action) => match action {
+                AlbumGridOutput::Clicked(data, click) => {
+                    sender.output(ArtistOutput::Activate(data, click)).ok();
+                }
+                AlbumGridOutput::Hovered(data) => {
+                    sender.output(ArtistOutput::Prefetch(data)).ok();
+                }
+                AlbumGridOutput::Retry => self.fetch(&sender),
+                AlbumGridOutput::ArtistClicked(_) | AlbumGridOutput::ScrolledToBottom => {}
+            },
+            ArtistMsg::OpenInBrowser => {
+                if let Some(home) = self.home.clone() {
+                    sender.output(ArtistOutput::OpenInBrowser(home)).ok();
+                }
+            }
+            ArtistMsg::Follow => {
+                if let Some(url) = self.home.clone() {
+                    let (band_id, name) = (self.band_id, self.name.clone());
+                    sender.output(ArtistOutput::Follow { band_id, url, name }).ok();
+                }
+            }
+            ArtistMsg::PlayAll => {
+                sender.output(ArtistOutput::PlayAll(self.albums.clone())).ok();
+            }
+            ArtistMsg::ShowArtist(artist) => {
+                sender.output(ArtistOutput::ShowArtist(artist)).ok();
+            }
+        }
+        self.update_view(widgets, sender);
+    }
+
+    fn update_cmd(&mut self, msg: Self::CommandOutput, sender: ComponentSender<Self>, _root: &Self::Root) {
+        sender.input(ArtistMsg::Loaded(msg));
+    }
+}
+
+impl ArtistPage {
+    fn fetch(&self, sender: &ComponentSender<Self>) {
+        if let Some(owned) = &self.owned {
+            let contents = Contents { albums: owned.clone(), ..Default::default() };
+            sender.input(ArtistMsg::Loaded(Ok(contents)));
+            return;
+        }
+        self.grid.emit(AlbumGridMsg::Loading);
+        let client = self.client.clone();
+        let band_id = self.band_id;
+        sender.oneshot_command(async move {
+            client
+                .get_band_discography(band_id)
+                .await
+                .map(|discography| Contents {
+                    albums: discography.albums.into_iter().map(AlbumData::from).collect(),
+                    roster: discography.roster,
+                    merch: discography.merch,
+                })
+                .map_err(ClientError::from)
+        });
+    }
+}
+
+/// Fills `chips` with a button per artist on a label's roster.
+fn show_roster(chips: &gtk4::FlowBox, roster: &[Account], sender: &ComponentSender<ArtistPage>) {
+    chips.remove_all();
+    for artist in roster {
+        let chip = gtk4::Button::with_label(&artist.name);
+        chip.add_css_class("tag-chip");
+        chip.set_tooltip_text(Some(&gettext("Open “{}”").replace("{}", &artist.name)));
+        let (sender, artist) = (sender.clone(), artist.clone());
+        chip.connect_clicked(move |_| sender.input(ArtistMsg::ShowArtist(artist.clone())));
+        chips.append(&chip);
+    }
+}
@@ -0,0 +1,184 @@
+use crate::bandcamp::AlbumDetails;
+use crate::diagnostics;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// Least recently played tracks are evicted once the cache grows past
+/// this. Pinned albums don't count and are never evicted.
+const MAX_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+/// Tracks being downloaded, so replaying one before it finished doesn't
+/// fetch it twice, and whether they are to be pinned once in.
+static DOWNLOADING: Mutex<Option<HashMap<String, bool>>> = Mutex::new(None);
+
+fn cache_dir() -> PathBuf {
+    crate::storage::cache_dir().join("audio")
+}
+
+fn pinned_dir() -> PathBuf {
+    crate::storage::cache_dir().join("audio-pinned")
+}
+
+/// Track lists of albums with cached tracks, for playing them offline.
+fn albums_dir() -> PathBuf {
+    cache_dir().join("albums")
+}
+
+/// Stream URLs are signed and expire, so tracks are keyed by their share
+/// link instead.
+fn file_name(track_url: &str) -> String {
+    format!("{}.mp3", crate::art_cache::key(track_url))
+}
+
+/// A `file://` URI to play `track_url` from, if it was cached or pinned.
+pub fn cached_uri(track_url: &str) -> Option<String> {
+    if track_url.is_empty() {
+        return None;
+    }
+    let name = file_name(track_url);
+    let path = [pinned_dir(), cache_dir()]
+        .into_iter()
+        .map(|dir| dir.join(&name))
+        .find(|path| path.exists())?;
+    // The modification time doubles as the last play time for LRU.
+    if let Ok(file) = fs::File::options().write(true).open(&path) {
+        let _ = file.set_modified(SystemTime::now());
+    }
+    gtk4::glib::filename_to_uri(&path, None).ok().map(Into::into)
+}
+
+/// Keeps the track list of `details` so its cached tracks can be played
+/// without reaching Bandcamp.
+pub fn remember_album(details: &AlbumDetails) {
+    let path = albums_dir().join(format!("{}.json", crate::art_cache::key(&details.url)));
+    let result = serde_json::to_vec(details)
+        .map_err(std::io::Error::other)
+        .and_then(|json| fs::create_dir_all(albums_dir()).and_then(|_| fs::write(&path, json)));
+    if let Err(e) = result {
+        diagnostics::error("audio-cache", format!("Failed to remember {}: {e}", details.url));
+    }
+}
+
+/// The album at `url` as remembered, with only the tracks on disk left
+/// playable, or `None` if none are.
+pub fn album(url: &str) -> Option<AlbumDetails> {
+    let path = albums_dir().join(format!("{}.json", crate::art_cache::key(url)));
+    let mut details: AlbumDetails = serde_json::from_slice(&fs::read(path).ok()?).ok()?;
+    for track in &mut details.tracks {
+        if cached_uri(&track.url).is_none() {
+            track.stream_url = None;
+        }
+    }
+    details.tracks.iter().any(|t| t.stream_url.is_some()).then_some(details)
+}
+
+/// Downloads the stream of `track_url` unless it is on disk already.
+/// Pinning moves a cached copy out of reach of eviction.
+pub async fn store(track_url: String, stream_url: String, pinned: bool) -> Result<(), String> {
+    let name = file_name(&track_url);
+    let (dir, other) = if pinned { (pinned_dir(), cache_dir()) } else { (cache_dir(), pinned_dir()) };
+    let path = dir.join(&name);
+    if path.exists() || (!pinned && other.join(&name).exists()) {
+        return Ok(());
+    }
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    if pinned && fs::rename(other.join(&name), &path).is_ok() {
+        return Ok(());
+    }
+
+    {
+        let mut downloading = DOWNLOADING.lock().map_err(|e| e.to_string())?;
+        let downloading = downloading.get_or_insert_with(HashMap::new);
+        if let Some(pin) = downloading.get_mut(&name) {
+            // Pinning a track already on its way pins it once it's in.
+            *pin |= pinned;
+            return Ok(());
+        }
+        downloading.insert(name.clone(), pinned);
+    }
+    let mut result = download(&stream_url, &path).await;
+    let pin = DOWNLOADING
+        .lock()
+        .ok()
+        .and_then(|mut downloading| downloading.get_or_insert_with(HashMap::new).remove(&name))
+        .unwrap_or(pinned);
+    if result.is_ok() && pin && !pinned {
+        result = fs::create_dir_all(pinned_dir())
+            .and_then(|_| fs::rename(&path, pinned_dir().join(&name)))
+            .map_err(|e| e.to_string());
+    }
+    if let Err(e) = &result {
+        diagnostics::error("audio-cache", format!("Failed to cache {track_url}: {e}"));
+    } else if !pin {
+        evict();
+    }
+    result
+}
+
+async fn download(stream_url: &str, path: &std::path::Path) -> Result<(), String> {
+    let response = crate::bandcamp::http()
+        .get(stream_url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| e.to_string())?;
+    let bytes = response.bytes().await.map_err(|e| e.to_string())?;
+    // Written aside first so a half-written file is never played.
+    let partial = path.with_extension("part");
+    fs::write(&partial, &bytes)
+        .and_then(|_| fs::rename(&partial, path))
+        .map_err(|e| e.to_string())
+}
+
+fn entries(dir: PathBuf) -> Vec<(PathBuf, u64, SystemTime)> {
+    let Ok(dir) = fs::read_dir(dir) else { return Vec::new() };
+    dir.flatten()
+        .filter_map(|entry| {
+            let meta = entry.metadata().ok()?;
+            meta.is_file()
+                .then(|| (entry.path(), meta.len(), meta.modified().unwrap_or(SystemTime::UNIX_EPOCH)))
+        })
+        .collect()
+}
+
+/// Removes the least recently played tracks until the cache fits
+/// `MAX_BYTES`.
+fn evict() {
+    let mut entries = entries(cache_dir());
+    let mut total: u64 = entries.iter().map(|(_, size, _)| size).sum();
+    if total <= MAX_BYTES {
+        return;
+    }
+    entries.sort_by_key(|(_, _, modified)| *modified);
+    for (path, size, _) in entries {
+        if total <= MAX_BYTES {
+            break;
+        }
+        if fs::remove_file(&path).is_ok() {
+            total -= size;
+        }
+    }
+}
+
+/// Bytes used by cached and pinned tracks.
+pub fn usage() -> u64 {
+    [cache_dir(), pinned_dir()]
+        .into_iter()
+        .flat_map(entries)
+        .map(|(_, size, _)| size)
+        .sum()
+}
+
+/// Removes cached and pinned tracks alike.
+pub fn clear() {
+    for dir in [cache_dir(), pinned_dir()] {
+        if let Err(e) = fs::remove_dir_all(dir) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                diagnostics::error("audio-cache", format!("Failed to clear cache: {e}"));
+            }
+        }
+    }
+}
@@ -1,12 +1,83 @@
 use anyhow::{anyhow, Result};
-use reqwest::header::{HeaderMap, HeaderValue, COOKIE};
-use reqwest::Client;
+use reqwest::header::{HeaderMap, HeaderValue, COOKIE, SET_COOKIE};
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
 use serde::Deserialize;
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+use crate::diagnostics::{self, Level};
+
+use super::error::ClientError;
+use super::links::artist_home;
 use super::types::*;
 
 const API_BASE: &str = "https://bandcamp.com/api";
+/// How long to back off when Bandcamp rate-limits without saying.
+const DEFAULT_RETRY_SECS: u64 = 30;
+/// How long resolved album details are reused, unless their stream URLs
+/// expire sooner.
+const DETAILS_TTL: Duration = Duration::from_secs(10 * 60);
+/// Stream URLs this close to expiring are treated as expired already.
+const STREAM_EXPIRY_MARGIN: u64 = 60;
+/// Discographies fetched at once while looking for upcoming releases.
+const UPCOMING_CONCURRENCY: usize = 4;
+const USER_AGENT: &str = "Mozilla/5.0 (X11; Linux x86_64; rv:120.0) Gecko/20100101 Firefox/120.0";
+/// How long DNS, TCP and TLS together may take. Addresses are raced
+/// happy-eyeballs style, IPv4 joining after 300 ms, so a broken IPv6 route
+/// costs that much rather than the whole timeout.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Idle connections kept per host. Over HTTP/2 one carries every request
+/// to a host; the rest are for hosts still on HTTP/1.1.
+const POOL_IDLE_PER_HOST: usize = 8;
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+/// Pings keep idle HTTP/2 connections from being dropped by NAT between
+/// pages, so the next one doesn't pay for a handshake.
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(30);
+/// Hosts connected to ahead of the first page: the API and artwork.
+const WARM_UP_HOSTS: &[&str] = &["https://bandcamp.com/", "https://f4.bcbits.com/"];
+
+/// The one HTTP client for the whole app, so artwork, audio and API
+/// requests share a connection pool, user agent and proxy settings.
+/// Cloning it is cheap and keeps the pool. Bandcamp's hosts speak HTTP/2,
+/// picked during the TLS handshake, so a page of artwork goes out over a
+/// single connection.
+pub fn http() -> &'static Client {
+    static HTTP: OnceLock<Client> = OnceLock::new();
+    HTTP.get_or_init(|| {
+        Client::builder()
+            .user_agent(USER_AGENT)
+            .connect_timeout(CONNECT_TIMEOUT)
+            .pool_max_idle_per_host(POOL_IDLE_PER_HOST)
+            .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+            .tcp_keepalive(KEEP_ALIVE_INTERVAL)
+            .http2_adaptive_window(true)
+            .http2_keep_alive_interval(KEEP_ALIVE_INTERVAL)
+            .http2_keep_alive_timeout(CONNECT_TIMEOUT)
+            .build()
+            .unwrap_or_default()
+    })
+}
+
+/// Connects to the API and artwork hosts before anything is asked of
+/// them. Requests made while no connection is up yet each open their own,
+/// as nobody knows the host speaks HTTP/2 until the first handshake ends;
+/// with one already pooled, a page's worth of artwork shares it instead.
+pub async fn warm_up() {
+    tokio::join!(connect(WARM_UP_HOSTS[0]), connect(WARM_UP_HOSTS[1]));
+}
+
+async fn connect(url: &str) {
+    let start = Instant::now();
+    match http().head(url).send().await {
+        Ok(resp) => diagnostics::record(
+            Level::Info,
+            "http",
+            format!("Connected to {} over {:?} in {} ms", url, resp.version(), start.elapsed().as_millis()),
+        ),
+        Err(e) => diagnostics::record(Level::Info, "http", format!("Couldn't connect to {}: {}", url, e)),
+    }
+}
 
 #[derive(Debug, Clone, Deserialize)]
 struct CollectionSummaryResponse {
@@ -24,14 +95,45 @@ struct CollectionResponse {
     items: Vec<CollectionItemData>,
     more_available: bool,
     last_token: Option<String>,
+    /// Download pages of purchases, keyed by sale item type and ID.
+    #[serde(default)]
+    redownload_urls: HashMap<String, String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 struct CollectionItemData {
     item_title: Option<String>,
     band_name: Option<String>,
+    band_id: Option<u64>,
     item_art_id: Option<u64>,
     item_url: Option<String>,
+    sale_item_type: Option<String>,
+    sale_item_id: Option<u64>,
+    tralbum_id: Option<u64>,
+    tralbum_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct DownloadPageData {
+    #[serde(default)]
+    digital_items: Vec<DigitalItem>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct DigitalItem {
+    #[serde(default)]
+    downloads: HashMap<String, DownloadLink>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct DownloadLink {
+    url: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct StatDownloadResponse {
+    result: Option<String>,
+    download_url: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -134,9 +236,79 @@ struct TralbumResponse {
     title: Option<String>,
     tralbum_artist: Option<String>,
     art_id: Option<u64>,
+    currency: Option<String>,
     band: Option<TralbumBand>,
     #[serde(default)]
     tracks: Vec<TralbumTrack>,
+    #[serde(default)]
+    tags: Vec<TralbumTag>,
+    #[serde(default)]
+    is_purchasable: bool,
+    /// The minimum price; zero for name-your-price releases free to take.
+    price: Option<f64>,
+    #[serde(default)]
+    require_email: bool,
+    /// 1 is all rights reserved, the others Creative Commons licenses.
+    license_type: Option<u32>,
+    /// The label account the release is on; the artist's own band ID for
+    /// self-released ones.
+    label: Option<String>,
+    label_id: Option<u64>,
+    /// Physical editions sold with the release.
+    #[serde(default)]
+    packages: Vec<MerchPackage>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct MerchPackage {
+    title: Option<String>,
+    /// E.g. "Vinyl LP" or "T-Shirt/Apparel".
+    type_str: Option<String>,
+    price: Option<f64>,
+    currency: Option<String>,
+    /// Absent for items without a limited run.
+    quantity_available: Option<u32>,
+    #[serde(default)]
+    arts: Vec<MerchArt>,
+    /// Relative to the band's site, e.g. `/merch/tour-shirt`.
+    url: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct MerchArt {
+    image_id: Option<u64>,
+}
+
+impl MerchPackage {
+    /// `page` is where the item is bought when it has no page of its own:
+    /// the release it comes with, or the band's site.
+    fn to_merch(self, page: &str, currency: Option<&str>) -> Merch {
+        let url = match self.url.filter(|url| !url.is_empty()) {
+            Some(url) if url.starts_with('/') => match artist_home(page) {
+                Some(home) => format!("{}{url}", home.trim_end_matches('/')),
+                None => page.to_string(),
+            },
+            Some(url) => url,
+            None => page.to_string(),
+        };
+        Merch {
+            title: self.title.unwrap_or_default(),
+            kind: self.type_str.filter(|kind| !kind.is_empty()),
+            art_url: self.arts.into_iter().find_map(|art| art.image_id).map(merch_art_url),
+            price: self.price.map(|amount| Price {
+                amount,
+                currency: self.currency.or_else(|| currency.map(str::to_string)).unwrap_or_default(),
+            }),
+            remaining: self.quantity_available,
+            url,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct TralbumTag {
+    name: String,
+    norm_name: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -147,9 +319,19 @@ struct TralbumBand {
 #[derive(Debug, Clone, Deserialize)]
 struct TralbumTrack {
     title: Option<String>,
+    /// Set on compilations whose label credited each track.
+    artist: Option<String>,
     streaming_url: Option<StreamingUrl>,
     duration: Option<f64>,
     art_id: Option<u64>,
+    track_num: Option<u32>,
+    /// Only sent for releases split into volumes.
+    disc_num: Option<u32>,
+    /// Whether the track is sold on its own, not just with the album.
+    #[serde(default)]
+    is_purchasable: bool,
+    price: Option<f64>,
+    currency: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -158,9 +340,85 @@ struct StreamingUrl {
     mp3_128: Option<String>,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+struct BandDetailsResponse {
+    name: Option<String>,
+    bandcamp_url: Option<String>,
+    #[serde(default)]
+    discography: Vec<DiscographyItem>,
+    /// Only sent for label accounts.
+    #[serde(default)]
+    artists: Vec<RosterArtist>,
+    #[serde(default)]
+    merch: Vec<MerchPackage>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct RosterArtist {
+    id: Option<u64>,
+    name: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct DiscographyItem {
+    item_id: Option<u64>,
+    item_type: Option<String>,
+    title: Option<String>,
+    art_id: Option<u64>,
+    band_id: Option<u64>,
+    artist_name: Option<String>,
+    /// E.g. `14 Mar 2025 00:00:00 GMT`; in the future for pre-orders.
+    release_date: Option<String>,
+}
+
+/// The band a discography belongs to.
+struct Band {
+    id: u64,
+    name: String,
+    /// Discography entries carry no page URL; the band page stands in for
+    /// "open in browser" while playback goes through the IDs.
+    url: String,
+}
+
+impl DiscographyItem {
+    fn to_album(self, band: &Band) -> Album {
+        Album {
+            title: self.title.unwrap_or_default(),
+            artist: self.artist_name.unwrap_or_else(|| band.name.clone()),
+            art_url: self.art_id.map(art_url_thumb),
+            url: band.url.clone(),
+            genre: None,
+            band_id: self.band_id.or(Some(band.id)),
+            item_id: self.item_id,
+            item_type: self.item_type.map(|t| match t.as_str() {
+                "track" | "t" => "t".to_string(),
+                _ => "a".to_string(),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct FollowingResponse {
+    /// Sic, Bandcamp's spelling.
+    #[serde(default)]
+    followeers: Vec<FollowedBand>,
+    #[serde(default)]
+    more_available: bool,
+    last_token: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct FollowedBand {
+    band_id: Option<u64>,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 struct TralbumPageData {
     current: Option<TralbumCurrent>,
+    /// Where free releases are downloaded, as purchases are from theirs.
+    #[serde(rename = "freeDownloadPage")]
+    free_download_page: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -171,11 +429,268 @@ struct TralbumCurrent {
     item_type: Option<String>,
 }
 
+/// Answer to the actions behind buttons on artist pages, e.g. wishlisting
+/// or following.
+#[derive(Debug, Clone, Deserialize)]
+struct PageActionResponse {
+    #[serde(default)]
+    ok: bool,
+    error: Option<String>,
+    /// A fresh crumb, sent along with an `invalid_crumb` error.
+    crumb: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct FanPageData {
+    fan_data: Option<FanData>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct PurchasesPageData {
+    orderhistory: Option<OrderHistory>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct OrderHistory {
+    #[serde(default)]
+    items: Vec<OrderItem>,
+    /// Where the next page starts; unset on the last one.
+    last_token: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct OrderItem {
+    item_title: Option<String>,
+    artist_name: Option<String>,
+    art_id: Option<u64>,
+    item_url: Option<String>,
+    /// `a` and `t` for albums and tracks, `p` for packages.
+    item_type: Option<String>,
+    /// E.g. `14 Mar 2025 18:04:11 GMT`.
+    payment_date: Option<String>,
+    total: Option<f64>,
+    currency: Option<String>,
+    sale_id: Option<u64>,
+    download_url: Option<String>,
+}
+
+impl OrderItem {
+    fn to_purchase(self) -> Purchase {
+        Purchase {
+            title: self.item_title.unwrap_or_default(),
+            artist: self.artist_name.unwrap_or_default(),
+            art_url: self.art_id.map(art_url_thumb),
+            url: self.item_url.unwrap_or_default(),
+            physical: self.item_type.as_deref() == Some("p"),
+            date: self.payment_date.as_deref().and_then(parse_release_date),
+            total: self.total.map(|amount| Price {
+                amount,
+                currency: self.currency.unwrap_or_default(),
+            }),
+            sale_id: self.sale_id,
+            download_url: self.download_url.filter(|url| !url.is_empty()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct FanData {
+    name: Option<String>,
+    photo: Option<FanPhoto>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct FanPhoto {
+    image_id: Option<u64>,
+}
+
+/// Sends a request and records its outcome and timing in the diagnostics log.
+/// Rejected sessions and rate limiting come back as [`ClientError`]s.
+trait SendTimed {
+    async fn send_timed(self) -> Result<Response>;
+}
+
+impl SendTimed for RequestBuilder {
+    async fn send_timed(self) -> Result<Response> {
+        let (client, request) = self.build_split();
+        let request = request?;
+        let label = format!("{} {}", request.method(), request.url().path());
+        let start = Instant::now();
+        let result = client.execute(request).await;
+        let elapsed = start.elapsed().as_millis();
+        match &result {
+            Ok(resp) => diagnostics::record(
+                Level::Info,
+                "http",
+                format!("{} → {} over {:?} in {} ms", label, resp.status(), resp.version(), elapsed),
+            ),
+            Err(e) => diagnostics::error(
+                "http",
+                format!("{} failed after {} ms: {}", label, elapsed, e),
+            ),
+        }
+        let response = result?;
+        match response.status() {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Err(ClientError::SessionExpired.into()),
+            StatusCode::TOO_MANY_REQUESTS => {
+                let secs = response
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.trim().parse().ok())
+                    .unwrap_or(DEFAULT_RETRY_SECS);
+                Err(ClientError::RateLimited(secs).into())
+            }
+            _ => Ok(response),
+        }
+    }
+}
+
+/// Band, tralbum type (`a` or `t`) and tralbum ID of a release page.
+fn tralbum_ids(html: &str) -> Result<(u64, String, u64)> {
+    let json_str = extract_data_attr(html, "data-tralbum")?;
+    let data: TralbumPageData = serde_json::from_str(&json_str)?;
+    let current = data
+        .current
+        .ok_or_else(|| anyhow!("No current field in tralbum"))?;
+    let band_id = current
+        .band_id
+        .ok_or_else(|| anyhow!("No band_id in tralbum"))?;
+    let tralbum_id = current
+        .id
+        .ok_or_else(|| anyhow!("No id in tralbum"))?;
+    let tralbum_type = match current.item_type.as_deref() {
+        Some("track") => "t",
+        _ => "a",
+    }
+    .to_string();
+
+    Ok((band_id, tralbum_type, tralbum_id))
+}
+
+/// The artist and title of each track. Compilations credit their artists
+/// per track, or else title every track "Artist - Title"; the latter only
+/// counts when more than one artist is named, so a single artist's song
+/// titles with dashes in them are left alone. `None` is the album artist.
+fn track_credits(tracks: &[TralbumTrack]) -> Vec<(Option<String>, String)> {
+    let split: Option<Vec<(&str, &str)>> = tracks
+        .iter()
+        .map(|t| t.title.as_deref()?.split_once(" - "))
+        .collect();
+    let split = split.filter(|split| {
+        let artists: HashSet<String> = split.iter().map(|(artist, _)| artist.trim().to_lowercase()).collect();
+        artists.len() > 1
+    });
+    tracks
+        .iter()
+        .enumerate()
+        .map(|(i, t)| match (t.artist.as_deref().filter(|a| !a.is_empty()), &split) {
+            (Some(artist), _) => (Some(artist.to_string()), t.title.clone().unwrap_or_default()),
+            (None, Some(split)) => (Some(split[i].0.trim().to_string()), split[i].1.trim().to_string()),
+            (None, None) => (None, t.title.clone().unwrap_or_default()),
+        })
+        .collect()
+}
+
+/// When the signed stream URLs in `details` stop working. Bandcamp puts
+/// the expiry, in seconds since the epoch, before the `_` of the `token`
+/// query parameter.
+fn stream_expiry(details: &AlbumDetails) -> Option<SystemTime> {
+    details
+        .tracks
+        .iter()
+        .filter_map(|t| t.stream_url.as_deref())
+        .filter_map(|url| {
+            let token = url.split(['?', '&']).find_map(|p| p.strip_prefix("token="))?;
+            token.split('_').next()?.parse::<u64>().ok()
+        })
+        .min()
+        .map(|secs| UNIX_EPOCH + Duration::from_secs(secs.saturating_sub(STREAM_EXPIRY_MARGIN)))
+}
+
+/// Seconds since the epoch of a date like `14 Mar 2025 00:00:00 GMT`, at
+/// the start of that day.
+fn parse_release_date(date: &str) -> Option<u64> {
+    const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+    let mut parts = date.split_whitespace();
+    let day: i64 = parts.next()?.parse().ok()?;
+    let month = MONTHS.iter().position(|m| Some(*m) == parts.next())? as i64 + 1;
+    let year: i64 = parts.next()?.parse().ok()?;
+    // Days from the epoch to a civil date, after Howard Hinnant.
+    let (y, m) = if month <= 2 { (year - 1, month + 9) } else { (year, month - 3) };
+    let era = y.div_euclid(400);
+    let year_of_era = y - era * 400;
+    let day_of_year = (153 * m + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146097 + day_of_era - 719468;
+    u64::try_from(days * 86400).ok()
+}
+
+/// Extract and unescape a JSON blob stored in an HTML data attribute.
+fn extract_data_attr(html: &str, attr: &str) -> Result<String> {
+    let marker = format!("{}=\"", attr);
+    let start = html
+        .find(&marker)
+        .ok_or_else(|| anyhow!("No {} found on page", attr))?
+        + marker.len();
+    let end = html[start..]
+        .find('"')
+        .ok_or_else(|| anyhow!("Malformed {}", attr))?
+        + start;
+    Ok(html[start..end]
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&")
+        .replace("&#39;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">"))
+}
+
+/// Whether `url` is served over HTTPS by bandcamp.com or one of its
+/// subdomains, the only hosts the session cookie is meant for.
+fn is_bandcamp(url: &str) -> bool {
+    reqwest::Url::parse(url).is_ok_and(|url| {
+        url.scheme() == "https"
+            && url.host_str().is_some_and(|host| host == "bandcamp.com" || host.ends_with(".bandcamp.com"))
+    })
+}
+
+/// Applies one `Set-Cookie` header to a `Cookie` header string: replaces
+/// or appends the named cookie, or drops it when the header expires it.
+fn merge_cookie(cookies: &str, set_cookie: &str) -> String {
+    let mut attrs = set_cookie.split(';').map(str::trim);
+    let Some((name, value)) = attrs.next().and_then(|pair| pair.split_once('=')) else {
+        return cookies.to_string();
+    };
+    let expired = attrs.any(|a| a.eq_ignore_ascii_case("max-age=0"));
+
+    let mut found = false;
+    let mut parts: Vec<String> = Vec::new();
+    for part in cookies.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+        if part.split_once('=').map(|(n, _)| n) == Some(name) {
+            found = true;
+            if !expired {
+                parts.push(format!("{}={}", name, value));
+            }
+        } else {
+            parts.push(part.to_string());
+        }
+    }
+    if !found && !expired {
+        parts.push(format!("{}={}", name, value));
+    }
+    parts.join("; ")
+}
+
 #[derive(Debug)]
 struct ClientInner {
     client: Client,
-    cookies: String,
+    /// Updated in place when Bandcamp refreshes the session.
+    cookies: RwLock<String>,
     fan: FanInfo,
+    /// Band, tralbum type and tralbum ID of release pages already scraped.
+    tralbum_ids: Mutex<HashMap<String, (u64, String, u64)>>,
+    /// Album details by band and tralbum ID, with when they go stale.
+    details: Mutex<HashMap<(u64, u64), (Instant, AlbumDetails)>>,
 }
 
 #[derive(Clone, Debug)]
@@ -185,9 +700,7 @@ pub struct BandcampClient {
 
 impl BandcampClient {
     pub async fn new(cookies: String) -> Result<Self> {
-        let client = Client::builder()
-            .user_agent("Mozilla/5.0 (X11; Linux x86_64; rv:120.0) Gecko/20100101 Firefox/120.0")
-            .build()?;
+        let client = http().clone();
 
         let mut headers = HeaderMap::new();
         headers.insert(COOKIE, HeaderValue::from_str(&cookies)?);
@@ -195,7 +708,7 @@ impl BandcampClient {
         let resp: CollectionSummaryResponse = client
             .get(format!("{}/fan/2/collection_summary", API_BASE))
             .headers(headers)
-            .send()
+            .send_timed()
             .await?
             .json()
             .await?;
@@ -212,20 +725,82 @@ impl BandcampClient {
         Ok(Self {
             inner: Arc::new(ClientInner {
                 client,
-                cookies,
+                cookies: RwLock::new(cookies),
+                fan,
+                tralbum_ids: Mutex::default(),
+                details: Mutex::default(),
+            }),
+        })
+    }
+
+    /// Builds a client from saved cookies and fan info without contacting
+    /// Bandcamp, so the cached library can be browsed while offline.
+    pub fn offline(cookies: String, fan: FanInfo) -> Result<Self> {
+        let client = http().clone();
+
+        Ok(Self {
+            inner: Arc::new(ClientInner {
+                client,
+                cookies: RwLock::new(cookies),
                 fan,
+                tralbum_ids: Mutex::default(),
+                details: Mutex::default(),
             }),
         })
     }
 
-    fn headers(&self) -> HeaderMap {
+    /// Headers for a request to `url`, carrying the session only to
+    /// Bandcamp itself. Release links can point at any host, such as an
+    /// artist's custom domain, and those are fetched without credentials.
+    fn headers(&self, url: &str) -> HeaderMap {
         let mut headers = HeaderMap::new();
-        if let Ok(cookie) = HeaderValue::from_str(&self.inner.cookies) {
+        if !is_bandcamp(url) {
+            return headers;
+        }
+        if let Ok(cookie) = HeaderValue::from_str(&self.cookies()) {
             headers.insert(COOKIE, cookie);
         }
         headers
     }
 
+    pub fn cookies(&self) -> String {
+        self.inner.cookies.read().map(|c| c.clone()).unwrap_or_default()
+    }
+
+    /// Touches an authenticated endpoint so the session doesn't lapse and
+    /// folds any cookies Bandcamp sets in the response into the session.
+    /// Returns the new cookie string when it changed.
+    pub async fn keep_alive(&self) -> Result<Option<String>> {
+        let resp = self
+            .inner
+            .client
+            .get(format!("{}/fan/2/collection_summary", API_BASE))
+            .headers(self.headers(API_BASE))
+            .send_timed()
+            .await?;
+
+        let current = self.cookies();
+        let updated = resp
+            .headers()
+            .get_all(SET_COOKIE)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .fold(current.clone(), |cookies, set_cookie| merge_cookie(&cookies, set_cookie));
+
+        let summary: CollectionSummaryResponse = resp.json().await?;
+        if summary.collection_summary.is_none() {
+            return Err(anyhow!("Not authenticated"));
+        }
+
+        if updated == current {
+            return Ok(None);
+        }
+        if let Ok(mut cookies) = self.inner.cookies.write() {
+            *cookies = updated.clone();
+        }
+        Ok(Some(updated))
+    }
+
     pub fn fan(&self) -> &FanInfo {
         &self.inner.fan
     }
@@ -243,7 +818,7 @@ impl BandcampClient {
             .inner
             .client
             .get(&url)
-            .send()
+            .send_timed()
             .await?
             .json()
             .await?;
@@ -277,23 +852,31 @@ impl BandcampClient {
                 .inner
                 .client
                 .post(url)
-                .headers(self.headers())
+                .headers(self.headers(url))
                 .json(&serde_json::json!({
                     "fan_id": fan_id,
                     "older_than_token": token,
                     "count": 50
                 }))
-                .send()
+                .send_timed()
                 .await?
                 .json()
                 .await?;
 
             for item in resp.items {
+                let download_url = match (&item.sale_item_type, item.sale_item_id) {
+                    (Some(kind), Some(id)) => resp.redownload_urls.get(&format!("{kind}{id}")).cloned(),
+                    _ => None,
+                };
                 all_items.push(CollectionItem {
                     title: item.item_title.unwrap_or_default(),
                     artist: item.band_name.unwrap_or_default(),
                     art_url: item.item_art_id.map(art_url_thumb),
                     url: item.item_url.unwrap_or_default(),
+                    download_url,
+                    band_id: item.band_id,
+                    item_id: item.tralbum_id,
+                    item_type: item.tralbum_type,
                 });
             }
 
@@ -307,6 +890,15 @@ impl BandcampClient {
         Ok(all_items)
     }
 
+    /// Loads a release's details straight by its band ID, type and item ID
+    /// when they are known, resolving them from its page otherwise.
+    pub async fn get_release_details(&self, url: &str, ids: Option<(u64, &str, u64)>) -> Result<AlbumDetails> {
+        match ids {
+            Some((band_id, item_type, item_id)) => self.get_album_details_by_id(band_id, item_type, item_id, url).await,
+            None => self.get_album_details(url).await,
+        }
+    }
+
     pub async fn get_album_details(&self, album_url: &str) -> Result<AlbumDetails> {
         let (band_id, tralbum_type, tralbum_id) = self.resolve_tralbum(album_url).await?;
         self.get_album_details_by_id(band_id, &tralbum_type, tralbum_id, album_url)
@@ -320,6 +912,15 @@ impl BandcampClient {
         tralbum_id: u64,
         album_url: &str,
     ) -> Result<AlbumDetails> {
+        let key = (band_id, tralbum_id);
+        let cached = self.inner.details.lock().ok().and_then(|cache| {
+            let (until, details) = cache.get(&key)?;
+            (*until > Instant::now()).then(|| details.clone())
+        });
+        if let Some(details) = cached {
+            return Ok(details);
+        }
+
         let resp: TralbumResponse = self
             .inner
             .client
@@ -329,7 +930,7 @@ impl BandcampClient {
                 "tralbum_type": tralbum_type,
                 "tralbum_id": tralbum_id
             }))
-            .send()
+            .send_timed()
             .await?
             .json()
             .await?;
@@ -340,70 +941,418 @@ impl BandcampClient {
             .or_else(|| resp.band.and_then(|b| b.name))
             .unwrap_or_default();
 
+        let free_download = (resp.is_purchasable && resp.price == Some(0.0)).then(|| FreeDownload {
+            email_required: resp.require_email,
+            creative_commons: resp.license_type.is_some_and(|t| t > 1),
+        });
+        let label = match (resp.label_id, resp.label) {
+            (Some(id), Some(name)) if id != band_id && !name.is_empty() => Some(Account { band_id: id, name }),
+            _ => None,
+        };
+        let merch: Vec<Merch> = resp
+            .packages
+            .into_iter()
+            .map(|package| package.to_merch(album_url, resp.currency.as_deref()))
+            .collect();
+        let credits = track_credits(&resp.tracks);
+        let tags: Vec<Tag> = resp
+            .tags
+            .into_iter()
+            .map(|t| Tag {
+                slug: t.norm_name.unwrap_or_else(|| t.name.to_lowercase().replace(' ', "-")),
+                name: t.name,
+            })
+            .collect();
         let tracks = resp
             .tracks
             .into_iter()
-            .map(|t| TrackInfo {
-                title: t.title.unwrap_or_default(),
-                artist: artist.clone(),
+            .zip(credits)
+            .enumerate()
+            .map(|(i, (t, (credit, title)))| TrackInfo {
+                title,
+                artist: credit.unwrap_or_else(|| artist.clone()),
+                album_artist: artist.clone(),
                 album: album_title.clone(),
                 art_url: t.art_id.or(resp.art_id).map(art_url_large),
                 stream_url: t.streaming_url.and_then(|s| s.mp3_128),
                 duration: t.duration,
+                url: format!("{album_url}#t{}", i + 1),
+                number: t.track_num.filter(|&n| n > 0),
+                disc: t.disc_num.filter(|&n| n > 0),
+                price: t.is_purchasable.then(|| Price {
+                    amount: t.price.unwrap_or(0.0),
+                    currency: t.currency.or_else(|| resp.currency.clone()).unwrap_or_default(),
+                }),
+                tags: tags.clone(),
+                free_download,
+                release_id: Some(tralbum_id),
+                label: label.clone(),
+                merch: merch.clone(),
             })
             .collect();
 
-        Ok(AlbumDetails {
+        let details = AlbumDetails {
             url: album_url.to_string(),
             tracks,
-        })
+        };
+        let now = Instant::now();
+        let ttl = match stream_expiry(&details) {
+            Some(expiry) => expiry.duration_since(SystemTime::now()).unwrap_or_default().min(DETAILS_TTL),
+            None => DETAILS_TTL,
+        };
+        if let Ok(mut cache) = self.inner.details.lock() {
+            cache.retain(|_, (until, _)| *until > now);
+            cache.insert(key, (now + ttl, details.clone()));
+        }
+        Ok(details)
     }
 
     async fn resolve_tralbum(&self, url: &str) -> Result<(u64, String, u64)> {
+        let cached = self.inner.tralbum_ids.lock().ok().and_then(|ids| ids.get(url).cloned());
+        if let Some(ids) = cached {
+            return Ok(ids);
+        }
+        let html = self
+            .inner
+            .client
+            .get(url)
+            .headers(self.headers(url))
+            .send_timed()
+            .await?
+            .text()
+            .await?;
+        let ids = tralbum_ids(&html)?;
+        if let Ok(mut cache) = self.inner.tralbum_ids.lock() {
+            cache.insert(url.to_string(), ids.clone());
+        }
+        Ok(ids)
+    }
+
+    /// Adds a release to the wishlist or takes it off, like the heart on
+    /// its page does. `ids` are the band, tralbum type (`a` or `t`) and
+    /// tralbum ID when known; otherwise `url` must be the release page.
+    pub async fn set_wishlisted(&self, url: &str, ids: Option<(u64, String, u64)>, wishlisted: bool) -> Result<()> {
+        let html = self
+            .inner
+            .client
+            .get(url)
+            .headers(self.headers(url))
+            .send_timed()
+            .await?
+            .text()
+            .await?;
+        let (band_id, tralbum_type, tralbum_id) = match ids {
+            Some(ids) => ids,
+            None => tralbum_ids(&html)?,
+        };
+        let endpoint = if wishlisted { "collect_item_cb" } else { "uncollect_item_cb" };
+        let item_type = if tralbum_type == "t" { "track" } else { "album" };
+        let form = [
+            ("fan_id", self.inner.fan.fan_id.to_string()),
+            ("item_id", tralbum_id.to_string()),
+            ("item_type", item_type.to_string()),
+            ("band_id", band_id.to_string()),
+        ];
+        self.post_crumbed(url, &html, endpoint, &form).await
+    }
+
+    /// Follows an artist, like the button on their page does. `url` is
+    /// any page on the artist's domain.
+    pub async fn follow_band(&self, url: &str, band_id: u64) -> Result<()> {
         let html = self
             .inner
             .client
             .get(url)
-            .headers(self.headers())
-            .send()
+            .headers(self.headers(url))
+            .send_timed()
             .await?
             .text()
             .await?;
+        let form = [
+            ("fan_id", self.inner.fan.fan_id.to_string()),
+            ("band_id", band_id.to_string()),
+        ];
+        self.post_crumbed(url, &html, "follow_band_cb", &form).await
+    }
+
+    /// Posts `form` to one of the page actions on the domain of `url`,
+    /// signed with the crumb the page `html` holds for `endpoint`.
+    async fn post_crumbed(&self, url: &str, html: &str, endpoint: &str, form: &[(&str, String)]) -> Result<()> {
+        let crumbs: HashMap<String, String> = serde_json::from_str(&extract_data_attr(html, "data-crumbs")?)?;
+        let mut crumb = crumbs.get(endpoint).cloned().unwrap_or_default();
 
-        let marker = "data-tralbum=\"";
-        let start = html
-            .find(marker)
-            .ok_or_else(|| anyhow!("No tralbum data found on page"))?
-            + marker.len();
-        let end = html[start..]
-            .find('"')
-            .ok_or_else(|| anyhow!("Malformed tralbum data"))?
-            + start;
-        let escaped = &html[start..end];
-        let json_str = escaped
-            .replace("&quot;", "\"")
-            .replace("&amp;", "&")
-            .replace("&#39;", "'")
-            .replace("&lt;", "<")
-            .replace("&gt;", ">");
-
-        let data: TralbumPageData = serde_json::from_str(&json_str)?;
-        let current = data
-            .current
-            .ok_or_else(|| anyhow!("No current field in tralbum"))?;
-        let band_id = current
-            .band_id
-            .ok_or_else(|| anyhow!("No band_id in tralbum"))?;
-        let tralbum_id = current
-            .id
-            .ok_or_else(|| anyhow!("No id in tralbum"))?;
-        let tralbum_type = match current.item_type.as_deref() {
-            Some("track") => "t",
-            _ => "a",
+        // The endpoint lives on the artist's own domain.
+        let origin = url.splitn(4, '/').take(3).collect::<Vec<_>>().join("/");
+        // An expired crumb is answered with a fresh one; try once more.
+        for _ in 0..2 {
+            let mut signed = form.to_vec();
+            signed.push(("crumb", crumb.clone()));
+            let resp: PageActionResponse = self
+                .inner
+                .client
+                .post(format!("{origin}/{endpoint}"))
+                .headers(self.headers(&origin))
+                .form(&signed)
+                .send_timed()
+                .await?
+                .json()
+                .await?;
+            match (resp.ok, resp.error.as_deref(), resp.crumb) {
+                (true, _, _) => return Ok(()),
+                (false, Some("invalid_crumb"), Some(fresh)) => crumb = fresh,
+                (false, error, _) => return Err(anyhow!("Bandcamp refused: {}", error.unwrap_or("unknown error"))),
+            }
+        }
+        Err(anyhow!("Bandcamp refused: invalid_crumb"))
+    }
+
+    /// The download page of a release given away for free, which works
+    /// like a purchase's with [`Self::get_download_url`]. `None` if the
+    /// release isn't free.
+    pub async fn free_download_page(&self, url: &str) -> Result<Option<String>> {
+        let html = self
+            .inner
+            .client
+            .get(url)
+            .headers(self.headers(url))
+            .send_timed()
+            .await?
+            .text()
+            .await?;
+        let data: TralbumPageData = serde_json::from_str(&extract_data_attr(&html, "data-tralbum")?)?;
+        Ok(data.free_download_page.filter(|page| !page.is_empty()))
+    }
+
+    /// Resolves a purchase's download page to a signed link to the file (a
+    /// zip for albums) in `format`, e.g. `mp3-320` or `flac`.
+    pub async fn get_download_url(&self, page_url: &str, format: &str) -> Result<String> {
+        let html = self
+            .inner
+            .client
+            .get(page_url)
+            .headers(self.headers(page_url))
+            .send_timed()
+            .await?
+            .text()
+            .await?;
+        let data: DownloadPageData = serde_json::from_str(&extract_data_attr(&html, "data-blob")?)?;
+        let link = data
+            .digital_items
+            .into_iter()
+            .find_map(|mut item| item.downloads.remove(format))
+            .ok_or_else(|| anyhow!("No {} download offered", format))?;
+
+        // The page's link only starts preparing the file; the stat endpoint
+        // answers with where to fetch it once it's ready.
+        let stat_url = format!("{}&.vrs=1", link.url.replacen("/download/", "/statdownload/", 1));
+        let stat: StatDownloadResponse = self
+            .inner
+            .client
+            .get(&stat_url)
+            .headers(self.headers(&stat_url))
+            .send_timed()
+            .await?
+            .json()
+            .await?;
+        match (stat.result.as_deref(), stat.download_url) {
+            (Some("ok"), Some(url)) => Ok(url),
+            _ => Ok(link.url),
+        }
+    }
+
+    pub async fn get_band_discography(&self, band_id: u64) -> Result<Discography> {
+        let (band, resp) = self.band_details(band_id).await?;
+        let roster = resp
+            .artists
+            .into_iter()
+            .filter_map(|artist| {
+                Some(Account {
+                    band_id: artist.id?,
+                    name: artist.name.filter(|name| !name.is_empty())?,
+                })
+            })
+            .collect();
+        let merch = resp.merch.into_iter().map(|package| package.to_merch(&band.url, None)).collect();
+        Ok(Discography {
+            albums: resp.discography.into_iter().map(|item| item.to_album(&band)).collect(),
+            roster,
+            merch,
+        })
+    }
+
+    async fn band_details(&self, band_id: u64) -> Result<(Band, BandDetailsResponse)> {
+        let resp: BandDetailsResponse = self
+            .inner
+            .client
+            .post(format!("{}/mobile/24/band_details", API_BASE))
+            .json(&serde_json::json!({ "band_id": band_id }))
+            .send_timed()
+            .await?
+            .json()
+            .await?;
+
+        let band = Band {
+            id: band_id,
+            name: resp.name.clone().unwrap_or_default(),
+            url: resp.bandcamp_url.clone().unwrap_or_default(),
+        };
+        Ok((band, resp))
+    }
+
+    /// Bands the fan follows on Bandcamp.
+    pub async fn get_followed_bands(&self) -> Result<Vec<u64>> {
+        let fan_id = self.inner.fan.fan_id;
+        let mut band_ids = Vec::new();
+        let mut token = format!(
+            "{}:9999999999",
+            SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+        );
+
+        loop {
+            let resp: FollowingResponse = self
+                .inner
+                .client
+                .post(format!("{}/fancollection/1/following_bands", API_BASE))
+                .headers(self.headers(API_BASE))
+                .json(&serde_json::json!({
+                    "fan_id": fan_id,
+                    "older_than_token": token,
+                    "count": 100
+                }))
+                .send_timed()
+                .await?
+                .json()
+                .await?;
+
+            band_ids.extend(resp.followeers.into_iter().filter_map(|band| band.band_id));
+            if !resp.more_available {
+                break;
+            }
+            token = resp.last_token.ok_or_else(|| anyhow!("Missing token"))?;
+        }
+
+        Ok(band_ids)
+    }
+
+    /// Releases of these bands that come out after today, soonest first.
+    /// Bands whose discography can't be fetched are left out, unless that
+    /// is all of them.
+    pub async fn get_upcoming(&self, band_ids: &[u64]) -> Result<Vec<Upcoming>> {
+        let today = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() / 86400 * 86400)
+            .unwrap_or(0);
+        let limit = Arc::new(tokio::sync::Semaphore::new(UPCOMING_CONCURRENCY));
+        let mut tasks = tokio::task::JoinSet::new();
+        for &band_id in band_ids {
+            let (client, limit) = (self.clone(), limit.clone());
+            tasks.spawn(async move {
+                let _permit = limit.acquire_owned().await;
+                client.band_releases(band_id).await
+            });
+        }
+
+        let mut upcoming = Vec::new();
+        let mut error = None;
+        while let Some(joined) = tasks.join_next().await {
+            match joined {
+                Ok(Ok(releases)) => upcoming.extend(releases.into_iter().filter(|r| r.release_date > today)),
+                Ok(Err(e)) => error = Some(e),
+                Err(e) => error = Some(e.into()),
+            }
+        }
+        match error {
+            Some(e) if upcoming.is_empty() => Err(e),
+            _ => {
+                upcoming.sort_by_key(|r| r.release_date);
+                Ok(upcoming)
+            }
+        }
+    }
+
+    /// A band's releases that have a release date.
+    async fn band_releases(&self, band_id: u64) -> Result<Vec<Upcoming>> {
+        let (band, resp) = self.band_details(band_id).await?;
+        Ok(resp
+            .discography
+            .into_iter()
+            .filter_map(|item| {
+                let release_date = item.release_date.as_deref().and_then(parse_release_date)?;
+                Some(Upcoming { album: item.to_album(&band), release_date })
+            })
+            .collect())
+    }
+
+    pub async fn get_fan_profile(&self) -> Result<FanProfile> {
+        let username = &self.inner.fan.username;
+        let url = format!("https://bandcamp.com/{}", username);
+        let html = self
+            .inner
+            .client
+            .get(&url)
+            .headers(self.headers(&url))
+            .send_timed()
+            .await?
+            .text()
+            .await?;
+
+        let data: FanPageData = serde_json::from_str(&extract_data_attr(&html, "data-blob")?)?;
+        let fan = data
+            .fan_data
+            .ok_or_else(|| anyhow!("No fan data on profile page"))?;
+
+        Ok(FanProfile {
+            name: fan
+                .name
+                .filter(|n| !n.is_empty())
+                .unwrap_or_else(|| username.clone()),
+            url,
+            avatar_url: fan.photo.and_then(|p| p.image_id).map(avatar_url),
+        })
+    }
+
+    /// The fan's orders, newest first, from the purchases page and the
+    /// pages it loads as it is scrolled.
+    pub async fn get_purchases(&self) -> Result<Vec<Purchase>> {
+        let username = &self.inner.fan.username;
+        let html = self
+            .inner
+            .client
+            .get(format!("https://bandcamp.com/{username}/purchases"))
+            .headers(self.headers("https://bandcamp.com"))
+            .send_timed()
+            .await?
+            .text()
+            .await?;
+
+        let data: PurchasesPageData = serde_json::from_str(&extract_data_attr(&html, "data-blob")?)?;
+        let mut history = data
+            .orderhistory
+            .ok_or_else(|| anyhow!("No order history on purchases page"))?;
+        let mut purchases = Vec::new();
+        loop {
+            let more = !history.items.is_empty();
+            purchases.extend(history.items.into_iter().map(OrderItem::to_purchase));
+            let Some(token) = history.last_token.filter(|_| more) else {
+                break;
+            };
+            history = self
+                .inner
+                .client
+                .post(format!("{}/orderhistory/1/get_items", API_BASE))
+                .headers(self.headers(API_BASE))
+                .json(&serde_json::json!({
+                    "username": username,
+                    "last_token": token,
+                    "platform": "desktop",
+                }))
+                .send_timed()
+                .await?
+                .json()
+                .await?;
         }
-        .to_string();
 
-        Ok((band_id, tralbum_type, tralbum_id))
+        Ok(purchases)
     }
 
     pub async fn search(&self, query: &str) -> Result<Vec<Album>> {
@@ -417,7 +1366,7 @@ impl BandcampClient {
                 "full_page": true,
                 "fan_id": self.inner.fan.fan_id,
             }))
-            .send()
+            .send_timed()
             .await?
             .json()
             .await?;
@@ -0,0 +1,66 @@
+use gettextrs::gettext;
+use std::fmt;
+
+/// Failures the UI handles differently from the rest, carried inside the
+/// client's `anyhow` errors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClientError {
+    /// Bandcamp no longer accepts the session cookies.
+    SessionExpired,
+    /// Too many requests; Bandcamp asked to wait this many seconds.
+    RateLimited(u64),
+    /// Bandcamp couldn't be reached.
+    Offline,
+    /// Anything else, as reported.
+    Other(String),
+}
+
+impl ClientError {
+    /// What to show the user, saying what to do about it where possible.
+    pub fn message(&self) -> String {
+        match self {
+            ClientError::SessionExpired => gettext("Session expired — sign in again"),
+            ClientError::RateLimited(secs) => {
+                gettext("Rate limited, retrying in {}s").replace("{}", &secs.to_string())
+            }
+            ClientError::Offline => gettext("Can't reach Bandcamp — check your connection"),
+            ClientError::Other(message) => message.clone(),
+        }
+    }
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::SessionExpired => write!(f, "Session expired"),
+            ClientError::RateLimited(secs) => write!(f, "Rate limited for {secs}s"),
+            ClientError::Offline => write!(f, "Bandcamp is unreachable"),
+            ClientError::Other(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<&anyhow::Error> for ClientError {
+    fn from(e: &anyhow::Error) -> Self {
+        if let Some(e) = e.downcast_ref::<ClientError>() {
+            return e.clone();
+        }
+        let unreachable = e
+            .chain()
+            .filter_map(|cause| cause.downcast_ref::<reqwest::Error>())
+            .any(|e| e.is_connect() || e.is_timeout());
+        if unreachable {
+            ClientError::Offline
+        } else {
+            ClientError::Other(e.to_string())
+        }
+    }
+}
+
+impl From<anyhow::Error> for ClientError {
+    fn from(e: anyhow::Error) -> Self {
+        Self::from(&e)
+    }
+}
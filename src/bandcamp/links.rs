@@ -0,0 +1,131 @@
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+/// An album or track page, optionally pointing at a specific track of an
+/// album through a `#t3` / `#track=3` style anchor, and at a time within it
+/// through `&at=90`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReleaseLink {
+    pub url: String,
+    /// Zero-based index of the track to start from.
+    pub track: Option<usize>,
+    /// Seconds into the track to start at.
+    #[serde(default)]
+    pub position: Option<f64>,
+}
+
+/// The link to share for `url` (a release page, possibly with a track
+/// anchor) at `position` seconds, in the form [`parse_release_link`] reads
+/// back. Bandcamp itself ignores the anchor.
+pub fn timestamped_link(url: &str, position: f64) -> String {
+    let separator = if url.contains('#') { '&' } else { '#' };
+    format!("{url}{separator}at={}", position.max(0.0) as u64)
+}
+
+/// The HTML snippet embedding Bandcamp's player for the release with
+/// `release_id` at `url`, cued to the track its anchor points at.
+pub fn embed_code(url: &str, release_id: u64, title: &str, artist: &str) -> Option<String> {
+    let link = parse_release_link(url)?;
+    let kind = if Url::parse(&link.url).ok()?.path().starts_with("/track/") { "track" } else { "album" };
+    let cue = match link.track {
+        Some(track) if kind == "album" => format!("t={}/", track + 1),
+        _ => String::new(),
+    };
+    let escape = |s: &str| {
+        s.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    };
+    Some(format!(
+        "<iframe style=\"border: 0; width: 350px; height: 470px;\" \
+         src=\"https://bandcamp.com/EmbeddedPlayer/{kind}={release_id}/size=large/bgcol=ffffff/linkcol=0687f5/\
+         tracklist=false/{cue}transparent=true/\" seamless><a href=\"{}\">{} by {}</a></iframe>",
+        escape(&link.url),
+        escape(title),
+        escape(artist),
+    ))
+}
+
+/// Parse a link to a Bandcamp album or track page, dropping the query and
+/// fragment. Custom artist domains are accepted as long as the path looks like
+/// a release page, and `bandcamp://` links handed over by the desktop are
+/// rewritten to https.
+pub fn parse_release_link(input: &str) -> Option<ReleaseLink> {
+    let input = input.trim();
+    let input = match input.strip_prefix("bandcamp://") {
+        Some(rest) => format!("https://{}", rest),
+        None => input.to_string(),
+    };
+
+    let mut url = Url::parse(&input).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+
+    let is_release = {
+        let mut segments = url.path_segments()?;
+        matches!(
+            (segments.next(), segments.next()),
+            (Some("album" | "track"), Some(slug)) if !slug.is_empty()
+        )
+    };
+    if !is_release {
+        return None;
+    }
+
+    let fragment = url.fragment().unwrap_or("");
+    let track = fragment.split('&').find_map(track_anchor);
+    let position = fragment
+        .split('&')
+        .find_map(|part| part.strip_prefix("at="))
+        .and_then(|secs| secs.parse::<f64>().ok())
+        .filter(|secs| secs.is_finite() && *secs > 0.0);
+    url.set_query(None);
+    url.set_fragment(None);
+
+    Some(ReleaseLink {
+        url: url.into(),
+        track,
+        position,
+    })
+}
+
+/// Parse a link to an artist or label home page on `*.bandcamp.com`,
+/// returning its origin. Such pages usually feature a release, so they can be
+/// resolved like a release link.
+pub fn parse_artist_link(input: &str) -> Option<String> {
+    let url = Url::parse(input.trim()).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    let host = url.host_str()?;
+    let subdomain = host.strip_suffix(".bandcamp.com")?;
+    if subdomain.is_empty() || matches!(subdomain, "www" | "daily") {
+        return None;
+    }
+    if !matches!(url.path(), "" | "/" | "/music") {
+        return None;
+    }
+    Some(format!("https://{}", host))
+}
+
+/// The home page of the artist or label a release link belongs to, e.g.
+/// `https://artist.bandcamp.com`; custom domains are kept.
+pub fn artist_home(url: &str) -> Option<String> {
+    let url = Url::parse(url.trim()).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    Some(url.origin().ascii_serialization())
+}
+
+/// Parse a one-based track anchor (`t3`, `track3`, `track=3`, `track-3`).
+fn track_anchor(fragment: &str) -> Option<usize> {
+    let rest = fragment
+        .strip_prefix("track")
+        .or_else(|| fragment.strip_prefix('t'))?;
+    let rest = rest.trim_start_matches(['=', '-']);
+    let n: usize = rest.parse().ok()?;
+    n.checked_sub(1)
+}
@@ -1,5 +1,9 @@
 mod client;
+mod error;
+mod links;
 mod types;
 
-pub use client::BandcampClient;
+pub use client::{http, warm_up, BandcampClient};
+pub use error::ClientError;
+pub use links::{artist_home, embed_code, parse_artist_link, parse_release_link, timestamped_link, ReleaseLink};
 pub use types::*;
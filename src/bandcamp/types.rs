@@ -1,3 +1,6 @@
+use crate::N_;
+use serde::{Deserialize, Serialize};
+
 #[derive(Debug, Clone)]
 pub struct Album {
     pub title: String,
@@ -10,31 +13,191 @@ pub struct Album {
     pub item_type: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CollectionItem {
     pub title: String,
     pub artist: String,
     pub art_url: Option<String>,
     pub url: String,
+    /// Download page of a purchase; wishlist items have none.
+    #[serde(default)]
+    pub download_url: Option<String>,
+    /// Missing from libraries cached before it was kept.
+    #[serde(default)]
+    pub band_id: Option<u64>,
+    /// The release's tralbum ID and type (`a` or `t`), so its details load
+    /// without fetching its page first.
+    #[serde(default)]
+    pub item_id: Option<u64>,
+    #[serde(default)]
+    pub item_type: Option<String>,
 }
 
+/// A past order from the fan's purchase history.
 #[derive(Debug, Clone)]
+pub struct Purchase {
+    pub title: String,
+    pub artist: String,
+    pub art_url: Option<String>,
+    pub url: String,
+    /// Merch, such as vinyl or a shirt, rather than a download.
+    pub physical: bool,
+    /// Seconds since the epoch.
+    pub date: Option<u64>,
+    pub total: Option<Price>,
+    /// Bandcamp's number for the order, as receipts show it.
+    pub sale_id: Option<u64>,
+    /// Download page, for digital purchases.
+    pub download_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FanInfo {
     pub fan_id: u64,
     pub username: String,
 }
 
 #[derive(Debug, Clone)]
+pub struct FanProfile {
+    pub name: String,
+    pub url: String,
+    pub avatar_url: Option<String>,
+}
+
+impl FanProfile {
+    /// Placeholder used until the profile page has been fetched.
+    pub fn from_fan(fan: &FanInfo) -> Self {
+        Self {
+            name: fan.username.clone(),
+            url: format!("https://bandcamp.com/{}", fan.username),
+            avatar_url: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrackInfo {
     pub title: String,
+    /// Who made the track; on compilations not the album artist.
     pub artist: String,
+    #[serde(default)]
+    pub album_artist: String,
     pub album: String,
     pub art_url: Option<String>,
     pub stream_url: Option<String>,
     pub duration: Option<f64>,
+    /// Release page with a `#tN` anchor pointing at this track.
+    #[serde(default)]
+    pub url: String,
+    /// Number on the release, as the artist set it.
+    #[serde(default)]
+    pub number: Option<u32>,
+    /// Disc of a multi-volume release.
+    #[serde(default)]
+    pub disc: Option<u32>,
+    /// What the track costs on its own, if it is sold separately.
+    #[serde(default)]
+    pub price: Option<Price>,
+    /// The release's tags, shared by all its tracks.
+    #[serde(default)]
+    pub tags: Vec<Tag>,
+    /// Set when the whole release can be downloaded for nothing.
+    #[serde(default)]
+    pub free_download: Option<FreeDownload>,
+    /// The album's ID, or the track's for single tracks, as embeds name it.
+    #[serde(default)]
+    pub release_id: Option<u64>,
+    /// The label account that put the release out, if not the artist's own.
+    #[serde(default)]
+    pub label: Option<Account>,
+    /// Physical editions of the release, shared by all its tracks.
+    #[serde(default)]
+    pub merch: Vec<Merch>,
+}
+
+/// A physical item for sale, e.g. a vinyl variant or a shirt.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Merch {
+    pub title: String,
+    /// What it is, as Bandcamp names it, e.g. "Vinyl LP".
+    pub kind: Option<String>,
+    pub art_url: Option<String>,
+    pub price: Option<Price>,
+    /// How many are left; `None` when Bandcamp doesn't say.
+    pub remaining: Option<u32>,
+    /// The page to buy it on.
+    pub url: String,
 }
 
+impl Merch {
+    pub fn sold_out(&self) -> bool {
+        self.remaining == Some(0)
+    }
+}
+
+/// A Bandcamp account, an artist's or a label's.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Account {
+    pub band_id: u64,
+    pub name: String,
+}
+
+/// An account's releases and merch and, for labels, the artists on its
+/// roster.
+#[derive(Debug, Clone, Default)]
+pub struct Discography {
+    pub albums: Vec<Album>,
+    pub roster: Vec<Account>,
+    pub merch: Vec<Merch>,
+}
+
+/// A release that isn't out yet, such as a pre-order.
 #[derive(Debug, Clone)]
+pub struct Upcoming {
+    pub album: Album,
+    /// Start of the release day, in seconds since the epoch.
+    pub release_date: u64,
+}
+
+/// A release offered for nothing: name your price starting at zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FreeDownload {
+    /// Bandcamp mails the link instead, so it can't be fetched directly.
+    pub email_required: bool,
+    /// Licensed under Creative Commons, so it may be shared on.
+    pub creative_commons: bool,
+}
+
+/// A tag an artist filed a release under, e.g. "Hip-Hop/Rap".
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tag {
+    pub name: String,
+    /// How Discover and tag URLs spell it, e.g. `hip-hop-rap`.
+    pub slug: String,
+}
+
+/// A price in the artist's currency. Zero means free, or name your price
+/// starting at nothing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Price {
+    pub amount: f64,
+    /// ISO 4217 code, e.g. `USD`.
+    pub currency: String,
+}
+
+impl Price {
+    pub fn is_free(&self) -> bool {
+        self.amount <= 0.0
+    }
+}
+
+impl std::fmt::Display for Price {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.2} {}", self.amount, self.currency)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AlbumDetails {
     pub url: String,
     pub tracks: Vec<TrackInfo>,
@@ -60,40 +223,40 @@ impl Default for DiscoverParams {
 }
 
 pub const GENRES: &[(&str, &str)] = &[
-    ("all", "All"),
-    ("electronic", "Electronic"),
-    ("rock", "Rock"),
-    ("metal", "Metal"),
-    ("alternative", "Alternative"),
-    ("hip-hop-rap", "Hip-Hop/Rap"),
-    ("experimental", "Experimental"),
-    ("punk", "Punk"),
-    ("folk", "Folk"),
-    ("pop", "Pop"),
-    ("ambient", "Ambient"),
-    ("soundtrack", "Soundtrack"),
-    ("world", "World"),
-    ("jazz", "Jazz"),
-    ("acoustic", "Acoustic"),
-    ("funk", "Funk"),
-    ("r-b-soul", "R&B/Soul"),
-    ("devotional", "Devotional"),
-    ("classical", "Classical"),
-    ("reggae", "Reggae"),
-    ("podcasts", "Podcasts"),
-    ("country", "Country"),
-    ("spoken-word", "Spoken Word"),
-    ("comedy", "Comedy"),
-    ("blues", "Blues"),
-    ("kids", "Kids"),
-    ("audiobooks", "Audiobooks"),
-    ("latin", "Latin"),
+    ("all", N_("All")),
+    ("electronic", N_("Electronic")),
+    ("rock", N_("Rock")),
+    ("metal", N_("Metal")),
+    ("alternative", N_("Alternative")),
+    ("hip-hop-rap", N_("Hip-Hop/Rap")),
+    ("experimental", N_("Experimental")),
+    ("punk", N_("Punk")),
+    ("folk", N_("Folk")),
+    ("pop", N_("Pop")),
+    ("ambient", N_("Ambient")),
+    ("soundtrack", N_("Soundtrack")),
+    ("world", N_("World")),
+    ("jazz", N_("Jazz")),
+    ("acoustic", N_("Acoustic")),
+    ("funk", N_("Funk")),
+    ("r-b-soul", N_("R&B/Soul")),
+    ("devotional", N_("Devotional")),
+    ("classical", N_("Classical")),
+    ("reggae", N_("Reggae")),
+    ("podcasts", N_("Podcasts")),
+    ("country", N_("Country")),
+    ("spoken-word", N_("Spoken Word")),
+    ("comedy", N_("Comedy")),
+    ("blues", N_("Blues")),
+    ("kids", N_("Kids")),
+    ("audiobooks", N_("Audiobooks")),
+    ("latin", N_("Latin")),
 ];
 
 pub const SORT_OPTIONS: &[(&str, &str)] = &[
-    ("new", "New Arrivals"),
-    ("rec", "Recommended"),
-    ("top", "Best Sellers"),
+    ("new", N_("New Arrivals")),
+    ("rec", N_("Recommended")),
+    ("top", N_("Best Sellers")),
 ];
 
 /// Build an image URL from an art_id using the given format ID.
@@ -111,3 +274,13 @@ pub fn art_url_thumb(art_id: u64) -> String {
 pub fn art_url_large(art_id: u64) -> String {
     art_url(art_id, 5)
 }
+
+/// 350px photo of a merch item.
+pub fn merch_art_url(image_id: u64) -> String {
+    format!("https://f4.bcbits.com/img/{:010}_10.jpg", image_id)
+}
+
+/// 50px square fan avatar, enough for the header avatar at twice its size.
+pub fn avatar_url(image_id: u64) -> String {
+    format!("https://f4.bcbits.com/img/{:010}_42.jpg", image_id)
+}
@@ -0,0 +1,218 @@
+use crate::album_grid::{AlbumData, AlbumGrid, AlbumGridMsg, AlbumGridOutput, Click, Section};
+use crate::bandcamp::{BandcampClient, ClientError, Upcoming};
+use gettextrs::gettext;
+use gtk4::prelude::*;
+use gtk4::{gio, glib};
+use relm4::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A release to announce once it comes out.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reminder {
+    pub title: String,
+    pub artist: String,
+    /// The artist's page; discography entries have no page of their own.
+    pub url: String,
+    /// Start of the release day, in seconds since the epoch.
+    pub release_date: u64,
+}
+
+impl From<&Upcoming> for Reminder {
+    fn from(upcoming: &Upcoming) -> Self {
+        Self {
+            title: upcoming.album.title.clone(),
+            artist: upcoming.album.artist.clone(),
+            url: upcoming.album.url.clone(),
+            release_date: upcoming.release_date,
+        }
+    }
+}
+
+/// Takes the reminders whose release day has come out of `reminders`.
+pub fn take_due(reminders: &mut Vec<Reminder>, now: u64) -> Vec<Reminder> {
+    let (due, later) = std::mem::take(reminders).into_iter().partition(|r| r.release_date <= now);
+    *reminders = later;
+    due
+}
+
+/// Announces a release that came out, opening the artist when clicked.
+pub fn notify(reminder: &Reminder) {
+    let notification = gio::Notification::new(&gettext("Out Today: {}").replace("{}", &reminder.title));
+    notification.set_body(Some(&reminder.artist));
+    notification.set_default_action_and_target_value("app.open-url", Some(&reminder.url.to_variant()));
+    let id = format!("release-{}-{}", reminder.url, reminder.release_date);
+    relm4::main_application().send_notification(Some(&id), &notification);
+}
+
+/// The release day, in the locale's date format.
+fn format_day(release_date: u64) -> String {
+    glib::DateTime::from_unix_utc(release_date as i64)
+        .and_then(|date| date.format("%x"))
+        .map(|date| date.to_string())
+        .unwrap_or_default()
+}
+
+/// Upcoming releases of wishlisted and followed artists, grouped by
+/// release day, pushed onto the library's navigation stack.
+pub struct CalendarPage {
+    grid: Controller<AlbumGrid>,
+    client: BandcampClient,
+    /// Artists of wishlisted releases; followed ones are fetched.
+    wishlisted: Vec<u64>,
+    upcoming: Vec<Upcoming>,
+    /// Whether to be notified when the releases shown come out.
+    remind: bool,
+}
+
+pub struct CalendarInit {
+    pub client: BandcampClient,
+    pub wishlisted: Vec<u64>,
+    pub remind: bool,
+}
+
+#[derive(Debug)]
+pub enum CalendarMsg {
+    Loaded(Result<Vec<Upcoming>, ClientError>),
+    GridAction(AlbumGridOutput),
+    SetRemind(bool),
+}
+
+#[derive(Debug)]
+pub enum CalendarOutput {
+    Activate(AlbumData, Click),
+    /// Resolve the album ahead of a likely click.
+    Prefetch(AlbumData),
+    /// Whether to be reminded, and of which releases.
+    Remind(bool, Vec<Reminder>),
+    Error(ClientError),
+}
+
+#[relm4::component(pub)]
+impl Component for CalendarPage {
+    type Init = CalendarInit;
+    type Input = CalendarMsg;
+    type Output = CalendarOutput;
+    type CommandOutput = Result<Vec<Upcoming>, ClientError>;
+
+    view! {
+        gtk4::Box {
+            set_orientation: gtk4::Orientation::Vertical,
+            set_hexpand: true,
+            set_vexpand: true,
+
+            gtk4::CenterBox {
+                add_css_class: "compact-toolbar",
+                set_margin_start: 6,
+                set_margin_end: 6,
+                set_margin_top: 6,
+
+                #[wrap(Some)]
+                set_start_widget = &gtk4::Button {
+                    set_icon_name: "go-previous-symbolic",
+                    set_tooltip_text: Some(&gettext("Back")),
+                    add_css_class: "flat",
+                    set_action_name: Some("navigation.pop"),
+                },
+
+                #[wrap(Some)]
+                set_center_widget = &gtk4::Label {
+                    set_label: &gettext("Release Calendar"),
+                    add_css_class: "heading",
+                },
+
+                #[wrap(Some)]
+                set_end_widget = &gtk4::ToggleButton {
+                    set_icon_name: "preferences-system-notifications-symbolic",
+                    set_tooltip_text: Some(&gettext("Notify on Release Day")),
+                    add_css_class: "flat",
+                    set_active: model.remind,
+                    connect_toggled[sender] => move |button| {
+                        sender.input(CalendarMsg::SetRemind(button.is_active()));
+                    },
+                },
+            },
+        }
+    }
+
+    fn init(init: Self::Init, root: Self::Root, sender: ComponentSender<Self>) -> ComponentParts<Self> {
+        let grid = AlbumGrid::builder()
+            .launch(())
+            .forward(sender.input_sender(), CalendarMsg::GridAction);
+
+        let CalendarInit { client, wishlisted, remind } = init;
+        let model = Self { grid, client, wishlisted, upcoming: Vec::new(), remind };
+
+        let widgets = view_output!();
+        root.append(model.grid.widget());
+        model.fetch(&sender);
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, msg: Self::Input, sender: ComponentSender<Self>, _root: &Self::Root) {
+        match msg {
+            CalendarMsg::Loaded(result) => match result {
+                Ok(upcoming) => {
+                    let mut sections: Vec<Section> = Vec::new();
+                    for release in &upcoming {
+                        let album = AlbumData::from(release.album.clone());
+                        let day = format_day(release.release_date);
+                        match sections.last_mut() {
+                            Some(section) if section.title == day => section.albums.push(album),
+                            _ => sections.push(Section { title: day, albums: vec![album] }),
+                        }
+                    }
+                    self.grid.emit(AlbumGridMsg::ReplaceSections(sections));
+                    self.upcoming = upcoming;
+                    if self.remind {
+                        self.send_reminders(&sender);
+                    }
+                }
+                Err(e) => {
+                    self.grid.emit(AlbumGridMsg::Error(e.clone()));
+                    sender.output(CalendarOutput::Error(e)).ok();
+                }
+            },
+            CalendarMsg::GridAction(action) => match action {
+                AlbumGridOutput::Clicked(data, click) => {
+                    sender.output(CalendarOutput::Activate(data, click)).ok();
+                }
+                AlbumGridOutput::Hovered(data) => {
+                    sender.output(CalendarOutput::Prefetch(data)).ok();
+                }
+                AlbumGridOutput::Retry => self.fetch(&sender),
+                AlbumGridOutput::ArtistClicked(_) | AlbumGridOutput::ScrolledToBottom => {}
+            },
+            CalendarMsg::SetRemind(remind) => {
+                self.remind = remind;
+                self.send_reminders(&sender);
+            }
+        }
+    }
+
+    fn update_cmd(&mut self, msg: Self::CommandOutput, sender: ComponentSender<Self>, _root: &Self::Root) {
+        sender.input(CalendarMsg::Loaded(msg));
+    }
+}
+
+impl CalendarPage {
+    fn fetch(&self, sender: &ComponentSender<Self>) {
+        self.grid.emit(AlbumGridMsg::Loading);
+        let client = self.client.clone();
+        let wishlisted = self.wishlisted.clone();
+        sender.oneshot_command(async move {
+            // Following is only a part of the picture; without it the
+            // wishlist still shows something.
+            let mut band_ids = client.get_followed_bands().await.unwrap_or_default();
+            band_ids.extend(wishlisted);
+            band_ids.sort_unstable();
+            band_ids.dedup();
+            client.get_upcoming(&band_ids).await.map_err(ClientError::from)
+        });
+    }
+
+    fn send_reminders(&self, sender: &ComponentSender<Self>) {
+        let reminders = if self.remind { self.upcoming.iter().map(Reminder::from).collect() } else { Vec::new() };
+        sender.output(CalendarOutput::Remind(self.remind, reminders)).ok();
+    }
+}
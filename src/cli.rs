@@ -0,0 +1,91 @@
+use crate::app::AppMsg;
+use crate::player::format_time;
+use gettextrs::gettext;
+use gtk4::gio;
+use gtk4::glib;
+use gtk4::prelude::*;
+
+/// Flags that control an already running instance instead of opening a
+/// window.
+const CONTROL_OPTIONS: &[&str] = &["play-pause", "next", "previous", "status", "queue"];
+
+pub fn add_options(app: &impl IsA<gio::Application>) {
+    let none = glib::Char::from(0);
+    let option = |name: &str, arg: glib::OptionArg, description: &str, arg_description: Option<&str>| {
+        app.add_main_option(name, none, glib::OptionFlags::NONE, arg, description, arg_description);
+    };
+    option("play-pause", glib::OptionArg::None, &gettext("Toggle playback in the running instance"), None);
+    option("next", glib::OptionArg::None, &gettext("Skip to the next track"), None);
+    option("previous", glib::OptionArg::None, &gettext("Go back to the previous track"), None);
+    option("status", glib::OptionArg::None, &gettext("Print what is playing"), None);
+    option("queue", glib::OptionArg::String, &gettext("Add a release to the queue"), Some("URL"));
+}
+
+/// Runs in the launching process. Control flags are only forwarded when
+/// Camper is already running; starting it just to skip a track would be
+/// surprising.
+pub fn handle_local_options(app: &impl IsA<gio::Application>, options: &glib::VariantDict) -> i32 {
+    if !CONTROL_OPTIONS.iter().any(|name| options.contains(name)) {
+        return -1;
+    }
+    if let Err(e) = app.register(None::<&gio::Cancellable>) {
+        eprintln!("{e}");
+        return 1;
+    }
+    if !app.is_remote() {
+        eprintln!("{}", gettext("Camper is not running"));
+        return 1;
+    }
+    -1
+}
+
+/// Runs in the primary instance for every launch, including its own.
+pub fn command_line(app: &impl IsA<gio::Application>, command_line: &gio::ApplicationCommandLine) -> i32 {
+    let options = command_line.options_dict();
+    if options.contains("status") {
+        command_line.print_literal(&status());
+        return 0;
+    }
+    if options.contains("play-pause") {
+        crate::APP_BROKER.send(AppMsg::PlayerToggle);
+        return 0;
+    }
+    if options.contains("next") {
+        crate::APP_BROKER.send(AppMsg::PlayerNext);
+        return 0;
+    }
+    if options.contains("previous") {
+        crate::APP_BROKER.send(AppMsg::PlayerPrev);
+        return 0;
+    }
+    if let Some(url) = options.lookup::<String>("queue").ok().flatten() {
+        crate::APP_BROKER.send(AppMsg::EnqueueUrl(url));
+        return 0;
+    }
+
+    // `camper <url>` plays the link, in the running instance if there is one.
+    app.activate();
+    for arg in command_line.arguments().iter().skip(1) {
+        let uri = command_line.create_file_for_arg(arg).uri();
+        crate::APP_BROKER.send(AppMsg::OpenUrl(uri.to_string()));
+    }
+    0
+}
+
+fn status() -> String {
+    let status = crate::remote::status().playback;
+    if status.title.is_empty() {
+        return format!("{}\n", gettext("Stopped"));
+    }
+    let state = if status.playing { gettext("Playing") } else { gettext("Paused") };
+    let track = if status.artist.is_empty() {
+        status.title
+    } else {
+        format!("{} — {}", status.artist, status.title)
+    };
+    format!(
+        "{state}: {track} ({} / {})\n",
+        format_time(status.position),
+        format_time(status.duration)
+    )
+}
@@ -0,0 +1,43 @@
+use crate::preferences::DataSaver;
+use gtk4::gio;
+use gtk4::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Bandcamp's 210px artwork, plenty for cards at their default size.
+const SMALL_ART_FORMAT: &str = "9";
+
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Whether to hold back on data right now: smaller artwork, no
+/// prefetching and no refreshes nobody asked for.
+pub fn active() -> bool {
+    ACTIVE.load(Ordering::Relaxed)
+}
+
+/// Applies `mode` against the current connection. Returns whether the
+/// saver is on.
+pub fn update(mode: DataSaver) -> bool {
+    let active = match mode {
+        DataSaver::Metered => gio::NetworkMonitor::default().is_network_metered(),
+        DataSaver::Always => true,
+        DataSaver::Never => false,
+    };
+    ACTIVE.store(active, Ordering::Relaxed);
+    active
+}
+
+/// The smaller variant of a Bandcamp artwork URL while saving data, e.g.
+/// `…/a0123456789_10.jpg` becomes `…/a0123456789_9.jpg`. Other URLs, and
+/// artwork already in the cache at full size, are left alone.
+pub fn art_url(url: &str) -> String {
+    if !active() || crate::art_cache::cached_path(url).is_some() {
+        return url.to_string();
+    }
+    let Some((stem, format)) = url.strip_suffix(".jpg").and_then(|u| u.rsplit_once('_')) else {
+        return url.to_string();
+    };
+    if !stem.contains("bcbits.com/img/a") || !format.chars().all(|c| c.is_ascii_digit()) {
+        return url.to_string();
+    }
+    format!("{stem}_{SMALL_ART_FORMAT}.jpg")
+}
@@ -0,0 +1,94 @@
+use crate::app::AppMsg;
+use crate::diagnostics;
+use gtk4::gio;
+use gtk4::glib;
+use gtk4::prelude::*;
+
+const INTERFACE: &str = "net.knoopx.Camper";
+
+/// Scripting methods beyond what MPRIS offers, on the application's own
+/// object path, e.g.
+/// `gdbus call --session -d net.knoopx.camper -o /net/knoopx/camper -m net.knoopx.Camper.GetQueue`.
+const INTROSPECTION: &str = r#"
+<node>
+  <interface name="net.knoopx.Camper">
+    <method name="SearchAndPlay">
+      <arg type="s" name="query" direction="in"/>
+    </method>
+    <method name="QueueAlbum">
+      <arg type="s" name="url" direction="in"/>
+    </method>
+    <method name="GetQueue">
+      <arg type="u" name="current" direction="out"/>
+      <arg type="a(sssd)" name="tracks" direction="out"/>
+    </method>
+    <method name="SetShuffle">
+      <arg type="b" name="shuffle" direction="in"/>
+    </method>
+  </interface>
+</node>
+"#;
+
+/// Exports the interface on the primary instance's session bus connection.
+pub fn register(app: &impl IsA<gio::Application>) {
+    let (Some(connection), Some(path)) = (app.dbus_connection(), app.dbus_object_path()) else {
+        return;
+    };
+    let interface = match gio::DBusNodeInfo::for_xml(INTROSPECTION) {
+        Ok(node) => node.lookup_interface(INTERFACE),
+        Err(e) => {
+            diagnostics::error("dbus", format!("Invalid introspection data: {e}"));
+            return;
+        }
+    };
+    let Some(interface) = interface else { return };
+
+    let result = connection
+        .register_object(&path, &interface)
+        .method_call(|_, _, _, _, method, parameters, invocation| {
+            handle_call(method, &parameters, invocation);
+        })
+        .build();
+    if let Err(e) = result {
+        diagnostics::error("dbus", format!("Failed to export {INTERFACE}: {e}"));
+    }
+}
+
+fn handle_call(method: &str, parameters: &glib::Variant, invocation: gio::DBusMethodInvocation) {
+    let msg = match method {
+        "SearchAndPlay" => string_arg(parameters).map(AppMsg::SearchAndPlay),
+        "QueueAlbum" => string_arg(parameters).map(AppMsg::EnqueueUrl),
+        "SetShuffle" => parameters.get::<(bool,)>().map(|(shuffle,)| AppMsg::PlayerSetShuffle(shuffle)),
+        "GetQueue" => {
+            let status = crate::remote::status();
+            let tracks: Vec<(String, String, String, f64)> = status
+                .queue
+                .into_iter()
+                .map(|t| (t.title, t.artist, t.album, t.duration.unwrap_or(0.0)))
+                .collect();
+            invocation.return_value(Some(&(status.queue_index as u32, tracks).to_variant()));
+            return;
+        }
+        _ => {
+            invocation.return_dbus_error(
+                "org.freedesktop.DBus.Error.UnknownMethod",
+                &format!("No method {method} on {INTERFACE}"),
+            );
+            return;
+        }
+    };
+    match msg {
+        Some(msg) => {
+            crate::APP_BROKER.send(msg);
+            invocation.return_value(None);
+        }
+        None => invocation.return_dbus_error("org.freedesktop.DBus.Error.InvalidArgs", "Expected a non-empty argument"),
+    }
+}
+
+fn string_arg(parameters: &glib::Variant) -> Option<String> {
+    parameters
+        .get::<(String,)>()
+        .map(|(s,)| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
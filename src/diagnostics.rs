@@ -0,0 +1,139 @@
+use gettextrs::gettext;
+use gtk4::prelude::*;
+use libadwaita as adw;
+use libadwaita::prelude::*;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Mutex;
+
+/// Oldest entries are dropped past this many.
+const MAX_ENTRIES: usize = 500;
+
+static ENTRIES: Mutex<VecDeque<Entry>> = Mutex::new(VecDeque::new());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Info,
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub time: String,
+    pub level: Level,
+    pub source: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = match self.level {
+            Level::Info => "INFO ",
+            Level::Error => "ERROR",
+        };
+        write!(f, "{} {} [{}] {}", self.time, level, self.source, self.message)
+    }
+}
+
+pub fn record(level: Level, source: &'static str, message: impl Into<String>) {
+    let time = gtk4::glib::DateTime::now_local()
+        .and_then(|t| t.format("%H:%M:%S"))
+        .map(|t| t.to_string())
+        .unwrap_or_default();
+    let entry = Entry {
+        time,
+        level,
+        source,
+        message: message.into(),
+    };
+    if level == Level::Error {
+        eprintln!("{entry}");
+    }
+
+    let Ok(mut entries) = ENTRIES.lock() else { return };
+    if entries.len() == MAX_ENTRIES {
+        entries.pop_front();
+    }
+    entries.push_back(entry);
+}
+
+pub fn error(source: &'static str, message: impl Into<String>) {
+    record(Level::Error, source, message);
+}
+
+pub fn entries() -> Vec<Entry> {
+    ENTRIES
+        .lock()
+        .map(|entries| entries.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Plain-text report suitable for pasting into a bug report.
+pub fn report() -> String {
+    let mut report = format!(
+        "Camper {}\nGTK {}.{}.{}, libadwaita {}.{}.{}\n\n",
+        env!("CARGO_PKG_VERSION"),
+        gtk4::major_version(),
+        gtk4::minor_version(),
+        gtk4::micro_version(),
+        adw::major_version(),
+        adw::minor_version(),
+        adw::micro_version(),
+    );
+    for entry in entries() {
+        report.push_str(&entry.to_string());
+        report.push('\n');
+    }
+    report
+}
+
+pub fn build_dialog() -> adw::Dialog {
+    let dialog = adw::Dialog::new();
+    dialog.set_title(&gettext("Diagnostics"));
+    dialog.set_content_width(640);
+    dialog.set_content_height(480);
+
+    let buffer = gtk4::TextBuffer::new(None);
+    buffer.set_text(&report());
+
+    let text_view = gtk4::TextView::with_buffer(&buffer);
+    text_view.set_editable(false);
+    text_view.set_monospace(true);
+    text_view.set_wrap_mode(gtk4::WrapMode::WordChar);
+    text_view.set_top_margin(12);
+    text_view.set_bottom_margin(12);
+    text_view.set_left_margin(12);
+    text_view.set_right_margin(12);
+
+    let scrolled = gtk4::ScrolledWindow::new();
+    scrolled.set_vexpand(true);
+    scrolled.set_child(Some(&text_view));
+
+    let toast_overlay = adw::ToastOverlay::new();
+    toast_overlay.set_child(Some(&scrolled));
+
+    let copy_btn = gtk4::Button::with_label(&gettext("Copy Report"));
+    copy_btn.add_css_class("suggested-action");
+    let overlay = toast_overlay.clone();
+    copy_btn.connect_clicked(move |btn| {
+        btn.clipboard().set_text(&report());
+        overlay.add_toast(adw::Toast::new(&gettext("Report copied")));
+    });
+
+    let refresh_btn = gtk4::Button::from_icon_name("view-refresh-symbolic");
+    refresh_btn.set_tooltip_text(Some(&gettext("Refresh")));
+    refresh_btn.connect_clicked(move |_| {
+        buffer.set_text(&report());
+    });
+
+    let header = adw::HeaderBar::new();
+    header.pack_start(&refresh_btn);
+    header.pack_end(&copy_btn);
+
+    let toolbar_view = adw::ToolbarView::new();
+    toolbar_view.add_top_bar(&header);
+    toolbar_view.set_content(Some(&toast_overlay));
+
+    dialog.set_child(Some(&toolbar_view));
+    dialog
+}
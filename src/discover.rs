@@ -1,5 +1,7 @@
-use crate::album_grid::{AlbumData, AlbumGrid, AlbumGridMsg, AlbumGridOutput};
-use crate::bandcamp::{BandcampClient, DiscoverParams, GENRES, SORT_OPTIONS};
+use crate::album_card::Card;
+use crate::album_grid::{AlbumData, AlbumGrid, AlbumGridMsg, AlbumGridOutput, Click};
+use crate::bandcamp::{BandcampClient, ClientError, DiscoverParams, GENRES, SORT_OPTIONS};
+use gettextrs::gettext;
 use gtk4::prelude::*;
 use relm4::prelude::*;
 
@@ -15,6 +17,17 @@ pub struct DiscoverPage {
     params: DiscoverParams,
     loading: bool,
     fetch_mode: FetchMode,
+    /// The listen later list, above the results while it isn't empty.
+    later: LaterSection,
+    later_albums: Vec<AlbumData>,
+}
+
+struct LaterSection {
+    revealer: gtk4::Revealer,
+    count: gtk4::Label,
+    cards: gtk4::Box,
+    /// Cards laid out by hand report to the page as if from its grid.
+    sender: relm4::Sender<AlbumGridOutput>,
 }
 
 #[derive(Debug)]
@@ -24,19 +37,30 @@ pub enum DiscoverMsg {
     LoadMore,
     SetGenre(u32),
     SetTag(String),
+    /// Browse a tag across all genres, e.g. one clicked on an album.
+    ShowTag(String),
     SetSort(u32),
 
-    Loaded(Result<Vec<AlbumData>, String>),
+    Loaded(Result<Vec<AlbumData>, ClientError>),
     GridAction(AlbumGridOutput),
+    FocusResults,
+    /// Albums were put on the listen later list or taken off it.
+    ListenLaterChanged,
+    PlayListenLater,
 }
 
 #[derive(Debug)]
 pub enum DiscoverOutput {
-    Play(AlbumData),
+    Activate(AlbumData, Click),
+    ShowArtist(AlbumData),
+    /// Resolve the album ahead of a likely click.
+    Prefetch(AlbumData),
     GenreChanged(u32),
     TagChanged(String),
     SortChanged(u32),
-    Error(String),
+    Error(ClientError),
+    /// Play the listen later list, in order.
+    PlayAll(Vec<AlbumData>),
 }
 
 #[relm4::component(pub)]
@@ -44,7 +68,7 @@ impl Component for DiscoverPage {
     type Init = ();
     type Input = DiscoverMsg;
     type Output = DiscoverOutput;
-    type CommandOutput = Result<Vec<AlbumData>, String>;
+    type CommandOutput = Result<Vec<AlbumData>, ClientError>;
 
     view! {
         gtk4::Box {
@@ -59,15 +83,22 @@ impl Component for DiscoverPage {
             .launch(())
             .forward(sender.input_sender(), DiscoverMsg::GridAction);
 
-        let model = Self {
+        let (card_sender, card_output) = relm4::channel();
+        relm4::spawn_local(card_output.forward(sender.input_sender().clone(), DiscoverMsg::GridAction));
+
+        let mut model = Self {
             client: None,
             grid,
             params: DiscoverParams::default(),
             loading: false,
             fetch_mode: FetchMode::Fresh,
+            later: build_later_section(&sender, card_sender),
+            later_albums: Vec::new(),
         };
+        model.show_later();
 
         let widgets = view_output!();
+        root.append(&model.later.revealer);
         root.append(model.grid.widget());
         ComponentParts { model, widgets }
     }
@@ -104,6 +135,13 @@ impl Component for DiscoverPage {
                 sender.output(DiscoverOutput::TagChanged(tag)).ok();
                 sender.input(DiscoverMsg::Refresh);
             }
+            DiscoverMsg::ShowTag(tag) => {
+                self.params.genre = GENRES[0].0.to_string();
+                self.params.tag = tag.clone();
+                sender.output(DiscoverOutput::GenreChanged(0)).ok();
+                sender.output(DiscoverOutput::TagChanged(tag)).ok();
+                sender.input(DiscoverMsg::Refresh);
+            }
             DiscoverMsg::SetSort(i) => {
                 if let Some((k, _)) = SORT_OPTIONS.get(i as usize) {
                     self.params.sort = k.to_string();
@@ -118,16 +156,33 @@ impl Component for DiscoverPage {
                         FetchMode::Fresh => self.grid.emit(AlbumGridMsg::Replace(albums)),
                         FetchMode::LoadMore => self.grid.emit(AlbumGridMsg::Append(albums)),
                     },
-                    Err(e) => { sender.output(DiscoverOutput::Error(format!("Discover failed: {e}"))).ok(); }
+                    Err(e) => {
+                        self.grid.emit(AlbumGridMsg::Error(e.clone()));
+                        sender.output(DiscoverOutput::Error(e)).ok();
+                    }
                 }
             }
+            DiscoverMsg::FocusResults => self.grid.emit(AlbumGridMsg::Focus),
+            DiscoverMsg::ListenLaterChanged => self.show_later(),
+            DiscoverMsg::PlayListenLater => {
+                sender.output(DiscoverOutput::PlayAll(self.later_albums.clone())).ok();
+            }
             DiscoverMsg::GridAction(action) => match action {
-                AlbumGridOutput::Clicked(data) => {
-                    sender.output(DiscoverOutput::Play(data)).ok();
+                AlbumGridOutput::Clicked(data, click) => {
+                    sender.output(DiscoverOutput::Activate(data, click)).ok();
+                }
+                AlbumGridOutput::ArtistClicked(data) => {
+                    sender.output(DiscoverOutput::ShowArtist(data)).ok();
+                }
+                AlbumGridOutput::Hovered(data) => {
+                    sender.output(DiscoverOutput::Prefetch(data)).ok();
                 }
                 AlbumGridOutput::ScrolledToBottom => {
                     sender.input(DiscoverMsg::LoadMore);
                 }
+                AlbumGridOutput::Retry => {
+                    sender.input(DiscoverMsg::Refresh);
+                }
             },
         }
     }
@@ -138,40 +193,131 @@ impl Component for DiscoverPage {
 }
 
 impl DiscoverPage {
+    fn show_later(&mut self) {
+        self.later_albums = crate::listen_later::albums();
+        while let Some(child) = self.later.cards.first_child() {
+            self.later.cards.remove(&child);
+        }
+        for album in &self.later_albums {
+            let card = Card::new(&self.later.sender, true);
+            card.bind(Some(album));
+            self.later.cards.append(&card.root);
+        }
+        self.later.count.set_label(&self.later_albums.len().to_string());
+        self.later.revealer.set_reveal_child(!self.later_albums.is_empty());
+    }
+
     fn fetch(&mut self, sender: ComponentSender<Self>) {
         let Some(client) = self.client.clone() else { return };
         self.loading = true;
+        self.grid.emit(AlbumGridMsg::Loading);
         let params = self.params.clone();
         sender.oneshot_command(async move {
             client
                 .discover(&params)
                 .await
                 .map(|albums| albums.into_iter().map(AlbumData::from).collect())
-                .map_err(|e| e.to_string())
+                .map_err(ClientError::from)
         });
     }
 }
 
+/// A row of the albums on the listen later list, with a button to play
+/// them all.
+fn build_later_section(
+    sender: &ComponentSender<DiscoverPage>,
+    cards_sender: relm4::Sender<AlbumGridOutput>,
+) -> LaterSection {
+    let title = gtk4::Label::new(Some(&gettext("Listen Later")));
+    title.add_css_class("heading");
+
+    let count = gtk4::Label::new(None);
+    count.add_css_class("dim-label");
+    count.add_css_class("numeric");
+    count.set_hexpand(true);
+    count.set_xalign(0.0);
+
+    let play_btn = gtk4::Button::from_icon_name("media-playback-start-symbolic");
+    play_btn.add_css_class("flat");
+    play_btn.set_tooltip_text(Some(&gettext("Play All")));
+    let s = sender.input_sender().clone();
+    play_btn.connect_clicked(move |_| s.emit(DiscoverMsg::PlayListenLater));
+
+    let header = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
+    header.set_margin_start(8);
+    header.set_margin_end(8);
+    header.append(&title);
+    header.append(&count);
+    header.append(&play_btn);
+
+    let cards = gtk4::Box::new(gtk4::Orientation::Horizontal, 8);
+    cards.set_margin_start(8);
+    cards.set_margin_end(8);
+    cards.set_margin_bottom(8);
+
+    let scroll = gtk4::ScrolledWindow::new();
+    scroll.set_policy(gtk4::PolicyType::Automatic, gtk4::PolicyType::Never);
+    scroll.set_child(Some(&cards));
+
+    let section = gtk4::Box::new(gtk4::Orientation::Vertical, 6);
+    section.set_margin_top(8);
+    section.append(&header);
+    section.append(&scroll);
+    section.append(&gtk4::Separator::new(gtk4::Orientation::Horizontal));
+
+    let revealer = gtk4::Revealer::new();
+    revealer.set_child(Some(&section));
+
+    LaterSection {
+        revealer,
+        count,
+        cards,
+        sender: cards_sender,
+    }
+}
+
+/// Discover's header bar controls, kept to reflect tags opened from
+/// elsewhere.
+pub struct Toolbar {
+    pub root: gtk4::Box,
+    genre: gtk4::DropDown,
+    genre_changed: gtk4::glib::SignalHandlerId,
+    tag: gtk4::SearchEntry,
+}
+
+impl Toolbar {
+    /// Shows `tag` across all genres, as [`DiscoverMsg::ShowTag`] fetches
+    /// it, without the controls asking for fetches of their own.
+    pub fn show_tag(&self, tag: &str) {
+        self.genre.block_signal(&self.genre_changed);
+        self.genre.set_selected(0);
+        self.genre.unblock_signal(&self.genre_changed);
+        self.tag.set_text(tag);
+    }
+}
+
 pub fn build_toolbar(
     sender: &relm4::Sender<DiscoverMsg>,
     ui_state: &crate::storage::UiState,
-) -> gtk4::Box {
+) -> Toolbar {
     let toolbar = gtk4::Box::new(gtk4::Orientation::Horizontal, 8);
     toolbar.add_css_class("compact-toolbar");
 
+    let genres: Vec<String> = GENRES.iter().map(|(_, l)| gettext(*l)).collect();
     let genre_dd = gtk4::DropDown::new(
         Some(gtk4::StringList::new(
-            &GENRES.iter().map(|(_, l)| *l).collect::<Vec<_>>(),
+            &genres.iter().map(String::as_str).collect::<Vec<_>>(),
         )),
         None::<gtk4::Expression>,
     );
+    genre_dd.set_widget_name("discover-genre");
     if let Some(i) = ui_state.discover_genre {
         genre_dd.set_selected(i);
     }
     toolbar.append(&genre_dd);
 
     let tag_entry = gtk4::SearchEntry::new();
-    tag_entry.set_placeholder_text(Some("Tag filter..."));
+    tag_entry.set_placeholder_text(Some(&gettext("Tag filter...")));
     tag_entry.set_max_width_chars(16);
     if let Some(ref t) = ui_state.discover_tag {
         tag_entry.set_text(t);
@@ -180,7 +326,7 @@ pub fn build_toolbar(
 
     let tag_entry_for_genre = tag_entry.clone();
     let s = sender.clone();
-    genre_dd.connect_selected_notify(move |dd| {
+    let genre_changed = genre_dd.connect_selected_notify(move |dd| {
         tag_entry_for_genre.set_text("");
         s.emit(DiscoverMsg::SetGenre(dd.selected()));
     });
@@ -195,13 +341,17 @@ pub fn build_toolbar(
             .replace(' ', "-");
         s.emit(DiscoverMsg::SetTag(text));
     });
+    let s = sender.clone();
+    crate::album_grid::connect_focus_results(&tag_entry, move || s.emit(DiscoverMsg::FocusResults));
 
+    let sorts: Vec<String> = SORT_OPTIONS.iter().map(|(_, l)| gettext(*l)).collect();
     let sort_dd = gtk4::DropDown::new(
         Some(gtk4::StringList::new(
-            &SORT_OPTIONS.iter().map(|(_, l)| *l).collect::<Vec<_>>(),
+            &sorts.iter().map(String::as_str).collect::<Vec<_>>(),
         )),
         None::<gtk4::Expression>,
     );
+    sort_dd.set_widget_name("discover-sort");
     if let Some(i) = ui_state.discover_sort {
         sort_dd.set_selected(i);
     }
@@ -211,5 +361,10 @@ pub fn build_toolbar(
     });
     toolbar.append(&sort_dd);
 
-    toolbar
+    Toolbar {
+        root: toolbar,
+        genre: genre_dd,
+        genre_changed,
+        tag: tag_entry,
+    }
 }
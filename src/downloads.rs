@@ -0,0 +1,300 @@
+use crate::album_grid::AlbumData;
+use crate::bandcamp::{BandcampClient, ClientError, TrackInfo};
+use crate::preferences::DownloadFormat;
+use gettextrs::gettext;
+use lofty::picture::{MimeType, Picture, PictureType};
+use lofty::prelude::*;
+use lofty::tag::Tag;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// One folder per `/`; the extension is added to the last part.
+pub const DEFAULT_TEMPLATE: &str = "{artist}/{album}/{track_num} {title}";
+
+const AUDIO_EXTENSIONS: &[&str] = &["mp3", "flac", "ogg", "m4a", "wav", "aiff"];
+const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png"];
+
+/// Held while the download index is read, changed and written back, so
+/// downloads finishing together don't drop each other's tracks.
+static INDEX: Mutex<()> = Mutex::new(());
+
+/// Numbers each download's staging folder, so downloading an album again
+/// while it is still arriving doesn't write over the first archive.
+static STAGING: AtomicU64 = AtomicU64::new(0);
+
+/// Where purchases are saved and how their files are named.
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub dir: PathBuf,
+    pub template: String,
+    pub format: DownloadFormat,
+}
+
+/// A `file://` URI for the downloaded copy of the track at `track_url`,
+/// if it is still where it was saved.
+pub fn local_uri(track_url: &str) -> Option<String> {
+    if track_url.is_empty() {
+        return None;
+    }
+    let path = crate::storage::load_download_index().tracks.remove(track_url)?;
+    if !path.exists() {
+        return None;
+    }
+    gtk4::glib::filename_to_uri(&path, None).ok().map(Into::into)
+}
+
+/// `~/Music`, or wherever the desktop keeps music.
+pub fn default_dir() -> PathBuf {
+    dirs::audio_dir()
+        .or_else(|| dirs::home_dir().map(|home| home.join("Music")))
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// What a track's file name is made of.
+struct Fields {
+    artist: String,
+    album: String,
+    title: String,
+    number: Option<u32>,
+    disc: Option<u32>,
+}
+
+/// The path of a track below the download folder, without extension.
+fn render(template: &str, fields: &Fields) -> PathBuf {
+    let number = fields.number.map(|n| format!("{n:02}")).unwrap_or_default();
+    template
+        .split('/')
+        .map(|part| {
+            let part = part
+                .replace("{artist}", &clean(&fields.artist))
+                .replace("{album}", &clean(&fields.album))
+                .replace("{track_num}", &number)
+                .replace("{title}", &clean(&fields.title));
+            // Leading dots would hide the file or climb out of the folder.
+            part.trim().trim_start_matches('.').trim().to_string()
+        })
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
+/// Keeps tag values from adding folders, and characters FAT-formatted
+/// players reject out of file names.
+fn clean(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect()
+}
+
+fn extension(path: &Path) -> String {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+/// Downloads a purchase, or a release given away for free, in the chosen
+/// format and files its tracks, tagged and with cover art, under the
+/// download folder, reporting how much of the archive arrived to
+/// `progress`. Returns how many tracks were saved.
+pub async fn download(
+    client: BandcampClient,
+    album: AlbumData,
+    options: Options,
+    progress: impl Fn(f64),
+) -> Result<usize, ClientError> {
+    let page = match album.download_url.clone() {
+        Some(page) => page,
+        None => client
+            .free_download_page(&album.url)
+            .await?
+            .ok_or_else(|| ClientError::Other(gettext("Only purchased or free albums can be downloaded")))?,
+    };
+    // The release's own tracks, so files are indexed under the links the
+    // player looks them up by.
+    let tracks = client.get_album_details(&album.url).await?.tracks;
+    let url = client.get_download_url(&page, options.format.id()).await?;
+    let mut response = crate::bandcamp::http()
+        .get(&url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| ClientError::from(anyhow::Error::from(e)))?;
+    let total = response.content_length().filter(|&len| len > 0);
+
+    // Albums in lossless formats run into gigabytes, so the archive goes
+    // to disk as it arrives rather than into memory.
+    let staging = crate::storage::cache_dir().join("downloads").join(format!(
+        "{}-{}-{}",
+        crate::art_cache::key(&album.url),
+        std::process::id(),
+        STAGING.fetch_add(1, Ordering::Relaxed)
+    ));
+    let archive = staging.join("download");
+    let received = async {
+        fs::create_dir_all(&staging)?;
+        let mut file = fs::File::create(&archive)?;
+        let mut received = 0;
+        while let Some(chunk) = response.chunk().await.map_err(std::io::Error::other)? {
+            file.write_all(&chunk)?;
+            received += chunk.len() as u64;
+            if let Some(total) = total {
+                progress(received as f64 / total as f64);
+            }
+        }
+        file.flush()
+    }
+    .await;
+    if let Err(e) = received {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(ClientError::Other(e.to_string()));
+    }
+    // Albums bring their cover along; single tracks don't.
+    let art = match album.art_url.as_deref() {
+        Some(url) => crate::art_cache::fetch(url).await,
+        None => None,
+    };
+
+    tokio::task::spawn_blocking(move || {
+        let result = unpack(&archive, art, &album, &tracks, &options, &staging);
+        let _ = fs::remove_dir_all(&staging);
+        result.map_err(|e| ClientError::Other(e.to_string()))
+    })
+    .await
+    .map_err(|e| ClientError::Other(e.to_string()))?
+}
+
+/// The link of the release track a downloaded file holds, matched by disc
+/// and track number, so same-numbered tracks on different discs of a
+/// multi-disc release each keep their own.
+fn track_url(fields: &Fields, tracks: &[TrackInfo], single: bool) -> Option<String> {
+    if single && tracks.len() == 1 {
+        return Some(tracks[0].url.clone());
+    }
+    let number = fields.number?;
+    tracks
+        .iter()
+        .find(|t| t.number == Some(number) && t.disc.unwrap_or(1) == fields.disc.unwrap_or(1))
+        .map(|t| t.url.clone())
+}
+
+/// `dest`, or if another file is there already, the first of
+/// `name (2).ext`, `name (3).ext`… that is free.
+fn free_path(dest: PathBuf) -> PathBuf {
+    if !dest.exists() {
+        return dest;
+    }
+    let stem = dest.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let ext = extension(&dest);
+    (2..)
+        .map(|n| dest.with_file_name(format!("{stem} ({n}).{ext}")))
+        .find(|path| !path.exists())
+        .unwrap_or(dest)
+}
+
+fn unpack(
+    archive: &Path,
+    art: Option<Vec<u8>>,
+    album: &AlbumData,
+    tracks: &[TrackInfo],
+    options: &Options,
+    staging: &Path,
+) -> anyhow::Result<usize> {
+    let mut cover = art;
+    let mut files = Vec::new();
+    let mut magic = [0; 4];
+    let is_zip = fs::File::open(archive)?.read_exact(&mut magic).is_ok() && magic == *b"PK\x03\x04";
+    if is_zip {
+        let mut archive = zip::ZipArchive::new(fs::File::open(archive)?)?;
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i)?;
+            let Some(name) = entry.enclosed_name().and_then(|p| p.file_name().map(PathBuf::from)) else {
+                continue;
+            };
+            let ext = extension(&name);
+            if IMAGE_EXTENSIONS.contains(&ext.as_str()) {
+                let mut image = Vec::new();
+                entry.read_to_end(&mut image)?;
+                cover = Some(image);
+            } else if AUDIO_EXTENSIONS.contains(&ext.as_str()) {
+                let path = staging.join(name);
+                std::io::copy(&mut entry, &mut fs::File::create(&path)?)?;
+                files.push(path);
+            }
+        }
+    } else {
+        let path = staging.join(format!("track.{}", options.format.extension()));
+        fs::rename(archive, &path)?;
+        files.push(path);
+    }
+
+    let mut saved = Vec::new();
+    let single = files.len() == 1;
+    for path in &files {
+        let fields = write_tags(path, album, cover.as_deref())?;
+        let mut dest = options.dir.join(render(&options.template, &fields)).into_os_string();
+        dest.push(".");
+        dest.push(extension(path));
+        let dest = PathBuf::from(dest);
+        let key = track_url(&fields, tracks, single);
+        // Downloading a track again replaces its earlier copy; another
+        // track that happens to render to the same name is left alone.
+        let previous = key.as_ref().and_then(|key| crate::storage::load_download_index().tracks.remove(key));
+        let dest = if previous.as_ref() == Some(&dest) { dest } else { free_path(dest) };
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // The cache and music folders may be on different file systems.
+        if fs::rename(path, &dest).is_err() {
+            fs::copy(path, &dest)?;
+        }
+        if let Some(key) = key {
+            saved.push((key, dest));
+        }
+    }
+
+    let _lock = INDEX.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
+    let mut index = crate::storage::load_download_index();
+    index.tracks.extend(saved);
+    crate::storage::save_download_index(&index)?;
+    Ok(files.len())
+}
+
+/// Fills in whatever the file's own ID3 or Vorbis tags lack from the
+/// album, and embeds the cover unless one is there already.
+fn write_tags(path: &Path, album: &AlbumData, cover: Option<&[u8]>) -> anyhow::Result<Fields> {
+    let mut file = lofty::read_from_path(path)?;
+    if file.primary_tag().is_none() {
+        file.insert_tag(Tag::new(file.primary_tag_type()));
+    }
+    let tag = file
+        .primary_tag_mut()
+        .ok_or_else(|| anyhow::anyhow!("Can't tag {}", path.display()))?;
+
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+    let fields = Fields {
+        artist: tag.artist().map(|s| s.into_owned()).unwrap_or_else(|| album.artist.clone()),
+        album: tag.album().map(|s| s.into_owned()).unwrap_or_else(|| album.title.clone()),
+        title: tag.title().map(|s| s.into_owned()).unwrap_or_else(|| stem.to_string()),
+        number: tag.track(),
+        disc: tag.disk(),
+    };
+    tag.set_artist(fields.artist.clone());
+    tag.set_album(fields.album.clone());
+    tag.set_title(fields.title.clone());
+
+    let has_cover = tag.pictures().iter().any(|p| p.pic_type() == PictureType::CoverFront);
+    if let (false, Some(cover)) = (has_cover, cover) {
+        let mime = if cover.starts_with(b"\x89PNG") { MimeType::Png } else { MimeType::Jpeg };
+        tag.push_picture(Picture::new_unchecked(PictureType::CoverFront, Some(mime), None, cover.to_vec()));
+    }
+    tag.save_to_path(path, lofty::config::WriteOptions::default())?;
+    Ok(fields)
+}
@@ -0,0 +1,202 @@
+use crate::bandcamp::CollectionItem;
+use gettextrs::gettext;
+use gtk4::glib;
+use gtk4::prelude::*;
+use libadwaita as adw;
+use libadwaita::prelude::*;
+use relm4::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+/// Words releases are marked with that don't make them different music.
+const MARKERS: &[&str] = &["single", "ep", "deluxe", "edition", "remastered", "version", "bonus", "tracks"];
+
+/// Lowercase words without punctuation, bracketed asides or trailing
+/// format markers, so "Song (Single)", "Song - EP" and "SONG" compare equal.
+fn normalize(text: &str) -> String {
+    let mut plain = String::new();
+    let mut depth = 0usize;
+    for c in text.to_lowercase().chars() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = depth.saturating_sub(1),
+            _ if depth > 0 => {}
+            c if c.is_alphanumeric() => plain.push(c),
+            _ => plain.push(' '),
+        }
+    }
+    let mut words: Vec<&str> = plain.split_whitespace().collect();
+    while words.len() > 1 && words.last().is_some_and(|w| MARKERS.contains(w)) {
+        words.pop();
+    }
+    words.join(" ")
+}
+
+/// Releases that look like the same music: the same artist and title
+/// once normalized, bought from different pages, such as an artist's and
+/// their label's, or as a single and again as part of a reissue.
+pub fn find(items: &[CollectionItem]) -> Vec<Vec<CollectionItem>> {
+    let mut groups: Vec<Vec<CollectionItem>> = Vec::new();
+    let mut by_key: HashMap<(String, String), usize> = HashMap::new();
+    let mut seen = HashSet::new();
+    for item in items {
+        let Some(release) = crate::bandcamp::parse_release_link(&item.url) else { continue };
+        if !seen.insert(release.url) {
+            continue;
+        }
+        let key = (normalize(&item.artist), normalize(&item.title));
+        match by_key.get(&key) {
+            Some(&i) => groups[i].push(item.clone()),
+            None => {
+                by_key.insert(key, groups.len());
+                groups.push(vec![item.clone()]);
+            }
+        }
+    }
+    groups.retain(|group| group.len() > 1);
+    groups
+}
+
+/// Likely duplicates in the collection, each with a switch to hide it
+/// from the library, pushed onto the library's navigation stack.
+pub struct DuplicatesPage {
+    groups: Vec<Vec<CollectionItem>>,
+}
+
+pub struct DuplicatesInit {
+    pub collection: Vec<CollectionItem>,
+    /// Release pages already hidden.
+    pub hidden: HashSet<String>,
+}
+
+#[derive(Debug)]
+pub enum DuplicatesOutput {
+    /// Hide the release at this URL from the library, or show it again.
+    SetHidden(String, bool),
+}
+
+/// Where a release was bought from, e.g. "label.bandcamp.com".
+fn page(item: &CollectionItem) -> String {
+    reqwest::Url::parse(&item.url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn build_group(
+    group: &[CollectionItem],
+    hidden: &HashSet<String>,
+    sender: &ComponentSender<DuplicatesPage>,
+) -> gtk4::Box {
+    let title = gtk4::Label::new(Some(&crate::album_card::accessible_label(&group[0].clone().into())));
+    title.add_css_class("heading");
+    title.set_xalign(0.0);
+    title.set_ellipsize(gtk4::pango::EllipsizeMode::End);
+
+    let list = gtk4::ListBox::new();
+    list.add_css_class("boxed-list");
+    list.set_selection_mode(gtk4::SelectionMode::None);
+    for item in group {
+        let Some(release) = crate::bandcamp::parse_release_link(&item.url) else { continue };
+        let row = adw::SwitchRow::new();
+        row.set_title(&glib::markup_escape_text(&item.title));
+        row.set_subtitle(&glib::markup_escape_text(&page(item)));
+        row.set_active(!hidden.contains(&release.url));
+        row.set_tooltip_text(Some(&gettext("Show in Library")));
+        let s = sender.clone();
+        row.connect_active_notify(move |row| {
+            s.output(DuplicatesOutput::SetHidden(release.url.clone(), !row.is_active())).ok();
+        });
+        list.append(&row);
+    }
+
+    let section = gtk4::Box::new(gtk4::Orientation::Vertical, 6);
+    section.append(&title);
+    section.append(&list);
+    section
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for DuplicatesPage {
+    type Init = DuplicatesInit;
+    type Input = ();
+    type Output = DuplicatesOutput;
+
+    view! {
+        gtk4::Box {
+            set_orientation: gtk4::Orientation::Vertical,
+            set_hexpand: true,
+            set_vexpand: true,
+
+            gtk4::CenterBox {
+                add_css_class: "compact-toolbar",
+                set_margin_start: 6,
+                set_margin_end: 6,
+                set_margin_top: 6,
+
+                #[wrap(Some)]
+                set_start_widget = &gtk4::Button {
+                    set_icon_name: "go-previous-symbolic",
+                    set_tooltip_text: Some(&gettext("Back")),
+                    add_css_class: "flat",
+                    set_action_name: Some("navigation.pop"),
+                },
+
+                #[wrap(Some)]
+                set_center_widget = &gtk4::Label {
+                    set_label: &gettext("Duplicates"),
+                    add_css_class: "heading",
+                },
+            },
+
+            gtk4::Stack {
+                set_vexpand: true,
+                set_visible_child_name: if model.groups.is_empty() { "empty" } else { "list" },
+
+                add_named[Some("empty")] = &adw::StatusPage {
+                    set_icon_name: Some("edit-copy-symbolic"),
+                    set_title: &gettext("No Duplicates"),
+                    set_description: Some(&gettext("Every release in your collection looks different")),
+                },
+
+                add_named[Some("list")] = &gtk4::ScrolledWindow {
+                    set_hscrollbar_policy: gtk4::PolicyType::Never,
+
+                    adw::Clamp {
+                        set_margin_top: 12,
+                        set_margin_bottom: 12,
+                        set_margin_start: 12,
+                        set_margin_end: 12,
+
+                        #[name = "groups"]
+                        gtk4::Box {
+                            set_orientation: gtk4::Orientation::Vertical,
+                            set_spacing: 18,
+                            set_valign: gtk4::Align::Start,
+
+                            gtk4::Label {
+                                add_css_class: "dim-label",
+                                set_xalign: 0.0,
+                                set_wrap: true,
+                                set_label: &gettext(
+                                    "These releases share an artist and title. Hidden ones stay in your collection \
+                                     but are left out of the library.",
+                                ),
+                            },
+                        },
+                    },
+                },
+            },
+        }
+    }
+
+    fn init(init: Self::Init, root: Self::Root, sender: ComponentSender<Self>) -> ComponentParts<Self> {
+        let model = Self { groups: find(&init.collection) };
+        let widgets = view_output!();
+        for group in &model.groups {
+            widgets.groups.append(&build_group(group, &init.hidden, &sender));
+        }
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, _msg: Self::Input, _sender: ComponentSender<Self>) {}
+}
@@ -0,0 +1,106 @@
+use crate::bandcamp::Price;
+use crate::storage::{self, ExchangeRates};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Reference rates of the European Central Bank, against the euro.
+const RATES_URL: &str = "https://api.frankfurter.app/latest";
+/// How long fetched rates are used before fetching them again. The ECB
+/// publishes once per working day.
+const RATES_TTL_SECS: u64 = 24 * 60 * 60;
+
+/// Currencies Bandcamp sells in, which prices can be shown in.
+pub const CURRENCIES: &[&str] = &[
+    "USD", "EUR", "GBP", "AUD", "CAD", "JPY", "NZD", "CHF", "SEK", "NOK", "DKK", "CZK", "PLN", "HUF", "ILS", "MXN",
+    "SGD", "HKD",
+];
+
+/// The currency to show prices in; `None` leaves them as the artist set them.
+static CURRENCY: RwLock<Option<String>> = RwLock::new(None);
+static RATES: RwLock<Option<ExchangeRates>> = RwLock::new(None);
+
+#[derive(Debug, Deserialize)]
+struct RatesResponse {
+    base: String,
+    rates: HashMap<String, f64>,
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+pub fn set_currency(currency: Option<String>) {
+    if let Ok(mut current) = CURRENCY.write() {
+        *current = currency;
+    }
+}
+
+/// `price` in the chosen currency, if one is chosen, it differs and the
+/// rates of both are known. Free stays free in any currency.
+pub fn convert(price: &Price) -> Option<Price> {
+    let currency = CURRENCY.read().ok()?.clone()?;
+    if price.is_free() || price.currency.eq_ignore_ascii_case(&currency) {
+        return None;
+    }
+    let rates = RATES.read().ok()?;
+    let rates = rates.as_ref()?;
+    let rate = |code: &str| {
+        if code.eq_ignore_ascii_case(&rates.base) {
+            Some(1.0)
+        } else {
+            rates.rates.get(&code.to_uppercase()).copied()
+        }
+    };
+    let amount = price.amount / rate(&price.currency)? * rate(&currency)?;
+    Some(Price { amount, currency })
+}
+
+/// What to show for `price`: converted and marked as approximate where
+/// [`convert`] can, as is otherwise.
+pub fn display(price: &Price) -> String {
+    match convert(price) {
+        Some(converted) => format!("≈ {converted}"),
+        None => price.to_string(),
+    }
+}
+
+/// Loads the cached rates, fetching new ones when they are older than a
+/// day. Stale rates are kept while saving data or when the fetch fails.
+pub async fn refresh() {
+    let cached = storage::load_exchange_rates();
+    let fresh = cached.as_ref().is_some_and(|r| now().saturating_sub(r.fetched_at) < RATES_TTL_SECS);
+    let rates = if fresh || (cached.is_some() && crate::data_saver::active()) {
+        cached
+    } else {
+        match fetch().await {
+            Ok(rates) => {
+                let _ = storage::save_exchange_rates(&rates);
+                Some(rates)
+            }
+            Err(e) => {
+                crate::diagnostics::error("exchange", format!("Failed to fetch exchange rates: {e}"));
+                cached
+            }
+        }
+    };
+    if let Ok(mut current) = RATES.write() {
+        *current = rates;
+    }
+}
+
+async fn fetch() -> anyhow::Result<ExchangeRates> {
+    let resp: RatesResponse = crate::bandcamp::http()
+        .get(RATES_URL)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(ExchangeRates {
+        fetched_at: now(),
+        base: resp.base,
+        rates: resp.rates,
+    })
+}
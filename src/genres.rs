@@ -0,0 +1,106 @@
+use crate::bandcamp::{BandcampClient, CollectionItem, Tag, GENRES};
+use crate::storage::{self, GenreCache};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Releases looked up at once.
+const CONCURRENCY: usize = 4;
+
+thread_local! {
+    /// Read from disk the first time a genre is asked for.
+    static CACHE: RefCell<Option<GenreCache>> = RefCell::default();
+    /// Releases looked up since launch, found or not, so one that failed
+    /// waits for the next launch instead of every refresh.
+    static TRIED: RefCell<HashSet<String>> = RefCell::default();
+}
+
+fn with_cache<T>(f: impl FnOnce(&mut GenreCache) -> T) -> T {
+    CACHE.with(|cache| f(cache.borrow_mut().get_or_insert_with(storage::load_genre_cache)))
+}
+
+/// The release page a track or release URL belongs to.
+fn release(url: &str) -> Option<String> {
+    crate::bandcamp::parse_release_link(url).map(|link| link.url)
+}
+
+/// The genre looked up for the release at `url`, if it has one.
+pub fn get(url: &str) -> Option<String> {
+    let url = release(url)?;
+    with_cache(|cache| cache.albums.get(&url).filter(|genre| !genre.is_empty()).cloned())
+}
+
+/// Items without a genre on record that haven't been looked up since
+/// launch, which are taken as looked up from here on.
+pub fn missing(items: &[CollectionItem]) -> Vec<CollectionItem> {
+    with_cache(|cache| {
+        TRIED.with(|tried| {
+            let mut tried = tried.borrow_mut();
+            items
+                .iter()
+                .filter(|item| {
+                    release(&item.url).is_some_and(|url| !cache.albums.contains_key(&url) && tried.insert(url))
+                })
+                .cloned()
+                .collect()
+        })
+    })
+}
+
+/// Remembers genres found by [`lookup`], by release page, and saves them.
+pub fn record(found: Vec<(String, String)>) {
+    if found.is_empty() {
+        return;
+    }
+    let result = with_cache(|cache| {
+        cache.albums.extend(found);
+        storage::save_genre_cache(cache)
+    });
+    if let Err(e) = result {
+        crate::diagnostics::error("storage", format!("Failed to cache genres: {e}"));
+    }
+}
+
+/// The genre a release's tags suggest: the first one Discover browses by,
+/// otherwise the first tag. Empty without tags.
+fn from_tags(tags: &[Tag]) -> String {
+    tags.iter()
+        .find_map(|tag| GENRES.iter().skip(1).find(|(slug, _)| *slug == tag.slug).map(|(_, name)| name.to_string()))
+        .or_else(|| tags.first().map(|tag| tag.name.clone()))
+        .unwrap_or_default()
+}
+
+/// Looks up the genre of each item from its release's tags, a few at a
+/// time, handing each release page and genre to `found` as it arrives.
+/// Releases that fail to load come with none, to be tried again after
+/// the next launch.
+pub async fn lookup(
+    client: BandcampClient,
+    items: Vec<CollectionItem>,
+    found: impl Fn(String, Option<String>) + Send + 'static,
+) {
+    let limit = Arc::new(tokio::sync::Semaphore::new(CONCURRENCY));
+    let mut tasks = tokio::task::JoinSet::new();
+    for item in items {
+        let Some(url) = release(&item.url) else { continue };
+        let (client, limit) = (client.clone(), limit.clone());
+        tasks.spawn(async move {
+            let _permit = limit.acquire_owned().await;
+            let ids = match (item.band_id, item.item_type.as_deref(), item.item_id) {
+                (Some(band_id), Some(kind), Some(id)) => Some((band_id, kind, id)),
+                _ => None,
+            };
+            let details = client.get_release_details(&url, ids).await;
+            let genre = details.ok().map(|details| {
+                let tags = details.tracks.into_iter().next().map(|track| track.tags).unwrap_or_default();
+                from_tags(&tags)
+            });
+            (url, genre)
+        });
+    }
+    while let Some(joined) = tasks.join_next().await {
+        if let Ok((url, genre)) = joined {
+            found(url, genre);
+        }
+    }
+}
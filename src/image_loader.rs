@@ -0,0 +1,195 @@
+use gtk4::gdk;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use tokio::sync::oneshot;
+
+/// Decoded textures kept in memory, on top of the disk cache.
+const MEMORY_ENTRIES: usize = 256;
+
+/// Artwork URL and the edge length it was decoded at.
+type Key = (String, i32);
+
+/// A decoded image, shared by every widget showing the same artwork.
+struct Entry {
+    /// See [`artwork`].
+    artwork: String,
+    /// Edge length it was decoded at.
+    size: i32,
+    /// The URL it was decoded from.
+    url: String,
+    texture: gdk::Texture,
+}
+
+thread_local! {
+    /// Least recently used first.
+    static MEMORY: RefCell<VecDeque<Entry>> = const { RefCell::new(VecDeque::new()) };
+    /// Callers waiting on a load that is already running.
+    static IN_FLIGHT: RefCell<HashMap<Key, Vec<oneshot::Sender<Option<gdk::Texture>>>>> =
+        RefCell::new(HashMap::new());
+}
+
+/// What a URL shows, regardless of size: Bandcamp serves each image in
+/// several formats, e.g. `…/a0123456789_10.jpg` for cards and
+/// `…/a0123456789_5.jpg` for the player, which are the same artwork.
+fn artwork(url: &str) -> &str {
+    match url.strip_suffix(".jpg").and_then(|u| u.rsplit_once('_')) {
+        Some((stem, format)) if stem.contains("bcbits.com/img/") && format.chars().all(|c| c.is_ascii_digit()) => {
+            stem
+        }
+        _ => url,
+    }
+}
+
+/// Keeps the texture unless one at least as large is kept already, which
+/// it replaces otherwise.
+fn remember(url: &str, size: i32, texture: &gdk::Texture) {
+    let artwork = artwork(url);
+    MEMORY.with(|memory| {
+        let mut memory = memory.borrow_mut();
+        if memory.iter().any(|e| e.artwork == artwork && e.size >= size) {
+            return;
+        }
+        memory.retain(|e| e.artwork != artwork);
+        if memory.len() >= MEMORY_ENTRIES {
+            memory.pop_front();
+        }
+        memory.push_back(Entry {
+            artwork: artwork.to_string(),
+            size,
+            url: url.to_string(),
+            texture: texture.clone(),
+        });
+    });
+}
+
+/// The texture kept for the artwork `url` shows, if it was decoded at
+/// `size` or larger. Smaller views scale it down.
+fn recall(url: &str, size: i32) -> Option<gdk::Texture> {
+    let artwork = artwork(url);
+    MEMORY.with(|memory| {
+        let mut memory = memory.borrow_mut();
+        let index = memory.iter().position(|e| e.artwork == artwork && e.size >= size)?;
+        let entry = memory.remove(index)?;
+        let texture = entry.texture.clone();
+        memory.push_back(entry);
+        Some(texture)
+    })
+}
+
+/// The file on disk the artwork `url` shows was decoded from, or else
+/// its own, once cached, so outside readers like the shell's media
+/// controls get the same image without downloading it again.
+pub fn file(url: &str) -> Option<PathBuf> {
+    let artwork = artwork(url);
+    let source = MEMORY.with(|memory| {
+        memory.borrow().iter().find(|e| e.artwork == artwork).map(|e| e.url.clone())
+    });
+    source.and_then(|source| crate::art_cache::cached_path(&source)).or_else(|| crate::art_cache::cached_path(url))
+}
+
+/// Artwork for `url` scaled to fit `size` pixels, from memory, disk or the
+/// network in that order. Every size variant of a Bandcamp image shares
+/// one texture, decoded at the largest size asked for so far, and
+/// concurrent requests for the same image share one download. Must be
+/// called on the main thread.
+pub async fn load(url: &str, size: i32) -> Option<gdk::Texture> {
+    let key = (url.to_string(), size);
+    loop {
+        if let Some(texture) = recall(url, size) {
+            return Some(texture);
+        }
+
+        let waiting = IN_FLIGHT.with(|in_flight| {
+            let mut in_flight = in_flight.borrow_mut();
+            match in_flight.get_mut(&key) {
+                Some(waiters) => {
+                    let (tx, rx) = oneshot::channel();
+                    waiters.push(tx);
+                    Some(rx)
+                }
+                None => {
+                    in_flight.insert(key.clone(), Vec::new());
+                    None
+                }
+            }
+        });
+        match waiting {
+            Some(rx) => match rx.await {
+                Ok(texture) => return texture,
+                // The load we joined was dropped halfway; start over.
+                Err(_) => continue,
+            },
+            None => break,
+        }
+    }
+
+    let guard = InFlight(Some(key));
+    let texture = fetch_and_decode(url.to_string(), size).await;
+    if let Some(texture) = &texture {
+        remember(url, size, texture);
+    }
+    guard.finish(texture.clone());
+    texture
+}
+
+/// Downloads and decodes on the worker runtime so scrolling never waits on
+/// it; only the finished texture comes back to the main thread.
+async fn fetch_and_decode(url: String, size: i32) -> Option<gdk::Texture> {
+    let task = relm4::spawn(async move {
+        let bytes = crate::art_cache::fetch(&url).await?;
+        relm4::spawn_blocking(move || decode(&bytes, size)).await.ok().flatten()
+    });
+    // Dropping the load, e.g. for a card scrolled away, cancels the download.
+    let _abort = AbortOnDrop(task.abort_handle());
+    task.await.ok().flatten()
+}
+
+struct AbortOnDrop(tokio::task::AbortHandle);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Runs on a blocking worker thread. The pixbuf never leaves it; textures
+/// are thread-safe.
+fn decode(bytes: &[u8], size: i32) -> Option<gdk::Texture> {
+    let stream = gtk4::gio::MemoryInputStream::from_bytes(&gtk4::glib::Bytes::from(bytes));
+    let pixbuf = gtk4::gdk_pixbuf::Pixbuf::from_stream_at_scale(
+        &stream,
+        size,
+        size,
+        true,
+        None::<&gtk4::gio::Cancellable>,
+    )
+    .ok()?;
+    Some(gdk::Texture::for_pixbuf(&pixbuf))
+}
+
+/// Marks a running load; dropping it unfinished lets the waiters retry.
+struct InFlight(Option<Key>);
+
+impl InFlight {
+    fn finish(mut self, texture: Option<gdk::Texture>) {
+        let Some(key) = self.0.take() else { return };
+        let waiters = IN_FLIGHT.with(|in_flight| in_flight.borrow_mut().remove(&key));
+        for tx in waiters.into_iter().flatten() {
+            let _ = tx.send(texture.clone());
+        }
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        if let Some(key) = self.0.take() {
+            IN_FLIGHT.with(|in_flight| in_flight.borrow_mut().remove(&key));
+        }
+    }
+}
+
+/// Drops decoded textures, e.g. after the disk cache was cleared.
+pub fn clear() {
+    MEMORY.with(|memory| memory.borrow_mut().clear());
+}
@@ -1,8 +1,14 @@
-use crate::album_grid::{AlbumData, AlbumGrid, AlbumGridMsg, AlbumGridOutput};
-use crate::bandcamp::{BandcampClient, CollectionItem};
+use crate::album_grid::{AlbumData, AlbumGrid, AlbumGridMsg, AlbumGridOutput, Click, Section};
+use crate::bandcamp::{BandcampClient, ClientError, CollectionItem};
+use gettextrs::gettext;
 use gtk4::prelude::*;
 use relm4::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+
+/// Genres looked up before they are saved together.
+const GENRE_BATCH: usize = 20;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -10,33 +16,112 @@ pub enum Sort {
     #[default]
     Date,
     Name,
+    /// Grouped under a header per artist.
+    Artist,
+    /// Most listened to first, as counted from the listening history.
+    Plays,
+    /// Most stars first, unrated releases last.
+    Rating,
+}
+
+/// Where a release sits in the library.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Owned {
+    Collection,
+    Wishlist,
+}
+
+thread_local! {
+    /// Release pages in the library, so albums shown elsewhere can tell
+    /// whether they are owned.
+    static OWNED: RefCell<HashMap<String, Owned>> = RefCell::default();
+}
+
+/// Whether the release at `url` is in the collection or on the wishlist,
+/// as far as the last library fetch knows.
+pub fn owned(url: &str) -> Option<Owned> {
+    let link = crate::bandcamp::parse_release_link(url)?;
+    OWNED.with(|owned| owned.borrow().get(&link.url).copied())
 }
 
 pub struct LibraryPage {
     client: Option<BandcampClient>,
     grid: Controller<AlbumGrid>,
+    collection: Vec<CollectionItem>,
+    wishlist: Vec<CollectionItem>,
     all_items: Vec<CollectionItem>,
     sort: Sort,
     query: String,
-    loading: bool,
+    /// Fewest stars a release needs to be shown; 0 shows everything.
+    min_rating: u8,
+    /// Release pages left out, see [`crate::duplicates`].
+    hidden: HashSet<String>,
+    /// Lists still being fetched.
+    pending: usize,
+    /// Whether any list fetched since the last refresh arrived.
+    fetched: bool,
+    /// The refresh, as listed among background work.
+    sync: Option<crate::activity::Handle>,
+    /// Genres being looked up, see [`crate::genres`], with how many are
+    /// left and how many there were.
+    genre_lookup: Option<(crate::activity::Handle, usize, usize)>,
+    /// Genres looked up but not saved yet.
+    found_genres: Vec<(String, String)>,
+}
+
+/// Collection and wishlist are fetched side by side and shown as each
+/// arrives.
+#[derive(Debug)]
+pub enum Fetched {
+    Collection(Result<Vec<CollectionItem>, ClientError>),
+    Wishlist(Result<Vec<CollectionItem>, ClientError>),
+}
+
+#[derive(Debug)]
+pub enum LibraryCmd {
+    Fetched(Fetched),
+    /// The genre of the release at this page was looked up: empty when it
+    /// has no tags, none when it couldn't be loaded.
+    Genre(String, Option<String>),
 }
 
 #[derive(Debug)]
 pub enum LibraryMsg {
-    SetClient(BandcampClient),
+    /// Sets the client; the flag asks for a refresh even when the cached
+    /// library could be shown.
+    SetClient(BandcampClient, bool),
     Refresh,
     SetSort(Sort),
     SetQuery(String),
-    Loaded(Result<(Vec<CollectionItem>, Vec<CollectionItem>), String>),
+    SetMinRating(u8),
+    /// Leave out the releases at these pages.
+    SetHidden(HashSet<String>),
+    /// A release's rating changed, see [`crate::ratings`].
+    RatingsChanged,
+    /// A release's note changed, see [`crate::notes`].
+    NotesChanged,
+    Loaded(Fetched),
+    GenreFound(String, Option<String>),
+    /// A release was added to the wishlist, or taken off, from elsewhere
+    /// in the app.
+    SetWishlisted(CollectionItem, bool),
     GridAction(AlbumGridOutput),
+    FocusResults,
 }
 
 #[derive(Debug)]
 pub enum LibraryOutput {
-    Play(crate::album_grid::AlbumData),
+    Activate(AlbumData, Click),
+    /// Resolve the album ahead of a likely click.
+    Prefetch(AlbumData),
+    /// An artist's name was clicked; these are their releases in the library.
+    ShowArtist(AlbumData, Vec<AlbumData>),
     SortChanged(Sort),
     QueryChanged(String),
-    Error(String),
+    MinRatingChanged(u8),
+    Error(ClientError),
+    /// What is owned or wishlisted changed, see [`owned`].
+    Changed,
 }
 
 #[relm4::component(pub)]
@@ -44,7 +129,7 @@ impl Component for LibraryPage {
     type Init = ();
     type Input = LibraryMsg;
     type Output = LibraryOutput;
-    type CommandOutput = Result<(Vec<CollectionItem>, Vec<CollectionItem>), String>;
+    type CommandOutput = LibraryCmd;
 
     view! {
         gtk4::Box {
@@ -62,10 +147,18 @@ impl Component for LibraryPage {
         let model = Self {
             client: None,
             grid,
+            collection: Vec::new(),
+            wishlist: Vec::new(),
             all_items: Vec::new(),
             sort: Sort::Date,
             query: String::new(),
-            loading: false,
+            min_rating: 0,
+            hidden: HashSet::new(),
+            pending: 0,
+            sync: None,
+            genre_lookup: None,
+            found_genres: Vec::new(),
+            fetched: false,
         };
 
         let widgets = view_output!();
@@ -75,9 +168,18 @@ impl Component for LibraryPage {
 
     fn update(&mut self, msg: Self::Input, sender: ComponentSender<Self>, _root: &Self::Root) {
         match msg {
-            LibraryMsg::SetClient(client) => {
+            LibraryMsg::SetClient(client, refresh) => {
                 self.client = Some(client);
-                sender.input(LibraryMsg::Refresh);
+                if let Some(cache) = crate::storage::load_library_cache() {
+                    self.collection = cache.collection;
+                    self.wishlist = cache.wishlist;
+                    self.merge();
+                    self.apply_sort();
+                    sender.output(LibraryOutput::Changed).ok();
+                }
+                if refresh || self.all_items.is_empty() {
+                    sender.input(LibraryMsg::Refresh);
+                }
             }
             LibraryMsg::Refresh => {
                 self.fetch(sender.clone());
@@ -92,21 +194,110 @@ impl Component for LibraryPage {
                 self.apply_sort();
                 sender.output(LibraryOutput::QueryChanged(q)).ok();
             }
-            LibraryMsg::Loaded(result) => {
-                self.loading = false;
+            LibraryMsg::SetMinRating(stars) => {
+                self.min_rating = stars;
+                self.apply_sort();
+                sender.output(LibraryOutput::MinRatingChanged(stars)).ok();
+            }
+            LibraryMsg::SetHidden(hidden) => {
+                self.hidden = hidden;
+                self.apply_sort();
+            }
+            LibraryMsg::RatingsChanged => {
+                if self.sort == Sort::Rating || self.min_rating > 0 {
+                    self.apply_sort();
+                }
+            }
+            LibraryMsg::NotesChanged => {
+                if !self.query.is_empty() {
+                    self.apply_sort();
+                }
+            }
+            LibraryMsg::Loaded(fetched) => {
+                self.pending = self.pending.saturating_sub(1);
+                let (list, result) = match fetched {
+                    Fetched::Collection(result) => (&mut self.collection, result),
+                    Fetched::Wishlist(result) => (&mut self.wishlist, result),
+                };
                 match result {
-                    Ok((collection, wishlist)) => {
-                        self.all_items.clear();
-                        self.all_items.extend(collection);
-                        self.all_items.extend(wishlist);
-                        self.apply_sort();
+                    Ok(items) => {
+                        *list = items;
+                        self.fetched = true;
+                        self.merge();
+                        sender.output(LibraryOutput::Changed).ok();
+                        // An empty first half would flash the empty page
+                        // while the other is still on its way.
+                        if !self.all_items.is_empty() || self.pending == 0 {
+                            self.apply_sort();
+                        }
                     }
-                    Err(e) => { sender.output(LibraryOutput::Error(format!("Library failed: {e}"))).ok(); }
+                    // Keep whatever was loaded from the cache on screen
+                    Err(e) => {
+                        if let Some(sync) = self.sync.take() {
+                            sync.fail(gettext("Library sync failed: {}").replace("{}", &e.message()));
+                        }
+                        self.grid.emit(AlbumGridMsg::Error(e.clone()));
+                        sender.output(LibraryOutput::Error(e)).ok();
+                    }
+                }
+                if let Some(sync) = &self.sync {
+                    sync.progress(0.5);
+                }
+                if self.pending == 0 {
+                    self.sync = None;
+                }
+                // A list that failed keeps its cached copy.
+                if self.pending == 0 && self.fetched {
+                    self.save_cache();
+                    self.lookup_genres(sender.clone());
+                }
+            }
+            LibraryMsg::GenreFound(url, genre) => {
+                self.found_genres.extend(genre.map(|genre| (url, genre)));
+                let Some((task, left, total)) = &mut self.genre_lookup else { return };
+                *left = left.saturating_sub(1);
+                task.progress(1.0 - *left as f64 / *total as f64);
+                // Saved in batches, and shown once all are in, rather than
+                // rebuilding the grid for every release.
+                if self.found_genres.len() >= GENRE_BATCH || *left == 0 {
+                    crate::genres::record(std::mem::take(&mut self.found_genres));
+                }
+                if *left == 0 {
+                    self.genre_lookup = None;
+                    self.apply_sort();
                 }
             }
+            LibraryMsg::SetWishlisted(item, wishlisted) => {
+                let known = owned(&item.url);
+                if wishlisted && known.is_none() {
+                    // Newest first, like the wishlist Bandcamp returns.
+                    self.wishlist.insert(0, item);
+                } else if !wishlisted && known == Some(Owned::Wishlist) {
+                    let url = crate::bandcamp::parse_release_link(&item.url).map(|link| link.url);
+                    self.wishlist
+                        .retain(|w| crate::bandcamp::parse_release_link(&w.url).map(|link| link.url) != url);
+                } else {
+                    return;
+                }
+                self.merge();
+                self.apply_sort();
+                self.save_cache();
+                sender.output(LibraryOutput::Changed).ok();
+            }
+            LibraryMsg::FocusResults => self.grid.emit(AlbumGridMsg::Focus),
             LibraryMsg::GridAction(action) => match action {
-                AlbumGridOutput::Clicked(data) => {
-                    sender.output(LibraryOutput::Play(data)).ok();
+                AlbumGridOutput::Clicked(data, click) => {
+                    sender.output(LibraryOutput::Activate(data, click)).ok();
+                }
+                AlbumGridOutput::Hovered(data) => {
+                    sender.output(LibraryOutput::Prefetch(data)).ok();
+                }
+                AlbumGridOutput::Retry => {
+                    sender.input(LibraryMsg::Refresh);
+                }
+                AlbumGridOutput::ArtistClicked(data) => {
+                    let owned = self.by_artist(&data);
+                    sender.output(LibraryOutput::ShowArtist(data, owned)).ok();
                 }
                 AlbumGridOutput::ScrolledToBottom => {}
             },
@@ -114,22 +305,110 @@ impl Component for LibraryPage {
     }
 
     fn update_cmd(&mut self, msg: Self::CommandOutput, sender: ComponentSender<Self>, _root: &Self::Root) {
-        sender.input(LibraryMsg::Loaded(msg));
+        sender.input(match msg {
+            LibraryCmd::Fetched(fetched) => LibraryMsg::Loaded(fetched),
+            LibraryCmd::Genre(url, genre) => LibraryMsg::GenreFound(url, genre),
+        });
     }
 }
 
 impl LibraryPage {
+    /// Everything bought, hidden releases included.
+    pub fn collection(&self) -> &[CollectionItem] {
+        &self.collection
+    }
+
     fn fetch(&mut self, sender: ComponentSender<Self>) {
         let Some(client) = self.client.clone() else { return };
-        self.loading = true;
+        // Results of an overlapping refresh would be counted twice.
+        if self.pending > 0 {
+            return;
+        }
+        self.pending = 2;
+        self.fetched = false;
+        self.sync = Some(crate::activity::start(gettext("Syncing library")));
+        self.grid.emit(AlbumGridMsg::Loading);
 
-        sender.oneshot_command(async move {
-            let collection = client.get_collection().await.map_err(|e| e.to_string())?;
-            let wishlist = client.get_wishlist().await.map_err(|e| e.to_string())?;
-            Ok((collection, wishlist))
+        sender.command(move |out, shutdown| {
+            shutdown
+                .register(async move {
+                    let collection = async {
+                        let result = client.get_collection().await.map_err(ClientError::from);
+                        out.send(LibraryCmd::Fetched(Fetched::Collection(result))).ok();
+                    };
+                    let wishlist = async {
+                        let result = client.get_wishlist().await.map_err(ClientError::from);
+                        out.send(LibraryCmd::Fetched(Fetched::Wishlist(result))).ok();
+                    };
+                    tokio::join!(collection, wishlist);
+                })
+                .drop_on_shutdown()
+        });
+    }
+
+    /// Looks up the genres of releases not looked up before, which the
+    /// collection and wishlist don't carry.
+    fn lookup_genres(&mut self, sender: ComponentSender<Self>) {
+        let Some(client) = self.client.clone() else { return };
+        // Every release is a page to fetch, which waits while saving data.
+        if self.genre_lookup.is_some() || crate::data_saver::active() {
+            return;
+        }
+        let missing = crate::genres::missing(&self.all_items);
+        if missing.is_empty() {
+            return;
+        }
+        let task = crate::activity::start(gettext("Looking up genres"));
+        self.genre_lookup = Some((task, missing.len(), missing.len()));
+        sender.command(move |out, shutdown| {
+            shutdown
+                .register(crate::genres::lookup(client, missing, move |url, genre| {
+                    out.send(LibraryCmd::Genre(url, genre)).ok();
+                }))
+                .drop_on_shutdown()
         });
     }
 
+    /// Purchases first, then the wishlist, as the date sort expects.
+    fn merge(&mut self) {
+        self.all_items.clear();
+        self.all_items.extend(self.collection.iter().cloned());
+        self.all_items.extend(self.wishlist.iter().cloned());
+
+        let wishlist = self.wishlist.iter().map(|item| (item, Owned::Wishlist));
+        // Owning a release beats wishing for it.
+        let collection = self.collection.iter().map(|item| (item, Owned::Collection));
+        let index = wishlist
+            .chain(collection)
+            .filter_map(|(item, owned)| Some((crate::bandcamp::parse_release_link(&item.url)?.url, owned)))
+            .collect();
+        OWNED.with(|owned| owned.replace(index));
+    }
+
+    /// Everything in the library from the artist of `album`, owned first.
+    /// Libraries cached before band IDs were kept match by name.
+    fn by_artist(&self, album: &AlbumData) -> Vec<AlbumData> {
+        let artist = album.artist.to_lowercase();
+        self.all_items
+            .iter()
+            .filter(|item| match (item.band_id, album.band_id) {
+                (Some(a), Some(b)) => a == b,
+                _ => item.artist.to_lowercase() == artist,
+            })
+            .map(|item| AlbumData::from(item.clone()))
+            .collect()
+    }
+
+    fn save_cache(&self) {
+        let cache = crate::storage::LibraryCache {
+            collection: self.collection.clone(),
+            wishlist: self.wishlist.clone(),
+        };
+        if let Err(e) = crate::storage::save_library_cache(&cache) {
+            crate::diagnostics::error("storage", format!("Failed to cache library: {e}"));
+        }
+    }
+
     fn apply_sort(&mut self) {
         let q = self.query.to_lowercase();
         let mut items: Vec<&CollectionItem> = self.all_items.iter()
@@ -137,11 +416,22 @@ impl LibraryPage {
                 q.is_empty()
                     || item.title.to_lowercase().contains(&q)
                     || item.artist.to_lowercase().contains(&q)
+                    || crate::notes::get(&item.url).is_some_and(|note| note.to_lowercase().contains(&q))
+                    || crate::genres::get(&item.url).is_some_and(|genre| genre.to_lowercase().contains(&q))
+            })
+            .filter(|item| self.min_rating == 0 || crate::ratings::get(&item.url).unwrap_or(0) >= self.min_rating)
+            .filter(|item| {
+                let link = crate::bandcamp::parse_release_link(&item.url);
+                self.hidden.is_empty() || !link.is_some_and(|link| self.hidden.contains(&link.url))
             })
             .collect();
         match self.sort {
             Sort::Date => {} // already in date order from API
             Sort::Name => items.sort_by(|a, b| a.title.to_lowercase().cmp(&b.title.to_lowercase())),
+            // Stable, so each artist's albums keep their date order
+            Sort::Artist => items.sort_by(|a, b| a.artist.to_lowercase().cmp(&b.artist.to_lowercase())),
+            Sort::Plays => items.sort_by_key(|item| std::cmp::Reverse(crate::plays::album(&item.url))),
+            Sort::Rating => items.sort_by_key(|item| std::cmp::Reverse(crate::ratings::get(&item.url))),
         }
 
         let albums: Vec<AlbumData> = items
@@ -149,7 +439,28 @@ impl LibraryPage {
             .map(|item| AlbumData::from(item.clone()))
             .collect();
 
-        self.grid.emit(AlbumGridMsg::Replace(albums));
+        self.grid.emit(AlbumGridMsg::ShowIndex(self.sort == Sort::Name));
+        if self.sort == Sort::Artist {
+            let mut sections: Vec<Section> = Vec::new();
+            for album in albums {
+                match sections.last_mut() {
+                    Some(section) if section.title.to_lowercase() == album.artist.to_lowercase() => {
+                        section.albums.push(album);
+                    }
+                    _ => sections.push(Section { title: album.artist.clone(), albums: vec![album] }),
+                }
+            }
+            self.grid.emit(AlbumGridMsg::ReplaceSections(sections));
+        } else {
+            self.grid.emit(AlbumGridMsg::Replace(albums));
+        }
+    }
+}
+
+impl Drop for LibraryPage {
+    /// The next account starts without this one's library.
+    fn drop(&mut self) {
+        OWNED.with(|owned| owned.borrow_mut().clear());
     }
 }
 
@@ -158,7 +469,7 @@ pub fn build_toolbar(sender: &relm4::Sender<LibraryMsg>, ui_state: &crate::stora
     toolbar.add_css_class("compact-toolbar");
 
     let entry = gtk4::SearchEntry::new();
-    entry.set_placeholder_text(Some("Filter library..."));
+    entry.set_placeholder_text(Some(&gettext("Filter library...")));
     entry.set_hexpand(true);
     if let Some(ref q) = ui_state.library_query {
         entry.set_text(q);
@@ -167,31 +478,75 @@ pub fn build_toolbar(sender: &relm4::Sender<LibraryMsg>, ui_state: &crate::stora
     entry.connect_search_changed(move |e| {
         s.emit(LibraryMsg::SetQuery(e.text().to_string()));
     });
+    let s = sender.clone();
+    crate::album_grid::connect_focus_results(&entry, move || s.emit(LibraryMsg::FocusResults));
     toolbar.append(&entry);
 
     let sort_group = gtk4::Box::new(gtk4::Orientation::Horizontal, 0);
     sort_group.add_css_class("linked");
+    sort_group.set_widget_name("library-sort");
 
     let saved_sort = ui_state.library_sort.unwrap_or_default();
 
     let date_btn = gtk4::ToggleButton::new();
     date_btn.set_icon_name("document-open-recent-symbolic");
-    date_btn.set_tooltip_text(Some("Sort by date"));
-    date_btn.set_active(saved_sort != Sort::Name);
+    date_btn.set_tooltip_text(Some(&gettext("Sort by date")));
+    date_btn.set_active(saved_sort == Sort::Date);
     let s = sender.clone();
     date_btn.connect_clicked(move |_| { s.emit(LibraryMsg::SetSort(Sort::Date)); });
     sort_group.append(&date_btn);
 
     let name_btn = gtk4::ToggleButton::new();
     name_btn.set_icon_name("view-sort-ascending-rtl-symbolic");
-    name_btn.set_tooltip_text(Some("Sort by name"));
+    name_btn.set_tooltip_text(Some(&gettext("Sort by name")));
     name_btn.set_group(Some(&date_btn));
     name_btn.set_active(saved_sort == Sort::Name);
     let s = sender.clone();
     name_btn.connect_clicked(move |_| { s.emit(LibraryMsg::SetSort(Sort::Name)); });
     sort_group.append(&name_btn);
 
+    let artist_btn = gtk4::ToggleButton::new();
+    artist_btn.set_icon_name("system-users-symbolic");
+    artist_btn.set_tooltip_text(Some(&gettext("Group by artist")));
+    artist_btn.set_group(Some(&date_btn));
+    artist_btn.set_active(saved_sort == Sort::Artist);
+    let s = sender.clone();
+    artist_btn.connect_clicked(move |_| { s.emit(LibraryMsg::SetSort(Sort::Artist)); });
+    sort_group.append(&artist_btn);
+
+    let plays_btn = gtk4::ToggleButton::new();
+    plays_btn.set_icon_name("media-playlist-repeat-symbolic");
+    plays_btn.set_tooltip_text(Some(&gettext("Sort by most played")));
+    plays_btn.set_group(Some(&date_btn));
+    plays_btn.set_active(saved_sort == Sort::Plays);
+    let s = sender.clone();
+    plays_btn.connect_clicked(move |_| { s.emit(LibraryMsg::SetSort(Sort::Plays)); });
+    sort_group.append(&plays_btn);
+
+    let rating_btn = gtk4::ToggleButton::new();
+    rating_btn.set_icon_name("starred-symbolic");
+    rating_btn.set_tooltip_text(Some(&gettext("Sort by rating")));
+    rating_btn.set_group(Some(&date_btn));
+    rating_btn.set_active(saved_sort == Sort::Rating);
+    let s = sender.clone();
+    rating_btn.connect_clicked(move |_| { s.emit(LibraryMsg::SetSort(Sort::Rating)); });
+    sort_group.append(&rating_btn);
+
     toolbar.append(&sort_group);
 
+    let choices: Vec<String> = std::iter::once(gettext("Any Rating"))
+        .chain((1..=crate::ratings::MAX).map(|stars| gettext("{} and Up").replace("{}", &crate::ratings::stars(stars))))
+        .collect();
+    let choices: Vec<&str> = choices.iter().map(String::as_str).collect();
+    let rating_filter = gtk4::DropDown::from_strings(&choices);
+    rating_filter.set_widget_name("library-rating");
+    rating_filter.set_tooltip_text(Some(&gettext("Filter by rating")));
+    rating_filter.set_selected(ui_state.library_rating.unwrap_or(0).min(crate::ratings::MAX) as u32);
+    let s = sender.clone();
+    rating_filter.connect_selected_notify(move |d| {
+        s.emit(LibraryMsg::SetMinRating(d.selected() as u8));
+    });
+    toolbar.append(&rating_filter);
+
     toolbar
 }
@@ -0,0 +1,84 @@
+use crate::album_grid::AlbumData;
+use crate::storage::{self, LaterAlbum, ListenLater};
+use std::cell::RefCell;
+
+thread_local! {
+    /// The signed-in account's list, as last read or written.
+    static LIST: RefCell<ListenLater> = RefCell::default();
+}
+
+/// The release page a track or release URL belongs to.
+fn release(url: &str) -> Option<String> {
+    crate::bandcamp::parse_release_link(url).map(|link| link.url)
+}
+
+impl From<&LaterAlbum> for AlbumData {
+    fn from(album: &LaterAlbum) -> Self {
+        Self {
+            title: album.title.clone(),
+            artist: album.artist.clone(),
+            genre: crate::genres::get(&album.url),
+            art_url: album.art_url.clone(),
+            url: album.url.clone(),
+            band_id: album.band_id,
+            item_id: album.item_id,
+            item_type: album.item_type.clone(),
+            download_url: None,
+        }
+    }
+}
+
+/// Reads the list of the account just signed in, or forgets it with
+/// nobody signed in.
+pub fn reload(signed_in: bool) {
+    let list = if signed_in { storage::load_listen_later() } else { ListenLater::default() };
+    LIST.with(|l| l.replace(list));
+}
+
+/// Whether the release at `url` is on the list.
+pub fn contains(url: &str) -> bool {
+    let Some(url) = release(url) else { return false };
+    LIST.with(|l| l.borrow().albums.iter().any(|album| album.url == url))
+}
+
+/// The releases on the list, most recently added first.
+pub fn albums() -> Vec<AlbumData> {
+    LIST.with(|l| l.borrow().albums.iter().map(AlbumData::from).collect())
+}
+
+/// Puts the release of `data` on the list, or takes it off, and saves
+/// right away. Releases already on it keep their place.
+pub fn set(data: &AlbumData, listed: bool) -> anyhow::Result<()> {
+    let Some(url) = release(&data.url) else { return Ok(()) };
+    let list = LIST.with(|l| {
+        let mut list = l.borrow_mut();
+        let position = list.albums.iter().position(|album| album.url == url);
+        match (position, listed) {
+            (None, true) => list.albums.insert(
+                0,
+                LaterAlbum {
+                    title: data.title.clone(),
+                    artist: data.artist.clone(),
+                    url,
+                    art_url: data.art_url.clone(),
+                    band_id: data.band_id,
+                    item_id: data.item_id,
+                    item_type: data.item_type.clone(),
+                    added_at: storage::now(),
+                },
+            ),
+            (Some(i), false) => {
+                list.albums.remove(i);
+            }
+            _ => {}
+        }
+        list.clone()
+    });
+    storage::save_listen_later(&list)
+}
+
+/// Empties the list and saves right away.
+pub fn clear() -> anyhow::Result<()> {
+    LIST.with(|l| l.borrow_mut().albums.clear());
+    storage::save_listen_later(&ListenLater::default())
+}
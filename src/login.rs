@@ -1,5 +1,8 @@
+use crate::bandcamp::BandcampClient;
+use gettextrs::gettext;
 use gtk4::prelude::*;
 use libadwaita as adw;
+use libadwaita::prelude::*;
 use relm4::prelude::*;
 use webkit6::prelude::*;
 use webkit6::WebView;
@@ -9,12 +12,25 @@ const BASE_URL: &str = "https://bandcamp.com";
 
 pub struct LoginPage {
     webview: WebView,
+    /// Switches between the browser, the signing-in page and the error page.
+    stack: gtk4::Stack,
+    error_page: adw::StatusPage,
+    /// The cookies being validated, kept for retrying.
+    cookies: Option<String>,
 }
 
 #[derive(Debug)]
 pub enum LoginMsg {
     UrlChanged,
     PageLoaded,
+    PasteCookies,
+    /// Forget the signed-in session and return to the login form.
+    ClearData,
+    /// Cookies were handed to the app and are being validated.
+    SigningIn(String),
+    Failed(String),
+    Retry,
+    ShowBrowser,
 }
 
 #[derive(Debug, Clone)]
@@ -24,7 +40,9 @@ pub enum LoginOutput {
 
 #[relm4::component(pub)]
 impl SimpleComponent for LoginPage {
-    type Init = ();
+    /// Whether to use an ephemeral network session that keeps no cookies
+    /// or storage on disk.
+    type Init = bool;
     type Input = LoginMsg;
     type Output = LoginOutput;
 
@@ -33,22 +51,28 @@ impl SimpleComponent for LoginPage {
             add_top_bar = &adw::HeaderBar {
                 #[wrap(Some)]
                 set_title_widget = &adw::WindowTitle {
-                    set_title: "Login to Bandcamp",
-                    set_subtitle: "Sign in to access your collection",
+                    set_title: &gettext("Login to Bandcamp"),
+                    set_subtitle: &gettext("Sign in to access your collection"),
                 },
                 
                 pack_start = &gtk4::Button {
                     set_icon_name: "go-home-symbolic",
-                    set_tooltip_text: Some("Back to login"),
+                    set_tooltip_text: Some(&gettext("Back to login")),
                     connect_clicked[webview] => move |_| {
                         webview.load_uri(LOGIN_URL);
                     },
                 },
+
+                pack_end = &gtk4::Button {
+                    set_icon_name: "edit-paste-symbolic",
+                    set_tooltip_text: Some(&gettext("Sign In with Cookies")),
+                    connect_clicked => LoginMsg::PasteCookies,
+                },
             },
 
             #[wrap(Some)]
             #[local_ref]
-            set_content = webview_ref -> WebView {
+            set_content = stack_ref -> gtk4::Stack {
                 set_vexpand: true,
                 set_hexpand: true,
             },
@@ -56,11 +80,17 @@ impl SimpleComponent for LoginPage {
     }
 
     fn init(
-        _: Self::Init,
+        ephemeral: Self::Init,
         root: Self::Root,
         sender: ComponentSender<Self>,
     ) -> ComponentParts<Self> {
-        let webview = WebView::new();
+        let webview = if ephemeral {
+            WebView::builder()
+                .network_session(&webkit6::NetworkSession::new_ephemeral())
+                .build()
+        } else {
+            WebView::new()
+        };
         
         if let Some(settings) = webkit6::prelude::WebViewExt::settings(&webview) {
             settings.set_javascript_can_access_clipboard(true);
@@ -80,8 +110,42 @@ impl SimpleComponent for LoginPage {
             }
         });
 
-        let model = LoginPage { webview: webview.clone() };
-        let webview_ref = &model.webview;
+        let progress_page = adw::StatusPage::new();
+        progress_page.set_paintable(Some(&adw::SpinnerPaintable::new(Some(&progress_page))));
+        progress_page.set_title(&gettext("Signing In…"));
+        progress_page.set_description(Some(&gettext("Checking your Bandcamp session")));
+
+        let error_page = adw::StatusPage::new();
+        error_page.set_icon_name(Some("dialog-error-symbolic"));
+        error_page.set_title(&gettext("Sign In Failed"));
+        let buttons = gtk4::Box::new(gtk4::Orientation::Horizontal, 12);
+        buttons.set_halign(gtk4::Align::Center);
+        let back_btn = gtk4::Button::with_label(&gettext("Back to Login"));
+        back_btn.add_css_class("pill");
+        let s = sender.clone();
+        back_btn.connect_clicked(move |_| s.input(LoginMsg::ShowBrowser));
+        let retry_btn = gtk4::Button::with_label(&gettext("Try Again"));
+        retry_btn.add_css_class("pill");
+        retry_btn.add_css_class("suggested-action");
+        let s = sender.clone();
+        retry_btn.connect_clicked(move |_| s.input(LoginMsg::Retry));
+        buttons.append(&back_btn);
+        buttons.append(&retry_btn);
+        error_page.set_child(Some(&buttons));
+
+        let stack = gtk4::Stack::new();
+        crate::a11y::bind_stack_transition(&stack);
+        stack.add_named(&webview, Some("browser"));
+        stack.add_named(&progress_page, Some("progress"));
+        stack.add_named(&error_page, Some("error"));
+
+        let model = LoginPage {
+            webview: webview.clone(),
+            stack,
+            error_page,
+            cookies: None,
+        };
+        let stack_ref = &model.stack;
         let widgets = view_output!();
 
         ComponentParts { model, widgets }
@@ -89,7 +153,58 @@ impl SimpleComponent for LoginPage {
 
     fn update(&mut self, msg: Self::Input, sender: ComponentSender<Self>) {
         match msg {
+            LoginMsg::PasteCookies => {
+                build_cookie_dialog(sender).present(Some(&self.webview));
+            }
+            LoginMsg::SigningIn(cookies) => {
+                self.cookies = Some(cookies);
+                self.stack.set_visible_child_name("progress");
+            }
+            LoginMsg::Failed(error) => {
+                self.error_page.set_description(Some(&error));
+                self.stack.set_visible_child_name("error");
+            }
+            LoginMsg::Retry => {
+                if let Some(cookies) = self.cookies.clone() {
+                    sender.output(LoginOutput::Success(cookies)).ok();
+                } else {
+                    sender.input(LoginMsg::ShowBrowser);
+                }
+            }
+            LoginMsg::ShowBrowser => {
+                self.cookies = None;
+                self.stack.set_visible_child_name("browser");
+                self.webview.load_uri(LOGIN_URL);
+            }
+            LoginMsg::ClearData => {
+                self.cookies = None;
+                self.stack.set_visible_child_name("browser");
+                let Some(manager) = self
+                    .webview
+                    .network_session()
+                    .and_then(|session| session.website_data_manager())
+                else {
+                    return;
+                };
+                let webview = self.webview.clone();
+                manager.clear(
+                    webkit6::WebsiteDataTypes::ALL,
+                    gtk4::glib::TimeSpan::from_seconds(0),
+                    None::<&gtk4::gio::Cancellable>,
+                    move |result| {
+                        if let Err(e) = result {
+                            crate::diagnostics::error("login", format!("Failed to clear website data: {e}"));
+                        }
+                        webview.load_uri(LOGIN_URL);
+                    },
+                );
+            }
             LoginMsg::UrlChanged | LoginMsg::PageLoaded => {
+                // Already handed off; further navigation would only
+                // resubmit the same session.
+                if self.stack.visible_child_name().as_deref() != Some("browser") {
+                    return;
+                }
                 let uri = self.webview.uri().map(|u| u.to_string()).unwrap_or_default();
                 
                 if uri.starts_with(BASE_URL) && !uri.contains("/login") && !uri.contains("/signup") {
@@ -136,3 +251,110 @@ impl LoginPage {
         });
     }
 }
+
+/// Fallback for when the embedded browser can't sign in: the `Cookie`
+/// header of any logged-in bandcamp.com request, as copied from a
+/// browser's developer tools. It is only accepted once Bandcamp confirms it.
+fn build_cookie_dialog(sender: ComponentSender<LoginPage>) -> adw::Dialog {
+    let dialog = adw::Dialog::new();
+    dialog.set_title(&gettext("Sign In with Cookies"));
+    dialog.set_content_width(420);
+
+    let content = gtk4::Box::new(gtk4::Orientation::Vertical, 12);
+    content.set_margin_start(18);
+    content.set_margin_end(18);
+    content.set_margin_top(6);
+    content.set_margin_bottom(18);
+
+    let hint = gtk4::Label::new(Some(&gettext(
+        "Copy the Cookie header of a request to bandcamp.com from your browser's developer tools and paste it here.",
+    )));
+    hint.set_wrap(true);
+    hint.set_xalign(0.0);
+    content.append(&hint);
+
+    let entry = gtk4::PasswordEntry::new();
+    entry.set_show_peek_icon(true);
+    entry.set_placeholder_text(Some("identity=…; js_logged_in=1"));
+    content.append(&entry);
+
+    let error_label = gtk4::Label::new(None);
+    error_label.add_css_class("error");
+    error_label.set_wrap(true);
+    error_label.set_xalign(0.0);
+    error_label.set_visible(false);
+    content.append(&error_label);
+
+    let spinner = adw::Spinner::new();
+    spinner.set_visible(false);
+    let sign_in_btn = gtk4::Button::with_label(&gettext("Sign In"));
+    sign_in_btn.add_css_class("suggested-action");
+    sign_in_btn.add_css_class("pill");
+    sign_in_btn.set_halign(gtk4::Align::Center);
+    let button_box = gtk4::Box::new(gtk4::Orientation::Horizontal, 12);
+    button_box.set_halign(gtk4::Align::Center);
+    button_box.append(&spinner);
+    button_box.append(&sign_in_btn);
+    content.append(&button_box);
+
+    let header = adw::HeaderBar::new();
+    let toolbar_view = adw::ToolbarView::new();
+    toolbar_view.add_top_bar(&header);
+    toolbar_view.set_content(Some(&content));
+    dialog.set_child(Some(&toolbar_view));
+
+    let submit = {
+        let dialog = dialog.downgrade();
+        let entry = entry.clone();
+        let error_label = error_label.clone();
+        let spinner = spinner.clone();
+        let sign_in_btn = sign_in_btn.clone();
+        move || {
+            let text = entry.text();
+            let cookies = text.trim();
+            let cookies = cookies.strip_prefix("Cookie:").unwrap_or(cookies).trim().to_string();
+            if !cookies.contains("identity=") {
+                error_label.set_label(&gettext("No identity cookie found. Make sure you are signed in and copied the whole header."));
+                error_label.set_visible(true);
+                return;
+            }
+
+            error_label.set_visible(false);
+            sign_in_btn.set_sensitive(false);
+            spinner.set_visible(true);
+
+            let dialog = dialog.clone();
+            let error_label = error_label.clone();
+            let spinner = spinner.clone();
+            let sign_in_btn = sign_in_btn.clone();
+            let sender = sender.clone();
+            gtk4::glib::spawn_future_local(async move {
+                let check = cookies.clone();
+                let result = relm4::spawn(async move { BandcampClient::new(check).await.map(|_| ()) }).await;
+                spinner.set_visible(false);
+                sign_in_btn.set_sensitive(true);
+                match result {
+                    Ok(Ok(())) => {
+                        sender.output(LoginOutput::Success(cookies)).ok();
+                        if let Some(dialog) = dialog.upgrade() {
+                            dialog.close();
+                        }
+                    }
+                    Ok(Err(e)) => {
+                        error_label.set_label(&gettext("Bandcamp rejected these cookies: {}").replace("{}", &e.to_string()));
+                        error_label.set_visible(true);
+                    }
+                    Err(e) => {
+                        error_label.set_label(&e.to_string());
+                        error_label.set_visible(true);
+                    }
+                }
+            });
+        }
+    };
+    let on_activate = submit.clone();
+    entry.connect_activate(move |_| on_activate());
+    sign_in_btn.connect_clicked(move |_| submit());
+
+    dialog
+}
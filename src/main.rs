@@ -1,21 +1,108 @@
 #![allow(unused_assignments)]
 
+mod a11y;
+mod activity;
+mod album_card;
 mod album_grid;
 mod app;
+mod art_cache;
+mod art_viewer;
+mod artist;
+mod audio_cache;
 mod bandcamp;
+mod calendar;
+mod cli;
+mod data_saver;
+mod dbus;
+mod diagnostics;
 mod discover;
+mod downloads;
+mod duplicates;
+mod exchange;
+mod genres;
+mod image_loader;
 mod library;
+mod listen_later;
 mod login;
+mod merch;
+mod mini_player;
+mod notes;
+mod now_playing;
+mod overflow;
+mod playback;
 mod player;
+mod plays;
+mod portal;
+mod preferences;
+mod purchases;
+mod ratings;
+mod recap;
+mod remote;
 mod search;
+mod skips;
 mod storage;
+mod tracklist;
+mod waveform;
 
-use app::App;
+use app::{App, AppMsg};
+use gtk4::{gio, glib};
+use gtk4::prelude::*;
 use relm4::prelude::*;
+use relm4::MessageBroker;
+
+const GETTEXT_PACKAGE: &str = "camper";
+/// Overridden at build time by packagers installing translations elsewhere.
+const LOCALEDIR: &str = match option_env!("LOCALEDIR") {
+    Some(dir) => dir,
+    None => "/usr/share/locale",
+};
+
+/// Marks a string for the translation template where it can't be
+/// translated yet, such as in a table of constants; it goes through
+/// `gettext` where it is shown.
+#[allow(non_snake_case)]
+pub const fn N_(msgid: &str) -> &str {
+    msgid
+}
+
+static APP_BROKER: MessageBroker<AppMsg> = MessageBroker::new();
 
 fn main() {
+    gettextrs::setlocale(gettextrs::LocaleCategory::LcAll, "");
+    gettextrs::bindtextdomain(GETTEXT_PACKAGE, LOCALEDIR).ok();
+    gettextrs::bind_textdomain_codeset(GETTEXT_PACKAGE, "UTF-8").ok();
+    gettextrs::textdomain(GETTEXT_PACKAGE).ok();
+
     relm4::RELM_THREADS.set(4).ok();
-    let app = RelmApp::new("net.knoopx.camper");
+    let app = RelmApp::new("net.knoopx.camper")
+        .with_broker(&APP_BROKER)
+        .with_args(std::env::args().collect());
+
+    // Launching again forwards the command line to the running instance
+    // instead of opening a second window: URLs to play, or control flags
+    // such as `--next`.
+    let gtk_app = relm4::main_application();
+    gtk_app.set_flags(gio::ApplicationFlags::HANDLES_COMMAND_LINE);
+    cli::add_options(&gtk_app);
+    gtk_app.connect_handle_local_options(cli::handle_local_options);
+    gtk_app.connect_command_line(cli::command_line);
+    gtk_app.connect_startup(|app| dbus::register(app));
+    gtk_app.connect_activate(|app| {
+        if let Some(window) = app.active_window() {
+            window.present();
+        }
+    });
+
+    // `gapplication action net.knoopx.camper open-url "'https://…'"`
+    let open_url = gio::SimpleAction::new("open-url", Some(glib::VariantTy::STRING));
+    open_url.connect_activate(|_, param| {
+        if let Some(url) = param.and_then(|p| p.get::<String>()) {
+            relm4::main_application().activate();
+            APP_BROKER.send(AppMsg::OpenUrl(url));
+        }
+    });
+    gtk_app.add_action(&open_url);
+
     gtk4::Window::set_default_icon_name("camper");
     app.run::<App>(());
 }
@@ -0,0 +1,81 @@
+use crate::bandcamp::Merch;
+use gettextrs::gettext;
+use gtk4::prelude::*;
+
+/// Edge length of an item's photo.
+const THUMB_SIZE: i32 = 40;
+
+/// What is left of an item, e.g. "12 left" or "Sold out"; nothing when
+/// Bandcamp doesn't say.
+fn stock(item: &Merch) -> Option<String> {
+    match item.remaining? {
+        0 => Some(gettext("Sold out")),
+        n => Some(gettext("{} left").replace("{}", &n.to_string())),
+    }
+}
+
+/// The line under an item's title: what it is, its price and stock.
+fn details(item: &Merch) -> String {
+    let mut parts: Vec<String> = item.kind.iter().cloned().collect();
+    parts.extend(item.price.as_ref().map(crate::exchange::display));
+    parts.extend(stock(item));
+    parts.join(" · ")
+}
+
+/// A button for an item, handing its page to `open` when clicked.
+fn button(item: &Merch, open: impl Fn(String) + 'static) -> gtk4::Button {
+    let title = gtk4::Label::new(Some(&item.title));
+    title.add_css_class("caption-heading");
+    title.set_xalign(0.0);
+    title.set_ellipsize(gtk4::pango::EllipsizeMode::End);
+    title.set_max_width_chars(24);
+
+    let subtitle = gtk4::Label::new(Some(&details(item)));
+    subtitle.add_css_class("caption");
+    subtitle.add_css_class("dim-label");
+    subtitle.set_xalign(0.0);
+    subtitle.set_ellipsize(gtk4::pango::EllipsizeMode::End);
+    subtitle.set_max_width_chars(24);
+
+    let labels = gtk4::Box::new(gtk4::Orientation::Vertical, 0);
+    labels.set_valign(gtk4::Align::Center);
+    labels.append(&title);
+    labels.append(&subtitle);
+
+    let thumb = gtk4::Image::from_icon_name("package-x-generic-symbolic");
+    thumb.set_pixel_size(THUMB_SIZE);
+    thumb.add_css_class("merch-thumb");
+    if let Some(url) = item.art_url.as_deref().map(crate::data_saver::art_url) {
+        let (thumb, size) = (thumb.clone(), THUMB_SIZE * thumb.scale_factor());
+        gtk4::glib::spawn_future_local(async move {
+            if let Some(texture) = crate::image_loader::load(&url, size).await {
+                thumb.set_paintable(Some(&texture));
+            }
+        });
+    }
+
+    let content = gtk4::Box::new(gtk4::Orientation::Horizontal, 8);
+    content.append(&thumb);
+    content.append(&labels);
+
+    let button = gtk4::Button::new();
+    button.set_child(Some(&content));
+    button.add_css_class("merch-item");
+    button.add_css_class("flat");
+    if item.sold_out() {
+        button.add_css_class("sold-out");
+    }
+    button.set_tooltip_text(Some(&gettext("Buy “{}” on Bandcamp").replace("{}", &item.title)));
+    let url = item.url.clone();
+    button.connect_clicked(move |_| open(url.clone()));
+    button
+}
+
+/// Fills `flow` with a button per item, hiding it when there are none.
+pub fn show(flow: &gtk4::FlowBox, merch: &[Merch], open: impl Fn(String) + Clone + 'static) {
+    flow.remove_all();
+    for item in merch {
+        flow.append(&button(item, open.clone()));
+    }
+    flow.set_visible(!merch.is_empty());
+}
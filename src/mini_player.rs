@@ -0,0 +1,158 @@
+use crate::player::PlaybackSnapshot;
+use gettextrs::gettext;
+use gtk4::prelude::*;
+use libadwaita as adw;
+use relm4::prelude::*;
+
+/// Compact window with art, title and transport controls, shown in place of
+/// the main window. GTK 4 leaves stacking to the compositor, so keeping it
+/// above other windows is up to the window manager.
+pub struct MiniPlayer {
+    visible: bool,
+    state: PlaybackSnapshot,
+    art: Option<gtk4::gdk::Texture>,
+}
+
+#[derive(Debug)]
+pub enum MiniPlayerMsg {
+    SetVisible(bool),
+    Update(PlaybackSnapshot),
+    SetArt(gtk4::gdk::Texture),
+}
+
+#[derive(Debug)]
+pub enum MiniPlayerOutput {
+    Toggle,
+    Next,
+    Prev,
+    Restore,
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for MiniPlayer {
+    type Init = ();
+    type Input = MiniPlayerMsg;
+    type Output = MiniPlayerOutput;
+
+    view! {
+        adw::Window {
+            set_title: Some(&gettext("Camper")),
+            set_default_width: 360,
+            set_resizable: false,
+            set_hide_on_close: true,
+            #[watch]
+            set_visible: model.visible,
+            connect_close_request[sender] => move |_| {
+                sender.output(MiniPlayerOutput::Restore).ok();
+                gtk4::glib::Propagation::Proceed
+            },
+
+            gtk4::WindowHandle {
+                gtk4::Box {
+                    set_orientation: gtk4::Orientation::Horizontal,
+                    set_spacing: 8,
+                    set_margin_start: 8,
+                    set_margin_end: 8,
+                    set_margin_top: 8,
+                    set_margin_bottom: 8,
+
+                    gtk4::Frame {
+                        add_css_class: "album-art",
+                        set_valign: gtk4::Align::Center,
+
+                        gtk4::Image {
+                            set_pixel_size: 48,
+                            #[watch]
+                            set_paintable: model.art.as_ref(),
+                        },
+                    },
+
+                    gtk4::Box {
+                        set_orientation: gtk4::Orientation::Vertical,
+                        set_valign: gtk4::Align::Center,
+                        set_hexpand: true,
+
+                        gtk4::Label {
+                            set_xalign: 0.0,
+                            set_ellipsize: gtk4::pango::EllipsizeMode::End,
+                            add_css_class: "album-title",
+                            add_css_class: "caption",
+                            #[watch]
+                            set_label: &model.state.title,
+                        },
+
+                        gtk4::Label {
+                            set_xalign: 0.0,
+                            set_ellipsize: gtk4::pango::EllipsizeMode::End,
+                            add_css_class: "dim-label",
+                            add_css_class: "caption",
+                            #[watch]
+                            set_label: &model.state.artist,
+                        },
+                    },
+
+                    gtk4::Button {
+                        set_icon_name: "media-skip-backward-symbolic",
+                        add_css_class: "flat",
+                        set_valign: gtk4::Align::Center,
+                        #[watch]
+                        set_sensitive: model.state.has_prev,
+                        connect_clicked[sender] => move |_| {
+                            sender.output(MiniPlayerOutput::Prev).ok();
+                        },
+                    },
+
+                    gtk4::Button {
+                        #[watch]
+                        set_icon_name: if model.state.playing { "media-playback-pause-symbolic" } else { "media-playback-start-symbolic" },
+                        add_css_class: "circular",
+                        add_css_class: "suggested-action",
+                        set_valign: gtk4::Align::Center,
+                        connect_clicked[sender] => move |_| {
+                            sender.output(MiniPlayerOutput::Toggle).ok();
+                        },
+                    },
+
+                    gtk4::Button {
+                        set_icon_name: "media-skip-forward-symbolic",
+                        add_css_class: "flat",
+                        set_valign: gtk4::Align::Center,
+                        #[watch]
+                        set_sensitive: model.state.has_next,
+                        connect_clicked[sender] => move |_| {
+                            sender.output(MiniPlayerOutput::Next).ok();
+                        },
+                    },
+
+                    gtk4::Button {
+                        set_icon_name: "view-fullscreen-symbolic",
+                        add_css_class: "flat",
+                        set_valign: gtk4::Align::Center,
+                        set_tooltip_text: Some(&gettext("Restore Full Player")),
+                        connect_clicked[sender] => move |_| {
+                            sender.output(MiniPlayerOutput::Restore).ok();
+                        },
+                    },
+                },
+            },
+        }
+    }
+
+    fn init(_: Self::Init, root: Self::Root, sender: ComponentSender<Self>) -> ComponentParts<Self> {
+        let model = Self {
+            visible: false,
+            state: PlaybackSnapshot::default(),
+            art: None,
+        };
+        let widgets = view_output!();
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, msg: Self::Input, _sender: ComponentSender<Self>) {
+        match msg {
+            MiniPlayerMsg::SetVisible(visible) => self.visible = visible,
+            MiniPlayerMsg::Update(state) => self.state = state,
+            MiniPlayerMsg::SetArt(texture) => self.art = Some(texture),
+        }
+    }
+}
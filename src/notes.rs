@@ -0,0 +1,88 @@
+use crate::storage::{self, Notes};
+use gettextrs::gettext;
+use gtk4::prelude::*;
+use libadwaita as adw;
+use libadwaita::prelude::*;
+use std::cell::RefCell;
+
+thread_local! {
+    /// The signed-in account's notes, as last read or written.
+    static NOTES: RefCell<Notes> = RefCell::default();
+}
+
+/// The release page a track or release URL belongs to.
+fn release(url: &str) -> Option<String> {
+    crate::bandcamp::parse_release_link(url).map(|link| link.url)
+}
+
+/// Reads the notes of the account just signed in, or forgets them with
+/// nobody signed in.
+pub fn reload(signed_in: bool) {
+    let notes = if signed_in { storage::load_notes() } else { Notes::default() };
+    NOTES.with(|n| n.replace(notes));
+}
+
+/// The note on the release at `url`, if any.
+pub fn get(url: &str) -> Option<String> {
+    let url = release(url)?;
+    NOTES.with(|n| n.borrow().albums.get(&url).cloned())
+}
+
+/// Sets the note on the release at `url`, dropping it when blank, and
+/// saves right away.
+pub fn set(url: &str, note: &str) -> anyhow::Result<()> {
+    let Some(url) = release(url) else { return Ok(()) };
+    let notes = NOTES.with(|n| {
+        let mut notes = n.borrow_mut();
+        match note.trim() {
+            "" => notes.albums.remove(&url),
+            note => notes.albums.insert(url, note.to_string()),
+        };
+        notes.clone()
+    });
+    storage::save_notes(&notes)
+}
+
+/// Edits the note on the release titled `title`, handing the text to
+/// `save` when the Save button is pressed.
+pub fn build_dialog(title: &str, note: Option<String>, save: impl Fn(String) + 'static) -> adw::Dialog {
+    let dialog = adw::Dialog::new();
+    dialog.set_title(&gettext("Note on “{}”").replace("{}", title));
+    dialog.set_content_width(420);
+    dialog.set_content_height(280);
+
+    let buffer = gtk4::TextBuffer::new(None);
+    buffer.set_text(note.as_deref().unwrap_or_default());
+
+    let text_view = gtk4::TextView::with_buffer(&buffer);
+    text_view.set_wrap_mode(gtk4::WrapMode::WordChar);
+    text_view.set_top_margin(12);
+    text_view.set_bottom_margin(12);
+    text_view.set_left_margin(12);
+    text_view.set_right_margin(12);
+    text_view.update_property(&[gtk4::accessible::Property::Label(&gettext("Note"))]);
+
+    let scrolled = gtk4::ScrolledWindow::new();
+    scrolled.set_vexpand(true);
+    scrolled.set_child(Some(&text_view));
+
+    let save_btn = gtk4::Button::with_label(&gettext("Save"));
+    save_btn.add_css_class("suggested-action");
+    let weak = dialog.downgrade();
+    save_btn.connect_clicked(move |_| {
+        let (start, end) = buffer.bounds();
+        save(buffer.text(&start, &end, false).to_string());
+        if let Some(dialog) = weak.upgrade() {
+            dialog.close();
+        }
+    });
+
+    let header = adw::HeaderBar::new();
+    header.pack_end(&save_btn);
+    let toolbar_view = adw::ToolbarView::new();
+    toolbar_view.add_top_bar(&header);
+    toolbar_view.set_content(Some(&scrolled));
+    dialog.set_child(Some(&toolbar_view));
+    dialog.set_focus(Some(&text_view));
+    dialog
+}
@@ -0,0 +1,195 @@
+use crate::player::{format_time, PlaybackSnapshot};
+use gettextrs::gettext;
+use gtk4::prelude::*;
+use libadwaita as adw;
+use relm4::prelude::*;
+
+/// Expanded now-playing view shown in the bottom sheet on narrow windows.
+pub struct NowPlaying {
+    state: PlaybackSnapshot,
+    art: Option<gtk4::gdk::Texture>,
+}
+
+#[derive(Debug)]
+pub enum NowPlayingMsg {
+    Update(PlaybackSnapshot),
+    SetArt(gtk4::gdk::Texture),
+}
+
+#[derive(Debug)]
+pub enum NowPlayingOutput {
+    Toggle,
+    Next,
+    Prev,
+    Seek(f64),
+    Collapse,
+    ShowArtwork,
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for NowPlaying {
+    type Init = ();
+    type Input = NowPlayingMsg;
+    type Output = NowPlayingOutput;
+
+    view! {
+        adw::ToolbarView {
+            add_top_bar = &adw::HeaderBar {
+                set_show_title: false,
+                set_show_end_title_buttons: false,
+
+                pack_start = &gtk4::Button {
+                    set_icon_name: "go-down-symbolic",
+                    add_css_class: "flat",
+                    set_tooltip_text: Some(&gettext("Collapse")),
+                    connect_clicked[sender] => move |_| {
+                        sender.output(NowPlayingOutput::Collapse).ok();
+                    },
+                },
+            },
+
+            #[wrap(Some)]
+            set_content = &adw::Clamp {
+                set_maximum_size: 360,
+
+                gtk4::Box {
+                    set_orientation: gtk4::Orientation::Vertical,
+                    set_spacing: 12,
+                    set_margin_start: 24,
+                    set_margin_end: 24,
+                    set_margin_bottom: 24,
+                    set_valign: gtk4::Align::Center,
+
+                    gtk4::Frame {
+                        add_css_class: "album-art",
+                        set_halign: gtk4::Align::Center,
+                        set_cursor_from_name: Some("pointer"),
+                        set_tooltip_text: Some(&gettext("View Artwork")),
+                        add_controller = gtk4::GestureClick {
+                            connect_released[sender] => move |_, _, _, _| {
+                                sender.output(NowPlayingOutput::ShowArtwork).ok();
+                            },
+                        },
+
+                        gtk4::Image {
+                            set_pixel_size: 280,
+                            #[watch]
+                            set_paintable: model.art.as_ref(),
+                        },
+                    },
+
+                    gtk4::Label {
+                        set_wrap: true,
+                        set_justify: gtk4::Justification::Center,
+                        add_css_class: "title-3",
+                        #[watch]
+                        set_label: &model.state.title,
+                    },
+
+                    gtk4::Label {
+                        set_wrap: true,
+                        set_justify: gtk4::Justification::Center,
+                        add_css_class: "dim-label",
+                        #[watch]
+                        set_label: &if model.state.album.is_empty() || model.state.album == model.state.artist {
+                            model.state.artist.clone()
+                        } else {
+                            format!("{} — {}", model.state.artist, model.state.album)
+                        },
+                    },
+
+                    gtk4::Scale {
+                        set_orientation: gtk4::Orientation::Horizontal,
+                        set_range: (0.0, 1.0),
+                        set_draw_value: false,
+                        #[watch]
+                        set_value: if model.state.duration > 0.0 {
+                            model.state.position / model.state.duration
+                        } else {
+                            0.0
+                        },
+                        connect_change_value[sender] => move |_, _, value| {
+                            sender.output(NowPlayingOutput::Seek(value.clamp(0.0, 1.0))).ok();
+                            gtk4::glib::Propagation::Proceed
+                        },
+                    },
+
+                    gtk4::CenterBox {
+                        #[wrap(Some)]
+                        set_start_widget = &gtk4::Label {
+                            add_css_class: "caption",
+                            add_css_class: "numeric",
+                            #[watch]
+                            set_label: &format_time(model.state.position),
+                        },
+                        #[wrap(Some)]
+                        set_end_widget = &gtk4::Label {
+                            add_css_class: "caption",
+                            add_css_class: "numeric",
+                            #[watch]
+                            set_label: &format_time(model.state.duration),
+                        },
+                    },
+
+                    gtk4::Box {
+                        set_orientation: gtk4::Orientation::Horizontal,
+                        set_spacing: 24,
+                        set_halign: gtk4::Align::Center,
+
+                        gtk4::Button {
+                            set_icon_name: "media-skip-backward-symbolic",
+                            add_css_class: "flat",
+                            add_css_class: "circular",
+                            set_valign: gtk4::Align::Center,
+                            #[watch]
+                            set_sensitive: model.state.has_prev,
+                            connect_clicked[sender] => move |_| {
+                                sender.output(NowPlayingOutput::Prev).ok();
+                            },
+                        },
+
+                        gtk4::Button {
+                            #[watch]
+                            set_icon_name: if model.state.playing { "media-playback-pause-symbolic" } else { "media-playback-start-symbolic" },
+                            add_css_class: "circular",
+                            add_css_class: "suggested-action",
+                            add_css_class: "now-playing-toggle",
+                            set_valign: gtk4::Align::Center,
+                            connect_clicked[sender] => move |_| {
+                                sender.output(NowPlayingOutput::Toggle).ok();
+                            },
+                        },
+
+                        gtk4::Button {
+                            set_icon_name: "media-skip-forward-symbolic",
+                            add_css_class: "flat",
+                            add_css_class: "circular",
+                            set_valign: gtk4::Align::Center,
+                            #[watch]
+                            set_sensitive: model.state.has_next,
+                            connect_clicked[sender] => move |_| {
+                                sender.output(NowPlayingOutput::Next).ok();
+                            },
+                        },
+                    },
+                },
+            },
+        }
+    }
+
+    fn init(_: Self::Init, root: Self::Root, sender: ComponentSender<Self>) -> ComponentParts<Self> {
+        let model = Self {
+            state: PlaybackSnapshot::default(),
+            art: None,
+        };
+        let widgets = view_output!();
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, msg: Self::Input, _sender: ComponentSender<Self>) {
+        match msg {
+            NowPlayingMsg::Update(state) => self.state = state,
+            NowPlayingMsg::SetArt(texture) => self.art = Some(texture),
+        }
+    }
+}
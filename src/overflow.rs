@@ -0,0 +1,98 @@
+use gtk4::glib;
+use gtk4::prelude::*;
+use libadwaita as adw;
+use libadwaita::prelude::*;
+use std::rc::Rc;
+
+/// Controls that move into a menu button while a narrow breakpoint
+/// applies, so small windows keep them within reach. Moves them back, and
+/// stops following the breakpoints, when dropped.
+pub struct Fold {
+    folded: Rc<Folded>,
+    handlers: Vec<(adw::Breakpoint, glib::SignalHandlerId)>,
+}
+
+impl Drop for Fold {
+    fn drop(&mut self) {
+        for (breakpoint, handler) in self.handlers.drain(..) {
+            breakpoint.disconnect(handler);
+        }
+        self.folded.unfold();
+    }
+}
+
+struct Folded {
+    /// The box the controls live in, with the menu button after them.
+    home: gtk4::Box,
+    /// Each control with the sibling it goes back after.
+    controls: Vec<(gtk4::Widget, Option<gtk4::Widget>)>,
+    menu: gtk4::Box,
+    button: gtk4::MenuButton,
+}
+
+impl Folded {
+    fn fold(&self) {
+        if self.button.is_visible() {
+            return;
+        }
+        for (control, _) in &self.controls {
+            self.home.remove(control);
+            self.menu.append(control);
+        }
+        self.button.set_visible(true);
+    }
+
+    fn unfold(&self) {
+        if !self.button.is_visible() {
+            return;
+        }
+        self.button.popdown();
+        self.button.set_visible(false);
+        for (control, after) in &self.controls {
+            self.menu.remove(control);
+            self.home.insert_child_after(control, after.as_ref());
+        }
+    }
+}
+
+/// Folds `controls`, children of one box, into a menu button labelled
+/// `tooltip` while any of `breakpoints` applies to `window`.
+pub fn fold(
+    window: &adw::ApplicationWindow,
+    breakpoints: &[&adw::Breakpoint],
+    controls: &[gtk4::Widget],
+    tooltip: &str,
+) -> Option<Fold> {
+    let home = controls.first()?.parent().and_downcast::<gtk4::Box>()?;
+    let controls: Vec<_> = controls.iter().map(|c| (c.clone(), c.prev_sibling())).collect();
+
+    let menu = gtk4::Box::new(gtk4::Orientation::Vertical, 6);
+    menu.set_margin_start(6);
+    menu.set_margin_end(6);
+    menu.set_margin_top(6);
+    menu.set_margin_bottom(6);
+    let popover = gtk4::Popover::new();
+    popover.set_child(Some(&menu));
+
+    let button = gtk4::MenuButton::new();
+    button.set_icon_name("view-more-symbolic");
+    button.add_css_class("flat");
+    button.set_valign(gtk4::Align::Center);
+    button.set_tooltip_text(Some(tooltip));
+    button.set_popover(Some(&popover));
+    button.set_visible(false);
+    home.insert_child_after(&button, controls.last().map(|(c, _)| c));
+
+    let folded = Rc::new(Folded { home, controls, menu, button });
+    let mut handlers = Vec::new();
+    for breakpoint in breakpoints {
+        let f = folded.clone();
+        handlers.push(((*breakpoint).clone(), breakpoint.connect_apply(move |_| f.fold())));
+        let f = folded.clone();
+        handlers.push(((*breakpoint).clone(), breakpoint.connect_unapply(move |_| f.unfold())));
+    }
+    if window.current_breakpoint().is_some_and(|current| breakpoints.contains(&&current)) {
+        folded.fold();
+    }
+    Some(Fold { folded, handlers })
+}
@@ -0,0 +1,424 @@
+use crate::player::Track;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Longest a track has to play to count as a listen.
+const LISTEN_AFTER: f64 = 240.0;
+/// Tracks shorter than this never count.
+const MIN_LISTEN_LENGTH: f64 = 30.0;
+
+/// What removing a track means for playback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Removed {
+    /// Another track; whatever plays keeps playing.
+    Other,
+    /// The current track; the one after it is current now.
+    Current,
+    /// The current track, with nothing after it. Playback stops.
+    Last,
+}
+
+/// What plays once a track ends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Repeat {
+    /// The next track, and nothing after the last one.
+    #[default]
+    Off,
+    /// The next track, and the queue from the top after the last one.
+    All,
+    /// The same track again.
+    One,
+}
+
+/// The play queue and the rules for moving through it: skipping, shuffling
+/// and when a track counts as listened to. Tracks without a stream stay in
+/// the queue to be bought, but are never made current. Knows nothing about
+/// widgets or the pipeline, so every surface driving playback shares the
+/// same rules.
+#[derive(Debug)]
+pub struct PlaybackController {
+    queue: Vec<Track>,
+    index: usize,
+    /// Upcoming tracks are shuffled, including ones queued later.
+    shuffle: bool,
+    repeat: Repeat,
+    /// Whether the current track already counted as a listen.
+    listened: bool,
+    /// State of the generator shuffling is drawn from.
+    seed: u64,
+}
+
+impl Default for PlaybackController {
+    fn default() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default();
+        Self::with_seed(nanos)
+    }
+}
+
+impl PlaybackController {
+    /// A controller that always shuffles the same way for the same seed.
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            queue: Vec::new(),
+            index: 0,
+            shuffle: false,
+            repeat: Repeat::Off,
+            listened: false,
+            seed,
+        }
+    }
+
+    pub fn queue(&self) -> &[Track] {
+        &self.queue
+    }
+
+    /// Position of the current track in the queue.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn current(&self) -> Option<&Track> {
+        self.queue.get(self.index)
+    }
+
+    pub fn shuffle(&self) -> bool {
+        self.shuffle
+    }
+
+    pub fn has_prev(&self) -> bool {
+        self.playable_before(self.index).is_some()
+    }
+
+    pub fn repeat(&self) -> Repeat {
+        self.repeat
+    }
+
+    pub fn set_repeat(&mut self, repeat: Repeat) {
+        self.repeat = repeat;
+    }
+
+    pub fn has_next(&self) -> bool {
+        self.playable_from(self.index + 1).is_some()
+            || (self.repeat == Repeat::All && self.playable_from(0).is_some())
+    }
+
+    /// Replaces the queue, starting at `index`, or the first streamable
+    /// track after it.
+    pub fn replace(&mut self, tracks: Vec<Track>, index: usize) {
+        self.queue = tracks;
+        self.select(self.playable_from(index).unwrap_or(index));
+    }
+
+    /// Appends to the queue, or starts it over if it is empty. Returns
+    /// whether it started over, and so the current track changed.
+    pub fn enqueue(&mut self, tracks: Vec<Track>) -> bool {
+        if self.queue.is_empty() {
+            self.replace(tracks, 0);
+            return true;
+        }
+        self.queue.extend(tracks);
+        if self.shuffle {
+            self.shuffle_upcoming();
+        }
+        false
+    }
+
+    /// Turning shuffle on shuffles the tracks after the current one;
+    /// turning it off keeps whatever order they are in.
+    pub fn set_shuffle(&mut self, shuffle: bool) {
+        self.shuffle = shuffle;
+        if shuffle {
+            self.shuffle_upcoming();
+        }
+    }
+
+    /// Moves to the next streamable track. Returns whether there was one.
+    pub fn next(&mut self) -> bool {
+        self.next_except(|_| false)
+    }
+
+    /// Moves to the next streamable track, passing over the ones `skip`
+    /// picks, from the top again after the last one when repeating all.
+    /// Returns whether there was one.
+    pub fn next_except(&mut self, skip: impl Fn(&Track) -> bool) -> bool {
+        let wrapped = if self.repeat == Repeat::All { 0..self.index + 1 } else { 0..0 };
+        (self.index + 1..self.queue.len())
+            .chain(wrapped)
+            .find(|&i| self.queue[i].streamable() && !skip(&self.queue[i]))
+            .is_some_and(|index| self.jump(index))
+    }
+
+    /// Moves on once the current track played to its end: to the same
+    /// track when repeating one, otherwise as [`Self::next_except`] does.
+    /// Returns whether anything plays next.
+    pub fn finish(&mut self, skip: impl Fn(&Track) -> bool) -> bool {
+        if self.repeat == Repeat::One && self.current().is_some_and(Track::streamable) {
+            self.select(self.index);
+            return true;
+        }
+        self.next_except(skip)
+    }
+
+    /// Moves to the previous streamable track. Returns whether there was
+    /// one.
+    pub fn prev(&mut self) -> bool {
+        self.playable_before(self.index).is_some_and(|index| self.jump(index))
+    }
+
+    /// Makes the track at `index` current. Returns whether there is one
+    /// that can be streamed.
+    pub fn jump(&mut self, index: usize) -> bool {
+        if !self.queue.get(index).is_some_and(Track::streamable) {
+            return false;
+        }
+        self.select(index);
+        true
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<(Track, Removed)> {
+        if index >= self.queue.len() {
+            return None;
+        }
+        let track = self.queue.remove(index);
+        let removed = if index < self.index {
+            self.index -= 1;
+            Removed::Other
+        } else if index > self.index {
+            Removed::Other
+        } else if let Some(next) = self.playable_from(self.index) {
+            self.select(next);
+            Removed::Current
+        } else {
+            self.select(self.queue.len().saturating_sub(1));
+            Removed::Last
+        };
+        Some((track, removed))
+    }
+
+    /// Puts a removed track back where it was, or at the end if the queue
+    /// got shorter since. Returns where it went.
+    pub fn restore(&mut self, index: usize, track: Track) -> usize {
+        let index = index.min(self.queue.len());
+        if index <= self.index && !self.queue.is_empty() {
+            self.index += 1;
+        }
+        self.queue.insert(index, track);
+        index
+    }
+
+    /// The current track, the first time it played long enough to count as
+    /// a listen: half its length or four minutes, whichever comes first.
+    pub fn listen(&mut self, position: f64, duration: f64) -> Option<Track> {
+        if self.listened || duration <= MIN_LISTEN_LENGTH || position < (duration / 2.0).min(LISTEN_AFTER) {
+            return None;
+        }
+        self.listened = true;
+        self.current().cloned()
+    }
+
+    /// The current track, if moving on now skips it: it hasn't played long
+    /// enough to count as a listen.
+    pub fn skipping(&self) -> Option<&Track> {
+        self.current().filter(|_| !self.listened)
+    }
+
+    /// The first streamable track at or after `index`.
+    fn playable_from(&self, index: usize) -> Option<usize> {
+        (index..self.queue.len()).find(|&i| self.queue[i].streamable())
+    }
+
+    /// The last streamable track before `index`.
+    fn playable_before(&self, index: usize) -> Option<usize> {
+        (0..index.min(self.queue.len())).rev().find(|&i| self.queue[i].streamable())
+    }
+
+    fn select(&mut self, index: usize) {
+        self.index = index;
+        self.listened = false;
+    }
+
+    fn shuffle_upcoming(&mut self) {
+        let start = self.index + 1;
+        for i in (start + 1..self.queue.len()).rev() {
+            let j = start + self.random_below(i - start + 1);
+            self.queue.swap(i, j);
+        }
+    }
+
+    /// A number below `n`, from a linear congruential generator; plenty
+    /// for ordering a queue.
+    fn random_below(&mut self, n: usize) -> usize {
+        self.seed = self
+            .seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((self.seed >> 33) % n as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(n: u32, streamable: bool) -> Track {
+        Track {
+            title: format!("Track {n}"),
+            artist: "Artist".to_string(),
+            album_artist: "Artist".to_string(),
+            album: "Album".to_string(),
+            art_url: None,
+            stream_url: if streamable { format!("https://example.com/stream/{n}") } else { String::new() },
+            duration: Some(180.0),
+            url: format!("https://artist.bandcamp.com/album/album#t{n}"),
+            number: Some(n),
+            disc: None,
+            price: None,
+            tags: Vec::new(),
+            free_download: None,
+            release_id: None,
+            label: None,
+            merch: Vec::new(),
+        }
+    }
+
+    fn controller(count: u32) -> PlaybackController {
+        let mut playback = PlaybackController::with_seed(42);
+        playback.replace((1..=count).map(|n| track(n, true)).collect(), 0);
+        playback
+    }
+
+    fn titles(playback: &PlaybackController) -> Vec<&str> {
+        playback.queue().iter().map(|t| t.title.as_str()).collect()
+    }
+
+    fn current(playback: &PlaybackController) -> &str {
+        playback.current().map(|t| t.title.as_str()).unwrap_or_default()
+    }
+
+    #[test]
+    fn replace_starts_at_the_first_streamable_track() {
+        let mut playback = PlaybackController::with_seed(1);
+        playback.replace(vec![track(1, false), track(2, false), track(3, true)], 0);
+        assert_eq!(playback.index(), 2);
+        assert!(!playback.has_prev());
+    }
+
+    #[test]
+    fn removing_before_the_current_track_keeps_it_current() {
+        let mut playback = controller(4);
+        playback.jump(2);
+        let (removed, kind) = playback.remove(0).unwrap();
+        assert_eq!(removed.title, "Track 1");
+        assert_eq!(kind, Removed::Other);
+        assert_eq!(playback.index(), 1);
+        assert_eq!(current(&playback), "Track 3");
+    }
+
+    #[test]
+    fn removing_the_current_track_moves_to_the_next() {
+        let mut playback = controller(3);
+        playback.jump(1);
+        assert_eq!(playback.remove(1).unwrap().1, Removed::Current);
+        assert_eq!(current(&playback), "Track 3");
+        assert_eq!(playback.remove(1).unwrap().1, Removed::Last);
+        assert_eq!(current(&playback), "Track 1");
+        assert!(playback.remove(5).is_none());
+    }
+
+    #[test]
+    fn restoring_puts_tracks_back_and_keeps_the_current_one() {
+        let mut playback = controller(4);
+        playback.jump(2);
+        let (first, _) = playback.remove(0).unwrap();
+        let (last, _) = playback.remove(2).unwrap();
+        assert_eq!(playback.restore(0, first), 0);
+        assert_eq!(current(&playback), "Track 3");
+        assert_eq!(playback.index(), 2);
+        // The queue got shorter since, so it goes at the end.
+        playback.remove(0);
+        playback.remove(0);
+        assert_eq!(playback.restore(3, last), 1);
+        assert_eq!(titles(&playback), ["Track 3", "Track 4"]);
+        assert_eq!(current(&playback), "Track 3");
+    }
+
+    #[test]
+    fn next_except_passes_over_picked_and_unstreamable_tracks() {
+        let mut playback = PlaybackController::with_seed(1);
+        playback.replace(vec![track(1, true), track(2, false), track(3, true), track(4, true)], 0);
+        assert!(playback.next_except(|t| t.title == "Track 3"));
+        assert_eq!(current(&playback), "Track 4");
+        assert!(!playback.next_except(|_| false));
+        assert_eq!(current(&playback), "Track 4");
+    }
+
+    #[test]
+    fn shuffling_keeps_the_current_and_played_tracks_in_place() {
+        let mut playback = controller(12);
+        playback.jump(3);
+        playback.set_shuffle(true);
+        let shuffled = titles(&playback);
+        assert_eq!(shuffled[..4], ["Track 1", "Track 2", "Track 3", "Track 4"]);
+        assert_eq!(current(&playback), "Track 4");
+        let mut sorted: Vec<_> = shuffled.iter().map(|t| t.to_string()).collect();
+        sorted.sort_by_key(|t| t[6..].parse::<u32>().unwrap());
+        assert_eq!(sorted, titles(&controller(12)));
+        assert_ne!(shuffled, titles(&controller(12)));
+    }
+
+    #[test]
+    fn shuffling_is_the_same_for_the_same_seed() {
+        let mut a = controller(10);
+        let mut b = controller(10);
+        a.set_shuffle(true);
+        b.set_shuffle(true);
+        assert_eq!(titles(&a), titles(&b));
+    }
+
+    #[test]
+    fn repeat_off_stops_after_the_last_track() {
+        let mut playback = controller(2);
+        assert!(playback.finish(|_| false));
+        assert_eq!(current(&playback), "Track 2");
+        assert!(!playback.has_next());
+        assert!(!playback.finish(|_| false));
+    }
+
+    #[test]
+    fn repeat_all_starts_over_after_the_last_track() {
+        let mut playback = controller(2);
+        playback.set_repeat(Repeat::All);
+        playback.jump(1);
+        assert!(playback.has_next());
+        assert!(playback.finish(|_| false));
+        assert_eq!(current(&playback), "Track 1");
+        playback.jump(1);
+        assert!(playback.next_except(|t| t.title == "Track 1"));
+        assert_eq!(current(&playback), "Track 2");
+    }
+
+    #[test]
+    fn repeat_one_plays_the_track_again_until_skipped() {
+        let mut playback = controller(2);
+        playback.set_repeat(Repeat::One);
+        assert!(playback.listen(120.0, 180.0).is_some());
+        assert!(playback.finish(|_| false));
+        assert_eq!(current(&playback), "Track 1");
+        // Played again, it counts as another listen.
+        assert!(playback.listen(120.0, 180.0).is_some());
+        assert!(playback.next());
+        assert_eq!(current(&playback), "Track 2");
+    }
+
+    #[test]
+    fn a_track_counts_as_listened_once() {
+        let mut playback = controller(1);
+        assert!(playback.listen(60.0, 180.0).is_none());
+        assert!(playback.skipping().is_some());
+        assert!(playback.listen(90.0, 180.0).is_some());
+        assert!(playback.listen(100.0, 180.0).is_none());
+        assert!(playback.skipping().is_none());
+    }
+}
@@ -1,79 +1,288 @@
 use gstreamer as gst;
 use gstreamer::prelude::*;
+use crate::album_grid::{album_data, AlbumData};
+use crate::library::Owned;
+use crate::mini_player::{MiniPlayer, MiniPlayerMsg, MiniPlayerOutput};
+use crate::now_playing::{NowPlaying, NowPlayingMsg, NowPlayingOutput};
+use crate::playback::{PlaybackController, Removed, Repeat};
+use crate::tracklist::{self, TrackRow, TrackRowOutput};
+use crate::waveform::Waveform;
+use gettextrs::gettext;
+use gtk4::glib::BoxedAnyObject;
 use gtk4::prelude::*;
-use mpris_server::{Metadata, PlaybackStatus, Player as MprisPlayer, Time};
+use libadwaita::prelude::*;
+use mpris_server::{LoopStatus, Metadata, PlaybackStatus, Player as MprisPlayer, Time};
+use relm4::actions::{RelmAction, RelmActionGroup};
+use relm4::factory::FactoryVecDeque;
 use relm4::prelude::*;
+use serde::Serialize;
 use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 use std::time::Duration;
 
-const WAVEFORM_BARS: usize = 120;
+
+relm4::new_action_group!(PlayerActionGroup, "player");
+relm4::new_stateless_action!(CopyLinkAction, PlayerActionGroup, "copy-link");
+relm4::new_stateless_action!(CopyLinkAtTimeAction, PlayerActionGroup, "copy-link-at-time");
+relm4::new_stateless_action!(CopyEmbedAction, PlayerActionGroup, "copy-embed");
+
+/// Edge length artwork is decoded at, the largest size it is shown.
+const ART_SIZE: i32 = 320;
+
+/// How often position and waveform are updated.
+const TICK: Duration = Duration::from_millis(250);
+/// Enough for MPRIS, the remote API and counting listens with nothing on
+/// screen.
+const BACKGROUND_TICK: Duration = Duration::from_secs(1);
 
 #[derive(Debug, Clone)]
 pub struct Track {
     pub title: String,
     pub artist: String,
+    /// Differs from `artist` on compilations.
+    pub album_artist: String,
     pub album: String,
     pub art_url: Option<String>,
     pub stream_url: String,
     pub duration: Option<f64>,
+    /// Where to share the track, see [`crate::bandcamp::TrackInfo::url`].
+    pub url: String,
+    pub number: Option<u32>,
+    pub disc: Option<u32>,
+    /// Set for tracks sold on their own.
+    pub price: Option<crate::bandcamp::Price>,
+    pub tags: Vec<crate::bandcamp::Tag>,
+    pub free_download: Option<crate::bandcamp::FreeDownload>,
+    pub release_id: Option<u64>,
+    pub label: Option<crate::bandcamp::Account>,
+    pub merch: Vec<crate::bandcamp::Merch>,
+}
+
+impl Track {
+    /// Whether the track can be played at all. Some releases only preview
+    /// a few tracks; the rest are heard after buying.
+    pub fn streamable(&self) -> bool {
+        !self.stream_url.is_empty()
+    }
+
+    /// The track's number on its release, with the disc on multi-volume
+    /// ones, e.g. `3` or `2-3`.
+    pub fn position(&self) -> Option<String> {
+        match (self.disc, self.number) {
+            (Some(disc), Some(number)) => Some(format!("{disc}-{number}")),
+            (None, Some(number)) => Some(number.to_string()),
+            _ => None,
+        }
+    }
 }
 
 impl From<crate::bandcamp::TrackInfo> for Track {
     fn from(t: crate::bandcamp::TrackInfo) -> Self {
         Self {
             title: t.title,
+            // Details cached before album artists were kept.
+            album_artist: if t.album_artist.is_empty() { t.artist.clone() } else { t.album_artist },
             artist: t.artist,
             album: t.album,
             art_url: t.art_url,
             stream_url: t.stream_url.unwrap_or_default(),
             duration: t.duration,
+            url: t.url,
+            number: t.number,
+            disc: t.disc,
+            price: t.price,
+            tags: t.tags,
+            free_download: t.free_download,
+            release_id: t.release_id,
+            label: t.label,
+            merch: t.merch,
+        }
+    }
+}
+
+/// Where the current track is played from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Source {
+    #[default]
+    Stream,
+    Cached,
+    Downloaded,
+}
+
+impl Source {
+    fn icon(self) -> &'static str {
+        match self {
+            Source::Stream => "network-transmit-receive-symbolic",
+            Source::Cached => "drive-harddisk-symbolic",
+            Source::Downloaded => "folder-music-symbolic",
         }
     }
+
+    fn description(self) -> String {
+        match self {
+            Source::Stream => gettext("Streaming at 128 kbps"),
+            Source::Cached => gettext("Playing from the audio cache"),
+            Source::Downloaded => gettext("Playing your downloaded copy"),
+        }
+    }
+}
+
+/// What the mini player and the expanded now-playing view need to render.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PlaybackSnapshot {
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    pub playing: bool,
+    pub has_prev: bool,
+    pub has_next: bool,
+    pub position: f64,
+    pub duration: f64,
 }
 
 pub struct Player {
     pipeline: gst::Element,
+    /// The track loaded into the pipeline, which may have left the queue.
     current_track: Option<Track>,
-    queue: Vec<Track>,
-    queue_index: usize,
+    playback: PlaybackController,
     playing: bool,
     position: f64,
     duration: f64,
     volume: f64,
     tracklist_visible: bool,
-    art_pixbuf: Option<gtk4::gdk_pixbuf::Pixbuf>,
     mpris: Rc<RefCell<Option<MprisPlayer>>>,
-    waveform_bars: Rc<RefCell<Vec<f64>>>,
-    waveform_progress: Rc<Cell<f64>>,
     waveform_dragging: Rc<Cell<bool>>,
-    waveform_area: gtk4::DrawingArea,
-    tracklist_box: gtk4::ListBox,
+    waveform_area: Waveform,
+    tracklist: FactoryVecDeque<TrackRow>,
+    mini: Controller<MiniPlayer>,
+    mini_mode: bool,
+    now_playing: Controller<NowPlaying>,
+    /// Where to seek once the current track is playing.
+    pending_seek: Option<f64>,
+    /// Keep streamed tracks on disk to play them again without streaming.
+    cache_audio: bool,
+    /// The bs2b crossfeed filter, created the first time it is enabled and
+    /// kept so toggling doesn't rebuild it.
+    crossfeed: Option<gst::Element>,
+    source: Source,
+    /// Whether the current track's release is in the library.
+    owned: Option<Owned>,
+    /// Tags the track list header has chips for.
+    shown_tags: Vec<crate::bandcamp::Tag>,
+    /// Merch the track list header lists.
+    shown_merch: Vec<crate::bandcamp::Merch>,
+    /// Stars the track list header shows; none while the queue mixes albums.
+    shown_rating: Option<u8>,
+    /// The note the track list header shows.
+    shown_note: Option<String>,
+    /// Whether the track list header shows auto-skip on.
+    shown_auto_skip: bool,
+    /// The window is hidden, minimized or otherwise not shown, so nothing
+    /// is redrawn on ticks.
+    background: bool,
+    tick: Option<gtk4::glib::SourceId>,
     _bus_watch: Option<gst::bus::BusWatchGuard>,
 }
 
 #[derive(Debug)]
 pub enum PlayerMsg {
     PlayQueue(Vec<Track>, usize),
+    SetPlaying(bool),
+    SetShuffle(bool),
+    SetRepeat(Repeat),
+    /// Append to the queue, or start playing it if nothing is loaded.
+    Enqueue(Vec<Track>),
     Toggle,
     Stop,
     Next,
     Prev,
     Seek(f64),
+    /// Seek relative to the current position, in seconds.
+    SeekBy(f64),
     SetVolume(f64),
+    SetAudioCache(bool),
+    /// Run playback through the headphone crossfeed filter.
+    SetCrossfeed(bool),
+    /// Start the track just queued this many seconds in, for shared links.
+    SeekOnStart(f64),
+    /// Copy the current track's link, optionally at the current time.
+    CopyLink(bool),
+    /// Copy the HTML embedding Bandcamp's player for the current track.
+    CopyEmbed,
     Tick,
+    /// The window stopped or started being shown.
+    SetBackground(bool),
     EOS,
-    SetArt(Vec<u8>),
+    /// Artwork for the track whose art URL this is.
+    SetArt(String, gtk4::gdk::Texture),
+    /// Open the current track's artwork at full size.
+    ShowArtwork,
     Wishlist,
+    /// Add the current release to the wishlist, or take it off.
+    ToggleWishlist,
+    /// Download the current release, given away for free.
+    DownloadFree,
+    /// Open the label the current release is on.
+    ShowLabel,
+    /// The library changed, so the current release may be owned now.
+    LibraryChanged,
+    /// Give the album the queue holds this many stars; 0 takes them away.
+    Rate(u8),
+    /// A release's rating changed, see [`crate::ratings`].
+    RatingsChanged,
+    /// Edit the note on the album the queue holds.
+    EditNote,
+    /// A release's note changed, see [`crate::notes`].
+    NotesChanged,
+    /// Turn auto-skip on or off for the album the queue holds.
+    SetAutoSkip(bool),
+    /// A release's auto-skip changed, see [`crate::skips`].
+    SkipsChanged,
+    OpenInBrowser(String),
+    /// An album card was dropped onto the player.
+    DropAlbum(AlbumData),
     ToggleTracklist,
     JumpToTrack(usize),
+    RemoveTrack(usize),
+    RestoreTrack(usize, Track),
+    SetMiniPlayer(bool),
+    CollapseNowPlaying,
 }
 
 #[derive(Debug)]
 pub enum PlayerOutput {
-    NowPlaying,
+    /// A new track started.
+    NowPlaying(Track),
     Wishlist,
+    /// Add the release of this track to the wishlist, or take it off.
+    SetWishlisted(Track, bool),
+    /// Download the release of this track, which is free.
+    DownloadFree(Track),
+    /// Open a page in the browser, e.g. to buy a track.
+    OpenInBrowser(String),
+    /// Browse a tag in Discover, by its slug.
+    ShowTag(String),
+    /// Open a label's page, with its artists and releases.
+    ShowLabel(crate::bandcamp::Account),
+    /// Give the release at this URL stars, or take its rating away.
+    Rate(String, Option<u8>),
+    /// Edit the note on the release at this URL, titled so.
+    EditNote(String, String),
+    /// Pass over often skipped tracks of the release at this URL, or stop.
+    SetAutoSkip(String, bool),
+    EnqueueAlbum(AlbumData),
     VolumeChanged(f64),
+    Raise,
+    Quit,
+    MiniMode(bool),
+    CollapseNowPlaying,
+    TrackRemoved(usize, Track),
+    Toast(String),
+    /// The current track played long enough to count as a listen: half its
+    /// length or four minutes, whichever comes first.
+    Listened(Track),
+    /// The current track was skipped before it counted as a listen.
+    Skipped(Track),
 }
 
 fn volume_icon(vol: f64) -> &'static str {
@@ -88,28 +297,51 @@ fn volume_icon(vol: f64) -> &'static str {
     }
 }
 
-fn generate_waveform(seed: &str) -> Vec<f64> {
-    let mut h: u64 = 5381;
-    for b in seed.bytes() {
-        h = h.wrapping_mul(33).wrapping_add(b as u64);
-    }
-    (0..WAVEFORM_BARS)
-        .map(|_| {
-            h = h
-                .wrapping_mul(6364136223846793005)
-                .wrapping_add(1442695040888963407);
-            let v = ((h >> 33) as f64) / (u32::MAX as f64);
-            0.2 + 0.8 * v
-        })
-        .collect()
+fn share_menu() -> gtk4::gio::Menu {
+    let menu = gtk4::gio::Menu::new();
+    menu.append(Some(&gettext("Copy Link")), Some("player.copy-link"));
+    menu.append(Some(&gettext("Copy Link at Current Time")), Some("player.copy-link-at-time"));
+    menu.append(Some(&gettext("Copy Embed Code")), Some("player.copy-embed"));
+    menu
+}
+
+fn start_tick(sender: &ComponentSender<Player>, interval: Duration) -> gtk4::glib::SourceId {
+    let s = sender.clone();
+    gtk4::glib::timeout_add_local(interval, move || {
+        s.input(PlayerMsg::Tick);
+        gtk4::glib::ControlFlow::Continue
+    })
+}
+
+/// Reports the player as in the background while its window is hidden
+/// (playing on with the window closed) or suspended (minimized, on another
+/// workspace, fully covered).
+fn connect_background(root: &gtk4::Revealer, sender: &ComponentSender<Player>) {
+    let connected = Cell::new(false);
+    let s = sender.clone();
+    root.connect_map(move |root| {
+        let Some(window) = root.root().and_downcast::<gtk4::Window>() else { return };
+        s.input(PlayerMsg::SetBackground(window.is_suspended()));
+        if !connected.replace(true) {
+            let s = s.clone();
+            let root = root.downgrade();
+            window.connect_suspended_notify(move |window| {
+                let mapped = root.upgrade().is_some_and(|root| root.is_mapped());
+                s.input(PlayerMsg::SetBackground(window.is_suspended() || !mapped));
+            });
+        }
+    });
+    let s = sender.clone();
+    root.connect_unmap(move |_| s.input(PlayerMsg::SetBackground(true)));
 }
 
 #[relm4::component(pub)]
 impl Component for Player {
-    type Init = ();
+    /// Container the expanded now-playing view is placed into.
+    type Init = gtk4::Box;
     type Input = PlayerMsg;
     type Output = PlayerOutput;
-    type CommandOutput = Vec<u8>;
+    type CommandOutput = ();
 
     view! {
         gtk4::Revealer {
@@ -118,7 +350,9 @@ impl Component for Player {
             #[watch]
             set_reveal_child: model.current_track.is_some(),
 
+            #[name = "bar"]
             gtk4::Box {
+                add_css_class: "player-bar",
             set_orientation: gtk4::Orientation::Vertical,
 
             // Tracklist revealer
@@ -127,18 +361,130 @@ impl Component for Player {
                 set_transition_type: gtk4::RevealerTransitionType::SlideDown,
                 set_transition_duration: 150,
                 #[watch]
-                set_reveal_child: model.tracklist_visible && model.queue.len() > 1,
-
-                gtk4::ScrolledWindow {
-                    set_max_content_height: 200,
-                    set_propagate_natural_height: true,
-                    set_hscrollbar_policy: gtk4::PolicyType::Never,
-                    add_css_class: "tracklist-scroll",
-
-                    #[name = "tracklist_box_ref"]
-                    gtk4::ListBox {
-                        set_selection_mode: gtk4::SelectionMode::None,
-                        add_css_class: "tracklist",
+                set_reveal_child: model.tracklist_visible && model.playback.queue().len() > 1,
+
+                gtk4::Box {
+                    set_orientation: gtk4::Orientation::Vertical,
+
+                    gtk4::Box {
+                        set_orientation: gtk4::Orientation::Horizontal,
+                        set_spacing: 8,
+                        add_css_class: "tracklist-header",
+
+                        gtk4::Box {
+                            set_orientation: gtk4::Orientation::Vertical,
+                            set_hexpand: true,
+                            set_valign: gtk4::Align::Center,
+
+                            gtk4::Label {
+                                add_css_class: "heading",
+                                set_xalign: 0.0,
+                                set_ellipsize: gtk4::pango::EllipsizeMode::End,
+                                #[watch]
+                                set_label: &tracklist::title(model.playback.queue()),
+                            },
+
+                            gtk4::Label {
+                                add_css_class: "dim-label",
+                                add_css_class: "caption",
+                                add_css_class: "numeric",
+                                set_xalign: 0.0,
+                                #[watch]
+                                set_label: &tracklist::summary(model.playback.queue()),
+                            },
+
+                            #[name = "rating"]
+                            gtk4::Box {
+                                add_css_class: "album-rating",
+                                set_halign: gtk4::Align::Start,
+                                set_visible: false,
+                                update_property: &[gtk4::accessible::Property::Label(&gettext("Rating"))],
+                            },
+
+                            #[name = "note"]
+                            gtk4::Label {
+                                add_css_class: "album-note",
+                                add_css_class: "caption",
+                                set_xalign: 0.0,
+                                set_wrap: true,
+                                set_selectable: true,
+                                set_visible: false,
+                            },
+
+                            #[name = "tag_chips"]
+                            gtk4::FlowBox {
+                                add_css_class: "tag-chips",
+                                set_selection_mode: gtk4::SelectionMode::None,
+                                set_column_spacing: 4,
+                                set_row_spacing: 4,
+                                set_max_children_per_line: 12,
+                                set_visible: false,
+                            },
+
+                            #[name = "merch_items"]
+                            gtk4::FlowBox {
+                                add_css_class: "merch-items",
+                                set_selection_mode: gtk4::SelectionMode::None,
+                                set_column_spacing: 4,
+                                set_row_spacing: 4,
+                                set_max_children_per_line: 4,
+                                set_visible: false,
+                                update_property: &[gtk4::accessible::Property::Label(&gettext("Merch"))],
+                            },
+                        },
+
+                        #[name = "auto_skip"]
+                        gtk4::ToggleButton {
+                            set_icon_name: "media-skip-forward-symbolic",
+                            add_css_class: "flat",
+                            add_css_class: "circular",
+                            set_valign: gtk4::Align::Center,
+                            #[watch]
+                            set_visible: tracklist::album_url(model.playback.queue()).is_some(),
+                            set_tooltip_text: Some(&tracklist::auto_skip_label()),
+                            update_property: &[gtk4::accessible::Property::Label(&tracklist::auto_skip_label())],
+                            connect_toggled[sender] => move |button| {
+                                sender.input(PlayerMsg::SetAutoSkip(button.is_active()));
+                            },
+                        },
+
+                        gtk4::Button {
+                            set_icon_name: "document-edit-symbolic",
+                            add_css_class: "flat",
+                            add_css_class: "circular",
+                            set_valign: gtk4::Align::Center,
+                            #[watch]
+                            set_visible: tracklist::album_url(model.playback.queue()).is_some(),
+                            set_tooltip_text: Some(&gettext("Edit Note")),
+                            update_property: &[gtk4::accessible::Property::Label(&gettext("Edit Note"))],
+                            connect_clicked => PlayerMsg::EditNote,
+                        },
+
+                        gtk4::Button {
+                            set_icon_name: "go-down-symbolic",
+                            add_css_class: "flat",
+                            add_css_class: "circular",
+                            set_valign: gtk4::Align::Center,
+                            set_tooltip_text: Some(&gettext("Hide Track List")),
+                            update_property: &[gtk4::accessible::Property::Label(&gettext("Hide Track List"))],
+                            connect_clicked => PlayerMsg::ToggleTracklist,
+                        },
+                    },
+
+                    gtk4::ScrolledWindow {
+                        set_max_content_height: 200,
+                        set_propagate_natural_height: true,
+                        set_hscrollbar_policy: gtk4::PolicyType::Never,
+
+                        #[local_ref]
+                        tracklist_box -> gtk4::ListBox {
+                            set_selection_mode: gtk4::SelectionMode::None,
+                            add_css_class: "tracklist",
+                            update_property: &[gtk4::accessible::Property::Label(&gettext("Queue"))],
+                            connect_row_activated[sender] => move |_, row| {
+                                sender.input(PlayerMsg::JumpToTrack(row.index() as usize));
+                            },
+                        },
                     },
                 },
             },
@@ -157,7 +503,8 @@ impl Component for Player {
                 gtk4::Box {
                     set_valign: gtk4::Align::Center,
                     set_cursor_from_name: Some("pointer"),
-                    set_tooltip_text: Some("Open in Browser"),
+                    set_tooltip_text: Some(&gettext("View Artwork")),
+                    update_property: &[gtk4::accessible::Property::Label(&gettext("View Artwork"))],
 
                     gtk4::Frame {
                         add_css_class: "album-art",
@@ -169,10 +516,13 @@ impl Component for Player {
                     },
                 },
 
+                #[name = "info_box"]
                 gtk4::Box {
                     set_orientation: gtk4::Orientation::Vertical,
                     set_valign: gtk4::Align::Center,
                     set_hexpand: true,
+                    set_cursor_from_name: Some("pointer"),
+                    set_tooltip_text: Some(&gettext("Open in Browser")),
 
                     gtk4::Label {
                         set_xalign: 0.0,
@@ -201,14 +551,93 @@ impl Component for Player {
                     },
                 },
 
+                gtk4::Image {
+                    add_css_class: "dim-label",
+                    set_valign: gtk4::Align::Center,
+                    #[watch]
+                    set_visible: model.current_track.is_some(),
+                    #[watch]
+                    set_icon_name: Some(model.source.icon()),
+                    #[watch]
+                    set_tooltip_text: Some(&model.source.description()),
+                },
+
+                gtk4::Button {
+                    set_label: &gettext("Label"),
+                    add_css_class: "flat",
+                    add_css_class: "caption",
+                    set_valign: gtk4::Align::Center,
+                    #[watch]
+                    set_visible: model.current_track.as_ref().is_some_and(|t| t.label.is_some()),
+                    #[watch]
+                    set_tooltip_text: model
+                        .current_track
+                        .as_ref()
+                        .and_then(|t| t.label.as_ref())
+                        .map(|label| gettext("Released on “{}”").replace("{}", &label.name))
+                        .as_deref(),
+                    connect_clicked => PlayerMsg::ShowLabel,
+                },
+
+                gtk4::Label {
+                    set_label: &gettext("Owned"),
+                    add_css_class: "owned-badge",
+                    add_css_class: "caption",
+                    set_valign: gtk4::Align::Center,
+                    set_tooltip_text: Some(&gettext("In your collection")),
+                    #[watch]
+                    set_visible: model.current_track.is_some() && model.owned == Some(Owned::Collection),
+                },
+
+                gtk4::Button {
+                    set_label: &gettext("Free Download"),
+                    add_css_class: "free-badge",
+                    add_css_class: "caption",
+                    set_valign: gtk4::Align::Center,
+                    #[watch]
+                    set_visible: model.free_download().is_some(),
+                    #[watch]
+                    set_tooltip_text: Some(&match model.free_download() {
+                        Some(free) if free.creative_commons => {
+                            gettext("Download this Creative Commons release for free")
+                        }
+                        _ => gettext("Download this release for free"),
+                    }),
+                    connect_clicked => PlayerMsg::DownloadFree,
+                },
+
+                gtk4::Button {
+                    set_icon_name: "emblem-favorite-symbolic",
+                    add_css_class: "flat",
+                    set_valign: gtk4::Align::Center,
+                    #[watch]
+                    set_visible: model.current_track.as_ref().is_some_and(|t| !t.url.is_empty())
+                        && model.owned != Some(Owned::Collection),
+                    #[watch]
+                    set_class_active: ("accent", model.owned == Some(Owned::Wishlist)),
+                    #[watch]
+                    set_tooltip_text: Some(&if model.owned == Some(Owned::Wishlist) {
+                        gettext("Remove from Wishlist")
+                    } else {
+                        gettext("Add to Wishlist")
+                    }),
+                    #[watch]
+                    update_property: &[gtk4::accessible::Property::Label(&if model.owned == Some(Owned::Wishlist) {
+                        gettext("Remove from Wishlist")
+                    } else {
+                        gettext("Add to Wishlist")
+                    })],
+                    connect_clicked => PlayerMsg::ToggleWishlist,
+                },
+
                 // Tracklist toggle button
                 gtk4::Button {
                     set_icon_name: "view-list-symbolic",
                     add_css_class: "flat",
                     set_valign: gtk4::Align::Center,
-                    set_tooltip_text: Some("Track list"),
+                    set_tooltip_text: Some(&gettext("Track list")),
                     #[watch]
-                    set_visible: model.queue.len() > 1,
+                    set_visible: model.playback.queue().len() > 1,
                     connect_clicked => PlayerMsg::ToggleTracklist,
                 },
 
@@ -218,21 +647,23 @@ impl Component for Player {
                     add_css_class: "numeric",
                     set_valign: gtk4::Align::Center,
                     #[watch]
-                    set_label: &if model.queue.len() > 1 {
-                        format!("{}/{}", model.queue_index + 1, model.queue.len())
+                    set_label: &if model.playback.queue().len() > 1 {
+                        format!("{}/{}", model.playback.index() + 1, model.playback.queue().len())
                     } else {
                         String::new()
                     },
                     #[watch]
-                    set_visible: model.queue.len() > 1,
+                    set_visible: model.playback.queue().len() > 1,
                 },
 
                 gtk4::Button {
                     set_icon_name: "media-skip-backward-symbolic",
                     add_css_class: "flat",
                     set_valign: gtk4::Align::Center,
+                    set_tooltip_text: Some(&gettext("Previous Track")),
+                    update_property: &[gtk4::accessible::Property::Label(&gettext("Previous Track"))],
                     #[watch]
-                    set_sensitive: model.queue_index > 0,
+                    set_sensitive: model.playback.has_prev(),
                     connect_clicked => PlayerMsg::Prev,
                 },
 
@@ -242,6 +673,12 @@ impl Component for Player {
                     add_css_class: "circular",
                     add_css_class: "suggested-action",
                     set_valign: gtk4::Align::Center,
+                    #[watch]
+                    update_property: &[gtk4::accessible::Property::Label(&if model.playing {
+                        gettext("Pause")
+                    } else {
+                        gettext("Play")
+                    })],
                     connect_clicked => PlayerMsg::Toggle,
                 },
 
@@ -249,8 +686,10 @@ impl Component for Player {
                     set_icon_name: "media-skip-forward-symbolic",
                     add_css_class: "flat",
                     set_valign: gtk4::Align::Center,
+                    set_tooltip_text: Some(&gettext("Next Track")),
+                    update_property: &[gtk4::accessible::Property::Label(&gettext("Next Track"))],
                     #[watch]
-                    set_sensitive: model.queue_index + 1 < model.queue.len(),
+                    set_sensitive: model.playback.has_next(),
                     connect_clicked => PlayerMsg::Next,
                 },
 
@@ -272,6 +711,7 @@ impl Component for Player {
                         set_orientation: gtk4::Orientation::Horizontal,
                         set_valign: gtk4::Align::Center,
                         set_width_request: 80,
+                        update_property: &[gtk4::accessible::Property::Label(&gettext("Volume"))],
                         set_range: (0.0, 1.0),
                         set_value: 1.0,
                         set_draw_value: false,
@@ -279,6 +719,24 @@ impl Component for Player {
                             sender.input(PlayerMsg::SetVolume(scale.value()));
                         },
                     },
+
+                    gtk4::MenuButton {
+                        set_icon_name: "send-to-symbolic",
+                        add_css_class: "flat",
+                        set_valign: gtk4::Align::Center,
+                        set_tooltip_text: Some(&gettext("Share")),
+                        set_menu_model: Some(&share_menu()),
+                        #[watch]
+                        set_sensitive: model.current_track.as_ref().is_some_and(|t| !t.url.is_empty()),
+                    },
+
+                    gtk4::Button {
+                        set_icon_name: "view-restore-symbolic",
+                        add_css_class: "flat",
+                        set_valign: gtk4::Align::Center,
+                        set_tooltip_text: Some(&gettext("Mini Player")),
+                        connect_clicked => PlayerMsg::SetMiniPlayer(true),
+                    },
                 },
             },
 
@@ -319,7 +777,11 @@ impl Component for Player {
         }
     }
 
-    fn init(_: Self::Init, root: Self::Root, sender: ComponentSender<Self>) -> ComponentParts<Self> {
+    fn init(
+        now_playing_box: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
         gst::init().expect("GStreamer init failed");
 
         let pipeline = gst::ElementFactory::make("playbin").build().unwrap();
@@ -332,7 +794,7 @@ impl Component for Player {
                 match msg.view() {
                     gst::MessageView::Eos(_) => s.input(PlayerMsg::EOS),
                     gst::MessageView::Error(err) => {
-                        eprintln!("GStreamer error: {:?}", err.error());
+                        crate::diagnostics::error("gstreamer", format!("{:?}", err.error()));
                         s.input(PlayerMsg::EOS);
                     }
                     _ => {}
@@ -341,18 +803,16 @@ impl Component for Player {
             })
             .unwrap();
 
-        let s = sender.clone();
-        gtk4::glib::timeout_add_local(Duration::from_millis(250), move || {
-            s.input(PlayerMsg::Tick);
-            gtk4::glib::ControlFlow::Continue
-        });
-
         let mpris: Rc<RefCell<Option<MprisPlayer>>> = Rc::new(RefCell::new(None));
         let mpris_clone = mpris.clone();
         let st = sender.clone();
         let sn = sender.clone();
         let sp = sender.clone();
         let ss = sender.clone();
+        let sr = sender.clone();
+        let sq = sender.clone();
+        let sh = sender.clone();
+        let sl = sender.clone();
 
         gtk4::glib::MainContext::default().spawn_local(async move {
             if let Ok(m) = MprisPlayer::builder("camper")
@@ -363,6 +823,8 @@ impl Component for Player {
                 .can_go_previous(true)
                 .can_seek(true)
                 .can_control(true)
+                .can_raise(true)
+                .can_quit(true)
                 .build()
                 .await
             {
@@ -370,85 +832,57 @@ impl Component for Player {
                 m.connect_next(move |_| sn.input(PlayerMsg::Next));
                 m.connect_previous(move |_| sp.input(PlayerMsg::Prev));
                 m.connect_stop(move |_| ss.input(PlayerMsg::Stop));
+                m.connect_raise(move |_| {
+                    sr.output(PlayerOutput::Raise).ok();
+                });
+                m.connect_quit(move |_| {
+                    sq.output(PlayerOutput::Quit).ok();
+                });
+                m.connect_set_shuffle(move |_, shuffle| sh.input(PlayerMsg::SetShuffle(shuffle)));
+                m.connect_set_loop_status(move |_, status| {
+                    let repeat = match status {
+                        LoopStatus::None => Repeat::Off,
+                        LoopStatus::Playlist => Repeat::All,
+                        LoopStatus::Track => Repeat::One,
+                    };
+                    sl.input(PlayerMsg::SetRepeat(repeat));
+                });
                 let run_task = m.run();
                 *mpris_clone.borrow_mut() = Some(m);
                 run_task.await;
             }
         });
 
-        let waveform_bars: Rc<RefCell<Vec<f64>>> = Rc::new(RefCell::new(Vec::new()));
-        let waveform_progress: Rc<Cell<f64>> = Rc::new(Cell::new(0.0));
         let waveform_dragging: Rc<Cell<bool>> = Rc::new(Cell::new(false));
 
-        let waveform_area = gtk4::DrawingArea::new();
-        waveform_area.set_content_height(28);
+        // Operable with the arrow keys, as the slider it is exposed as.
+        let waveform_area = Waveform::default();
+        waveform_area.update_property(&[
+            gtk4::accessible::Property::Label(&gettext("Seek")),
+            gtk4::accessible::Property::ValueMin(0.0),
+        ]);
         waveform_area.set_hexpand(true);
         waveform_area.set_cursor_from_name(Some("pointer"));
 
-        let bars_draw = waveform_bars.clone();
-        let progress_draw = waveform_progress.clone();
-        waveform_area.set_draw_func(move |_, cr, w, h| {
-            let bars = bars_draw.borrow();
-            let n = bars.len();
-            if n == 0 {
-                return;
-            }
-
-            let w = w as f64;
-            let h = h as f64;
-            let progress = progress_draw.get();
-            let bar_pitch = w / n as f64;
-            let gap = 1.0_f64.min(bar_pitch * 0.25);
-            let bar_w = (bar_pitch - gap).max(1.0);
-
-            cr.set_source_rgba(0.85, 0.28, 0.28, 1.0);
-            for (i, &val) in bars.iter().enumerate() {
-                let x = i as f64 * bar_pitch;
-                if (x + bar_w * 0.5) / w > progress {
-                    continue;
-                }
-                let bar_h = val * h * 0.85;
-                let y = (h - bar_h) / 2.0;
-                cr.rectangle(x, y, bar_w, bar_h);
-            }
-            let _ = cr.fill();
-
-            cr.set_source_rgba(1.0, 1.0, 1.0, 0.12);
-            for (i, &val) in bars.iter().enumerate() {
-                let x = i as f64 * bar_pitch;
-                if (x + bar_w * 0.5) / w <= progress {
-                    continue;
-                }
-                let bar_h = val * h * 0.85;
-                let y = (h - bar_h) / 2.0;
-                cr.rectangle(x, y, bar_w, bar_h);
-            }
-            let _ = cr.fill();
-        });
-
         let drag = gtk4::GestureDrag::new();
         {
             let area = waveform_area.clone();
-            let progress = waveform_progress.clone();
             let dragging = waveform_dragging.clone();
             drag.connect_drag_begin(move |_, start_x, _| {
                 dragging.set(true);
                 let w = area.width() as f64;
                 if w > 0.0 {
-                    progress.set((start_x / w).clamp(0.0, 1.0));
-                    area.queue_draw();
+                    area.set_progress(start_x / w);
                 }
             });
         }
         {
             let area = waveform_area.clone();
-            let progress = waveform_progress.clone();
             drag.connect_drag_update(move |gesture, offset_x, _| {
                 if let Some((start_x, _)) = gesture.start_point() {
                     let w = area.width() as f64;
                     if w > 0.0 {
-                        progress.set(((start_x + offset_x) / w).clamp(0.0, 1.0));
-                        area.queue_draw();
+                        area.set_progress((start_x + offset_x) / w);
                     }
                 }
             });
@@ -470,40 +904,141 @@ impl Component for Player {
         }
         waveform_area.add_controller(drag);
 
-        // Placeholder — replaced after view_output!()
-        let tracklist_box_placeholder = gtk4::ListBox::new();
+        let s = sender.clone();
+        let seek_keys = gtk4::EventControllerKey::new();
+        seek_keys.connect_key_pressed(move |_, key, _, _| match key {
+            gtk4::gdk::Key::Left => {
+                s.input(PlayerMsg::SeekBy(-5.0));
+                gtk4::glib::Propagation::Stop
+            }
+            gtk4::gdk::Key::Right => {
+                s.input(PlayerMsg::SeekBy(5.0));
+                gtk4::glib::Propagation::Stop
+            }
+            _ => gtk4::glib::Propagation::Proceed,
+        });
+        waveform_area.add_controller(seek_keys);
+
+        let style_manager = libadwaita::StyleManager::default();
+        let area = waveform_area.downgrade();
+        style_manager.connect_dark_notify(move |_| {
+            if let Some(area) = area.upgrade() {
+                area.queue_draw();
+            }
+        });
+        let area = waveform_area.downgrade();
+        style_manager.connect_high_contrast_notify(move |_| {
+            if let Some(area) = area.upgrade() {
+                area.queue_draw();
+            }
+        });
+
+        let mini = MiniPlayer::builder()
+            .launch(())
+            .forward(sender.input_sender(), |msg| match msg {
+                MiniPlayerOutput::Toggle => PlayerMsg::Toggle,
+                MiniPlayerOutput::Next => PlayerMsg::Next,
+                MiniPlayerOutput::Prev => PlayerMsg::Prev,
+                MiniPlayerOutput::Restore => PlayerMsg::SetMiniPlayer(false),
+            });
+
+        let now_playing = NowPlaying::builder()
+            .launch(())
+            .forward(sender.input_sender(), |msg| match msg {
+                NowPlayingOutput::Toggle => PlayerMsg::Toggle,
+                NowPlayingOutput::Next => PlayerMsg::Next,
+                NowPlayingOutput::Prev => PlayerMsg::Prev,
+                NowPlayingOutput::Seek(frac) => PlayerMsg::Seek(frac),
+                NowPlayingOutput::Collapse => PlayerMsg::CollapseNowPlaying,
+                NowPlayingOutput::ShowArtwork => PlayerMsg::ShowArtwork,
+            });
+        now_playing_box.append(now_playing.widget());
 
-        let mut model = Self {
+        let tracklist = FactoryVecDeque::builder()
+            .launch(gtk4::ListBox::new())
+            .forward(sender.input_sender(), |output| match output {
+                TrackRowOutput::Remove(index) => PlayerMsg::RemoveTrack(index.current_index()),
+                TrackRowOutput::Buy(url) => PlayerMsg::OpenInBrowser(url),
+            });
+
+        let model = Self {
             pipeline,
             current_track: None,
-            queue: Vec::new(),
-            queue_index: 0,
+            playback: PlaybackController::default(),
             playing: false,
             position: 0.0,
             duration: 0.0,
             volume: 1.0,
             tracklist_visible: false,
-            art_pixbuf: None,
             mpris,
-            waveform_bars,
-            waveform_progress,
             waveform_dragging,
             waveform_area: waveform_area.clone(),
-            tracklist_box: tracklist_box_placeholder,
+            tracklist,
+            mini,
+            mini_mode: false,
+            now_playing,
+            pending_seek: None,
+            cache_audio: false,
+            crossfeed: None,
+            source: Source::Stream,
+            owned: None,
+            shown_tags: Vec::new(),
+            shown_merch: Vec::new(),
+            shown_rating: None,
+            shown_note: None,
+            shown_auto_skip: false,
+            background: false,
+            tick: Some(start_tick(&sender, TICK)),
             _bus_watch: Some(bus_watch),
         };
 
+        let tracklist_box = model.tracklist.widget();
         let widgets = view_output!();
-        model.tracklist_box = widgets.tracklist_box_ref.clone();
         widgets.waveform_container.append(&waveform_area);
 
         let s = sender.clone();
         let art_click = gtk4::GestureClick::new();
         art_click.connect_released(move |_, _, _, _| {
-            s.input(PlayerMsg::Wishlist);
+            s.input(PlayerMsg::ShowArtwork);
         });
         widgets.art_box.add_controller(art_click);
 
+        let s = sender.clone();
+        let info_click = gtk4::GestureClick::new();
+        info_click.connect_released(move |_, _, _, _| {
+            s.input(PlayerMsg::Wishlist);
+        });
+        widgets.info_box.add_controller(info_click);
+
+        let mut actions = RelmActionGroup::<PlayerActionGroup>::new();
+        let s = sender.clone();
+        actions.add_action(RelmAction::<CopyLinkAction>::new_stateless(move |_| {
+            s.input(PlayerMsg::CopyLink(false));
+        }));
+        let s = sender.clone();
+        actions.add_action(RelmAction::<CopyLinkAtTimeAction>::new_stateless(move |_| {
+            s.input(PlayerMsg::CopyLink(true));
+        }));
+        let s = sender.clone();
+        actions.add_action(RelmAction::<CopyEmbedAction>::new_stateless(move |_| {
+            s.input(PlayerMsg::CopyEmbed);
+        }));
+        actions.register_for_widget(&root);
+
+        connect_background(&root, &sender);
+
+        // Album cards dragged onto the bar are queued.
+        let drop_target = gtk4::DropTarget::new(BoxedAnyObject::static_type(), gtk4::gdk::DragAction::COPY);
+        let s = sender.clone();
+        drop_target.connect_drop(move |_, value, _, _| {
+            let Some(data) = value.get::<gtk4::glib::Object>().ok().as_ref().and_then(album_data) else {
+                return false;
+            };
+            s.input(PlayerMsg::DropAlbum(data));
+            true
+        });
+        widgets.bar.add_controller(drop_target);
+
         ComponentParts { model, widgets }
     }
 
@@ -514,13 +1049,51 @@ impl Component for Player {
         sender: ComponentSender<Self>,
         _root: &Self::Root,
     ) {
+        let is_tick = matches!(msg, PlayerMsg::Tick);
         match msg {
             PlayerMsg::PlayQueue(tracks, idx) => {
-                self.queue = tracks;
-                self.queue_index = idx;
-                self.rebuild_tracklist(&sender);
+                self.playback.replace(tracks, idx);
+                // Played from the top, an album passes over an often skipped
+                // opener too; a track picked by hand plays regardless.
+                if idx == 0 && self.playback.current().is_some_and(crate::skips::passes_over) {
+                    self.playback.next_except(crate::skips::passes_over);
+                }
+                tracklist::replace(&mut self.tracklist, self.playback.queue(), self.playback.index());
                 self.play_current(sender.clone());
             }
+            PlayerMsg::Enqueue(tracks) => {
+                let start = self.playback.queue().len();
+                if self.playback.enqueue(tracks) {
+                    tracklist::replace(&mut self.tracklist, self.playback.queue(), self.playback.index());
+                    self.play_current(sender.clone());
+                } else if self.playback.shuffle() {
+                    tracklist::replace(&mut self.tracklist, self.playback.queue(), self.playback.index());
+                    self.sync_mpris();
+                } else {
+                    let mut rows = self.tracklist.guard();
+                    for track in &self.playback.queue()[start..] {
+                        rows.push_back(track.clone());
+                    }
+                    drop(rows);
+                    self.sync_mpris();
+                }
+            }
+            PlayerMsg::SetShuffle(shuffle) => {
+                self.playback.set_shuffle(shuffle);
+                if shuffle {
+                    tracklist::replace(&mut self.tracklist, self.playback.queue(), self.playback.index());
+                }
+                self.sync_mpris();
+            }
+            PlayerMsg::SetRepeat(repeat) => {
+                self.playback.set_repeat(repeat);
+                self.sync_mpris();
+            }
+            PlayerMsg::SetPlaying(playing) => {
+                if playing != self.playing {
+                    sender.input(PlayerMsg::Toggle);
+                }
+            }
             PlayerMsg::Toggle => {
                 if self.playing {
                     self.pipeline.set_state(gst::State::Paused).ok();
@@ -538,15 +1111,17 @@ impl Component for Player {
                 self.sync_mpris();
             }
             PlayerMsg::Next => {
-                if self.queue_index + 1 < self.queue.len() {
-                    self.queue_index += 1;
+                let skipped = self.playback.skipping().cloned();
+                if self.playback.next_except(crate::skips::passes_over) {
+                    if let Some(track) = skipped {
+                        sender.output(PlayerOutput::Skipped(track)).ok();
+                    }
                     self.highlight_current_track();
                     self.play_current(sender.clone());
                 }
             }
             PlayerMsg::Prev => {
-                if self.queue_index > 0 {
-                    self.queue_index -= 1;
+                if self.playback.prev() {
                     self.highlight_current_track();
                     self.play_current(sender.clone());
                 }
@@ -558,8 +1133,13 @@ impl Component for Player {
                         .seek_simple(gst::SeekFlags::FLUSH, gst::ClockTime::from_nseconds(ns))
                         .ok();
                     self.position = frac * self.duration;
-                    self.waveform_progress.set(frac);
-                    self.waveform_area.queue_draw();
+                    self.waveform_area.set_progress(frac);
+                }
+            }
+            PlayerMsg::SeekBy(secs) => {
+                if self.duration > 0.0 {
+                    let frac = ((self.position + secs) / self.duration).clamp(0.0, 1.0);
+                    sender.input(PlayerMsg::Seek(frac));
                 }
             }
             PlayerMsg::SetVolume(v) => {
@@ -570,6 +1150,55 @@ impl Component for Player {
                 }
                 sender.output(PlayerOutput::VolumeChanged(v)).ok();
             }
+            PlayerMsg::SetAudioCache(enabled) => {
+                self.cache_audio = enabled;
+            }
+            PlayerMsg::SetCrossfeed(enabled) => {
+                if enabled && self.crossfeed.is_none() {
+                    match gst::ElementFactory::make("bs2b").build() {
+                        Ok(filter) => self.crossfeed = Some(filter),
+                        Err(e) => {
+                            crate::diagnostics::error("gstreamer", e.to_string());
+                            let message = gettext("Crossfeed needs the GStreamer bs2b plugin");
+                            sender.output(PlayerOutput::Toast(message)).ok();
+                        }
+                    }
+                }
+                // playbin only rebuilds its audio chain when a track loads, so
+                // the change is heard from the next track on and the one
+                // playing isn't cut off.
+                let filter = self.crossfeed.as_ref().filter(|_| enabled);
+                self.pipeline.set_property("audio-filter", filter);
+            }
+            PlayerMsg::SeekOnStart(secs) => {
+                self.pending_seek = Some(secs);
+            }
+            PlayerMsg::CopyLink(at_time) => {
+                if let Some(track) = self.current_track.as_ref().filter(|t| !t.url.is_empty()) {
+                    let link = if at_time {
+                        crate::bandcamp::timestamped_link(&track.url, self.position)
+                    } else {
+                        track.url.clone()
+                    };
+                    widgets.info_box.clipboard().set_text(&link);
+                    sender.output(PlayerOutput::Toast(gettext("Link copied"))).ok();
+                }
+            }
+            PlayerMsg::CopyEmbed => {
+                let embed = self.current_track.as_ref().and_then(|track| {
+                    let title = if track.album.is_empty() { &track.title } else { &track.album };
+                    crate::bandcamp::embed_code(&track.url, track.release_id?, title, &track.album_artist)
+                });
+                match embed {
+                    Some(embed) => {
+                        widgets.info_box.clipboard().set_text(&embed);
+                        sender.output(PlayerOutput::Toast(gettext("Embed code copied"))).ok();
+                    }
+                    None => {
+                        sender.output(PlayerOutput::Toast(gettext("No embed code for this track"))).ok();
+                    }
+                }
+            }
             PlayerMsg::Tick => {
                 if self.playing {
                     if let Some(pos) = self.pipeline.query_position::<gst::ClockTime>() {
@@ -577,17 +1206,37 @@ impl Component for Player {
                     }
                     if let Some(dur) = self.pipeline.query_duration::<gst::ClockTime>() {
                         self.duration = dur.seconds() as f64;
+                        // Seeking only works once the stream's length is known.
+                        if self.duration > 0.0 {
+                            if let Some(secs) = self.pending_seek.take() {
+                                sender.input(PlayerMsg::Seek((secs / self.duration).min(1.0)));
+                            }
+                        }
                     }
-                    if self.duration > 0.0 && !self.waveform_dragging.get() {
-                        self.waveform_progress.set(self.position / self.duration);
-                        self.waveform_area.queue_draw();
+                    if self.duration > 0.0 && !self.waveform_dragging.get() && !self.background {
+                        self.waveform_area.set_progress(self.position / self.duration);
+                    }
+                    if let Some(track) = self.playback.listen(self.position, self.duration) {
+                        sender.output(PlayerOutput::Listened(track)).ok();
                     }
                     self.sync_mpris_position();
                 }
             }
+            PlayerMsg::SetBackground(background) => {
+                if self.background != background {
+                    self.background = background;
+                    if let Some(tick) = self.tick.take() {
+                        tick.remove();
+                    }
+                    let interval = if background { BACKGROUND_TICK } else { TICK };
+                    self.tick = Some(start_tick(&sender, interval));
+                    if !background && self.duration > 0.0 && !self.waveform_dragging.get() {
+                        self.waveform_area.set_progress(self.position / self.duration);
+                    }
+                }
+            }
             PlayerMsg::EOS => {
-                if self.queue_index + 1 < self.queue.len() {
-                    self.queue_index += 1;
+                if self.playback.finish(crate::skips::passes_over) {
                     self.highlight_current_track();
                     self.play_current(sender.clone());
                 } else {
@@ -597,11 +1246,21 @@ impl Component for Player {
                     self.sync_mpris();
                 }
             }
-            PlayerMsg::SetArt(bytes) => {
-                if let Some(pb) = load_pixbuf(&bytes, 42) {
-                    let texture = gtk4::gdk::Texture::for_pixbuf(&pb);
+            PlayerMsg::SetArt(url, texture) => {
+                // The track may have changed while the art was loading.
+                let current = self.current_track.as_ref().and_then(|t| t.art_url.as_deref());
+                if current == Some(url.as_str()) {
                     widgets.art_image.set_paintable(Some(&texture));
-                    self.art_pixbuf = Some(pb);
+                    self.mini.emit(MiniPlayerMsg::SetArt(texture.clone()));
+                    self.now_playing.emit(NowPlayingMsg::SetArt(texture));
+                }
+            }
+            PlayerMsg::ShowArtwork => {
+                if let Some(track) = self.current_track.as_ref() {
+                    if let Some(url) = track.art_url.as_deref() {
+                        let title = if track.album.is_empty() { &track.title } else { &track.album };
+                        crate::art_viewer::build_dialog(url, title).present(Some(&widgets.art_box));
+                    }
                 }
             }
             PlayerMsg::Wishlist => {
@@ -609,151 +1268,260 @@ impl Component for Player {
                     sender.output(PlayerOutput::Wishlist).ok();
                 }
             }
+            PlayerMsg::ToggleWishlist => {
+                if let Some(track) = self.current_track.clone() {
+                    let wishlisted = self.owned != Some(Owned::Wishlist);
+                    sender.output(PlayerOutput::SetWishlisted(track, wishlisted)).ok();
+                }
+            }
+            PlayerMsg::DownloadFree => {
+                if let Some(track) = self.current_track.clone().filter(|_| self.free_download().is_some()) {
+                    sender.output(PlayerOutput::DownloadFree(track)).ok();
+                }
+            }
+            PlayerMsg::ShowLabel => {
+                if let Some(label) = self.current_track.as_ref().and_then(|t| t.label.clone()) {
+                    sender.output(PlayerOutput::ShowLabel(label)).ok();
+                }
+            }
+            PlayerMsg::DropAlbum(data) => {
+                sender.output(PlayerOutput::EnqueueAlbum(data)).ok();
+            }
+            PlayerMsg::OpenInBrowser(url) => {
+                sender.output(PlayerOutput::OpenInBrowser(url)).ok();
+            }
+            PlayerMsg::LibraryChanged => {
+                self.owned = self.current_track.as_ref().and_then(|t| crate::library::owned(&t.url));
+            }
+            PlayerMsg::Rate(stars) => {
+                if let Some(url) = tracklist::album_url(self.playback.queue()) {
+                    sender.output(PlayerOutput::Rate(url.to_string(), Some(stars).filter(|&s| s > 0))).ok();
+                }
+            }
+            PlayerMsg::EditNote => {
+                let queue = self.playback.queue();
+                if let Some(url) = tracklist::album_url(queue) {
+                    sender.output(PlayerOutput::EditNote(url.to_string(), tracklist::title(queue))).ok();
+                }
+            }
+            PlayerMsg::SetAutoSkip(on) => {
+                // Also sent when the header catches up with a change.
+                if let Some(url) = tracklist::album_url(self.playback.queue()) {
+                    if crate::skips::auto_skip(url) != on {
+                        sender.output(PlayerOutput::SetAutoSkip(url.to_string(), on)).ok();
+                    }
+                }
+            }
+            // Picked up below, with the rest of the header.
+            PlayerMsg::RatingsChanged | PlayerMsg::NotesChanged | PlayerMsg::SkipsChanged => {}
             PlayerMsg::ToggleTracklist => {
                 self.tracklist_visible = !self.tracklist_visible;
             }
             PlayerMsg::JumpToTrack(idx) => {
-                if idx < self.queue.len() {
-                    self.queue_index = idx;
+                if self.playback.jump(idx) {
                     self.highlight_current_track();
                     self.play_current(sender.clone());
                 }
             }
+            PlayerMsg::RemoveTrack(idx) => {
+                if let Some((track, removed)) = self.playback.remove(idx) {
+                    match removed {
+                        Removed::Other => {}
+                        Removed::Current => self.play_current(sender.clone()),
+                        Removed::Last => {
+                            self.pipeline.set_state(gst::State::Null).ok();
+                            self.playing = false;
+                            self.position = 0.0;
+                            self.sync_mpris();
+                        }
+                    }
+                    self.tracklist.guard().remove(idx);
+                    self.highlight_current_track();
+                    sender.output(PlayerOutput::TrackRemoved(idx, track)).ok();
+                }
+            }
+            PlayerMsg::RestoreTrack(idx, track) => {
+                let idx = self.playback.restore(idx, track.clone());
+                self.tracklist.guard().insert(idx, track);
+                self.highlight_current_track();
+            }
+            PlayerMsg::SetMiniPlayer(active) => {
+                if self.mini_mode != active {
+                    self.mini_mode = active;
+                    self.mini.emit(MiniPlayerMsg::SetVisible(active));
+                    sender.output(PlayerOutput::MiniMode(active)).ok();
+                }
+            }
+            PlayerMsg::CollapseNowPlaying => {
+                sender.output(PlayerOutput::CollapseNowPlaying).ok();
+            }
         }
 
-        self.update_view(widgets, sender);
-    }
+        if tracklist::tags(self.playback.queue()) != self.shown_tags.as_slice() {
+            self.shown_tags = tracklist::tags(self.playback.queue()).to_vec();
+            tracklist::show_tags(&widgets.tag_chips, &self.shown_tags, &sender);
+        }
+        let rating = tracklist::album_url(self.playback.queue()).map(|url| crate::ratings::get(url).unwrap_or(0));
+        if rating != self.shown_rating {
+            self.shown_rating = rating;
+            tracklist::show_rating(&widgets.rating, rating, &sender);
+        }
+        let note = tracklist::album_url(self.playback.queue()).and_then(crate::notes::get);
+        if note != self.shown_note {
+            widgets.note.set_label(note.as_deref().unwrap_or_default());
+            widgets.note.set_visible(note.is_some());
+            self.shown_note = note;
+        }
+        let auto_skip = tracklist::album_url(self.playback.queue()).is_some_and(crate::skips::auto_skip);
+        if auto_skip != self.shown_auto_skip {
+            self.shown_auto_skip = auto_skip;
+            widgets.auto_skip.set_active(auto_skip);
+        }
+        if tracklist::merch(self.playback.queue()) != self.shown_merch.as_slice() {
+            self.shown_merch = tracklist::merch(self.playback.queue()).to_vec();
+            let sender = sender.clone();
+            crate::merch::show(&widgets.merch_items, &self.shown_merch, move |url| {
+                sender.output(PlayerOutput::OpenInBrowser(url)).ok();
+            });
+        }
 
-    fn update_cmd(
-        &mut self,
-        bytes: Self::CommandOutput,
-        sender: ComponentSender<Self>,
-        _root: &Self::Root,
-    ) {
-        if !bytes.is_empty() {
-            sender.input(PlayerMsg::SetArt(bytes));
+        let snapshot = self.snapshot();
+        // Ticks only move the position; the queue is copied out for the
+        // remote API when anything else happened.
+        let queue = (!is_tick).then(|| {
+            let entries = self
+                .playback
+                .queue()
+                .iter()
+                .map(|t| crate::remote::QueueEntry {
+                    title: t.title.clone(),
+                    artist: t.artist.clone(),
+                    album: t.album.clone(),
+                    duration: t.duration,
+                })
+                .collect();
+            (self.playback.index(), entries)
+        });
+        crate::remote::publish(snapshot.clone(), queue);
+        // Nobody sees the widgets; showing the window again catches up.
+        if is_tick && self.background {
+            return;
+        }
+
+        self.waveform_area.update_property(&[
+            gtk4::accessible::Property::ValueMax(self.duration),
+            gtk4::accessible::Property::ValueNow(self.position),
+            gtk4::accessible::Property::ValueText(
+                &gettext("{position} of {duration}")
+                    .replace("{position}", &format_time(self.position))
+                    .replace("{duration}", &format_time(self.duration)),
+            ),
+        ]);
+
+        if self.mini_mode && !is_tick {
+            self.mini.emit(MiniPlayerMsg::Update(snapshot.clone()));
         }
+        self.now_playing.emit(NowPlayingMsg::Update(snapshot));
+
+        self.update_view(widgets, sender);
     }
 }
 
 impl Player {
     fn play_current(&mut self, sender: ComponentSender<Self>) {
-        let Some(track) = self.queue.get(self.queue_index).cloned() else {
+        let Some(track) = self.playback.current().cloned() else {
             return;
         };
 
+        // Local copies play whether offline or not; a download, possibly
+        // lossless, beats the cached stream.
+        let (uri, source) = match crate::downloads::local_uri(&track.url) {
+            Some(uri) => (uri, Source::Downloaded),
+            None => match crate::audio_cache::cached_uri(&track.url) {
+                Some(uri) => (uri, Source::Cached),
+                None => (track.stream_url.clone(), Source::Stream),
+            },
+        };
+        // Caching fetches the track a second time, which waits while
+        // saving data; pinning still downloads.
+        if source == Source::Stream && self.cache_audio && !track.url.is_empty() && !crate::data_saver::active() {
+            relm4::spawn(crate::audio_cache::store(track.url.clone(), track.stream_url.clone(), false));
+        }
+        self.source = source;
         self.pipeline.set_state(gst::State::Null).ok();
-        self.pipeline.set_property("uri", &track.stream_url);
+        self.pipeline.set_property("uri", &uri);
         self.pipeline.set_property("volume", self.volume);
         self.pipeline.set_state(gst::State::Playing).ok();
 
         self.playing = true;
         self.position = 0.0;
         self.duration = track.duration.unwrap_or(0.0);
-        self.art_pixbuf = None;
+        self.owned = crate::library::owned(&track.url);
         self.current_track = Some(track.clone());
+        self.pending_seek = None;
 
         let seed = format!("{}-{}", track.title, track.artist);
-        *self.waveform_bars.borrow_mut() = generate_waveform(&seed);
-        self.waveform_progress.set(0.0);
-        self.waveform_area.queue_draw();
+        self.waveform_area.set_seed(&seed);
 
-        if let Some(url) = &track.art_url {
-            let url = url.clone();
-            sender.oneshot_command(async move {
-                match reqwest::get(&url).await {
-                    Ok(r) => r.bytes().await.map(|b| b.to_vec()).unwrap_or_default(),
-                    Err(_) => Vec::new(),
+        if let Some(url) = track.art_url.clone() {
+            // Decoded once at the largest size shown; smaller views scale
+            // the same texture down.
+            let s = sender.clone();
+            gtk4::glib::spawn_future_local(async move {
+                let load_url = crate::data_saver::art_url(&url);
+                if let Some(texture) = crate::image_loader::load(&load_url, ART_SIZE).await {
+                    s.input(PlayerMsg::SetArt(url, texture));
                 }
             });
         }
 
         self.sync_mpris();
-        sender.output(PlayerOutput::NowPlaying).ok();
+        sender.output(PlayerOutput::NowPlaying(track)).ok();
     }
 
-    fn rebuild_tracklist(&self, sender: &ComponentSender<Self>) {
-        while let Some(child) = self.tracklist_box.first_child() {
-            self.tracklist_box.remove(&child);
-        }
-
-        for (i, track) in self.queue.iter().enumerate() {
-            let row = gtk4::Box::new(gtk4::Orientation::Horizontal, 8);
-            row.set_margin_start(12);
-            row.set_margin_end(12);
-            row.set_margin_top(4);
-            row.set_margin_bottom(4);
-
-            let num_label = gtk4::Label::new(Some(&format!("{}", i + 1)));
-            num_label.add_css_class("dim-label");
-            num_label.add_css_class("caption");
-            num_label.add_css_class("numeric");
-            num_label.set_width_chars(3);
-            num_label.set_xalign(1.0);
-            row.append(&num_label);
-
-            let title_label = gtk4::Label::new(Some(&track.title));
-            title_label.set_ellipsize(gtk4::pango::EllipsizeMode::End);
-            title_label.set_hexpand(true);
-            title_label.set_xalign(0.0);
-            title_label.add_css_class("caption");
-            if i == self.queue_index {
-                title_label.add_css_class("accent");
-            }
-            row.append(&title_label);
-
-            if let Some(dur) = track.duration {
-                let dur_label = gtk4::Label::new(Some(&format_time(dur)));
-                dur_label.add_css_class("dim-label");
-                dur_label.add_css_class("caption");
-                dur_label.add_css_class("numeric");
-                row.append(&dur_label);
-            }
-
-            let list_row = gtk4::ListBoxRow::new();
-            list_row.set_child(Some(&row));
-            list_row.set_cursor_from_name(Some("pointer"));
-
-            let s = sender.clone();
-            let click = gtk4::GestureClick::new();
-            click.connect_released(move |_, _, _, _| {
-                s.input(PlayerMsg::JumpToTrack(i));
-            });
-            list_row.add_controller(click);
+    /// Whether the queue holds releases in the signed-in account's
+    /// collection, which may stream in full only for their owner.
+    pub fn needs_account(&self) -> bool {
+        self.playback
+            .queue()
+            .iter()
+            .any(|t| crate::library::owned(&t.url) == Some(Owned::Collection))
+    }
 
-            self.tracklist_box.append(&list_row);
+    /// The current release's free download, unless it is owned already.
+    fn free_download(&self) -> Option<crate::bandcamp::FreeDownload> {
+        if self.owned == Some(Owned::Collection) {
+            return None;
         }
+        self.current_track.as_ref()?.free_download
     }
 
-    fn highlight_current_track(&self) {
-        let mut idx = 0;
-        let mut row = self.tracklist_box.first_child();
-        while let Some(widget) = row {
-            if let Some(list_row) = widget.downcast_ref::<gtk4::ListBoxRow>() {
-                if let Some(hbox) = list_row.child() {
-                    // The title label is the second child (after the number label)
-                    let mut child = hbox.first_child();
-                    let mut child_idx = 0;
-                    while let Some(c) = child {
-                        if child_idx == 1 {
-                            if idx == self.queue_index {
-                                c.add_css_class("accent");
-                            } else {
-                                c.remove_css_class("accent");
-                            }
-                        }
-                        child = c.next_sibling();
-                        child_idx += 1;
-                    }
-                }
-            }
-            row = widget.next_sibling();
-            idx += 1;
+    fn highlight_current_track(&mut self) {
+        tracklist::refresh(&mut self.tracklist, self.playback.index());
+    }
+
+    fn snapshot(&self) -> PlaybackSnapshot {
+        let track = self.current_track.as_ref();
+        PlaybackSnapshot {
+            title: track.map(|t| t.title.clone()).unwrap_or_default(),
+            artist: track.map(|t| t.artist.clone()).unwrap_or_default(),
+            album: track.map(|t| t.album.clone()).unwrap_or_default(),
+            playing: self.playing,
+            has_prev: self.playback.has_prev(),
+            has_next: self.playback.has_next(),
+            position: self.position,
+            duration: self.duration,
         }
     }
 
     fn sync_mpris(&self) {
         let mpris = self.mpris.clone();
+        let shuffle = self.playback.shuffle();
+        let loop_status = match self.playback.repeat() {
+            Repeat::Off => LoopStatus::None,
+            Repeat::All => LoopStatus::Playlist,
+            Repeat::One => LoopStatus::Track,
+        };
 
         let status = if self.playing {
             PlaybackStatus::Playing
@@ -767,13 +1535,21 @@ impl Player {
             let mut m = Metadata::new();
             m.set_title(Some(&t.title));
             m.set_artist(Some([&t.artist]));
+            m.set_album_artist(Some([&t.album_artist]));
             m.set_album(Some(&t.album));
             if let Some(url) = &t.art_url {
-                m.set_art_url(Some(url));
+                // Point at the local copy once cached so the shell doesn't
+                // download it again, even one of another size shown here.
+                match crate::image_loader::file(url) {
+                    Some(path) => m.set_art_url(Some(format!("file://{}", path.display()))),
+                    None => m.set_art_url(Some(url)),
+                }
             }
             if let Some(d) = t.duration {
                 m.set_length(Some(Time::from_micros((d * 1_000_000.0) as i64)));
             }
+            m.set_track_number(t.number.map(|n| n as i32));
+            m.set_disc_number(t.disc.map(|n| n as i32));
             m
         });
 
@@ -781,6 +1557,8 @@ impl Player {
             let binding = mpris.borrow();
             let Some(m) = binding.as_ref() else { return };
             m.set_playback_status(status).await.ok();
+            m.set_shuffle(shuffle).await.ok();
+            m.set_loop_status(loop_status).await.ok();
             if let Some(meta) = meta {
                 m.set_metadata(meta).await.ok();
             }
@@ -801,22 +1579,11 @@ impl Player {
 impl Drop for Player {
     fn drop(&mut self) {
         self.pipeline.set_state(gst::State::Null).ok();
+        self.mini.widget().destroy();
     }
 }
 
-fn load_pixbuf(bytes: &[u8], size: i32) -> Option<gtk4::gdk_pixbuf::Pixbuf> {
-    let stream = gtk4::gio::MemoryInputStream::from_bytes(&gtk4::glib::Bytes::from(bytes));
-    gtk4::gdk_pixbuf::Pixbuf::from_stream_at_scale(
-        &stream,
-        size,
-        size,
-        true,
-        None::<&gtk4::gio::Cancellable>,
-    )
-    .ok()
-}
-
-fn format_time(secs: f64) -> String {
+pub fn format_time(secs: f64) -> String {
     let t = secs as u64;
     format!("{}:{:02}", t / 60, t % 60)
 }
@@ -0,0 +1,54 @@
+use crate::storage::{History, Listen};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// How often releases and their tracks were listened to, counted from
+/// the listening history, so they follow its retention setting.
+#[derive(Debug, Default)]
+struct Counts {
+    /// By release page.
+    albums: HashMap<String, u32>,
+    /// By release page and track title.
+    tracks: HashMap<(String, String), u32>,
+}
+
+impl Counts {
+    fn add(&mut self, listen: &Listen) {
+        let Some(url) = listen.url.as_deref().and_then(release) else { return };
+        *self.albums.entry(url.clone()).or_default() += 1;
+        *self.tracks.entry((url, listen.title.clone())).or_default() += 1;
+    }
+}
+
+thread_local! {
+    static COUNTS: RefCell<Counts> = RefCell::default();
+}
+
+/// The release page a track or release URL belongs to.
+fn release(url: &str) -> Option<String> {
+    crate::bandcamp::parse_release_link(url).map(|link| link.url)
+}
+
+/// Counts the listens in `history` afresh, e.g. for another account or
+/// after it was cleared.
+pub fn reload(history: &History) {
+    let mut counts = Counts::default();
+    history.listens.iter().for_each(|listen| counts.add(listen));
+    COUNTS.with(|c| c.replace(counts));
+}
+
+/// Counts a listen just recorded.
+pub fn add(listen: &Listen) {
+    COUNTS.with(|c| c.borrow_mut().add(listen));
+}
+
+pub fn album(url: &str) -> u32 {
+    let Some(url) = release(url) else { return 0 };
+    COUNTS.with(|c| c.borrow().albums.get(&url).copied().unwrap_or(0))
+}
+
+/// Plays of the track titled `title` on the release at `url`.
+pub fn track(url: &str, title: &str) -> u32 {
+    let Some(url) = release(url) else { return 0 };
+    COUNTS.with(|c| c.borrow().tracks.get(&(url, title.to_string())).copied().unwrap_or(0))
+}
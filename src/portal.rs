@@ -0,0 +1,98 @@
+use anyhow::Result;
+use ashpd::desktop::background::Background;
+use ashpd::desktop::global_shortcuts::{GlobalShortcuts, NewShortcut};
+use ashpd::desktop::open_uri::OpenFileRequest;
+use ashpd::WindowIdentifier;
+use futures_util::StreamExt;
+use gettextrs::gettext;
+use gtk4::prelude::*;
+
+/// Opens `uri` in the default browser, going through the OpenURI portal
+/// when running inside a sandbox.
+pub async fn open_uri(window: &impl IsA<gtk4::Native>, uri: &str) -> Result<()> {
+    if !ashpd::is_sandboxed().await {
+        open::that(uri)?;
+        return Ok(());
+    }
+
+    let uri = ashpd::url::Url::parse(uri)?;
+    let identifier = WindowIdentifier::from_native(window).await;
+    OpenFileRequest::default()
+        .identifier(identifier)
+        .send_uri(&uri)
+        .await?;
+    Ok(())
+}
+
+/// Asks the Background portal to let playback continue with the window
+/// closed. Unsandboxed processes don't need permission.
+pub async fn request_background(window: &impl IsA<gtk4::Native>) -> Result<bool> {
+    if !ashpd::is_sandboxed().await {
+        return Ok(true);
+    }
+
+    let identifier = WindowIdentifier::from_native(window).await;
+    let response = Background::request()
+        .identifier(identifier)
+        .reason(gettext("Keep playing music after the window is closed").as_str())
+        .auto_start(false)
+        .dbus_activatable(false)
+        .send()
+        .await?
+        .response()?;
+    Ok(response.run_in_background())
+}
+
+/// Playback actions bound system-wide through the GlobalShortcuts portal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlobalShortcut {
+    PlayPause,
+    Next,
+    Previous,
+}
+
+impl GlobalShortcut {
+    /// What the desktop lists the shortcut as in its settings.
+    fn description(self) -> String {
+        match self {
+            GlobalShortcut::PlayPause => gettext("Play or pause"),
+            GlobalShortcut::Next => gettext("Next track"),
+            GlobalShortcut::Previous => gettext("Previous track"),
+        }
+    }
+}
+
+/// Shortcut IDs and the triggers suggested to the desktop, which lets the
+/// user pick others.
+const GLOBAL_SHORTCUTS: &[(GlobalShortcut, &str, &str)] = &[
+    (GlobalShortcut::PlayPause, "play-pause", "XF86AudioPlay"),
+    (GlobalShortcut::Next, "next", "XF86AudioNext"),
+    (GlobalShortcut::Previous, "previous", "XF86AudioPrev"),
+];
+
+/// Binds the playback shortcuts and calls `activated` each time one is
+/// pressed, for as long as the future runs. Dropping it ends the session.
+pub async fn global_shortcuts(window: &impl IsA<gtk4::Native>, activated: impl Fn(GlobalShortcut)) -> Result<()> {
+    let portal = GlobalShortcuts::new().await?;
+    let session = portal.create_session().await?;
+    let shortcuts: Vec<NewShortcut> = GLOBAL_SHORTCUTS
+        .iter()
+        .map(|(shortcut, id, trigger)| NewShortcut::new(*id, shortcut.description()).preferred_trigger(*trigger))
+        .collect();
+    let identifier = WindowIdentifier::from_native(window).await;
+    portal
+        .bind_shortcuts(&session, &shortcuts, identifier.as_ref())
+        .await?
+        .response()?;
+
+    let mut activations = std::pin::pin!(portal.receive_activated().await?);
+    while let Some(activation) = activations.next().await {
+        let shortcut = GLOBAL_SHORTCUTS
+            .iter()
+            .find(|(_, id, _)| *id == activation.shortcut_id());
+        if let Some((shortcut, ..)) = shortcut {
+            activated(*shortcut);
+        }
+    }
+    Ok(())
+}
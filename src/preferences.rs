@@ -0,0 +1,698 @@
+use crate::album_grid::{self, CARD_SIZE_MAX, CARD_SIZE_MIN, CARD_SIZE_STEP};
+use crate::app::AppMsg;
+use crate::art_cache;
+use crate::audio_cache;
+use crate::remote;
+use crate::storage::Settings;
+use crate::N_;
+use gettextrs::gettext;
+use gtk4::prelude::*;
+use libadwaita as adw;
+use libadwaita::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorScheme {
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+const COLOR_SCHEMES: &[(ColorScheme, &str)] = &[
+    (ColorScheme::System, N_("Follow System")),
+    (ColorScheme::Light, N_("Light")),
+    (ColorScheme::Dark, N_("Dark")),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StartPage {
+    #[default]
+    Last,
+    Search,
+    Discover,
+    Library,
+}
+
+const START_PAGES: &[(StartPage, &str)] = &[
+    (StartPage::Last, N_("Last Opened")),
+    (StartPage::Search, N_("Search")),
+    (StartPage::Discover, N_("Discover")),
+    (StartPage::Library, N_("Library")),
+];
+
+/// Where the Bandcamp session cookie is kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CookieBackend {
+    #[default]
+    Keyring,
+    File,
+}
+
+const COOKIE_BACKENDS: &[(CookieBackend, &str)] = &[
+    (CookieBackend::Keyring, N_("System Keyring")),
+    (CookieBackend::File, N_("Plain File")),
+];
+
+/// How long play and search history are kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryRetention {
+    #[default]
+    Forever,
+    Days90,
+    /// Cleared whenever Camper starts or quits.
+    Session,
+    Off,
+}
+
+const HISTORY_RETENTIONS: &[(HistoryRetention, &str)] = &[
+    (HistoryRetention::Forever, N_("Forever")),
+    (HistoryRetention::Days90, N_("90 Days")),
+    (HistoryRetention::Session, N_("Until Camper Quits")),
+    (HistoryRetention::Off, N_("Don't Keep")),
+];
+
+/// When to hold back on data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DataSaver {
+    /// While the connection is metered, e.g. a phone hotspot.
+    #[default]
+    Metered,
+    Always,
+    Never,
+}
+
+const DATA_SAVERS: &[(DataSaver, &str)] = &[
+    (DataSaver::Metered, N_("On Metered Connections")),
+    (DataSaver::Always, N_("Always")),
+    (DataSaver::Never, N_("Never")),
+];
+
+/// What a middle or modified click on an album card does. The card's
+/// context menu offers all of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CardAction {
+    #[default]
+    Play,
+    Enqueue,
+    OpenInBrowser,
+    CopyLink,
+    KeepOffline,
+    Download,
+    Wishlist,
+}
+
+const CARD_ACTIONS: &[(CardAction, &str)] = &[
+    (CardAction::Play, N_("Play")),
+    (CardAction::Enqueue, N_("Add to Queue")),
+    (CardAction::OpenInBrowser, N_("Open in Browser")),
+    (CardAction::CopyLink, N_("Copy Link")),
+    (CardAction::KeepOffline, N_("Keep Offline")),
+    (CardAction::Download, N_("Download")),
+    (CardAction::Wishlist, N_("Add to Wishlist")),
+];
+
+/// The file format purchases are downloaded in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DownloadFormat {
+    #[default]
+    Mp3,
+    Flac,
+    Vorbis,
+}
+
+const DOWNLOAD_FORMATS: &[(DownloadFormat, &str)] = &[
+    (DownloadFormat::Mp3, N_("MP3 320")),
+    (DownloadFormat::Flac, N_("FLAC")),
+    (DownloadFormat::Vorbis, N_("Ogg Vorbis")),
+];
+
+impl DownloadFormat {
+    /// Bandcamp's name for the format.
+    pub fn id(self) -> &'static str {
+        match self {
+            DownloadFormat::Mp3 => "mp3-320",
+            DownloadFormat::Flac => "flac",
+            DownloadFormat::Vorbis => "vorbis",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            DownloadFormat::Mp3 => "mp3",
+            DownloadFormat::Flac => "flac",
+            DownloadFormat::Vorbis => "ogg",
+        }
+    }
+}
+
+impl StartPage {
+    /// The tab to open, or `None` to restore the last one.
+    pub fn tab(self) -> Option<&'static str> {
+        match self {
+            StartPage::Last => None,
+            StartPage::Search => Some("search"),
+            StartPage::Discover => Some("discover"),
+            StartPage::Library => Some("library"),
+        }
+    }
+}
+
+impl ColorScheme {
+    pub fn apply(self) {
+        adw::StyleManager::default().set_color_scheme(match self {
+            ColorScheme::System => adw::ColorScheme::Default,
+            ColorScheme::Light => adw::ColorScheme::ForceLight,
+            ColorScheme::Dark => adw::ColorScheme::ForceDark,
+        });
+    }
+}
+
+pub fn build_dialog(
+    sender: &relm4::Sender<AppMsg>,
+    settings: &Settings,
+    username: &str,
+) -> adw::PreferencesDialog {
+    let dialog = adw::PreferencesDialog::new();
+
+    let page = adw::PreferencesPage::new();
+    page.set_title(&gettext("General"));
+    page.set_icon_name(Some("preferences-system-symbolic"));
+
+    let appearance = adw::PreferencesGroup::new();
+    appearance.set_title(&gettext("Appearance"));
+
+    let scheme_row = adw::ComboRow::new();
+    scheme_row.set_title(&gettext("Style"));
+    let labels: Vec<String> = COLOR_SCHEMES.iter().map(|(_, l)| gettext(*l)).collect();
+    scheme_row.set_model(Some(&gtk4::StringList::new(
+        &labels.iter().map(String::as_str).collect::<Vec<_>>(),
+    )));
+    let current = settings.color_scheme.unwrap_or_default();
+    if let Some(i) = COLOR_SCHEMES.iter().position(|(c, _)| *c == current) {
+        scheme_row.set_selected(i as u32);
+    }
+    let s = sender.clone();
+    scheme_row.connect_selected_notify(move |row| {
+        if let Some((scheme, _)) = COLOR_SCHEMES.get(row.selected() as usize) {
+            s.emit(AppMsg::SetColorScheme(*scheme));
+        }
+    });
+    appearance.add(&scheme_row);
+
+    let card_size_row = adw::ActionRow::new();
+    card_size_row.set_title(&gettext("Album Art Size"));
+    card_size_row.set_subtitle(&gettext("Also adjustable with Ctrl+Plus and Ctrl+Minus"));
+    let card_size_scale = gtk4::Scale::with_range(
+        gtk4::Orientation::Horizontal,
+        CARD_SIZE_MIN as f64,
+        CARD_SIZE_MAX as f64,
+        CARD_SIZE_STEP as f64,
+    );
+    card_size_scale.set_value(album_grid::card_size() as f64);
+    card_size_scale.set_round_digits(0);
+    card_size_scale.set_width_request(160);
+    card_size_scale.set_valign(gtk4::Align::Center);
+    card_size_scale.update_property(&[gtk4::accessible::Property::Label(&gettext("Album Art Size"))]);
+    let s = sender.clone();
+    card_size_scale.connect_value_changed(move |scale| {
+        // Snap to the same steps the keyboard zoom uses.
+        let steps = ((scale.value() - CARD_SIZE_MIN as f64) / CARD_SIZE_STEP as f64).round() as i32;
+        s.emit(AppMsg::SetCardSize(CARD_SIZE_MIN + steps * CARD_SIZE_STEP));
+    });
+    card_size_row.add_suffix(&card_size_scale);
+    appearance.add(&card_size_row);
+
+    let currency_row = adw::ComboRow::new();
+    currency_row.set_title(&gettext("Show Prices In"));
+    currency_row.set_subtitle(&gettext("Converted at the day's exchange rates, for comparing"));
+    let mut labels = vec![gettext("Artist's Currency")];
+    labels.extend(crate::exchange::CURRENCIES.iter().map(|c| c.to_string()));
+    currency_row.set_model(Some(&gtk4::StringList::new(
+        &labels.iter().map(String::as_str).collect::<Vec<_>>(),
+    )));
+    if let Some(i) = settings
+        .price_currency
+        .as_deref()
+        .and_then(|current| crate::exchange::CURRENCIES.iter().position(|c| *c == current))
+    {
+        currency_row.set_selected(i as u32 + 1);
+    }
+    let s = sender.clone();
+    currency_row.connect_selected_notify(move |row| {
+        let currency = (row.selected() as usize).checked_sub(1).and_then(|i| crate::exchange::CURRENCIES.get(i));
+        s.emit(AppMsg::SetPriceCurrency(currency.map(|c| c.to_string())));
+    });
+    appearance.add(&currency_row);
+    page.add(&appearance);
+
+    let behavior = adw::PreferencesGroup::new();
+    behavior.set_title(&gettext("Behavior"));
+
+    let clipboard_row = adw::SwitchRow::new();
+    clipboard_row.set_title(&gettext("Watch Clipboard"));
+    clipboard_row.set_subtitle(&gettext("Offer to play Bandcamp links when they are copied"));
+    clipboard_row.set_active(settings.watch_clipboard.unwrap_or(false));
+    let s = sender.clone();
+    clipboard_row.connect_active_notify(move |row| {
+        s.emit(AppMsg::SetWatchClipboard(row.is_active()));
+    });
+    behavior.add(&clipboard_row);
+
+    let background_row = adw::SwitchRow::new();
+    background_row.set_title(&gettext("Run in Background"));
+    background_row.set_subtitle(&gettext("Keep playing after the window is closed"));
+    background_row.set_active(settings.run_in_background.unwrap_or(false));
+    let s = sender.clone();
+    background_row.connect_active_notify(move |row| {
+        s.emit(AppMsg::SetRunInBackground(row.is_active()));
+    });
+    behavior.add(&background_row);
+
+    let shortcuts_row = adw::SwitchRow::new();
+    shortcuts_row.set_title(&gettext("Global Shortcuts"));
+    shortcuts_row.set_subtitle(&gettext("Play, pause and skip from any app, where media keys don't reach Camper"));
+    shortcuts_row.set_active(settings.global_shortcuts.unwrap_or(false));
+    let s = sender.clone();
+    shortcuts_row.connect_active_notify(move |row| {
+        s.emit(AppMsg::SetGlobalShortcuts(row.is_active()));
+    });
+    behavior.add(&shortcuts_row);
+
+    let data_saver_row = adw::ComboRow::new();
+    data_saver_row.set_title(&gettext("Save Data"));
+    data_saver_row.set_subtitle(&gettext("Smaller artwork, no prefetching and no automatic refreshes"));
+    let labels: Vec<String> = DATA_SAVERS.iter().map(|(_, l)| gettext(*l)).collect();
+    data_saver_row.set_model(Some(&gtk4::StringList::new(
+        &labels.iter().map(String::as_str).collect::<Vec<_>>(),
+    )));
+    let current = settings.data_saver.unwrap_or_default();
+    if let Some(i) = DATA_SAVERS.iter().position(|(d, _)| *d == current) {
+        data_saver_row.set_selected(i as u32);
+    }
+    let s = sender.clone();
+    data_saver_row.connect_selected_notify(move |row| {
+        if let Some((mode, _)) = DATA_SAVERS.get(row.selected() as usize) {
+            s.emit(AppMsg::SetDataSaver(*mode));
+        }
+    });
+    behavior.add(&data_saver_row);
+
+    behavior.add(&card_action_row(
+        &gettext("Middle-Click or Ctrl+Click"),
+        settings.middle_click.unwrap_or(CardAction::Enqueue),
+        sender,
+        AppMsg::SetMiddleClick,
+    ));
+    behavior.add(&card_action_row(
+        &gettext("Shift+Click"),
+        settings.shift_click.unwrap_or(CardAction::OpenInBrowser),
+        sender,
+        AppMsg::SetShiftClick,
+    ));
+    page.add(&behavior);
+
+    let playback = adw::PreferencesGroup::new();
+    playback.set_title(&gettext("Playback"));
+
+    let crossfeed_row = adw::SwitchRow::new();
+    crossfeed_row.set_title(&gettext("Headphone Crossfeed"));
+    crossfeed_row.set_subtitle(&gettext("Blend the stereo channels a little, for less fatigue on headphones"));
+    crossfeed_row.set_active(settings.crossfeed.unwrap_or(false));
+    let s = sender.clone();
+    crossfeed_row.connect_active_notify(move |row| {
+        s.emit(AppMsg::SetCrossfeed(row.is_active()));
+    });
+    playback.add(&crossfeed_row);
+    page.add(&playback);
+
+    let startup = adw::PreferencesGroup::new();
+    startup.set_title(&gettext("Startup"));
+
+    let start_page_row = adw::ComboRow::new();
+    start_page_row.set_title(&gettext("Start Page"));
+    let labels: Vec<String> = START_PAGES.iter().map(|(_, l)| gettext(*l)).collect();
+    start_page_row.set_model(Some(&gtk4::StringList::new(
+        &labels.iter().map(String::as_str).collect::<Vec<_>>(),
+    )));
+    let current = settings.start_page.unwrap_or_default();
+    if let Some(i) = START_PAGES.iter().position(|(p, _)| *p == current) {
+        start_page_row.set_selected(i as u32);
+    }
+    let s = sender.clone();
+    start_page_row.connect_selected_notify(move |row| {
+        if let Some((page, _)) = START_PAGES.get(row.selected() as usize) {
+            s.emit(AppMsg::SetStartPage(*page));
+        }
+    });
+    startup.add(&start_page_row);
+
+    let resume_row = adw::SwitchRow::new();
+    resume_row.set_title(&gettext("Resume Playback"));
+    resume_row.set_subtitle(&gettext("Play the last album again when Camper starts"));
+    resume_row.set_active(settings.resume_playback.unwrap_or(false));
+    let s = sender.clone();
+    resume_row.connect_active_notify(move |row| {
+        s.emit(AppMsg::SetResumePlayback(row.is_active()));
+    });
+    startup.add(&resume_row);
+
+    let refresh_row = adw::SwitchRow::new();
+    refresh_row.set_title(&gettext("Refresh Library Automatically"));
+    refresh_row.set_subtitle(&gettext("Reload your collection at startup and when opening the Library"));
+    refresh_row.set_active(settings.auto_refresh_library.unwrap_or(true));
+    let s = sender.clone();
+    refresh_row.connect_active_notify(move |row| {
+        s.emit(AppMsg::SetAutoRefreshLibrary(row.is_active()));
+    });
+    startup.add(&refresh_row);
+    page.add(&startup);
+
+    let privacy = adw::PreferencesGroup::new();
+    privacy.set_title(&gettext("Privacy"));
+
+    let retention_row = adw::ComboRow::new();
+    retention_row.set_title(&gettext("Keep History"));
+    retention_row.set_subtitle(&gettext("Played tracks and searches"));
+    let labels: Vec<String> = HISTORY_RETENTIONS.iter().map(|(_, l)| gettext(*l)).collect();
+    retention_row.set_model(Some(&gtk4::StringList::new(
+        &labels.iter().map(String::as_str).collect::<Vec<_>>(),
+    )));
+    let current = settings.history_retention.unwrap_or_default();
+    if let Some(i) = HISTORY_RETENTIONS.iter().position(|(r, _)| *r == current) {
+        retention_row.set_selected(i as u32);
+    }
+    let s = sender.clone();
+    retention_row.connect_selected_notify(move |row| {
+        if let Some((retention, _)) = HISTORY_RETENTIONS.get(row.selected() as usize) {
+            s.emit(AppMsg::SetHistoryRetention(*retention));
+        }
+    });
+    privacy.add(&retention_row);
+
+    let clear_history_row = adw::ButtonRow::new();
+    clear_history_row.set_title(&gettext("Clear History"));
+    clear_history_row.add_css_class("destructive-action");
+    let s = sender.clone();
+    clear_history_row.connect_activated(move |_| s.emit(AppMsg::ClearHistory));
+    privacy.add(&clear_history_row);
+
+    let ephemeral_row = adw::SwitchRow::new();
+    ephemeral_row.set_title(&gettext("Private Login Browser"));
+    ephemeral_row.set_subtitle(&gettext("Keep no website data from the sign-in page. Applies after restarting."));
+    ephemeral_row.set_active(settings.ephemeral_login.unwrap_or(false));
+    let s = sender.clone();
+    ephemeral_row.connect_active_notify(move |row| {
+        s.emit(AppMsg::SetEphemeralLogin(row.is_active()));
+    });
+    privacy.add(&ephemeral_row);
+    page.add(&privacy);
+
+    page.add(&remote_group(sender, settings));
+    page.add(&downloads_group(sender, settings));
+
+    let storage = adw::PreferencesGroup::new();
+    storage.set_title(&gettext("Storage"));
+
+    let cache_row = adw::ActionRow::new();
+    cache_row.set_title(&gettext("Artwork Cache"));
+    cache_row.set_subtitle(&gtk4::glib::format_size(art_cache::usage()));
+    let clear_btn = gtk4::Button::with_label(&gettext("Clear"));
+    clear_btn.set_valign(gtk4::Align::Center);
+    let row = cache_row.downgrade();
+    clear_btn.connect_clicked(move |_| {
+        art_cache::clear();
+        crate::image_loader::clear();
+        if let Some(row) = row.upgrade() {
+            row.set_subtitle(&gtk4::glib::format_size(art_cache::usage()));
+        }
+    });
+    cache_row.add_suffix(&clear_btn);
+    storage.add(&cache_row);
+
+    let audio_cache_row = adw::SwitchRow::new();
+    audio_cache_row.set_title(&gettext("Cache Played Tracks"));
+    audio_cache_row.set_subtitle(&gettext("Keep streamed tracks on disk to replay them offline"));
+    audio_cache_row.set_active(settings.audio_cache.unwrap_or(false));
+    let s = sender.clone();
+    audio_cache_row.connect_active_notify(move |row| {
+        s.emit(AppMsg::SetAudioCache(row.is_active()));
+    });
+    storage.add(&audio_cache_row);
+
+    let audio_usage_row = adw::ActionRow::new();
+    audio_usage_row.set_title(&gettext("Audio Cache"));
+    audio_usage_row.set_subtitle(&gtk4::glib::format_size(audio_cache::usage()));
+    let clear_btn = gtk4::Button::with_label(&gettext("Clear"));
+    clear_btn.set_valign(gtk4::Align::Center);
+    let row = audio_usage_row.downgrade();
+    clear_btn.connect_clicked(move |_| {
+        audio_cache::clear();
+        if let Some(row) = row.upgrade() {
+            row.set_subtitle(&gtk4::glib::format_size(audio_cache::usage()));
+        }
+    });
+    audio_usage_row.add_suffix(&clear_btn);
+    storage.add(&audio_usage_row);
+
+    let backup_row = adw::ActionRow::new();
+    backup_row.set_title(&gettext("Settings Backup"));
+    backup_row.set_subtitle(&gettext(
+        "Move preferences, play history, ratings, notes and your listen later list to another computer. \
+         Your session is not included.",
+    ));
+    let import_btn = gtk4::Button::with_label(&gettext("Import…"));
+    import_btn.set_valign(gtk4::Align::Center);
+    let s = sender.clone();
+    import_btn.connect_clicked(move |_| s.emit(AppMsg::ImportSettings));
+    let export_btn = gtk4::Button::with_label(&gettext("Export…"));
+    export_btn.set_valign(gtk4::Align::Center);
+    let s = sender.clone();
+    export_btn.connect_clicked(move |_| s.emit(AppMsg::ExportSettings));
+    backup_row.add_suffix(&import_btn);
+    backup_row.add_suffix(&export_btn);
+    storage.add(&backup_row);
+    page.add(&storage);
+
+    let account = adw::PreferencesGroup::new();
+    account.set_title(&gettext("Account"));
+
+    let account_row = adw::ActionRow::new();
+    account_row.set_use_markup(false);
+    account_row.set_title(username);
+    account_row.set_subtitle(&gettext("Signed in to Bandcamp"));
+
+    let logout_btn = gtk4::Button::with_label(&gettext("Logout"));
+    logout_btn.set_valign(gtk4::Align::Center);
+    logout_btn.add_css_class("destructive-action");
+    let s = sender.clone();
+    let dialog_ref = dialog.downgrade();
+    logout_btn.connect_clicked(move |_| {
+        s.emit(AppMsg::Logout);
+        if let Some(d) = dialog_ref.upgrade() {
+            d.close();
+        }
+    });
+    account_row.add_suffix(&logout_btn);
+    account.add(&account_row);
+
+    let backend_row = adw::ComboRow::new();
+    backend_row.set_title(&gettext("Store Session In"));
+    backend_row.set_subtitle(&gettext("Falls back to a private file when no keyring is available"));
+    let labels: Vec<String> = COOKIE_BACKENDS.iter().map(|(_, l)| gettext(*l)).collect();
+    backend_row.set_model(Some(&gtk4::StringList::new(
+        &labels.iter().map(String::as_str).collect::<Vec<_>>(),
+    )));
+    let current = settings.cookie_backend.unwrap_or_default();
+    if let Some(i) = COOKIE_BACKENDS.iter().position(|(b, _)| *b == current) {
+        backend_row.set_selected(i as u32);
+    }
+    let s = sender.clone();
+    backend_row.connect_selected_notify(move |row| {
+        if let Some((backend, _)) = COOKIE_BACKENDS.get(row.selected() as usize) {
+            s.emit(AppMsg::SetCookieBackend(*backend));
+        }
+    });
+    account.add(&backend_row);
+
+    page.add(&account);
+    dialog.add(&page);
+
+    dialog
+}
+
+fn card_action_row(
+    title: &str,
+    current: CardAction,
+    sender: &relm4::Sender<AppMsg>,
+    msg: fn(CardAction) -> AppMsg,
+) -> adw::ComboRow {
+    let row = adw::ComboRow::new();
+    row.set_title(title);
+    row.set_subtitle(&gettext("On an album"));
+    let labels: Vec<String> = CARD_ACTIONS.iter().map(|(_, l)| gettext(*l)).collect();
+    row.set_model(Some(&gtk4::StringList::new(
+        &labels.iter().map(String::as_str).collect::<Vec<_>>(),
+    )));
+    if let Some(i) = CARD_ACTIONS.iter().position(|(a, _)| *a == current) {
+        row.set_selected(i as u32);
+    }
+    let s = sender.clone();
+    row.connect_selected_notify(move |row| {
+        if let Some((action, _)) = CARD_ACTIONS.get(row.selected() as usize) {
+            s.emit(msg(*action));
+        }
+    });
+    row
+}
+
+fn remote_group(sender: &relm4::Sender<AppMsg>, settings: &Settings) -> adw::PreferencesGroup {
+    let group = adw::PreferencesGroup::new();
+    group.set_title(&gettext("Remote Control"));
+    group.set_description(Some(&gettext(
+        "An HTTP API on this computer for scripts and web remotes: GET /now-playing, /queue and /events; POST /play, /pause, /play-pause, /next, /previous, or /queue with a release URL as the body.",
+    )));
+
+    let enable_row = adw::SwitchRow::new();
+    enable_row.set_title(&gettext("Enable Remote Control"));
+    enable_row.set_active(settings.remote_control.unwrap_or(false));
+    let s = sender.clone();
+    enable_row.connect_active_notify(move |row| {
+        s.emit(AppMsg::SetRemoteControl(row.is_active()));
+    });
+    group.add(&enable_row);
+
+    let port_row = adw::SpinRow::with_range(1024.0, 65535.0, 1.0);
+    port_row.set_title(&gettext("Port"));
+    port_row.set_value(settings.remote_port.unwrap_or(remote::DEFAULT_PORT) as f64);
+    let s = sender.clone();
+    port_row.connect_value_notify(move |row| {
+        s.emit(AppMsg::SetRemotePort(row.value() as u16));
+    });
+    group.add(&port_row);
+
+    // Generated up front so the token can be copied before enabling.
+    let token = match settings.remote_token.clone() {
+        Some(token) => token,
+        None => {
+            let token = remote::generate_token();
+            sender.emit(AppMsg::SetRemoteToken(token.clone()));
+            token
+        }
+    };
+    let token_row = adw::ActionRow::new();
+    token_row.set_title(&gettext("Access Token"));
+    token_row.set_subtitle(&token);
+    token_row.set_subtitle_selectable(true);
+    token_row.add_css_class("property");
+
+    let copy_btn = gtk4::Button::from_icon_name("edit-copy-symbolic");
+    copy_btn.set_tooltip_text(Some(&gettext("Copy Token")));
+    copy_btn.set_valign(gtk4::Align::Center);
+    copy_btn.add_css_class("flat");
+    let row = token_row.downgrade();
+    copy_btn.connect_clicked(move |btn| {
+        if let Some(row) = row.upgrade() {
+            btn.clipboard().set_text(&row.subtitle().unwrap_or_default());
+        }
+    });
+
+    let regenerate_btn = gtk4::Button::from_icon_name("view-refresh-symbolic");
+    regenerate_btn.set_tooltip_text(Some(&gettext("Generate New Token")));
+    regenerate_btn.set_valign(gtk4::Align::Center);
+    regenerate_btn.add_css_class("flat");
+    let row = token_row.downgrade();
+    let s = sender.clone();
+    regenerate_btn.connect_clicked(move |_| {
+        let token = remote::generate_token();
+        if let Some(row) = row.upgrade() {
+            row.set_subtitle(&token);
+        }
+        s.emit(AppMsg::SetRemoteToken(token));
+    });
+    token_row.add_suffix(&copy_btn);
+    token_row.add_suffix(&regenerate_btn);
+    group.add(&token_row);
+
+    for row in [port_row.upcast_ref::<gtk4::Widget>(), token_row.upcast_ref()] {
+        enable_row.bind_property("active", row, "sensitive").sync_create().build();
+    }
+    group
+}
+
+fn downloads_group(sender: &relm4::Sender<AppMsg>, settings: &Settings) -> adw::PreferencesGroup {
+    let group = adw::PreferencesGroup::new();
+    group.set_title(&gettext("Downloads"));
+    group.set_description(Some(&gettext("Where purchases are saved when using Download on an album")));
+
+    let dir = settings
+        .download_dir
+        .as_ref()
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(crate::downloads::default_dir);
+    let folder_row = adw::ActionRow::new();
+    folder_row.set_title(&gettext("Folder"));
+    folder_row.set_subtitle(&dir.display().to_string());
+    let choose_btn = gtk4::Button::with_label(&gettext("Choose…"));
+    choose_btn.set_valign(gtk4::Align::Center);
+    let row = folder_row.downgrade();
+    let s = sender.clone();
+    choose_btn.connect_clicked(move |btn| {
+        let window = btn.root().and_downcast::<gtk4::Window>();
+        let file_dialog = gtk4::FileDialog::new();
+        file_dialog.set_title(&gettext("Download Folder"));
+        file_dialog.set_initial_folder(Some(&gtk4::gio::File::for_path(&dir)));
+        let (row, s) = (row.clone(), s.clone());
+        gtk4::glib::spawn_future_local(async move {
+            let Ok(folder) = file_dialog.select_folder_future(window.as_ref()).await else { return };
+            let Some(path) = folder.path() else { return };
+            if let Some(row) = row.upgrade() {
+                row.set_subtitle(&path.display().to_string());
+            }
+            s.emit(AppMsg::SetDownloadDir(path.display().to_string()));
+        });
+    });
+    folder_row.add_suffix(&choose_btn);
+    group.add(&folder_row);
+
+    let template_row = adw::EntryRow::new();
+    template_row.set_title(&gettext("File Names ({artist}, {album}, {track_num}, {title})"));
+    template_row.set_text(settings.download_template.as_deref().unwrap_or(crate::downloads::DEFAULT_TEMPLATE));
+    template_row.set_show_apply_button(true);
+    let s = sender.clone();
+    template_row.connect_apply(move |row| {
+        let template = row.text().trim().to_string();
+        if template.is_empty() {
+            row.set_text(crate::downloads::DEFAULT_TEMPLATE);
+        }
+        s.emit(AppMsg::SetDownloadTemplate(template));
+    });
+    group.add(&template_row);
+
+    let format_row = adw::ComboRow::new();
+    format_row.set_title(&gettext("Format"));
+    let labels: Vec<String> = DOWNLOAD_FORMATS.iter().map(|(_, l)| gettext(*l)).collect();
+    format_row.set_model(Some(&gtk4::StringList::new(
+        &labels.iter().map(String::as_str).collect::<Vec<_>>(),
+    )));
+    let current = settings.download_format.unwrap_or_default();
+    if let Some(i) = DOWNLOAD_FORMATS.iter().position(|(f, _)| *f == current) {
+        format_row.set_selected(i as u32);
+    }
+    let s = sender.clone();
+    format_row.connect_selected_notify(move |row| {
+        if let Some((format, _)) = DOWNLOAD_FORMATS.get(row.selected() as usize) {
+            s.emit(AppMsg::SetDownloadFormat(*format));
+        }
+    });
+    group.add(&format_row);
+
+    group
+}
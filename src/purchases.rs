@@ -0,0 +1,235 @@
+use crate::album_grid::{AlbumData, Click};
+use crate::bandcamp::{BandcampClient, ClientError, Purchase};
+use gettextrs::gettext;
+use gtk4::glib;
+use gtk4::prelude::*;
+use libadwaita as adw;
+use libadwaita::prelude::*;
+use relm4::prelude::*;
+
+/// The fan's order history, digital and physical, pushed onto the
+/// library's navigation stack.
+pub struct PurchasesPage {
+    client: BandcampClient,
+    purchases: Vec<Purchase>,
+    loading: bool,
+    error: Option<ClientError>,
+}
+
+#[derive(Debug)]
+pub enum PurchasesMsg {
+    Loaded(Result<Vec<Purchase>, ClientError>),
+    Retry,
+    Activate(usize),
+    Download(usize),
+}
+
+#[derive(Debug)]
+pub enum PurchasesOutput {
+    Activate(AlbumData, Click),
+    /// Save a digital purchase to the download folder.
+    Download(AlbumData),
+    Error(ClientError),
+}
+
+/// The receipt line under an order: when, for how much and its number.
+fn receipt(purchase: &Purchase) -> String {
+    let mut parts = vec![purchase.artist.clone()];
+    if let Some(date) = purchase.date.and_then(|d| glib::DateTime::from_unix_local(d as i64).ok()) {
+        parts.extend(date.format("%x").ok().map(|d| d.to_string()));
+    }
+    if let Some(total) = &purchase.total {
+        parts.push(if total.is_free() { gettext("Free") } else { total.to_string() });
+    }
+    if let Some(sale_id) = purchase.sale_id {
+        parts.push(gettext("Order {}").replace("{}", &sale_id.to_string()));
+    }
+    parts.retain(|part| !part.is_empty());
+    parts.join(" · ")
+}
+
+fn build_row(index: usize, purchase: &Purchase, sender: &ComponentSender<PurchasesPage>) -> adw::ActionRow {
+    let row = adw::ActionRow::new();
+    row.set_title(&glib::markup_escape_text(&purchase.title));
+    row.set_subtitle(&glib::markup_escape_text(&receipt(purchase)));
+    row.set_activatable(!purchase.url.is_empty() && !purchase.physical);
+    let s = sender.clone();
+    row.connect_activated(move |_| s.input(PurchasesMsg::Activate(index)));
+
+    if purchase.physical {
+        let badge = gtk4::Label::new(Some(&gettext("Physical")));
+        badge.add_css_class("owned-badge");
+        badge.add_css_class("caption");
+        badge.set_valign(gtk4::Align::Center);
+        row.add_suffix(&badge);
+    } else if purchase.download_url.is_some() {
+        let button = gtk4::Button::from_icon_name("folder-download-symbolic");
+        button.add_css_class("flat");
+        button.set_valign(gtk4::Align::Center);
+        button.set_tooltip_text(Some(&gettext("Download")));
+        button.update_property(&[gtk4::accessible::Property::Label(
+            &gettext("Download “{}”").replace("{}", &purchase.title),
+        )]);
+        let s = sender.clone();
+        button.connect_clicked(move |_| s.input(PurchasesMsg::Download(index)));
+        row.add_suffix(&button);
+    }
+    row
+}
+
+#[relm4::component(pub)]
+impl Component for PurchasesPage {
+    type Init = BandcampClient;
+    type Input = PurchasesMsg;
+    type Output = PurchasesOutput;
+    type CommandOutput = Result<Vec<Purchase>, ClientError>;
+
+    view! {
+        gtk4::Box {
+            set_orientation: gtk4::Orientation::Vertical,
+            set_hexpand: true,
+            set_vexpand: true,
+
+            gtk4::CenterBox {
+                add_css_class: "compact-toolbar",
+                set_margin_start: 6,
+                set_margin_end: 6,
+                set_margin_top: 6,
+
+                #[wrap(Some)]
+                set_start_widget = &gtk4::Button {
+                    set_icon_name: "go-previous-symbolic",
+                    set_tooltip_text: Some(&gettext("Back")),
+                    add_css_class: "flat",
+                    set_action_name: Some("navigation.pop"),
+                },
+
+                #[wrap(Some)]
+                set_center_widget = &gtk4::Label {
+                    set_label: &gettext("Purchases"),
+                    add_css_class: "heading",
+                },
+            },
+
+            gtk4::Stack {
+                set_vexpand: true,
+                #[watch]
+                set_visible_child_name: if model.loading && model.purchases.is_empty() {
+                    "loading"
+                } else if model.error.is_some() && model.purchases.is_empty() {
+                    "error"
+                } else if model.purchases.is_empty() {
+                    "empty"
+                } else {
+                    "list"
+                },
+
+                #[name = "loading_page"]
+                add_named[Some("loading")] = &adw::StatusPage {
+                    set_title: &gettext("Loading…"),
+                },
+
+                add_named[Some("error")] = &adw::StatusPage {
+                    set_icon_name: Some("network-error-symbolic"),
+                    set_title: &gettext("Couldn't Load Purchases"),
+                    #[watch]
+                    set_description: model.error.as_ref().map(|e| e.message()).as_deref(),
+
+                    #[wrap(Some)]
+                    set_child = &gtk4::Button {
+                        set_label: &gettext("Try Again"),
+                        add_css_class: "pill",
+                        add_css_class: "suggested-action",
+                        set_halign: gtk4::Align::Center,
+                        connect_clicked => PurchasesMsg::Retry,
+                    },
+                },
+
+                add_named[Some("empty")] = &adw::StatusPage {
+                    set_icon_name: Some("folder-music-symbolic"),
+                    set_title: &gettext("No Purchases"),
+                },
+
+                add_named[Some("list")] = &gtk4::ScrolledWindow {
+                    set_hscrollbar_policy: gtk4::PolicyType::Never,
+
+                    adw::Clamp {
+                        set_margin_top: 12,
+                        set_margin_bottom: 12,
+                        set_margin_start: 12,
+                        set_margin_end: 12,
+
+                        #[name = "list"]
+                        gtk4::ListBox {
+                            add_css_class: "boxed-list",
+                            set_selection_mode: gtk4::SelectionMode::None,
+                            set_valign: gtk4::Align::Start,
+                        },
+                    },
+                },
+            },
+        }
+    }
+
+    fn init(client: Self::Init, root: Self::Root, sender: ComponentSender<Self>) -> ComponentParts<Self> {
+        let model = Self {
+            client,
+            purchases: Vec::new(),
+            loading: true,
+            error: None,
+        };
+        let widgets = view_output!();
+        let spinner = adw::SpinnerPaintable::new(Some(&widgets.loading_page));
+        widgets.loading_page.set_paintable(Some(&spinner));
+        sender.input(PurchasesMsg::Retry);
+        ComponentParts { model, widgets }
+    }
+
+    fn update_with_view(
+        &mut self,
+        widgets: &mut Self::Widgets,
+        msg: Self::Input,
+        sender: ComponentSender<Self>,
+        _root: &Self::Root,
+    ) {
+        match msg {
+            PurchasesMsg::Loaded(result) => {
+                self.loading = false;
+                match result {
+                    Ok(purchases) => {
+                        widgets.list.remove_all();
+                        for (i, purchase) in purchases.iter().enumerate() {
+                            widgets.list.append(&build_row(i, purchase, &sender));
+                        }
+                        self.purchases = purchases;
+                        self.error = None;
+                    }
+                    Err(e) => {
+                        self.error = Some(e.clone());
+                        sender.output(PurchasesOutput::Error(e)).ok();
+                    }
+                }
+            }
+            PurchasesMsg::Retry => {
+                self.loading = true;
+                let client = self.client.clone();
+                sender.oneshot_command(async move { client.get_purchases().await.map_err(ClientError::from) });
+            }
+            PurchasesMsg::Activate(i) => {
+                if let Some(purchase) = self.purchases.get(i) {
+                    sender.output(PurchasesOutput::Activate(purchase.into(), Click::Primary)).ok();
+                }
+            }
+            PurchasesMsg::Download(i) => {
+                if let Some(purchase) = self.purchases.get(i) {
+                    sender.output(PurchasesOutput::Download(purchase.into())).ok();
+                }
+            }
+        }
+        self.update_view(widgets, sender);
+    }
+
+    fn update_cmd(&mut self, msg: Self::CommandOutput, sender: ComponentSender<Self>, _root: &Self::Root) {
+        sender.input(PurchasesMsg::Loaded(msg));
+    }
+}
@@ -0,0 +1,49 @@
+use crate::storage::{self, Ratings};
+use std::cell::RefCell;
+
+/// The most stars a release can get.
+pub const MAX: u8 = 5;
+
+thread_local! {
+    /// The signed-in account's ratings, as last read or written.
+    static RATINGS: RefCell<Ratings> = RefCell::default();
+}
+
+/// The release page a track or release URL belongs to.
+fn release(url: &str) -> Option<String> {
+    crate::bandcamp::parse_release_link(url).map(|link| link.url)
+}
+
+/// Reads the ratings of the account just signed in, or forgets them with
+/// nobody signed in.
+pub fn reload(signed_in: bool) {
+    let ratings = if signed_in { storage::load_ratings() } else { Ratings::default() };
+    RATINGS.with(|r| r.replace(ratings));
+}
+
+/// Stars given to the release at `url`, if any.
+pub fn get(url: &str) -> Option<u8> {
+    let url = release(url)?;
+    RATINGS.with(|r| r.borrow().albums.get(&url).copied())
+}
+
+/// Gives the release at `url` this many stars, or takes its rating away
+/// with `None`, and saves right away.
+pub fn set(url: &str, stars: Option<u8>) -> anyhow::Result<()> {
+    let Some(url) = release(url) else { return Ok(()) };
+    let ratings = RATINGS.with(|r| {
+        let mut ratings = r.borrow_mut();
+        match stars.map(|s| s.min(MAX)).filter(|&s| s > 0) {
+            Some(stars) => ratings.albums.insert(url, stars),
+            None => ratings.albums.remove(&url),
+        };
+        ratings.clone()
+    });
+    storage::save_ratings(&ratings)
+}
+
+/// Stars as text, e.g. "★★★☆☆".
+pub fn stars(stars: u8) -> String {
+    let stars = stars.min(MAX) as usize;
+    "★".repeat(stars) + &"☆".repeat(MAX as usize - stars)
+}
@@ -0,0 +1,224 @@
+use crate::storage::{self, History};
+use gettextrs::{gettext, ngettext};
+use gtk4::gio;
+use gtk4::glib;
+use gtk4::prelude::*;
+use libadwaita as adw;
+use libadwaita::prelude::*;
+use std::collections::HashMap;
+
+const TOP: usize = 5;
+
+/// A year of listening, summed up from the local play history.
+#[derive(Debug, Default)]
+pub struct Recap {
+    pub year: i32,
+    pub listens: usize,
+    /// Listens recorded before their length was kept add nothing here.
+    pub seconds: f64,
+    pub artists: Vec<(String, usize)>,
+    /// Title and artist.
+    pub albums: Vec<((String, String), usize)>,
+    pub genres: Vec<(String, usize)>,
+}
+
+fn year_of(played_at: u64) -> Option<i32> {
+    glib::DateTime::from_unix_local(played_at as i64).ok().map(|d| d.year())
+}
+
+/// Most played first, ties by name so the list doesn't shuffle between
+/// openings.
+fn top<K: Ord>(counts: HashMap<K, usize>) -> Vec<(K, usize)> {
+    let mut counts: Vec<_> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts.truncate(TOP);
+    counts
+}
+
+pub fn compute(history: &History, year: i32) -> Recap {
+    let mut recap = Recap { year, ..Default::default() };
+    let mut artists = HashMap::new();
+    let mut albums = HashMap::new();
+    let mut genres = HashMap::new();
+    for listen in history.listens.iter().filter(|l| year_of(l.played_at) == Some(year)) {
+        recap.listens += 1;
+        recap.seconds += listen.duration.unwrap_or(0.0);
+        if !listen.artist.is_empty() {
+            *artists.entry(listen.artist.clone()).or_default() += 1;
+        }
+        if !listen.album.is_empty() {
+            *albums.entry((listen.album.clone(), listen.artist.clone())).or_default() += 1;
+        }
+        if let Some(genre) = listen.genre.as_ref().filter(|g| !g.is_empty()) {
+            *genres.entry(genre.clone()).or_default() += 1;
+        }
+    }
+    recap.artists = top(artists);
+    recap.albums = top(albums);
+    recap.genres = top(genres);
+    recap
+}
+
+/// The year worth looking back on right now: the current one through
+/// December, the one just ended during the first half of January.
+pub fn season_year() -> Option<i32> {
+    let now = glib::DateTime::now_local().ok()?;
+    match (now.month(), now.day_of_month()) {
+        (12, _) => Some(now.year()),
+        (1, day) if day <= 15 => Some(now.year() - 1),
+        _ => None,
+    }
+}
+
+/// The year the menu entry shows: the seasonal one, otherwise the
+/// current year so far.
+pub fn default_year() -> i32 {
+    season_year()
+        .or_else(|| glib::DateTime::now_local().ok().map(|d| d.year()))
+        .unwrap_or(1970)
+}
+
+fn stat(value: &str, caption: &str) -> gtk4::Box {
+    let value = gtk4::Label::new(Some(value));
+    value.add_css_class("title-1");
+    value.add_css_class("numeric");
+    let caption = gtk4::Label::new(Some(caption));
+    caption.add_css_class("dim-label");
+
+    let stat = gtk4::Box::new(gtk4::Orientation::Vertical, 4);
+    stat.set_hexpand(true);
+    stat.append(&value);
+    stat.append(&caption);
+    stat
+}
+
+fn ranking(title: &str, rows: impl IntoIterator<Item = (String, String, usize)>) -> adw::PreferencesGroup {
+    let group = adw::PreferencesGroup::new();
+    group.set_title(title);
+    for (i, (title, subtitle, count)) in rows.into_iter().enumerate() {
+        let row = adw::ActionRow::new();
+        row.set_title(&glib::markup_escape_text(&title));
+        row.set_subtitle(&glib::markup_escape_text(&subtitle));
+
+        let rank = gtk4::Label::new(Some(&(i + 1).to_string()));
+        rank.add_css_class("title-4");
+        rank.add_css_class("numeric");
+        rank.set_width_chars(2);
+        row.add_prefix(&rank);
+
+        let plays = ngettext("{} play", "{} plays", count as u32).replace("{}", &count.to_string());
+        let plays = gtk4::Label::new(Some(&plays));
+        plays.add_css_class("dim-label");
+        row.add_suffix(&plays);
+        group.add(&row);
+    }
+    group
+}
+
+fn build_summary(recap: &Recap) -> gtk4::Box {
+    let content = gtk4::Box::new(gtk4::Orientation::Vertical, 24);
+    content.add_css_class("recap");
+
+    let title = gtk4::Label::new(Some(&gettext("Your {} in Camper").replace("{}", &recap.year.to_string())));
+    title.add_css_class("title-1");
+    title.set_wrap(true);
+    content.append(&title);
+
+    let stats = gtk4::Box::new(gtk4::Orientation::Horizontal, 12);
+    stats.set_homogeneous(true);
+    stats.append(&stat(&format!("{:.1}", recap.seconds / 3600.0), &gettext("Hours Listened")));
+    stats.append(&stat(&recap.listens.to_string(), &gettext("Tracks Played")));
+    content.append(&stats);
+
+    let artists = recap.artists.iter().map(|(name, n)| (name.clone(), String::new(), *n));
+    content.append(&ranking(&gettext("Top Artists"), artists));
+    let albums = recap
+        .albums
+        .iter()
+        .map(|((album, artist), n)| (album.clone(), artist.clone(), *n));
+    content.append(&ranking(&gettext("Top Albums"), albums));
+    if !recap.genres.is_empty() {
+        let genres = recap.genres.iter().map(|(genre, n)| (genre.clone(), String::new(), *n));
+        content.append(&ranking(&gettext("Top Genres"), genres));
+    }
+    content
+}
+
+/// Draws `widget` as it is on screen into a PNG.
+fn render_png(widget: &gtk4::Widget) -> Option<glib::Bytes> {
+    let (width, height) = (widget.width() as f64, widget.height() as f64);
+    let paintable = gtk4::WidgetPaintable::new(Some(widget));
+    let snapshot = gtk4::Snapshot::new();
+    paintable.snapshot(&snapshot, width, height);
+    let node = snapshot.to_node()?;
+    let renderer = widget.native()?.renderer()?;
+    Some(renderer.render_texture(&node, None).save_to_png_bytes())
+}
+
+pub fn build_dialog(year: i32) -> adw::Dialog {
+    let dialog = adw::Dialog::new();
+    dialog.set_title(&gettext("Year in Review"));
+    dialog.set_content_width(480);
+    dialog.set_content_height(640);
+
+    let recap = compute(&storage::load_history(), year);
+
+    let toast_overlay = adw::ToastOverlay::new();
+    let header = adw::HeaderBar::new();
+
+    if recap.listens == 0 {
+        let empty = adw::StatusPage::new();
+        empty.set_icon_name(Some("audio-x-generic-symbolic"));
+        empty.set_title(&gettext("Nothing Played in {}").replace("{}", &year.to_string()));
+        empty.set_description(Some(&gettext("Tracks you listen to with play history on show up here")));
+        toast_overlay.set_child(Some(&empty));
+    } else {
+        let summary = build_summary(&recap);
+        let clamp = adw::Clamp::new();
+        clamp.set_child(Some(&summary));
+        let scrolled = gtk4::ScrolledWindow::new();
+        scrolled.set_vexpand(true);
+        scrolled.set_hscrollbar_policy(gtk4::PolicyType::Never);
+        scrolled.set_child(Some(&clamp));
+        toast_overlay.set_child(Some(&scrolled));
+
+        let save_btn = gtk4::Button::from_icon_name("document-save-symbolic");
+        save_btn.set_tooltip_text(Some(&gettext("Save as Image")));
+        header.pack_end(&save_btn);
+
+        let overlay = toast_overlay.clone();
+        save_btn.connect_clicked(move |btn| {
+            let Some(png) = render_png(summary.upcast_ref()) else {
+                overlay.add_toast(adw::Toast::new(&gettext("Failed to save image")));
+                return;
+            };
+            let window = btn.root().and_downcast::<gtk4::Window>();
+            let file_dialog = gtk4::FileDialog::new();
+            file_dialog.set_title(&gettext("Save Image"));
+            file_dialog.set_initial_name(Some(&format!("camper-{year}.png")));
+            let overlay = overlay.clone();
+            glib::spawn_future_local(async move {
+                // Dismissing the file chooser is not an error worth reporting.
+                let Ok(file) = file_dialog.save_future(window.as_ref()).await else { return };
+                let result = file
+                    .replace_contents_future(png, None, false, gio::FileCreateFlags::REPLACE_DESTINATION)
+                    .await;
+                match result {
+                    Ok(_) => overlay.add_toast(adw::Toast::new(&gettext("Image saved"))),
+                    Err((_, e)) => {
+                        crate::diagnostics::error("recap", format!("Failed to save image: {e}"));
+                        let message = gettext("Failed to save image: {}").replace("{}", &e.to_string());
+                        overlay.add_toast(adw::Toast::new(&message));
+                    }
+                }
+            });
+        });
+    }
+
+    let toolbar_view = adw::ToolbarView::new();
+    toolbar_view.add_top_bar(&header);
+    toolbar_view.set_content(Some(&toast_overlay));
+
+    dialog.set_child(Some(&toolbar_view));
+    dialog
+}
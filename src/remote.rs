@@ -0,0 +1,315 @@
+use crate::app::AppMsg;
+use crate::diagnostics;
+use crate::player::PlaybackSnapshot;
+use serde::Serialize;
+use std::sync::{Mutex, OnceLock};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+
+pub const DEFAULT_PORT: u16 = 8650;
+
+const MAX_HEADER_BYTES: u64 = 16 * 1024;
+const MAX_BODY_BYTES: usize = 8 * 1024;
+/// How long a client has to send its request before it is hung up on.
+const READ_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// What `/now-playing`, `/queue` and `/events` report.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Status {
+    #[serde(flatten)]
+    pub playback: PlaybackSnapshot,
+    pub queue_index: usize,
+    pub queue: Vec<QueueEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QueueEntry {
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    pub duration: Option<f64>,
+}
+
+fn status_channel() -> &'static watch::Sender<Status> {
+    static STATUS: OnceLock<watch::Sender<Status>> = OnceLock::new();
+    STATUS.get_or_init(|| watch::channel(Status::default()).0)
+}
+
+/// Called by the player whenever playback changes, with the queue and the
+/// current track's place in it unless only the position moved.
+pub fn publish(playback: PlaybackSnapshot, queue: Option<(usize, Vec<QueueEntry>)>) {
+    status_channel().send_modify(|status| {
+        status.playback = playback;
+        if let Some((index, queue)) = queue {
+            status.queue_index = index;
+            status.queue = queue;
+        }
+    });
+}
+
+/// Clients must present this; read per request so a new token applies
+/// without restarting the server.
+static TOKEN: Mutex<String> = Mutex::new(String::new());
+
+/// The last published status, for the command line.
+pub fn status() -> Status {
+    status_channel().borrow().clone()
+}
+
+/// A random token for authenticating clients.
+pub fn generate_token() -> String {
+    gtk4::glib::uuid_string_random().replace('-', "")
+}
+
+pub fn set_token(token: String) {
+    if let Ok(mut current) = TOKEN.lock() {
+        *current = token;
+    }
+}
+
+fn token_matches(token: &str) -> bool {
+    TOKEN.lock().is_ok_and(|current| !current.is_empty() && constant_time_eq(current.as_bytes(), token.as_bytes()))
+}
+
+/// Compares every byte instead of stopping at the first difference, so
+/// response times don't give the token away a byte at a time.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// The running server. Dropping it closes the listener and every open
+/// connection.
+pub struct Server(tokio::task::AbortHandle);
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Serves the remote-control API on localhost only. Every request but
+/// CORS preflights must carry the token set with [`set_token`], as a
+/// bearer token or `?token=`.
+pub fn start(port: u16) -> Server {
+    let task = relm4::spawn(async move {
+        let listener = match TcpListener::bind(("127.0.0.1", port)).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                diagnostics::error("remote", format!("Failed to listen on port {port}: {e}"));
+                return;
+            }
+        };
+        diagnostics::record(
+            diagnostics::Level::Info,
+            "remote",
+            format!("Listening on http://127.0.0.1:{port}"),
+        );
+
+        // Owned here so aborting the server also ends event streams.
+        let mut connections = JoinSet::new();
+        loop {
+            tokio::select! {
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, _)) => {
+                        connections.spawn(handle(stream));
+                    }
+                    Err(e) => diagnostics::error("remote", format!("Failed to accept connection: {e}")),
+                },
+                Some(_) = connections.join_next(), if !connections.is_empty() => {}
+            }
+        }
+    });
+    Server(task.abort_handle())
+}
+
+/// Kept apart from `AppMsg`, which can't live in futures running on the
+/// worker threads.
+enum Command {
+    SetPlaying(bool),
+    Toggle,
+    Next,
+    Previous,
+    Enqueue(String),
+}
+
+impl Command {
+    fn send(self) {
+        crate::APP_BROKER.send(match self {
+            Command::SetPlaying(playing) => AppMsg::PlayerSetPlaying(playing),
+            Command::Toggle => AppMsg::PlayerToggle,
+            Command::Next => AppMsg::PlayerNext,
+            Command::Previous => AppMsg::PlayerPrev,
+            Command::Enqueue(url) => AppMsg::EnqueueUrl(url),
+        });
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: String,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn query(&self, name: &str) -> Option<&str> {
+        self.query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(n, _)| *n == name)
+            .map(|(_, v)| v)
+    }
+
+    fn authorized(&self) -> bool {
+        let bearer = self.header("authorization").and_then(|v| v.strip_prefix("Bearer "));
+        bearer.or_else(|| self.query("token")).is_some_and(|t| token_matches(t.trim()))
+    }
+}
+
+async fn read_request(reader: &mut BufReader<impl AsyncRead + Unpin>) -> std::io::Result<Option<Request>> {
+    // The request line and headers are read through a limit, so a client
+    // sending one endless line can't grow it without bound.
+    let mut head = (&mut *reader).take(MAX_HEADER_BYTES);
+    let mut line = String::new();
+    head.read_line(&mut line).await?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else { return Ok(None) };
+    let method = method.to_string();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let (path, query) = (path.to_string(), query.to_string());
+
+    let mut headers = Vec::new();
+    loop {
+        line.clear();
+        if head.read_line(&mut line).await? == 0 {
+            return Ok(None);
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+
+    let mut request = Request { method, path, query, headers, body: String::new() };
+    let length: usize = request.header("content-length").and_then(|v| v.parse().ok()).unwrap_or(0);
+    if length > MAX_BODY_BYTES {
+        return Ok(None);
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await?;
+    request.body = String::from_utf8_lossy(&body).into_owned();
+    Ok(Some(request))
+}
+
+async fn handle(stream: TcpStream) {
+    let (read, mut write) = stream.into_split();
+    let mut reader = BufReader::new(read);
+    let request = match tokio::time::timeout(READ_TIMEOUT, read_request(&mut reader)).await {
+        Ok(Ok(Some(request))) => request,
+        Ok(Ok(None)) => {
+            let _ = respond(&mut write, "400 Bad Request", "text/plain", "").await;
+            return;
+        }
+        Ok(Err(_)) | Err(_) => return,
+    };
+    if let Err(e) = route(&request, &mut write).await {
+        diagnostics::error("remote", format!("{} {}: {e}", request.method, request.path));
+    }
+}
+
+async fn route(request: &Request, write: &mut OwnedWriteHalf) -> std::io::Result<()> {
+    // Browsers ask before sending the Authorization header cross-origin.
+    if request.method == "OPTIONS" {
+        return respond(write, "204 No Content", "text/plain", "").await;
+    }
+    if !request.authorized() {
+        return respond(write, "401 Unauthorized", "text/plain", "").await;
+    }
+
+    let command = match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/now-playing") => {
+            let status = status_channel().borrow().clone();
+            return respond_json(write, &status.playback).await;
+        }
+        ("GET", "/queue") => {
+            let status = status_channel().borrow().clone();
+            let queue = serde_json::json!({ "index": status.queue_index, "tracks": status.queue });
+            return respond_json(write, &queue).await;
+        }
+        ("GET", "/events") => return stream_events(write).await,
+        ("POST", "/play") => Command::SetPlaying(true),
+        ("POST", "/pause") => Command::SetPlaying(false),
+        ("POST", "/play-pause") => Command::Toggle,
+        ("POST", "/next") => Command::Next,
+        ("POST", "/previous") => Command::Previous,
+        ("POST", "/queue") => {
+            let url = request.body.trim();
+            if url.is_empty() {
+                return respond(write, "400 Bad Request", "text/plain", "Expected a release URL").await;
+            }
+            Command::Enqueue(url.to_string())
+        }
+        (_, "/now-playing" | "/queue" | "/events" | "/play" | "/pause" | "/play-pause" | "/next" | "/previous") => {
+            return respond(write, "405 Method Not Allowed", "text/plain", "").await;
+        }
+        _ => return respond(write, "404 Not Found", "text/plain", "").await,
+    };
+    command.send();
+    respond(write, "204 No Content", "text/plain", "").await
+}
+
+/// Server-sent events with the full status whenever it changes.
+async fn stream_events(write: &mut OwnedWriteHalf) -> std::io::Result<()> {
+    let head = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\n{}\r\n",
+        cors_headers()
+    );
+    write.write_all(head.as_bytes()).await?;
+
+    let mut status = status_channel().subscribe();
+    loop {
+        let json = serde_json::to_string(&*status.borrow_and_update()).unwrap_or_default();
+        // The client went away.
+        if write.write_all(format!("data: {json}\n\n").as_bytes()).await.is_err() {
+            return Ok(());
+        }
+        if status.changed().await.is_err() {
+            return Ok(());
+        }
+    }
+}
+
+async fn respond_json(write: &mut OwnedWriteHalf, value: &impl Serialize) -> std::io::Result<()> {
+    let body = serde_json::to_string(value).unwrap_or_default();
+    respond(write, "200 OK", "application/json", &body).await
+}
+
+async fn respond(write: &mut OwnedWriteHalf, status: &str, content_type: &str, body: &str) -> std::io::Result<()> {
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n{}\r\n{body}",
+        body.len(),
+        cors_headers(),
+    );
+    write.write_all(response.as_bytes()).await?;
+    write.shutdown().await
+}
+
+fn cors_headers() -> &'static str {
+    "Access-Control-Allow-Origin: *\r\n\
+     Access-Control-Allow-Methods: GET, POST, OPTIONS\r\n\
+     Access-Control-Allow-Headers: Authorization, Content-Type\r\n"
+}
@@ -1,13 +1,25 @@
-use crate::album_grid::{AlbumData, AlbumGrid, AlbumGridMsg, AlbumGridOutput};
-use crate::bandcamp::BandcampClient;
+use crate::album_grid::{AlbumData, AlbumGrid, AlbumGridMsg, AlbumGridOutput, Click};
+use crate::bandcamp::{BandcampClient, ClientError};
+use crate::storage::{self, CachedSearch, SearchCache};
+use gettextrs::gettext;
 use gtk4::prelude::*;
 use relm4::prelude::*;
 
+/// Searches whose results are kept.
+const CACHE_ENTRIES: usize = 20;
+/// How long kept results are shown instead of searching again, in seconds.
+const CACHE_TTL: u64 = 30 * 60;
+
 pub struct SearchPage {
     client: Option<BandcampClient>,
     grid: Controller<AlbumGrid>,
     query: String,
     loading: bool,
+    /// Recent searches and their results, most recent last.
+    cache: SearchCache,
+    /// Whether `cache` is saved to disk, which it is while history
+    /// outlives the session.
+    keep_cache: bool,
 }
 
 #[derive(Debug)]
@@ -15,15 +27,27 @@ pub enum SearchMsg {
     SetClient(BandcampClient),
     Submit,
     QueryChanged(String),
-    Loaded(Result<Vec<AlbumData>, String>),
+    /// Results for the query searched for.
+    Loaded(String, Result<Vec<AlbumData>, ClientError>),
     GridAction(AlbumGridOutput),
+    FocusResults,
+    /// Save recent searches to disk and read back the ones saved, or
+    /// forget the saved ones and keep them in memory only.
+    SetKeepCache(bool),
+    /// Forget recent searches, along with the rest of the history.
+    ClearCache,
 }
 
 #[derive(Debug)]
 pub enum SearchOutput {
-    Play(AlbumData),
+    Activate(AlbumData, Click),
+    ShowArtist(AlbumData),
+    /// Resolve the album ahead of a likely click.
+    Prefetch(AlbumData),
     QueryChanged(String),
-    Error(String),
+    /// A query was submitted, for search history.
+    Searched(String),
+    Error(ClientError),
 }
 
 #[relm4::component(pub)]
@@ -31,7 +55,7 @@ impl Component for SearchPage {
     type Init = ();
     type Input = SearchMsg;
     type Output = SearchOutput;
-    type CommandOutput = Result<Vec<AlbumData>, String>;
+    type CommandOutput = (String, Result<Vec<AlbumData>, ClientError>);
 
     view! {
         gtk4::Box {
@@ -55,6 +79,8 @@ impl Component for SearchPage {
             grid,
             query: String::new(),
             loading: false,
+            cache: SearchCache::default(),
+            keep_cache: false,
         };
 
         let widgets = view_output!();
@@ -69,24 +95,64 @@ impl Component for SearchPage {
             }
             SearchMsg::QueryChanged(q) => {
                 self.query = q.clone();
+                // Typing a recent query again brings its results straight back.
+                if !self.loading {
+                    if let Some(albums) = self.cached(&q) {
+                        self.grid.emit(AlbumGridMsg::Replace(albums));
+                    }
+                }
                 sender.output(SearchOutput::QueryChanged(q)).ok();
             }
             SearchMsg::Submit => {
                 if self.query.trim().is_empty() || self.loading {
                     return;
                 }
-                self.fetch(sender.clone());
+                sender.output(SearchOutput::Searched(self.query.trim().to_string())).ok();
+                match self.cached(&self.query) {
+                    Some(albums) => self.grid.emit(AlbumGridMsg::Replace(albums)),
+                    None => self.fetch(sender.clone()),
+                }
             }
-            SearchMsg::Loaded(result) => {
+            SearchMsg::Loaded(query, result) => {
                 self.loading = false;
                 match result {
-                    Ok(albums) => self.grid.emit(AlbumGridMsg::Replace(albums)),
-                    Err(e) => { sender.output(SearchOutput::Error(format!("Search failed: {e}"))).ok(); }
+                    Ok(albums) => {
+                        self.remember(query, &albums);
+                        self.grid.emit(AlbumGridMsg::Replace(albums));
+                    }
+                    Err(e) => {
+                        self.grid.emit(AlbumGridMsg::Error(e.clone()));
+                        sender.output(SearchOutput::Error(e)).ok();
+                    }
+                }
+            }
+            SearchMsg::FocusResults => self.grid.emit(AlbumGridMsg::Focus),
+            SearchMsg::SetKeepCache(keep) => {
+                self.keep_cache = keep;
+                if !keep {
+                    storage::clear_search_cache();
+                } else if self.cache.searches.is_empty() {
+                    self.cache = storage::load_search_cache();
+                    if let Some(albums) = self.cached(&self.query) {
+                        self.grid.emit(AlbumGridMsg::Replace(albums));
+                    }
                 }
             }
+            SearchMsg::ClearCache => self.cache = SearchCache::default(),
             SearchMsg::GridAction(action) => match action {
-                AlbumGridOutput::Clicked(data) => {
-                    sender.output(SearchOutput::Play(data)).ok();
+                AlbumGridOutput::Clicked(data, click) => {
+                    sender.output(SearchOutput::Activate(data, click)).ok();
+                }
+                AlbumGridOutput::ArtistClicked(data) => {
+                    sender.output(SearchOutput::ShowArtist(data)).ok();
+                }
+                AlbumGridOutput::Hovered(data) => {
+                    sender.output(SearchOutput::Prefetch(data)).ok();
+                }
+                AlbumGridOutput::Retry => {
+                    if !self.loading {
+                        self.fetch(sender.clone());
+                    }
                 }
                 AlbumGridOutput::ScrolledToBottom => {}
             },
@@ -99,23 +165,59 @@ impl Component for SearchPage {
         sender: ComponentSender<Self>,
         _root: &Self::Root,
     ) {
-        sender.input(SearchMsg::Loaded(msg));
+        let (query, result) = msg;
+        sender.input(SearchMsg::Loaded(query, result));
     }
 }
 
+/// Queries differing only in case or surrounding space find the same.
+fn same_query(a: &str, b: &str) -> bool {
+    a.trim().to_lowercase() == b.trim().to_lowercase()
+}
+
 impl SearchPage {
+    /// Results kept for `query`, unless they are too old to trust.
+    fn cached(&self, query: &str) -> Option<Vec<AlbumData>> {
+        let fresh_since = storage::now().saturating_sub(CACHE_TTL);
+        self.cache
+            .searches
+            .iter()
+            .rfind(|search| same_query(&search.query, query) && search.searched_at >= fresh_since)
+            .map(|search| search.albums.clone())
+    }
+
+    fn remember(&mut self, query: String, albums: &[AlbumData]) {
+        let searches = &mut self.cache.searches;
+        searches.retain(|search| !same_query(&search.query, &query));
+        searches.push(CachedSearch {
+            query,
+            searched_at: storage::now(),
+            albums: albums.to_vec(),
+        });
+        if searches.len() > CACHE_ENTRIES {
+            searches.drain(..searches.len() - CACHE_ENTRIES);
+        }
+        if self.keep_cache {
+            if let Err(e) = storage::save_search_cache(&self.cache) {
+                crate::diagnostics::error("storage", format!("Failed to cache search results: {e}"));
+            }
+        }
+    }
+
     fn fetch(&mut self, sender: ComponentSender<Self>) {
         let Some(client) = self.client.clone() else {
             return;
         };
         self.loading = true;
+        self.grid.emit(AlbumGridMsg::Loading);
         let query = self.query.clone();
         sender.oneshot_command(async move {
-            client
+            let result = client
                 .search(&query)
                 .await
                 .map(|albums| albums.into_iter().map(AlbumData::from).collect())
-                .map_err(|e| e.to_string())
+                .map_err(ClientError::from);
+            (query, result)
         });
     }
 }
@@ -128,7 +230,7 @@ pub fn build_toolbar(
     toolbar.add_css_class("compact-toolbar");
 
     let entry = gtk4::SearchEntry::new();
-    entry.set_placeholder_text(Some("Search artists, albums, tracks..."));
+    entry.set_placeholder_text(Some(&gettext("Search artists, albums, tracks...")));
     entry.set_hexpand(true);
     if let Some(ref q) = ui_state.search_query {
         entry.set_text(q);
@@ -141,6 +243,8 @@ pub fn build_toolbar(
     entry.connect_activate(move |_| {
         s.emit(SearchMsg::Submit);
     });
+    let s = sender.clone();
+    crate::album_grid::connect_focus_results(&entry, move || s.emit(SearchMsg::FocusResults));
     toolbar.append(&entry);
 
     toolbar
@@ -0,0 +1,68 @@
+use crate::player::Track;
+use crate::storage::{self, Skips};
+use std::cell::RefCell;
+
+/// Times a track has to be skipped before auto-skip passes over it.
+pub const THRESHOLD: u32 = 3;
+
+thread_local! {
+    /// The signed-in account's skips, as last read or written.
+    static SKIPS: RefCell<Skips> = RefCell::default();
+}
+
+/// The release page a track or release URL belongs to.
+fn release(url: &str) -> Option<String> {
+    crate::bandcamp::parse_release_link(url).map(|link| link.url)
+}
+
+/// Reads the skips of the account just signed in, or forgets them with
+/// nobody signed in.
+pub fn reload(signed_in: bool) {
+    let skips = if signed_in { storage::load_skips() } else { Skips::default() };
+    SKIPS.with(|s| s.replace(skips));
+}
+
+/// Times the track titled `title` on the release at `url` was skipped.
+pub fn count(url: &str, title: &str) -> u32 {
+    let Some(url) = release(url) else { return 0 };
+    SKIPS.with(|s| s.borrow().tracks.get(&url).and_then(|tracks| tracks.get(title)).copied().unwrap_or(0))
+}
+
+/// Counts a skip of `track` and saves right away.
+pub fn record(track: &Track) -> anyhow::Result<()> {
+    let Some(url) = release(&track.url) else { return Ok(()) };
+    let skips = SKIPS.with(|s| {
+        let mut skips = s.borrow_mut();
+        *skips.tracks.entry(url).or_default().entry(track.title.clone()).or_default() += 1;
+        skips.clone()
+    });
+    storage::save_skips(&skips)
+}
+
+/// Whether often skipped tracks of the release at `url` are passed over.
+pub fn auto_skip(url: &str) -> bool {
+    let Some(url) = release(url) else { return false };
+    SKIPS.with(|s| s.borrow().auto_skip.contains(&url))
+}
+
+/// Turns auto-skip on or off for the release at `url` and saves right
+/// away.
+pub fn set_auto_skip(url: &str, on: bool) -> anyhow::Result<()> {
+    let Some(url) = release(url) else { return Ok(()) };
+    let skips = SKIPS.with(|s| {
+        let mut skips = s.borrow_mut();
+        if on {
+            skips.auto_skip.insert(url);
+        } else {
+            skips.auto_skip.remove(&url);
+        }
+        skips.clone()
+    });
+    storage::save_skips(&skips)
+}
+
+/// Whether playback passes over `track`: its release has auto-skip on and
+/// it was skipped at least [`THRESHOLD`] times.
+pub fn passes_over(track: &Track) -> bool {
+    auto_skip(&track.url) && count(&track.url, &track.title) >= THRESHOLD
+}
@@ -1,60 +1,846 @@
+use crate::bandcamp::{CollectionItem, FanInfo, ReleaseLink};
+use crate::calendar::Reminder;
+use crate::diagnostics;
 use crate::library::Sort;
-use anyhow::Result;
-use serde::{Deserialize, Serialize};
+use crate::preferences::{
+    CardAction, ColorScheme, CookieBackend, DataSaver, DownloadFormat, HistoryRetention, StartPage,
+};
+use anyhow::{anyhow, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use gtk4::glib;
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write;
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::future::Future;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
 
+/// Identifies the session secret in the keyring.
+const KEYRING_ATTRIBUTES: &[(&str, &str)] = &[
+    ("application", "net.knoopx.camper"),
+    ("type", "bandcamp-session"),
+];
+
+/// Fan ID of the signed-in account, or 0 before login. Per-account stores
+/// live under its own directory.
+static ACCOUNT: AtomicU64 = AtomicU64::new(0);
+
+/// How long a store has to stay unchanged before it is written.
+const SAVE_DELAY: Duration = Duration::from_secs(1);
+
+/// Coalesces bursts of saves (such as one per keystroke in a search entry)
+/// into a single write once changes settle.
+#[derive(Default)]
+pub struct Debouncer {
+    pending: Rc<Cell<Option<glib::SourceId>>>,
+}
+
+impl Debouncer {
+    /// Runs `save` after `SAVE_DELAY`, replacing any save still waiting.
+    pub fn schedule(&self, save: impl FnOnce() + 'static) {
+        self.cancel();
+        let pending = self.pending.clone();
+        let id = glib::timeout_add_local_once(SAVE_DELAY, move || {
+            pending.take();
+            save();
+        });
+        self.pending.set(Some(id));
+    }
+
+    /// Drops the waiting save, returning whether there was one.
+    pub fn cancel(&self) -> bool {
+        match self.pending.take() {
+            Some(id) => {
+                id.remove();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Preferences the user chose ($XDG_CONFIG_HOME).
 fn config_dir() -> PathBuf {
     dirs::config_dir()
         .unwrap_or_else(|| PathBuf::from("."))
         .join("camper")
 }
 
+/// Session and UI state worth keeping but not worth backing up
+/// ($XDG_STATE_HOME).
+fn state_dir() -> PathBuf {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("camper")
+}
+
+/// Anything that can be fetched again ($XDG_CACHE_HOME).
+pub fn cache_dir() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("camper")
+}
+
+fn settings_path() -> PathBuf {
+    config_dir().join("settings.json")
+}
+
 fn cookies_path() -> PathBuf {
-    config_dir().join("cookies")
+    state_dir().join("cookies")
+}
+
+fn account_dir(base: PathBuf) -> PathBuf {
+    match ACCOUNT.load(Ordering::Relaxed) {
+        0 => base,
+        fan_id => base.join("accounts").join(fan_id.to_string()),
+    }
 }
 
 fn ui_state_path() -> PathBuf {
-    config_dir().join("ui_state.json")
+    account_dir(state_dir()).join("ui_state.json")
+}
+
+fn fan_info_path() -> PathBuf {
+    state_dir().join("fan.json")
+}
+
+fn library_cache_path() -> PathBuf {
+    account_dir(cache_dir()).join("library.json")
+}
+
+fn search_cache_path() -> PathBuf {
+    account_dir(cache_dir()).join("searches.json")
+}
+
+fn history_path() -> PathBuf {
+    account_dir(state_dir()).join("history.json")
+}
+
+fn ratings_path() -> PathBuf {
+    account_dir(state_dir()).join("ratings.json")
+}
+
+fn notes_path() -> PathBuf {
+    account_dir(state_dir()).join("notes.json")
+}
+
+fn skips_path() -> PathBuf {
+    account_dir(state_dir()).join("skips.json")
+}
+
+fn listen_later_path() -> PathBuf {
+    account_dir(state_dir()).join("listen_later.json")
+}
+
+fn downloads_path() -> PathBuf {
+    state_dir().join("downloads.json")
+}
+
+fn genres_path() -> PathBuf {
+    cache_dir().join("genres.json")
+}
+
+fn exchange_rates_path() -> PathBuf {
+    cache_dir().join("exchange_rates.json")
+}
+
+/// Switches per-account stores to `fan_id`'s, or back to the shared
+/// location with `None`. Stores written before namespacing are claimed by
+/// the first account to sign in.
+pub fn set_account(fan_id: Option<u64>) {
+    let shared = [state_dir().join("ui_state.json"), cache_dir().join("library.json")];
+    ACCOUNT.store(fan_id.unwrap_or(0), Ordering::Relaxed);
+    if fan_id.is_none() {
+        return;
+    }
+    for (from, to) in shared.into_iter().zip([ui_state_path(), library_cache_path()]) {
+        if from.exists() && !to.exists() {
+            if let Err(e) = move_file(&from, &to) {
+                diagnostics::error("storage", format!("Failed to migrate {}: {e}", from.display()));
+            }
+        }
+    }
+}
+
+/// Stamped into every store. Bump when a change needs a migration; files
+/// from newer versions are still read as far as they can be understood.
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct VersionedRef<'a, T> {
+    version: u32,
+    #[serde(flatten)]
+    data: &'a T,
 }
 
-pub fn save_cookies(cookies: &str) -> Result<()> {
-    let dir = config_dir();
-    fs::create_dir_all(&dir)?;
-    fs::write(cookies_path(), cookies)?;
+#[derive(Deserialize)]
+struct Versioned<T> {
+    /// Missing from files written before versioning, which count as 0.
+    #[serde(default)]
+    version: u32,
+    #[serde(flatten)]
+    data: T,
+}
+
+/// Treats a value that no longer parses (say, an enum variant added by a
+/// newer version) as unset instead of failing the whole file.
+fn lenient<'de, D, T>(deserializer: D) -> std::result::Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    let value = serde_json::Value::deserialize(deserializer)?;
+    Ok(serde_json::from_value(value).ok())
+}
+
+/// Writes to a temporary file and renames it over `path`, so an
+/// interrupted write leaves the previous contents intact.
+fn write_json<T: Serialize>(path: PathBuf, value: &T) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let json = serde_json::to_string(&VersionedRef {
+        version: FORMAT_VERSION,
+        data: value,
+    })?;
+    let tmp = path.with_extension("json.tmp");
+    let mut file = fs::File::create(&tmp)?;
+    file.write_all(json.as_bytes())?;
+    file.sync_all()?;
+    fs::rename(&tmp, &path)?;
+    Ok(())
+}
+
+fn move_file(from: &Path, to: &Path) -> Result<()> {
+    if let Some(dir) = to.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    // Rename fails across filesystems, where copying is the only option.
+    if fs::rename(from, to).is_err() {
+        fs::copy(from, to)?;
+        fs::remove_file(from)?;
+    }
+    Ok(())
+}
+
+/// Moves files from the single config directory used by earlier versions
+/// into the config/state/cache split. Existing files at the new location
+/// win.
+pub fn migrate_legacy_layout() {
+    let legacy = config_dir();
+    for (name, to) in [
+        ("cookies", cookies_path()),
+        ("fan.json", fan_info_path()),
+        ("library.json", library_cache_path()),
+    ] {
+        let from = legacy.join(name);
+        if from.exists() && !to.exists() {
+            if let Err(e) = move_file(&from, &to) {
+                diagnostics::error("storage", format!("Failed to migrate {name}: {e}"));
+            }
+        }
+    }
+
+    // Settings used to share ui_state.json with the UI state, so the old
+    // file seeds both. Each struct would otherwise keep the other's fields
+    // as unknown extras.
+    let from = legacy.join("ui_state.json");
+    if !from.exists() {
+        return;
+    }
+    if !settings_path().exists() {
+        if let Some(mut settings) = read_json::<Settings>(from.clone()) {
+            settings.extra.clear();
+            let _ = save_settings(&settings);
+        }
+    }
+    if !ui_state_path().exists() {
+        if let Some(mut state) = read_json::<UiState>(from.clone()) {
+            state.extra.clear();
+            let _ = save_ui_state(&state);
+        }
+    }
+    if let Err(e) = fs::remove_file(&from) {
+        diagnostics::error("storage", format!("Failed to migrate ui_state.json: {e}"));
+    }
+}
+
+/// Reads a store written by [`write_json`]. A file that exists but can't be
+/// parsed is set aside as `*.corrupt` for inspection rather than being
+/// overwritten by defaults on the next save.
+fn read_json<T: DeserializeOwned>(path: PathBuf) -> Option<T> {
+    let contents = fs::read_to_string(&path).ok()?;
+    match serde_json::from_str::<Versioned<T>>(&contents) {
+        Ok(versioned) => {
+            if versioned.version > FORMAT_VERSION {
+                diagnostics::record(
+                    diagnostics::Level::Info,
+                    "storage",
+                    format!("{} is from a newer version ({})", path.display(), versioned.version),
+                );
+            }
+            Some(versioned.data)
+        }
+        Err(e) => {
+            let backup = path.with_extension("json.corrupt");
+            diagnostics::error(
+                "storage",
+                format!("Failed to parse {}: {e}; moved to {}", path.display(), backup.display()),
+            );
+            let _ = fs::rename(&path, &backup);
+            None
+        }
+    }
+}
+
+async fn keyring_save(cookies: &str) -> Result<()> {
+    let keyring = oo7::Keyring::new().await?;
+    keyring
+        .create_item("Camper Bandcamp session", &KEYRING_ATTRIBUTES, cookies, true)
+        .await?;
+    Ok(())
+}
+
+async fn keyring_load() -> Result<Option<String>> {
+    let keyring = oo7::Keyring::new().await?;
+    let Some(item) = keyring.search_items(&KEYRING_ATTRIBUTES).await?.into_iter().next() else {
+        return Ok(None);
+    };
+    let secret = item.secret().await?;
+    let cookies = std::str::from_utf8(secret.as_bytes()).map_err(|_| anyhow!("Stored session is not valid text"))?;
+    Ok(Some(cookies.to_string()))
+}
+
+async fn keyring_clear() -> Result<()> {
+    oo7::Keyring::new().await?.delete(&KEYRING_ATTRIBUTES).await?;
+    Ok(())
+}
+
+/// Readable only by the current user, unlike the world-readable file
+/// earlier versions wrote.
+fn write_cookie_file(cookies: &str) -> Result<()> {
+    fs::create_dir_all(state_dir())?;
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(cookies_path())?;
+    // The mode only applies to a new file; one left readable by others,
+    // such as one moved over from an older location, is tightened too.
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    file.write_all(cookies.as_bytes())?;
     Ok(())
 }
 
-pub fn load_cookies() -> Option<String> {
-    fs::read_to_string(cookies_path()).ok()
+/// Stores the session with `backend` and removes any copy left in the
+/// other one. Falls back to the file when the keyring is unavailable.
+pub async fn save_cookies(cookies: &str, backend: CookieBackend) -> Result<()> {
+    if backend == CookieBackend::Keyring {
+        match keyring_save(cookies).await {
+            Ok(()) => {
+                let _ = fs::remove_file(cookies_path());
+                return Ok(());
+            }
+            Err(e) => diagnostics::error("storage", format!("Keyring unavailable, using file: {e}")),
+        }
+    } else if let Err(e) = keyring_clear().await {
+        diagnostics::error("storage", format!("Failed to clear keyring: {e}"));
+    }
+    write_cookie_file(cookies)
 }
 
-pub fn clear_cookies() {
+/// Looks in the preferred backend first, then the other one, moving a
+/// session found there (such as a file from before keyring support).
+pub async fn load_cookies(backend: CookieBackend) -> Option<String> {
+    let from_keyring = || async {
+        keyring_load().await.unwrap_or_else(|e| {
+            diagnostics::error("storage", format!("Failed to read keyring: {e}"));
+            None
+        })
+    };
+    let from_file = || fs::read_to_string(cookies_path()).ok();
+
+    let (cookies, migrate) = match backend {
+        CookieBackend::Keyring => match from_keyring().await {
+            Some(cookies) => (cookies, false),
+            None => (from_file()?, true),
+        },
+        CookieBackend::File => match from_file() {
+            Some(cookies) => (cookies, false),
+            None => (from_keyring().await?, true),
+        },
+    };
+    if migrate {
+        if let Err(e) = save_cookies(&cookies, backend).await {
+            diagnostics::error("storage", format!("Failed to migrate session: {e}"));
+        }
+    }
+    Some(cookies)
+}
+
+/// Files go right away, while the current account is still known; the
+/// keyring entry goes when the returned future runs.
+pub fn clear_cookies() -> impl Future<Output = ()> {
     let _ = fs::remove_file(cookies_path());
+    let _ = fs::remove_file(fan_info_path());
+    let _ = fs::remove_file(library_cache_path());
+    async {
+        if let Err(e) = keyring_clear().await {
+            diagnostics::error("storage", format!("Failed to clear keyring: {e}"));
+        }
+    }
+}
+
+pub fn save_fan_info(fan: &FanInfo) -> Result<()> {
+    write_json(fan_info_path(), fan)
+}
+
+pub fn load_fan_info() -> Option<FanInfo> {
+    read_json(fan_info_path())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct LibraryCache {
+    pub collection: Vec<CollectionItem>,
+    pub wishlist: Vec<CollectionItem>,
+}
+
+pub fn save_library_cache(cache: &LibraryCache) -> Result<()> {
+    write_json(library_cache_path(), cache)
+}
+
+pub fn load_library_cache() -> Option<LibraryCache> {
+    read_json(library_cache_path())
+}
+
+/// Currency exchange rates, kept for a day so prices convert without a
+/// lookup each launch.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ExchangeRates {
+    /// Seconds since the epoch.
+    pub fetched_at: u64,
+    /// The currency the rates are against, itself at 1.
+    pub base: String,
+    pub rates: HashMap<String, f64>,
+}
+
+pub fn save_exchange_rates(rates: &ExchangeRates) -> Result<()> {
+    write_json(exchange_rates_path(), rates)
+}
+
+pub fn load_exchange_rates() -> Option<ExchangeRates> {
+    read_json(exchange_rates_path())
+}
+
+/// Genres of releases, looked up from their tags, by release page. An
+/// empty genre marks a release without tags, so it isn't looked up again.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct GenreCache {
+    #[serde(default)]
+    pub albums: HashMap<String, String>,
+}
+
+pub fn save_genre_cache(cache: &GenreCache) -> Result<()> {
+    write_json(genres_path(), cache)
+}
+
+pub fn load_genre_cache() -> GenreCache {
+    read_json(genres_path()).unwrap_or_default()
+}
+
+/// Downloaded purchases, so their tracks play from disk.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct DownloadIndex {
+    /// Track share links (release page with `#tN`) to the downloaded file.
+    #[serde(default)]
+    pub tracks: HashMap<String, PathBuf>,
+}
+
+pub fn save_download_index(index: &DownloadIndex) -> Result<()> {
+    write_json(downloads_path(), index)
+}
+
+pub fn load_download_index() -> DownloadIndex {
+    read_json(downloads_path()).unwrap_or_default()
+}
+
+/// Stars given to releases, from 1 to 5, by release page.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Ratings {
+    #[serde(default)]
+    pub albums: HashMap<String, u8>,
+}
+
+pub fn save_ratings(ratings: &Ratings) -> Result<()> {
+    write_json(ratings_path(), ratings)
+}
+
+pub fn load_ratings() -> Ratings {
+    read_json(ratings_path()).unwrap_or_default()
+}
+
+/// Free-text notes on releases, by release page.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Notes {
+    #[serde(default)]
+    pub albums: HashMap<String, String>,
+}
+
+pub fn save_notes(notes: &Notes) -> Result<()> {
+    write_json(notes_path(), notes)
+}
+
+pub fn load_notes() -> Notes {
+    read_json(notes_path()).unwrap_or_default()
+}
+
+/// Tracks skipped before they counted as a listen, and the releases
+/// whose often skipped tracks are passed over.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Skips {
+    /// Times skipped, by release page and track title.
+    #[serde(default)]
+    pub tracks: HashMap<String, HashMap<String, u32>>,
+    /// Release pages with auto-skip on.
+    #[serde(default)]
+    pub auto_skip: HashSet<String>,
+}
+
+pub fn save_skips(skips: &Skips) -> Result<()> {
+    write_json(skips_path(), skips)
+}
+
+pub fn load_skips() -> Skips {
+    read_json(skips_path()).unwrap_or_default()
+}
+
+/// A release put aside to listen to later.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LaterAlbum {
+    pub title: String,
+    pub artist: String,
+    pub url: String,
+    #[serde(default)]
+    pub art_url: Option<String>,
+    #[serde(default)]
+    pub band_id: Option<u64>,
+    #[serde(default)]
+    pub item_id: Option<u64>,
+    #[serde(default)]
+    pub item_type: Option<String>,
+    /// Seconds since the Unix epoch.
+    #[serde(default)]
+    pub added_at: u64,
+}
+
+/// Releases put aside to listen to later, apart from the wishlist, most
+/// recently added first.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ListenLater {
+    #[serde(default)]
+    pub albums: Vec<LaterAlbum>,
+}
+
+pub fn save_listen_later(list: &ListenLater) -> Result<()> {
+    write_json(listen_later_path(), list)
+}
+
+pub fn load_listen_later() -> ListenLater {
+    read_json(listen_later_path()).unwrap_or_default()
+}
+
+/// A track played past the point it counts as listened to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Listen {
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    /// Release page the track was played from.
+    pub url: Option<String>,
+    /// Seconds since the Unix epoch.
+    pub played_at: u64,
+    /// Track length in seconds.
+    #[serde(default)]
+    pub duration: Option<f64>,
+    /// Genre of the release, when the card it was played from had one.
+    #[serde(default)]
+    pub genre: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Search {
+    pub query: String,
+    pub searched_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct History {
+    #[serde(default)]
+    pub listens: Vec<Listen>,
+    #[serde(default)]
+    pub searches: Vec<Search>,
+}
+
+impl History {
+    fn prune(&mut self, retention: HistoryRetention) {
+        match retention {
+            HistoryRetention::Forever | HistoryRetention::Session => {}
+            HistoryRetention::Days90 => {
+                let cutoff = now().saturating_sub(90 * 24 * 60 * 60);
+                self.listens.retain(|l| l.played_at >= cutoff);
+                self.searches.retain(|s| s.searched_at >= cutoff);
+            }
+            HistoryRetention::Off => {
+                self.listens.clear();
+                self.searches.clear();
+            }
+        }
+    }
+}
+
+/// Seconds since the Unix epoch.
+pub fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+pub fn load_history() -> History {
+    read_json(history_path()).unwrap_or_default()
+}
+
+/// Applies `change` and writes the log right away, so entries survive a
+/// crash or a session spent offline. Nothing is written with history off.
+fn update_history(retention: HistoryRetention, change: impl FnOnce(&mut History)) -> Result<()> {
+    if retention == HistoryRetention::Off {
+        return Ok(());
+    }
+    let mut history = load_history();
+    change(&mut history);
+    history.prune(retention);
+    write_json(history_path(), &history)
+}
+
+/// Replaces the log with `history`, as far as `retention` allows.
+pub fn replace_history(history: History, retention: HistoryRetention) -> Result<()> {
+    update_history(retention, |h| *h = history)
+}
+
+pub fn record_listen(listen: Listen, retention: HistoryRetention) -> Result<()> {
+    update_history(retention, |h| h.listens.push(listen))
+}
+
+pub fn record_search(query: String, retention: HistoryRetention) -> Result<()> {
+    update_history(retention, |h| {
+        h.searches.push(Search {
+            query,
+            searched_at: now(),
+        })
+    })
+}
+
+/// Drops entries the retention setting no longer allows. Session-only
+/// history goes entirely, as this runs when a session starts and ends.
+pub fn prune_history(retention: HistoryRetention) {
+    match retention {
+        HistoryRetention::Session | HistoryRetention::Off => clear_history(),
+        _ => {
+            if !history_path().exists() {
+                return;
+            }
+            let mut history = load_history();
+            history.prune(retention);
+            if let Err(e) = write_json(history_path(), &history) {
+                diagnostics::error("history", format!("Failed to prune history: {e}"));
+            }
+        }
+    }
+}
+
+/// Cached search results are history too, and go with it.
+pub fn clear_history() {
+    let _ = fs::remove_file(history_path());
+    let _ = fs::remove_file(search_cache_path());
+}
+
+/// A search and the albums it found.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedSearch {
+    pub query: String,
+    /// Seconds since the Unix epoch.
+    pub searched_at: u64,
+    #[serde(default)]
+    pub albums: Vec<crate::album_grid::AlbumData>,
+}
+
+/// Recent searches, most recent last, so the Search page shows their
+/// results again without asking Bandcamp.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SearchCache {
+    #[serde(default)]
+    pub searches: Vec<CachedSearch>,
+}
+
+pub fn save_search_cache(cache: &SearchCache) -> Result<()> {
+    write_json(search_cache_path(), cache)
+}
+
+pub fn load_search_cache() -> SearchCache {
+    read_json(search_cache_path()).unwrap_or_default()
+}
+
+pub fn clear_search_cache() {
+    let _ = fs::remove_file(search_cache_path());
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct UiState {
+    #[serde(default, deserialize_with = "lenient")]
     pub active_tab: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
     pub search_query: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
     pub discover_genre: Option<u32>,
+    #[serde(default, deserialize_with = "lenient")]
     pub discover_tag: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
     pub discover_sort: Option<u32>,
+    #[serde(default, deserialize_with = "lenient")]
     pub library_sort: Option<Sort>,
+    #[serde(default, deserialize_with = "lenient")]
     pub library_query: Option<String>,
+    /// Fewest stars a release needs to be listed in the library.
+    #[serde(default, deserialize_with = "lenient")]
+    pub library_rating: Option<u8>,
+    /// Release pages left out of the library, picked among duplicates.
+    #[serde(default, deserialize_with = "lenient")]
+    pub hidden_albums: Option<Vec<String>>,
+    #[serde(default, deserialize_with = "lenient")]
     pub volume: Option<f64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub last_played: Option<ReleaseLink>,
+    /// The last year whose recap was offered at startup.
+    #[serde(default, deserialize_with = "lenient")]
+    pub recap_offered: Option<i32>,
+    /// Upcoming releases to announce on their release day; unset while
+    /// release reminders are off.
+    #[serde(default, deserialize_with = "lenient")]
+    pub reminders: Option<Vec<Reminder>>,
+    /// Fields this version doesn't know about, kept so saving doesn't drop
+    /// settings written by a newer one.
+    #[serde(flatten)]
+    extra: serde_json::Map<String, serde_json::Value>,
 }
 
 pub fn save_ui_state(state: &UiState) -> Result<()> {
-    let dir = config_dir();
-    fs::create_dir_all(&dir)?;
-    fs::write(ui_state_path(), serde_json::to_string(state)?)?;
-    Ok(())
+    write_json(ui_state_path(), state)
 }
 
 pub fn load_ui_state() -> UiState {
-    fs::read_to_string(ui_state_path())
-        .ok()
-        .and_then(|s| serde_json::from_str(&s).ok())
-        .unwrap_or_default()
+    read_json(ui_state_path()).unwrap_or_default()
+}
+
+/// Choices made in the preferences dialog.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Settings {
+    #[serde(default, deserialize_with = "lenient")]
+    pub color_scheme: Option<ColorScheme>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub watch_clipboard: Option<bool>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub run_in_background: Option<bool>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub global_shortcuts: Option<bool>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub crossfeed: Option<bool>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub start_page: Option<StartPage>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub resume_playback: Option<bool>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub auto_refresh_library: Option<bool>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub card_size: Option<i32>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub cookie_backend: Option<CookieBackend>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub history_retention: Option<HistoryRetention>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub data_saver: Option<DataSaver>,
+    /// ISO 4217 code to convert prices to; unset shows them as sold.
+    #[serde(default, deserialize_with = "lenient")]
+    pub price_currency: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub ephemeral_login: Option<bool>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub middle_click: Option<CardAction>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub shift_click: Option<CardAction>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub remote_control: Option<bool>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub remote_port: Option<u16>,
+    /// Stays on this machine; exports leave it out.
+    #[serde(default, deserialize_with = "lenient")]
+    pub remote_token: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub audio_cache: Option<bool>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub download_dir: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub download_template: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub download_format: Option<DownloadFormat>,
+    /// Fields this version doesn't know about, kept so saving doesn't drop
+    /// settings written by a newer one.
+    #[serde(flatten)]
+    extra: serde_json::Map<String, serde_json::Value>,
+}
+
+pub fn save_settings(settings: &Settings) -> Result<()> {
+    write_json(settings_path(), settings)
+}
+
+pub fn load_settings() -> Settings {
+    read_json(settings_path()).unwrap_or_default()
+}
+
+/// What an export carries to another machine: settings, UI state and the
+/// signed-in account's history, ratings, notes, listen later list and
+/// skips, but neither the session nor anything cached.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Bundle {
+    #[serde(default)]
+    pub settings: Settings,
+    #[serde(default)]
+    pub ui_state: UiState,
+    #[serde(default)]
+    pub history: History,
+    #[serde(default)]
+    pub ratings: Ratings,
+    #[serde(default)]
+    pub notes: Notes,
+    #[serde(default)]
+    pub listen_later: ListenLater,
+    #[serde(default)]
+    pub skips: Skips,
+}
+
+pub fn export_bundle(path: PathBuf, bundle: &Bundle) -> Result<()> {
+    write_json(path, bundle)
+}
+
+/// Unlike the stores, a file the user picked is left alone when it doesn't
+/// parse; the error is reported instead.
+pub fn import_bundle(path: PathBuf) -> Result<Bundle> {
+    let contents = fs::read_to_string(path)?;
+    Ok(serde_json::from_str::<Versioned<Bundle>>(&contents)?.data)
 }
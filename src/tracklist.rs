@@ -0,0 +1,312 @@
+use crate::bandcamp::{Merch, Tag};
+use crate::player::{format_time, Player, PlayerMsg, PlayerOutput, Track};
+use gettextrs::gettext;
+use gtk4::prelude::*;
+use relm4::factory::{DynamicIndex, FactoryComponent, FactorySender, FactoryVecDeque};
+use relm4::{ComponentSender, RelmWidgetExt};
+
+/// A queued track in the player's track list.
+pub struct TrackRow {
+    track: Track,
+    index: DynamicIndex,
+    /// One-based queue position, kept in step by [`refresh`].
+    number: usize,
+    current: bool,
+    /// Listens counted when the row was added.
+    plays: u32,
+    /// Skips counted when the row was added.
+    skips: u32,
+}
+
+#[derive(Debug)]
+pub enum TrackRowOutput {
+    Remove(DynamicIndex),
+    /// Open the page selling the track at this URL.
+    Buy(String),
+}
+
+impl TrackRow {
+    /// The number on the release where known, otherwise the position in
+    /// the queue.
+    fn shown_number(&self) -> String {
+        self.track.position().unwrap_or_else(|| self.number.to_string())
+    }
+
+    /// What the buy button says, for tracks sold on their own that aren't
+    /// already in the collection.
+    fn offer(&self) -> Option<String> {
+        let price = self.track.price.as_ref()?;
+        if crate::library::owned(&self.track.url) == Some(crate::library::Owned::Collection) {
+            return None;
+        }
+        Some(if price.is_free() { gettext("Free") } else { crate::exchange::display(price) })
+    }
+
+    fn label(&self) -> String {
+        let mut label = gettext("Track {number}: {title}")
+            .replace("{number}", &self.shown_number())
+            .replace("{title}", &self.track.title);
+        if let Some(dur) = self.track.duration {
+            label = format!("{}, {}", label, format_time(dur));
+        }
+        if self.plays > 0 {
+            label = format!("{}, {}", label, plays_label(self.plays));
+        }
+        if self.skips >= crate::skips::THRESHOLD {
+            label = format!("{}, {}", label, skips_label(self.skips));
+        }
+        if !self.track.streamable() {
+            label = format!("{}, {}", label, unavailable_hint());
+        }
+        label
+    }
+}
+
+/// How often a track was listened to, e.g. "Played 3 times".
+fn plays_label(plays: u32) -> String {
+    if plays == 1 {
+        gettext("Played once")
+    } else {
+        gettext("Played {} times").replace("{}", &plays.to_string())
+    }
+}
+
+/// How often a track was skipped, e.g. "Skipped 4 times". Only said of
+/// tracks skipped often enough for auto-skip to pass over.
+fn skips_label(skips: u32) -> String {
+    gettext("Skipped {} times").replace("{}", &skips.to_string())
+}
+
+/// What the track list header's auto-skip toggle does.
+pub fn auto_skip_label() -> String {
+    gettext("Skip Tracks Skipped {} or More Times").replace("{}", &crate::skips::THRESHOLD.to_string())
+}
+
+/// Why a track without a stream is greyed out.
+fn unavailable_hint() -> String {
+    gettext("Preview unavailable — buy to listen")
+}
+
+#[relm4::factory(pub)]
+impl FactoryComponent for TrackRow {
+    type Init = Track;
+    type Input = ();
+    type Output = TrackRowOutput;
+    type CommandOutput = ();
+    type ParentWidget = gtk4::ListBox;
+
+    view! {
+        gtk4::ListBoxRow {
+            set_cursor_from_name: Some(if self.track.streamable() { "pointer" } else { "default" }),
+            set_activatable: self.track.streamable(),
+            set_class_active: ("unavailable", !self.track.streamable()),
+            set_tooltip_text: (!self.track.streamable()).then(unavailable_hint).as_deref(),
+            #[watch]
+            update_property: &[gtk4::accessible::Property::Label(&self.label())],
+
+            gtk4::Box {
+                set_orientation: gtk4::Orientation::Horizontal,
+                set_spacing: 8,
+                set_margin_start: 12,
+                set_margin_end: 12,
+                set_margin_top: 4,
+                set_margin_bottom: 4,
+
+                gtk4::Label {
+                    add_css_class: "dim-label",
+                    add_css_class: "caption",
+                    add_css_class: "numeric",
+                    set_width_chars: 3,
+                    set_xalign: 1.0,
+                    #[watch]
+                    set_label: &self.shown_number(),
+                },
+
+                gtk4::Label {
+                    set_label: &self.track.title,
+                    set_ellipsize: gtk4::pango::EllipsizeMode::End,
+                    set_hexpand: true,
+                    set_xalign: 0.0,
+                    add_css_class: "caption",
+                    #[watch]
+                    set_class_active: ("accent", self.current),
+                },
+
+                gtk4::Label {
+                    add_css_class: "dim-label",
+                    add_css_class: "caption",
+                    add_css_class: "numeric",
+                    set_visible: self.plays > 0,
+                    set_label: &format!("{}×", self.plays),
+                    set_tooltip_text: Some(&plays_label(self.plays)),
+                },
+
+                gtk4::Image {
+                    set_icon_name: Some("media-skip-forward-symbolic"),
+                    add_css_class: "dim-label",
+                    set_visible: self.skips >= crate::skips::THRESHOLD,
+                    set_tooltip_text: Some(&skips_label(self.skips)),
+                },
+
+                gtk4::Label {
+                    add_css_class: "dim-label",
+                    add_css_class: "caption",
+                    add_css_class: "numeric",
+                    set_visible: self.track.duration.is_some(),
+                    set_label: &self.track.duration.map(format_time).unwrap_or_default(),
+                },
+
+                gtk4::Button {
+                    add_css_class: "flat",
+                    add_css_class: "caption",
+                    add_css_class: "numeric",
+                    set_visible: self.offer().is_some(),
+                    set_label: &self.offer().unwrap_or_default(),
+                    set_tooltip_text: Some(&if self.track.price.as_ref().is_some_and(|p| p.is_free()) {
+                        gettext("Download “{}” for free").replace("{}", &self.track.title)
+                    } else {
+                        gettext("Buy “{}”").replace("{}", &self.track.title)
+                    }),
+                    connect_clicked[sender, url = self.track.url.clone()] => move |_| {
+                        sender.output(TrackRowOutput::Buy(url.clone())).ok();
+                    },
+                },
+
+                gtk4::Button {
+                    set_icon_name: "list-remove-symbolic",
+                    add_css_class: "flat",
+                    add_css_class: "circular",
+                    set_tooltip_text: Some(&gettext("Remove from Queue")),
+                    update_property: &[gtk4::accessible::Property::Label(
+                        &gettext("Remove “{}” from queue").replace("{}", &self.track.title),
+                    )],
+                    connect_clicked[sender, index = self.index.clone()] => move |_| {
+                        sender.output(TrackRowOutput::Remove(index.clone())).ok();
+                    },
+                },
+            },
+        }
+    }
+
+    fn init_model(track: Self::Init, index: &DynamicIndex, _sender: FactorySender<Self>) -> Self {
+        Self {
+            plays: crate::plays::track(&track.url, &track.title),
+            skips: crate::skips::count(&track.url, &track.title),
+            track,
+            index: index.clone(),
+            number: index.current_index() + 1,
+            current: false,
+        }
+    }
+}
+
+/// The album the queue holds, or just "Queue" when it mixes several.
+pub fn title(tracks: &[Track]) -> String {
+    let album = tracks.first().map(|t| t.album.as_str()).unwrap_or_default();
+    if album.is_empty() || tracks.iter().any(|t| t.album != album) {
+        gettext("Queue")
+    } else {
+        album.to_string()
+    }
+}
+
+/// The tags of the album the queue holds; none when it mixes several.
+pub fn tags(tracks: &[Track]) -> &[Tag] {
+    match tracks.first() {
+        Some(first) if tracks.iter().all(|t| t.album == first.album) => &first.tags,
+        _ => &[],
+    }
+}
+
+/// Physical editions of the album the queue holds; none when it mixes
+/// several.
+pub fn merch(tracks: &[Track]) -> &[Merch] {
+    match tracks.first() {
+        Some(first) if tracks.iter().all(|t| t.album == first.album) => &first.merch,
+        _ => &[],
+    }
+}
+
+/// The release page of the album the queue holds; none when it mixes
+/// several.
+pub fn album_url(tracks: &[Track]) -> Option<&str> {
+    match tracks.first() {
+        Some(first) if !first.url.is_empty() && tracks.iter().all(|t| t.album == first.album) => Some(&first.url),
+        _ => None,
+    }
+}
+
+/// Fills `row` with a button per star, hiding it without an album to
+/// rate. Picking the current rating again takes it away.
+pub fn show_rating(row: &gtk4::Box, stars: Option<u8>, sender: &ComponentSender<Player>) {
+    while let Some(child) = row.first_child() {
+        row.remove(&child);
+    }
+    row.set_visible(stars.is_some());
+    let Some(stars) = stars else { return };
+    for n in 1..=crate::ratings::MAX {
+        let button = gtk4::Button::from_icon_name(if n <= stars { "starred-symbolic" } else { "non-starred-symbolic" });
+        button.add_css_class("flat");
+        button.add_css_class("star");
+        let tooltip = if n == stars { gettext("Clear Rating") } else { crate::album_card::rating_label(n) };
+        button.set_tooltip_text(Some(&tooltip));
+        let (sender, picked) = (sender.clone(), if n == stars { 0 } else { n });
+        button.connect_clicked(move |_| sender.input(PlayerMsg::Rate(picked)));
+        row.append(&button);
+    }
+    row.update_property(&[gtk4::accessible::Property::Description(&crate::album_card::rating_label(stars))]);
+}
+
+/// Fills `chips` with a button per tag, each opening the tag in Discover.
+pub fn show_tags(chips: &gtk4::FlowBox, tags: &[Tag], sender: &ComponentSender<Player>) {
+    chips.remove_all();
+    for tag in tags {
+        let chip = gtk4::Button::with_label(&tag.name);
+        chip.add_css_class("tag-chip");
+        chip.set_tooltip_text(Some(&gettext("Discover more tagged “{}”").replace("{}", &tag.name)));
+        let (sender, slug) = (sender.clone(), tag.slug.clone());
+        chip.connect_clicked(move |_| {
+            sender.output(PlayerOutput::ShowTag(slug.clone())).ok();
+        });
+        chips.append(&chip);
+    }
+    chips.set_visible(!tags.is_empty());
+}
+
+/// Track count and total length, e.g. "12 tracks · 48:05". Tracks of
+/// unknown length add nothing.
+pub fn summary(tracks: &[Track]) -> String {
+    let total: f64 = tracks.iter().filter_map(|t| t.duration).sum();
+    gettext("{count} tracks · {duration}")
+        .replace("{count}", &tracks.len().to_string())
+        .replace("{duration}", &format_time(total))
+}
+
+/// Replaces every row, for when the whole queue changed.
+pub fn replace(list: &mut FactoryVecDeque<TrackRow>, tracks: &[Track], current: usize) {
+    let mut guard = list.guard();
+    guard.clear();
+    for track in tracks {
+        guard.push_back(track.clone());
+    }
+    drop(guard);
+    refresh(list, current);
+}
+
+/// Brings numbers and the highlight up to date after rows moved or the
+/// current track changed, redrawing only the rows that differ.
+pub fn refresh(list: &mut FactoryVecDeque<TrackRow>, current: usize) {
+    let mut guard = list.guard();
+    for i in 0..guard.len() {
+        let stale = guard
+            .get(i)
+            .is_some_and(|row| row.number != i + 1 || row.current != (i == current));
+        if !stale {
+            continue;
+        }
+        if let Some(row) = guard.get_mut(i) {
+            row.number = i + 1;
+            row.current = i == current;
+        }
+    }
+}